@@ -1,84 +1,822 @@
-use clap::Parser as ClapParser;
+use clap::{Parser as ClapParser, Subcommand, ValueEnum};
+use rlox_lib::base::ast::Ast;
+use rlox_lib::base::diagnostic::{render_caret, Diagnostic, DiagnosticError, DEFAULT_TAB_WIDTH};
+use rlox_lib::base::expr::Expr;
 use rlox_lib::base::parser::Parser;
-use rlox_lib::base::scanner::Scanner;
+use rlox_lib::base::program::Program;
+use rlox_lib::base::scanner::{Pragma, Scanner, ScannerError, Token};
+use rlox_lib::base::source::decode_source;
+use rlox_lib::base::stmt::Stmt;
+use rlox_lib::bytecode::compiler::Compiler;
+use rlox_lib::bytecode::disassembler::disassemble;
+use rlox_lib::bytecode::vm::Vm;
+use rlox_lib::closures::compiler::Compiler as ClosureCompiler;
+use rlox_lib::closures::runtime::Runtime;
 use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::lint::LintLevels;
+use rlox_lib::interpreter::options::InterpreterOptions;
 use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+#[cfg(feature = "jit")]
+use rlox_lib::jit::compiler::Compiler as JitCompiler;
+use rlox_lib::project::manifest::{Project, ProjectError};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
 use std::cell::RefCell;
+use std::fmt::{Debug, Display};
 use std::fs;
-use std::io::{stdout, Write};
+use std::fs::File;
+use std::io::{stdout, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Interval in which `rlox watch` checks the script for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Name of the variable holding the value of the last expression typed into the REPL.
+const LAST_RESULT: &str = "_";
+
+/// Input ending a REPL session.
+const QUIT_COMMAND: &str = ":quit";
+
+/// Name of the file in the home directory that keeps the input of REPL sessions.
+const HISTORY_FILE: &str = ".rlox_history";
+
+/// Marker appended to a bundled executable, directly after the payload length.
+const BUNDLE_MAGIC: &[u8; 8] = b"RLOXBNDL";
+
+/// Engine executing scripts after they have been resolved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+enum Backend {
+    /// Walk the syntax tree, supporting all language features
+    #[default]
+    TreeWalker,
+    /// Compile to bytecode and run it on a virtual machine, supporting the language of the book
+    Vm,
+    /// Compile to a tree of closures before running, supporting the language of the book
+    Closures,
+    /// Compile numeric functions to machine code and walk the syntax tree for everything else
+    #[cfg(feature = "jit")]
+    Jit,
+}
+
+/// How errors and warnings are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+enum DiagnosticFormat {
+    /// Messages on the error stream, with the source line of scanner errors
+    #[default]
+    Human,
+    /// One JSON object per line on the output stream
+    Json,
+}
+
+/// Result of running a script, mapped to the exit codes of the book's reference implementation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RunOutcome {
+    Success,
+    /// The script was rejected by the scanner, parser, resolver or compiler.
+    StaticError,
+    /// The script failed while running.
+    RuntimeError,
+    /// The script file could not be read.
+    UnreadableFile,
+}
+
+impl RunOutcome {
+    /// Returns the exit code of the process, following the `sysexits.h` conventions used by
+    /// the official Lox test suite.
+    fn exit_code(self) -> i32 {
+        match self {
+            RunOutcome::Success => 0,
+            RunOutcome::StaticError => 65,
+            RunOutcome::RuntimeError => 70,
+            RunOutcome::UnreadableFile => 66,
+        }
+    }
+}
+
+/// Returns the exit code of the process for a project that can't be run, following the
+/// `sysexits.h` conventions.
+fn project_exit_code(error: &ProjectError) -> i32 {
+    match error {
+        ProjectError::UnreadableFile { .. } | ProjectError::MissingEntryPoint { .. } => 66,
+        ProjectError::InvalidManifest { .. }
+        | ProjectError::UnknownStdlibFeature { .. }
+        | ProjectError::InvalidLint { .. } => 78,
+    }
+}
+
+/// The syntax tree and the pragmas of a parsed script.
+type ParsedScript = (Arc<Ast>, Vec<Pragma>);
 
 struct LoxRuntime<'a> {
     interpreter: Rc<Interpreter<'a>>,
+    vm: RefCell<Vm<'a>>,
+    /// Compiles for the closure backend. Keeps the slots of global variables between scripts.
+    closure_compiler: ClosureCompiler,
+    closure_runtime: RefCell<Runtime<'a>>,
+    #[cfg(feature = "jit")]
+    jit: JitCompiler,
+    backend: Backend,
+    diagnostics: DiagnosticFormat,
+    /// Whether lints reported as warnings are printed.
+    show_warnings: bool,
+    dump_tokens: bool,
+    dump_bytecode: bool,
+    tab_width: usize,
 }
 
 impl LoxRuntime<'_> {
-    fn new() -> Self {
+    fn new(options: InterpreterOptions, arguments: &[String]) -> Self {
+        let interpreter = Rc::new(
+            Interpreter::new(Rc::new(RefCell::new(stdout())))
+                .with_options(options.clone())
+                .with_arguments(arguments),
+        );
+
         LoxRuntime {
-            interpreter: Rc::new(Interpreter::new(Rc::new(RefCell::new(stdout())))),
+            vm: RefCell::new(
+                Vm::new(Rc::new(RefCell::new(stdout()))).with_options(options.clone()),
+            ),
+            closure_compiler: ClosureCompiler::new(),
+            closure_runtime: RefCell::new(
+                Runtime::new(Rc::new(RefCell::new(stdout()))).with_options(options.clone()),
+            ),
+            #[cfg(feature = "jit")]
+            jit: JitCompiler::new(interpreter.interrupt_handle()).with_options(options),
+            interpreter,
+            backend: Backend::default(),
+            diagnostics: DiagnosticFormat::default(),
+            show_warnings: true,
+            dump_tokens: false,
+            dump_bytecode: false,
+            tab_width: DEFAULT_TAB_WIDTH,
         }
     }
 
-    fn run(&self, input: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut scanner = Scanner::new(input);
-        let tokens = scanner
-            .scan_tokens()
-            .map_err(|error| eprintln!("{}", error))
-            .unwrap_or_default();
+    /// Lets Ctrl-C stop the running script instead of terminating the process.
+    fn install_interrupt_handler(&self) -> Result<(), ctrlc::Error> {
+        let interrupt = self.interpreter.interrupt_handle();
+        let vm_interrupt = self.vm.borrow().interrupt_handle();
+        let closure_interrupt = self.closure_runtime.borrow().interrupt_handle();
+        ctrlc::set_handler(move || {
+            interrupt.interrupt();
+            vm_interrupt.interrupt();
+            closure_interrupt.interrupt();
+        })
+    }
+
+    /// Runs a script and reports all errors in the configured format. Returns which kind of
+    /// error stopped the script, if any.
+    fn run(
+        &self,
+        input: &str,
+        file: Option<&str>,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        match self.parse(input, file)? {
+            Ok((ast, pragmas)) => self.execute(&ast, &pragmas, file),
+            Err(outcome) => Ok(outcome),
+        }
+    }
+
+    /// Scans and parses a script and reports all errors in the configured format. Returns the
+    /// tree and the pragmas of the script, or the outcome of the run if it ends here.
+    fn parse(
+        &self,
+        input: &str,
+        file: Option<&str>,
+    ) -> Result<Result<ParsedScript, RunOutcome>, Box<dyn std::error::Error>> {
+        let mut scanner = match file {
+            Some(file) => Scanner::new_with_file(input, file),
+            None => Scanner::new(input),
+        };
+        let scanned = scanner.scan_all();
+        if !scanned.errors.is_empty() {
+            for error in &scanned.errors {
+                let (line, column) = error.location();
+                self.emit(error, Diagnostic::error(error), file)?;
+                if self.diagnostics == DiagnosticFormat::Human {
+                    if let Some(snippet) = render_caret(input, line, column, self.tab_width) {
+                        self.interpreter.report_error(&snippet)?;
+                    }
+                }
+            }
+            return Ok(Err(RunOutcome::StaticError));
+        }
+        let tokens = scanned.tokens;
+        if self.dump_tokens {
+            return self.dump_tokens(&tokens).map(Err);
+        }
 
         let parser = Parser::new(tokens);
-        let statements = parser
-            .parse()
-            .map_err(|error| eprintln!("{}", error))
-            .unwrap_or_default();
-
-        let resolver = Resolver::new(Rc::clone(&self.interpreter));
-        if let Err(error) = resolver.resolve_stmts(&statements) {
-            eprintln!("{}", error)
+        let outcome = parser.parse_all();
+        if !outcome.errors.is_empty() {
+            for error in &outcome.errors {
+                self.report(error, file)?;
+            }
+            return Ok(Err(RunOutcome::StaticError));
+        }
+
+        Ok(Ok((outcome.ast, scanner.pragmas().to_vec())))
+    }
+
+    /// Runs a line typed into the REPL. The value of a lone expression, like `1 + 2`, is
+    /// printed and bound to the global variable `_`, so that the next input can use it.
+    fn run_interactive(&self, input: &str) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        if let Ok(tokens) = Scanner::new(input).scan_tokens() {
+            if self.dump_tokens {
+                return self.dump_tokens(&tokens);
+            }
+            if let Ok(parsed) = Parser::new(tokens).parse_expression() {
+                let mut ast = Arc::unwrap_or_clone(parsed.ast);
+                let name = Token::identifier(LAST_RESULT, 1);
+                let define = ast.add_stmt(Stmt::var(name.clone(), Some(parsed.expr)));
+                let value = ast.add_expr(Expr::variable(name));
+                let print = ast.add_stmt(Stmt::print(value));
+                ast.set_statements(vec![define, print]);
+                return self.execute(&Arc::new(ast), &[], None);
+            }
+        }
+
+        self.run(input, None)
+    }
+
+    /// Resolves and runs a parsed script on the configured backend.
+    fn execute(
+        &self,
+        statements: &Arc<Ast>,
+        pragmas: &[Pragma],
+        file: Option<&str>,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        if let Err(outcome) = self.check(statements, pragmas, file)? {
+            return Ok(outcome);
+        }
+
+        if self.dump_bytecode {
+            return self.dump_bytecode(statements, file);
+        }
+
+        let result = match self.backend {
+            Backend::TreeWalker => self.interpreter.interpret(statements),
+            Backend::Vm => match Compiler::new().compile(statements) {
+                Ok(function) => self.vm.borrow_mut().interpret(function),
+                Err(error) => return self.report(&error, file),
+            },
+            Backend::Closures => match self.closure_compiler.compile(statements) {
+                Ok(function) => self.closure_runtime.borrow_mut().interpret(function),
+                Err(error) => return self.report(&error, file),
+            },
+            #[cfg(feature = "jit")]
+            Backend::Jit => match self.jit.compile(statements) {
+                Ok(functions) => {
+                    self.interpreter.add_compiled_functions(functions);
+                    self.interpreter.interpret(statements)
+                }
+                Err(error) => return self.report(&error, file),
+            },
         };
 
-        if let Err(error) = self.interpreter.interpret(&statements) {
-            eprintln!("{}", error)
+        if let Err(error) = result {
+            if let RuntimeError::Exit { code } = error {
+                std::process::exit(code);
+            }
+            match self.diagnostics {
+                DiagnosticFormat::Human => {
+                    let error = match file {
+                        Some(file) => error.in_file(&Arc::from(file)),
+                        None => error,
+                    };
+                    self.interpreter.report_error(&error)?;
+                }
+                DiagnosticFormat::Json => self.print_json(Diagnostic::error(&error), file)?,
+            }
+            return Ok(RunOutcome::RuntimeError);
+        }
+
+        Ok(RunOutcome::Success)
+    }
+
+    /// Prints the tokens of a script instead of running it, one per line with its position,
+    /// type and lexeme.
+    fn dump_tokens(&self, tokens: &[Token]) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        let mut output = stdout().lock();
+        for token in tokens {
+            let position = format!("{}:{}", token.line(), token.column());
+            let token_type = format!("{:?}", token.token_type());
+            writeln!(
+                output,
+                "{position:<8} {token_type:<16} {:?}",
+                token.lexeme()
+            )?;
+        }
+        Ok(RunOutcome::Success)
+    }
+
+    /// Prints the bytecode of a script instead of running it.
+    fn dump_bytecode(
+        &self,
+        ast: &Arc<Ast>,
+        file: Option<&str>,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        match Compiler::new().compile(ast) {
+            Ok(function) => {
+                print!("{}", disassemble(&function));
+                Ok(RunOutcome::Success)
+            }
+            Err(error) => self.report(&error, file),
+        }
+    }
+
+    /// Reports an error that rejected the script before it ran.
+    fn report(
+        &self,
+        error: &impl DiagnosticError,
+        file: Option<&str>,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        self.emit(error, Diagnostic::error(error), file)?;
+        Ok(RunOutcome::StaticError)
+    }
+
+    /// Reports an error or warning, as its message or as its diagnostic depending on the
+    /// configured format.
+    fn emit(
+        &self,
+        message: &dyn Display,
+        diagnostic: Diagnostic,
+        file: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.diagnostics {
+            DiagnosticFormat::Human => match file {
+                Some(file) => self
+                    .interpreter
+                    .report_error(&format!("{}:{}", file, message))?,
+                None => self.interpreter.report_error(&message)?,
+            },
+            DiagnosticFormat::Json => self.print_json(diagnostic, file)?,
         }
 
         Ok(())
     }
 
+    fn print_json(
+        &self,
+        diagnostic: Diagnostic,
+        file: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = stdout().lock();
+        writeln!(
+            out,
+            "{}",
+            serde_json::to_string(&diagnostic.with_file(file))?
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Reads and runs input line by line. Input that ends in the middle of a statement, like
+    /// `fun f() {`, is continued on the next lines until it is complete or an empty line is
+    /// entered. Ctrl-C discards the input, Ctrl-D or `:quit` ends the session.
     fn run_prompt(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Lox interpreter...");
+        println!("Lox interpreter... Press Ctrl-D or type {QUIT_COMMAND} to exit.");
+        let mut editor = DefaultEditor::new()?;
+        let history = history_file();
+        if let Some(history) = &history {
+            /* there is no history before the first session. */
+            let _ = editor.load_history(history);
+        }
+
+        let mut input = String::new();
         loop {
-            print!("> ");
-            let _ = stdout().flush();
-            let mut input = String::new();
-            std::io::stdin()
-                .read_line(&mut input)
-                .expect("Unable to read user input");
+            let prompt = if input.is_empty() { "> " } else { "... " };
+            let line = match editor.readline(prompt) {
+                Ok(line) if line.trim() == QUIT_COMMAND => return Ok(()),
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => {
+                    input.clear();
+                    continue;
+                }
+                Err(ReadlineError::Eof) => return Ok(()),
+                Err(error) => return Err(error.into()),
+            };
+
+            input.push_str(&line);
+            input.push('\n');
+            if !line.trim().is_empty() && is_incomplete(&input) {
+                continue;
+            }
+
+            if !input.trim().is_empty() {
+                editor.add_history_entry(input.trim_end())?;
+                if let Some(history) = &history {
+                    /* the session goes on without a history, if it can't be saved. */
+                    let _ = editor.save_history(history);
+                }
+            }
 
-            if let Err(error) = self.run(input.as_str()) {
-                eprintln!("{}", error)
+            if let Err(error) = self.run_interactive(input.as_str()) {
+                self.interpreter.report_error(error.as_ref())?;
             }
+            input.clear();
         }
     }
 
-    fn run_file(&self, script_file: String) -> Result<(), Box<dyn std::error::Error>> {
-        let script_content = fs::read_to_string(script_file).expect("Unable to read input file");
-        self.run(script_content.as_str())
+    /// Runs the scripts one after another in the same global environment, until a script
+    /// fails.
+    fn run_files(
+        &self,
+        script_files: Vec<String>,
+        lossy_utf8: bool,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        for script_file in script_files {
+            let script_content = match self.read_script(&script_file, lossy_utf8)? {
+                Ok(script_content) => script_content,
+                Err(outcome) => return Ok(outcome),
+            };
+            let outcome = self.run(script_content.as_str(), Some(&script_file))?;
+            if outcome != RunOutcome::Success {
+                return Ok(outcome);
+            }
+        }
+
+        Ok(RunOutcome::Success)
     }
+
+    /// Reads and decodes a script file. A file that can't be read or isn't valid UTF-8 is
+    /// reported and yields the outcome of the failed run instead.
+    fn read_script(
+        &self,
+        script_file: &str,
+        lossy_utf8: bool,
+    ) -> Result<Result<String, RunOutcome>, Box<dyn std::error::Error>> {
+        let bytes = match fs::read(script_file) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                self.interpreter
+                    .report_error(&format!("{}: {}", script_file, error))?;
+                return Ok(Err(RunOutcome::UnreadableFile));
+            }
+        };
+
+        match decode_source(&bytes, lossy_utf8) {
+            Ok(script_content) => Ok(Ok(script_content)),
+            Err(error) => {
+                self.emit(&error, Diagnostic::error(&error), Some(script_file))?;
+                Ok(Err(RunOutcome::StaticError))
+            }
+        }
+    }
+
+    /// Runs the entry point of a project directory with the settings of its manifest.
+    fn run_project(
+        &self,
+        project: Project,
+        lossy_utf8: bool,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        if let Some(features) = &project.manifest.stdlib {
+            self.interpreter.restrict_stdlib(features);
+        }
+
+        let entry_point = project.entry_point().to_string_lossy().into_owned();
+        self.run_files(vec![entry_point], lossy_utf8)
+    }
+
+    /// Runs a script and hot reloads its top-level functions and classes whenever the file
+    /// changes, while it is running and after it has finished, until interrupted.
+    fn run_watch(
+        &self,
+        script_file: String,
+        lossy_utf8: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let script_content = match self.read_script(&script_file, lossy_utf8)? {
+            Ok(script_content) => script_content,
+            Err(outcome) => std::process::exit(outcome.exit_code()),
+        };
+        if let Ok(tokens) = Scanner::new_with_file(&script_content, &script_file).scan_tokens() {
+            self.interpreter.track_declarations(&tokens);
+        }
+
+        let reload = self.interpreter.reload_handle();
+        let interrupt = self.interpreter.interrupt_handle();
+        let watched_file = script_file.to_owned();
+        thread::spawn(move || {
+            let modified = |file: &str| fs::metadata(file).and_then(|m| m.modified()).ok();
+            let mut last_modified = modified(&watched_file).unwrap_or(SystemTime::UNIX_EPOCH);
+            while interrupt.sleep(WATCH_INTERVAL) {
+                match modified(&watched_file) {
+                    Some(time) if time != last_modified => last_modified = time,
+                    _ => continue,
+                }
+
+                if let Ok(bytes) = fs::read(&watched_file) {
+                    if let Ok(source) = decode_source(&bytes, lossy_utf8) {
+                        reload.request(source, &watched_file);
+                    }
+                }
+            }
+        });
+
+        self.run(script_content.as_str(), Some(&script_file))?;
+
+        let interrupt = self.interpreter.interrupt_handle();
+        while interrupt.sleep(WATCH_INTERVAL) {
+            self.interpreter.apply_pending_reload()?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles a script ahead of time and writes a standalone executable to `output`: a copy
+    /// of the running executable with the compiled program appended as payload, followed by
+    /// the payload length and [`BUNDLE_MAGIC`]. The script is checked like a script that is
+    /// run, and the bundle runs it with the same `options`.
+    fn build(
+        &self,
+        script_file: &str,
+        output: &str,
+        options: InterpreterOptions,
+        lossy_utf8: bool,
+    ) -> Result<RunOutcome, Box<dyn std::error::Error>> {
+        let script_content = match self.read_script(script_file, lossy_utf8)? {
+            Ok(script_content) => script_content,
+            Err(outcome) => return Ok(outcome),
+        };
+        let (ast, pragmas) = match self.parse(&script_content, Some(script_file))? {
+            Ok(parsed) => parsed,
+            Err(outcome) => return Ok(outcome),
+        };
+        if let Err(outcome) = self.check(&ast, &pragmas, Some(script_file))? {
+            return Ok(outcome);
+        }
+
+        let payload = Program {
+            ast,
+            pragmas,
+            options,
+        }
+        .to_bytes()?;
+
+        fs::copy(std::env::current_exe()?, output)?;
+
+        let mut bundle = fs::OpenOptions::new().append(true).open(output)?;
+        bundle.write_all(&payload)?;
+        bundle.write_all(&(payload.len() as u64).to_le_bytes())?;
+        bundle.write_all(BUNDLE_MAGIC)?;
+
+        Ok(RunOutcome::Success)
+    }
+
+    /// Resolves a parsed script and reports its warnings and errors in the configured format.
+    /// Returns the outcome of the run if the script is rejected.
+    fn check(
+        &self,
+        ast: &Arc<Ast>,
+        pragmas: &[Pragma],
+        file: Option<&str>,
+    ) -> Result<Result<(), RunOutcome>, Box<dyn std::error::Error>> {
+        let resolver = match Resolver::new(Rc::clone(&self.interpreter)).with_pragmas(pragmas) {
+            Ok(resolver) => resolver,
+            Err(error) => return self.report(&error, file).map(Err),
+        };
+        let resolved = resolver.resolve_stmts(ast);
+        if self.show_warnings {
+            for warning in resolver.warnings() {
+                self.emit(&warning, Diagnostic::warning(&warning), file)?;
+            }
+        }
+        if let Err(error) = resolved {
+            return self.report(&error, file).map(Err);
+        };
+
+        Ok(Ok(()))
+    }
+}
+
+/// Returns the file keeping the input of REPL sessions, `~/.rlox_history`.
+fn history_file() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(Path::new(&home).join(HISTORY_FILE))
+}
+
+/// Returns whether the input ends in the middle of a string, comment or statement, so that the
+/// REPL should read more lines before running it.
+fn is_incomplete(input: &str) -> bool {
+    let scanned = Scanner::new(input).scan_all();
+    if let Some(error) = scanned.errors.first() {
+        return matches!(
+            error,
+            ScannerError::UnterminatedString { .. } | ScannerError::UnterminatedComment { .. }
+        );
+    }
+
+    if Parser::new(scanned.tokens.clone())
+        .parse_expression()
+        .is_ok()
+    {
+        return false;
+    }
+
+    Parser::new(scanned.tokens).parse_all().incomplete
+}
+
+/// Returns the compiled program embedded into the running executable by `rlox build`, if any.
+fn read_bundle() -> Option<Vec<u8>> {
+    let mut executable = File::open(std::env::current_exe().ok()?).ok()?;
+
+    let mut trailer = [0u8; 16];
+    executable.seek(SeekFrom::End(-16)).ok()?;
+    executable.read_exact(&mut trailer).ok()?;
+
+    if &trailer[8..] != BUNDLE_MAGIC {
+        return None;
+    }
+
+    let length = u64::from_le_bytes(trailer[..8].try_into().ok()?);
+    executable
+        .seek(SeekFrom::End(-16 - i64::try_from(length).ok()?))
+        .ok()?;
+
+    let mut payload = Vec::new();
+    executable.take(length).read_to_end(&mut payload).ok()?;
+
+    Some(payload)
 }
 
 #[derive(ClapParser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg()]
-    script: Option<String>,
+    scripts: Vec<String>,
+    /// Arguments passed to the script in the ARGS list
+    #[arg(last = true, global = true)]
+    arguments: Vec<String>,
+    /// Replace invalid UTF-8 in script files instead of rejecting them
+    #[arg(long, global = true)]
+    lossy_utf8: bool,
+    /// Allow a lint
+    #[arg(long, global = true, value_name = "LINT")]
+    allow: Vec<String>,
+    /// Report a lint as a warning
+    #[arg(long, global = true, value_name = "LINT")]
+    warn: Vec<String>,
+    /// Report a lint as an error
+    #[arg(long, global = true, value_name = "LINT")]
+    deny: Vec<String>,
+    /// Report all warnings as errors
+    #[arg(long, global = true)]
+    deny_warnings: bool,
+    /// Don't print warnings
+    #[arg(long, global = true)]
+    no_warnings: bool,
+    /// Reject undeclared globals, uninitialized variables and non-boolean conditions
+    #[arg(long, global = true)]
+    strict: bool,
+    /// Abort scripts that write more than this number of bytes to the output
+    #[arg(long, global = true, value_name = "BYTES")]
+    max_output: Option<usize>,
+    /// Abort scripts that execute more than this number of statements
+    #[arg(long, global = true, value_name = "STEPS")]
+    max_steps: Option<usize>,
+    /// Abort scripts that run longer than this number of milliseconds
+    #[arg(long, global = true, value_name = "MILLISECONDS")]
+    max_duration: Option<u64>,
+    /// Abort scripts that hold more than this number of bytes in memory
+    #[arg(long, global = true, value_name = "BYTES")]
+    max_memory: Option<usize>,
+    /// Report division by zero as an error instead of producing infinity or NaN
+    #[arg(long, global = true)]
+    checked_division: bool,
+    /// Collect garbage before every function call, to find objects that are freed too early
+    #[arg(long, global = true)]
+    gc_stress: bool,
+    /// Format of errors and warnings
+    #[arg(long, global = true, value_enum, default_value_t = DiagnosticFormat::default())]
+    diagnostics: DiagnosticFormat,
+    /// Number of columns a tab advances to in diagnostics
+    #[arg(long, global = true, default_value_t = DEFAULT_TAB_WIDTH)]
+    tab_width: usize,
+    /// Print the tokens of scripts instead of running them
+    #[arg(long, global = true)]
+    dump_tokens: bool,
+    /// Print the compiled bytecode of scripts instead of running them
+    #[arg(long, global = true)]
+    dump_bytecode: bool,
+    /// Engine running the scripts
+    #[arg(long, global = true, value_enum, default_value_t = Backend::default())]
+    backend: Backend,
+    /// Print the stack and each instruction while running on the virtual machine
+    #[arg(long, global = true)]
+    trace_execution: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Bundle a script and the interpreter into a standalone executable
+    Build {
+        #[arg()]
+        script: String,
+        #[arg(short, long)]
+        output: String,
+    },
+    /// Run a script and hot reload its functions and classes when the file changes
+    Watch {
+        #[arg()]
+        script: String,
+    },
+    /// Run a project directory containing a main.lox and an optional lox.toml manifest
+    Run {
+        #[arg()]
+        project: String,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(payload) = read_bundle() {
+        let program = Program::from_bytes(&payload)?;
+        let arguments: Vec<String> = std::env::args().skip(1).collect();
+        let mut environment = LoxRuntime::new(program.options, &arguments);
+        /* warnings were reported when the bundle was built. */
+        environment.show_warnings = false;
+        environment.install_interrupt_handler()?;
+        let outcome = environment.execute(&program.ast, &program.pragmas, None)?;
+        std::process::exit(outcome.exit_code());
+    }
+
     let args = Args::parse();
-    let environment = LoxRuntime::new();
 
-    match args.script {
-        Some(script_file) => environment.run_file(script_file),
-        None => environment.run_prompt(),
+    let project = match &args.command {
+        Some(Command::Run { project }) => match Project::open(Path::new(project)) {
+            Ok(project) => Some(project),
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(project_exit_code(&error));
+            }
+        },
+        _ => None,
+    };
+
+    let mut lints = match &project {
+        Some(project) => project.manifest.lint_levels()?,
+        None => LintLevels::new(),
+    };
+    for (names, level) in [
+        (&args.allow, "allow"),
+        (&args.warn, "warn"),
+        (&args.deny, "deny"),
+    ] {
+        for name in names {
+            lints.set_by_name(name, level)?;
+        }
+    }
+    if args.deny_warnings {
+        lints.deny_warnings();
+    }
+
+    let options = InterpreterOptions {
+        strict: args.strict,
+        lints,
+        checked_division: args.checked_division,
+        max_output: args.max_output,
+        max_steps: args.max_steps,
+        max_duration: args.max_duration.map(Duration::from_millis),
+        max_memory: args.max_memory,
+        gc_stress: args.gc_stress,
+        import_paths: project
+            .as_ref()
+            .map(Project::import_paths)
+            .unwrap_or_default(),
+        lossy_utf8: args.lossy_utf8,
+    };
+    let mut environment = LoxRuntime::new(options.clone(), &args.arguments);
+    environment.backend = args.backend;
+    environment.diagnostics = args.diagnostics;
+    environment.show_warnings = !args.no_warnings;
+    environment.vm.get_mut().set_trace(args.trace_execution);
+    environment.dump_tokens = args.dump_tokens;
+    environment.dump_bytecode = args.dump_bytecode;
+    environment.tab_width = args.tab_width;
+    environment.install_interrupt_handler()?;
+
+    let outcome = match args.command {
+        Some(Command::Build { script, output }) => {
+            environment.build(&script, &output, options, args.lossy_utf8)?
+        }
+        Some(Command::Watch { script }) => return environment.run_watch(script, args.lossy_utf8),
+        Some(Command::Run { .. }) => match project {
+            Some(project) => environment.run_project(project, args.lossy_utf8)?,
+            None => RunOutcome::Success,
+        },
+        None if args.scripts.is_empty() => return environment.run_prompt(),
+        None => environment.run_files(args.scripts, args.lossy_utf8)?,
+    };
+
+    if outcome != RunOutcome::Success {
+        std::process::exit(outcome.exit_code());
     }
+
+    Ok(())
 }
@@ -0,0 +1,115 @@
+use crate::base::expr::Expr;
+use crate::base::stmt::Stmt;
+use serde::{Deserialize, Serialize};
+use std::ops::Index;
+use std::sync::Arc;
+
+/// An expression allocated in an [`Ast`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct ExprRef(u32);
+
+/// A statement allocated in an [`Ast`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct StmtRef(u32);
+
+/// The nodes of a parsed script. Nodes refer to their children by index, so the whole tree is
+/// stored in two flat vectors and a function body is just a list of indices.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ast {
+    exprs: Vec<Expr>,
+    stmts: Vec<Stmt>,
+    statements: Vec<StmtRef>,
+}
+
+impl Ast {
+    pub fn add_expr(&mut self, expr: Expr) -> ExprRef {
+        self.exprs.push(expr);
+        ExprRef(index(self.exprs.len() - 1))
+    }
+
+    pub fn add_stmt(&mut self, stmt: Stmt) -> StmtRef {
+        self.stmts.push(stmt);
+        StmtRef(index(self.stmts.len() - 1))
+    }
+
+    /// Sets the top-level statements of the script.
+    pub fn set_statements(&mut self, statements: Vec<StmtRef>) {
+        self.statements = statements;
+    }
+
+    /// Returns the top-level statements of the script.
+    pub fn statements(&self) -> &[StmtRef] {
+        &self.statements
+    }
+
+    /// Returns all expressions of the script, in no particular order.
+    pub fn expressions(&self) -> &[Expr] {
+        &self.exprs
+    }
+
+    /// Returns whether the statement is or contains a `yield` statement, not counting the
+    /// bodies of nested functions and classes.
+    pub fn contains_yield(&self, statement: StmtRef) -> bool {
+        match &self[statement] {
+            Stmt::Yield { .. } => true,
+            Stmt::Block { statements } => statements.iter().any(|s| self.contains_yield(*s)),
+            Stmt::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.contains_yield(*then_branch)
+                    || else_branch.is_some_and(|branch| self.contains_yield(branch))
+            }
+            Stmt::While { body, .. } | Stmt::ForIn { body, .. } => self.contains_yield(*body),
+            Stmt::Var { initializer, .. } => {
+                matches!(initializer.map(|i| &self[i]), Some(Expr::Yield { .. }))
+            }
+            Stmt::Expression { expression } => matches!(
+                &self[*expression],
+                Expr::Assign { value, .. } if matches!(self[*value], Expr::Yield { .. })
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl Index<ExprRef> for Ast {
+    type Output = Expr;
+
+    fn index(&self, expr: ExprRef) -> &Expr {
+        &self.exprs[expr.0 as usize]
+    }
+}
+
+impl Index<StmtRef> for Ast {
+    type Output = Stmt;
+
+    fn index(&self, stmt: StmtRef) -> &Stmt {
+        &self.stmts[stmt.0 as usize]
+    }
+}
+
+fn index(position: usize) -> u32 {
+    u32::try_from(position).expect("too many nodes in a script")
+}
+
+/// Statements of an [`Ast`] that run together, like the body of a function. The body keeps the
+/// tree alive, so that functions can be called after the rest of the script is gone.
+#[derive(Clone, Debug)]
+pub struct Body {
+    pub(crate) ast: Arc<Ast>,
+    pub(crate) statements: Arc<[StmtRef]>,
+}
+
+impl Body {
+    pub fn new(ast: Arc<Ast>, statements: Arc<[StmtRef]>) -> Self {
+        Self { ast, statements }
+    }
+}
+
+impl PartialEq for Body {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.ast, &other.ast) && Arc::ptr_eq(&self.statements, &other.statements)
+    }
+}
@@ -0,0 +1,193 @@
+use crate::interpreter::lint::Warning;
+use serde::Serialize;
+use std::fmt::Display;
+use unicode_width::UnicodeWidthChar;
+
+/// Tab width used for diagnostics unless configured otherwise.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A 1-based position in a script.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The characters of a script a diagnostic refers to, excluding `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// An error or warning in a form that editors and other tools can consume, e.g. as JSON.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    /// Identifies the kind of diagnostic: the name of the error variant or of the lint.
+    pub code: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub span: Option<Span>,
+}
+
+/// An error that can be reported as a [`Diagnostic`]. Its message starts with its location,
+/// like `3: Expect ';' after value.`, if it refers to a line.
+pub trait DiagnosticError: Display {
+    /// Identifies the kind of error: the name of its variant.
+    fn code(&self) -> &'static str;
+
+    /// Returns the 1-based line the error occurred in.
+    fn line(&self) -> Option<usize>;
+
+    /// Returns the 1-based column at which the error occurred, counted in characters.
+    fn column(&self) -> Option<usize>;
+
+    /// Returns the script the error occurred in, if it is not the one being run.
+    fn file(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the message without the location it starts with, like `main.lox:3: `.
+    fn message(&self) -> String {
+        let file = self.file().map(|file| format!("{file}:"));
+        let line = self.line().map(|line| format!("{line}: "));
+        let location = format!("{}{}", file.unwrap_or_default(), line.unwrap_or_default());
+
+        let message = self.to_string();
+        match message.strip_prefix(&location) {
+            Some(message) => message.to_owned(),
+            None => message,
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn error(error: &impl DiagnosticError) -> Self {
+        let line = error.line();
+        let column = line.and(error.column());
+        Diagnostic {
+            severity: DiagnosticSeverity::Error,
+            code: error.code().to_owned(),
+            message: error.message(),
+            file: error.file().map(str::to_owned),
+            line,
+            column,
+            span: line.zip(column).map(|(line, column)| span(line, column)),
+        }
+    }
+
+    pub fn warning(warning: &Warning) -> Self {
+        Diagnostic {
+            severity: DiagnosticSeverity::Warning,
+            code: warning.lint.name().to_owned(),
+            message: warning.message.to_owned(),
+            file: None,
+            line: Some(warning.line),
+            column: Some(warning.column),
+            span: Some(span(warning.line, warning.column)),
+        }
+    }
+
+    /// Sets the script the diagnostic refers to, unless it occurred in another one, like a
+    /// module the script imports.
+    pub fn with_file(mut self, file: Option<&str>) -> Self {
+        if self.file.is_none() {
+            self.file = file.map(str::to_owned);
+        }
+        self
+    }
+}
+
+/// Returns the span of the character at `column` of `line`.
+fn span(line: usize, column: usize) -> Span {
+    Span {
+        start: Position { line, column },
+        end: Position {
+            line,
+            column: column + 1,
+        },
+    }
+}
+
+/// Returns the 1-based column at which the character at the 1-based character position
+/// `column` of `line` is displayed. Tabs advance to the next multiple of `tab_width`, wide
+/// characters take two columns and combining characters none.
+pub fn display_column(line: &str, column: usize, tab_width: usize) -> usize {
+    line.chars()
+        .take(column.saturating_sub(1))
+        .fold(0, |width, c| advance(width, c, tab_width))
+        + 1
+}
+
+/// Renders line `line` of `source` with a caret below the character at position `column`.
+/// Tabs are expanded to spaces, so that the caret lines up regardless of the terminal.
+pub fn render_caret(source: &str, line: usize, column: usize, tab_width: usize) -> Option<String> {
+    let text = source_line(source, line)?;
+
+    let mut expanded = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let next = advance(width, c, tab_width);
+        if c == '\t' {
+            expanded.push_str(&" ".repeat(next - width));
+        } else {
+            expanded.push(c);
+        }
+        width = next;
+    }
+
+    let gutter = line.to_string();
+    Some(format!(
+        "{} | {}\n{} | {}^",
+        gutter,
+        expanded,
+        " ".repeat(gutter.len()),
+        " ".repeat(display_column(text, column, tab_width) - 1)
+    ))
+}
+
+fn advance(width: usize, c: char, tab_width: usize) -> usize {
+    match c {
+        '\t' if tab_width > 0 => (width / tab_width + 1) * tab_width,
+        '\t' => width,
+        c => width + c.width().unwrap_or(0),
+    }
+}
+
+/// Returns the 1-based line `line` of `source`, counting line breaks like the scanner does.
+fn source_line(source: &str, line: usize) -> Option<&str> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\n' => {
+                lines.push(&source[start..index]);
+                start = index + 1;
+            }
+            '\r' => {
+                lines.push(&source[start..index]);
+                if let Some((_, '\n')) = chars.peek() {
+                    chars.next();
+                    start = index + 2;
+                } else {
+                    start = index + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    lines.push(&source[start..]);
+
+    lines.get(line.checked_sub(1)?).copied()
+}
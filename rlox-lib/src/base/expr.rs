@@ -1,12 +1,68 @@
+use crate::base::ast::{ExprRef, StmtRef};
 use crate::base::scanner::Token;
 use crate::base::visitor::Visitor;
-use uuid::Uuid;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub trait ExprUuid {
-    fn uuid(&self) -> Uuid;
+/// Identifies an expression or declaration, so that the resolver can record how it resolved a
+/// variable and which variables a function captures.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Returns the next id. Ids are unique within the process, because the expressions of
+    /// several scripts, like REPL lines and modules, are resolved into the same table.
+    pub fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        NodeId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Ids are not written to a compiled program, the nodes get fresh ids when it is loaded, so
+/// that they don't collide with the ids of the loading process.
+impl Serialize for NodeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(NodeId::next())
+    }
+}
+
+/// A map keyed by [`NodeId`]. Ids are sequential, so they are used as hashes directly.
+pub type NodeMap<V> = HashMap<NodeId, V, BuildHasherDefault<NodeIdHasher>>;
+
+/// Hashes a [`NodeId`] to its own value.
+#[derive(Default)]
+pub struct NodeIdHasher(u64);
+
+impl Hasher for NodeIdHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 << 8) | u64::from(*byte);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.0 = value as u64;
+    }
+}
+
+pub trait ExprId {
+    fn id(&self) -> NodeId;
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LiteralValue {
     Number(f64),
     String(String),
@@ -14,232 +70,436 @@ pub enum LiteralValue {
     None,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Expr {
     Assign {
-        uuid: Uuid,
+        id: NodeId,
         name: Box<Token>,
-        value: Box<Expr>,
+        value: ExprRef,
+    },
+    Await {
+        id: NodeId,
+        keyword: Box<Token>,
+        value: ExprRef,
     },
     Binary {
-        uuid: Uuid,
-        left: Box<Expr>,
+        id: NodeId,
+        left: ExprRef,
         operator: Box<Token>,
-        right: Box<Expr>,
+        right: ExprRef,
     },
     Call {
-        uuid: Uuid,
+        id: NodeId,
         paren: Box<Token>,
-        callee: Box<Expr>,
-        arguments: Vec<Expr>,
+        callee: ExprRef,
+        arguments: Vec<ExprRef>,
     },
     Get {
-        uuid: Uuid,
-        object: Box<Expr>,
+        id: NodeId,
+        object: ExprRef,
         name: Box<Token>,
     },
     Grouping {
-        uuid: Uuid,
-        expression: Box<Expr>,
+        id: NodeId,
+        expression: ExprRef,
+    },
+    Lambda {
+        id: NodeId,
+        function: StmtRef,
+    },
+    Index {
+        id: NodeId,
+        object: ExprRef,
+        bracket: Box<Token>,
+        index: ExprRef,
+    },
+    IndexSet {
+        id: NodeId,
+        object: ExprRef,
+        bracket: Box<Token>,
+        index: ExprRef,
+        value: ExprRef,
+    },
+    List {
+        id: NodeId,
+        bracket: Box<Token>,
+        elements: Vec<ExprRef>,
     },
     Literal {
-        uuid: Uuid,
+        id: NodeId,
         value: LiteralValue,
     },
     Logical {
-        uuid: Uuid,
-        left: Box<Expr>,
+        id: NodeId,
+        left: ExprRef,
         operator: Box<Token>,
-        right: Box<Expr>,
+        right: ExprRef,
+    },
+    Map {
+        id: NodeId,
+        brace: Box<Token>,
+        entries: Vec<(ExprRef, ExprRef)>,
     },
     Set {
-        uuid: Uuid,
-        object: Box<Expr>,
+        id: NodeId,
+        object: ExprRef,
         name: Box<Token>,
-        value: Box<Expr>,
+        value: ExprRef,
+    },
+    Stringify {
+        id: NodeId,
+        expression: ExprRef,
     },
     Super {
-        uuid: Uuid,
+        id: NodeId,
+        /// Resolves `this`, the instance the method of the superclass is bound to.
+        this_id: NodeId,
         keyword: Box<Token>,
         method: Box<Token>,
     },
+    Ternary {
+        id: NodeId,
+        condition: ExprRef,
+        question: Box<Token>,
+        then_branch: ExprRef,
+        else_branch: ExprRef,
+    },
     This {
-        uuid: Uuid,
+        id: NodeId,
         keyword: Box<Token>,
     },
     Unary {
-        uuid: Uuid,
+        id: NodeId,
+        operator: Box<Token>,
+        right: ExprRef,
+    },
+    Update {
+        id: NodeId,
         operator: Box<Token>,
-        right: Box<Expr>,
+        target: ExprRef,
+        prefix: bool,
     },
     Variable {
-        uuid: Uuid,
+        id: NodeId,
         name: Box<Token>,
     },
+    /// A `yield` whose result is the value the generator is resumed with. It may only be the
+    /// initializer of a variable or the value of an assignment statement.
+    Yield {
+        id: NodeId,
+        keyword: Box<Token>,
+        value: Option<ExprRef>,
+    },
 }
 
 impl Expr {
-    pub fn assign(name: Token, value: Expr) -> Self {
+    pub fn assign(name: Token, value: ExprRef) -> Self {
         Expr::Assign {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
             name: Box::new(name),
-            value: Box::new(value),
+            value,
+        }
+    }
+
+    pub fn await_expr(keyword: Token, value: ExprRef) -> Self {
+        Expr::Await {
+            id: NodeId::next(),
+            keyword: Box::new(keyword),
+            value,
         }
     }
 
-    pub fn binary(left: Expr, operator: Token, right: Expr) -> Self {
+    pub fn binary(left: ExprRef, operator: Token, right: ExprRef) -> Self {
         Expr::Binary {
-            uuid: Uuid::new_v4(),
-            left: Box::new(left),
+            id: NodeId::next(),
+            left,
             operator: Box::new(operator),
-            right: Box::new(right),
+            right,
         }
     }
 
-    pub fn call(paren: Token, callee: Expr, arguments: Vec<Expr>) -> Self {
+    pub fn call(paren: Token, callee: ExprRef, arguments: Vec<ExprRef>) -> Self {
         Expr::Call {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
             paren: Box::new(paren),
-            callee: Box::new(callee),
+            callee,
             arguments,
         }
     }
 
-    pub fn get(object: Expr, name: Token) -> Self {
+    pub fn get(object: ExprRef, name: Token) -> Self {
         Expr::Get {
-            uuid: Uuid::new_v4(),
-            object: Box::new(object),
+            id: NodeId::next(),
+            object,
             name: Box::new(name),
         }
     }
 
-    pub fn grouping(expression: Expr) -> Self {
+    pub fn grouping(expression: ExprRef) -> Self {
         Expr::Grouping {
-            uuid: Uuid::new_v4(),
-            expression: Box::new(expression),
+            id: NodeId::next(),
+            expression,
+        }
+    }
+
+    pub fn index(object: ExprRef, bracket: Token, index: ExprRef) -> Self {
+        Expr::Index {
+            id: NodeId::next(),
+            object,
+            bracket: Box::new(bracket),
+            index,
+        }
+    }
+
+    pub fn index_set(object: ExprRef, bracket: Token, index: ExprRef, value: ExprRef) -> Self {
+        Expr::IndexSet {
+            id: NodeId::next(),
+            object,
+            bracket: Box::new(bracket),
+            index,
+            value,
+        }
+    }
+
+    pub fn list(bracket: Token, elements: Vec<ExprRef>) -> Self {
+        Expr::List {
+            id: NodeId::next(),
+            bracket: Box::new(bracket),
+            elements,
+        }
+    }
+
+    /// An anonymous function. `function` is a [`Stmt::Function`] named `lambda`.
+    pub fn lambda(function: StmtRef) -> Self {
+        Expr::Lambda {
+            id: NodeId::next(),
+            function,
         }
     }
 
     pub fn literal(value: LiteralValue) -> Self {
         Expr::Literal {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
             value,
         }
     }
 
-    pub fn logical(left: Expr, operator: Token, right: Expr) -> Self {
+    pub fn logical(left: ExprRef, operator: Token, right: ExprRef) -> Self {
         Expr::Logical {
-            uuid: Uuid::new_v4(),
-            left: Box::new(left),
+            id: NodeId::next(),
+            left,
             operator: Box::new(operator),
-            right: Box::new(right),
+            right,
+        }
+    }
+
+    pub fn map(brace: Token, entries: Vec<(ExprRef, ExprRef)>) -> Self {
+        Expr::Map {
+            id: NodeId::next(),
+            brace: Box::new(brace),
+            entries,
         }
     }
 
-    pub fn set(object: Expr, name: Token, value: Expr) -> Self {
+    pub fn set(object: ExprRef, name: Token, value: ExprRef) -> Self {
         Expr::Set {
-            uuid: Uuid::new_v4(),
-            object: Box::new(object),
+            id: NodeId::next(),
+            object,
             name: Box::new(name),
-            value: Box::new(value),
+            value,
+        }
+    }
+
+    /// Converts the value of an expression embedded in an interpolated string to a string.
+    pub fn stringify(expression: ExprRef) -> Self {
+        Expr::Stringify {
+            id: NodeId::next(),
+            expression,
         }
     }
 
     pub fn super_expr(keyword: Token, method: Token) -> Self {
         Expr::Super {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
+            this_id: NodeId::next(),
             keyword: Box::new(keyword),
             method: Box::new(method),
         }
     }
 
+    pub fn ternary(
+        condition: ExprRef,
+        question: Token,
+        then_branch: ExprRef,
+        else_branch: ExprRef,
+    ) -> Self {
+        Expr::Ternary {
+            id: NodeId::next(),
+            condition,
+            question: Box::new(question),
+            then_branch,
+            else_branch,
+        }
+    }
+
     pub fn this(keyword: Token) -> Self {
         Expr::This {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
             keyword: Box::new(keyword),
         }
     }
 
-    pub fn unary(operator: Token, right: Expr) -> Self {
+    pub fn unary(operator: Token, right: ExprRef) -> Self {
         Expr::Unary {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
+            operator: Box::new(operator),
+            right,
+        }
+    }
+
+    /// An increment or decrement of a variable or field. `target` is a `Variable` or `Get`
+    /// expression, which is both read and written.
+    pub fn update(operator: Token, target: ExprRef, prefix: bool) -> Self {
+        Expr::Update {
+            id: NodeId::next(),
             operator: Box::new(operator),
-            right: Box::new(right),
+            target,
+            prefix,
         }
     }
 
     pub fn variable(name: Token) -> Self {
         Expr::Variable {
-            uuid: Uuid::new_v4(),
+            id: NodeId::next(),
             name: Box::new(name),
         }
     }
 
+    pub fn yield_expr(keyword: Token, value: Option<ExprRef>) -> Self {
+        Expr::Yield {
+            id: NodeId::next(),
+            keyword: Box::new(keyword),
+            value,
+        }
+    }
+
     pub fn accept<R, E>(&self, visitor: &dyn Visitor<Expr, R, E>) -> Result<R, E> {
         visitor.visit(self)
     }
 }
 
-impl ExprUuid for Expr {
-    fn uuid(&self) -> Uuid {
+impl ExprId for Expr {
+    fn id(&self) -> NodeId {
         *match &self {
             Expr::Assign {
-                uuid,
+                id,
                 name: _name,
                 value: _value,
-            } => uuid,
+            } => id,
+            Expr::Await {
+                id,
+                keyword: _keyword,
+                value: _value,
+            } => id,
             Expr::Binary {
-                uuid,
+                id,
                 left: _left,
                 operator: _operator,
                 right: _right,
-            } => uuid,
+            } => id,
             Expr::Call {
-                uuid,
+                id,
                 paren: _paren,
                 callee: _callee,
                 arguments: _arguments,
-            } => uuid,
+            } => id,
             Expr::Get {
-                uuid,
+                id,
                 object: _object,
                 name: _name,
-            } => uuid,
+            } => id,
             Expr::Grouping {
-                uuid,
+                id,
                 expression: _expression,
-            } => uuid,
-            Expr::Literal {
-                uuid,
+            } => id,
+            Expr::Index {
+                id,
+                object: _object,
+                bracket: _bracket,
+                index: _index,
+            } => id,
+            Expr::IndexSet {
+                id,
+                object: _object,
+                bracket: _bracket,
+                index: _index,
                 value: _value,
-            } => uuid,
+            } => id,
+            Expr::Lambda {
+                id,
+                function: _function,
+            } => id,
+            Expr::List {
+                id,
+                bracket: _bracket,
+                elements: _elements,
+            } => id,
+            Expr::Literal { id, value: _value } => id,
             Expr::Logical {
-                uuid,
+                id,
                 left: _left,
                 operator: _operator,
                 right: _right,
-            } => uuid,
+            } => id,
+            Expr::Map {
+                id,
+                brace: _brace,
+                entries: _entries,
+            } => id,
             Expr::Set {
-                uuid,
+                id,
                 object: _object,
                 name: _name,
                 value: _value,
-            } => uuid,
+            } => id,
+            Expr::Stringify {
+                id,
+                expression: _expression,
+            } => id,
             Expr::Super {
-                uuid,
+                id,
+                this_id: _this_id,
                 keyword: _keyword,
                 method: _method,
-            } => uuid,
+            } => id,
+            Expr::Ternary {
+                id,
+                condition: _condition,
+                question: _question,
+                then_branch: _then_branch,
+                else_branch: _else_branch,
+            } => id,
             Expr::This {
-                uuid,
+                id,
                 keyword: _keyword,
-            } => uuid,
+            } => id,
             Expr::Unary {
-                uuid,
+                id,
                 operator: _operator,
                 right: _right,
-            } => uuid,
-            Expr::Variable { uuid, name: _name } => uuid,
+            } => id,
+            Expr::Update {
+                id,
+                operator: _operator,
+                target: _target,
+                prefix: _prefix,
+            } => id,
+            Expr::Variable { id, name: _name } => id,
+            Expr::Yield {
+                id,
+                keyword: _keyword,
+                value: _value,
+            } => id,
         }
     }
 }
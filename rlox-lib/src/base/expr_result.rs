@@ -1,22 +1,40 @@
+use crate::base::ast::Body;
 use crate::base::scanner::Token;
-use crate::base::stmt::Stmt;
 use crate::interpreter::environment::Environment;
+use crate::interpreter::generator::{LoxCoroutine, LoxGenerator};
+use crate::interpreter::heap;
 use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::interrupt::InterruptHandle;
 use crate::interpreter::runtime_error::RuntimeError;
+use crate::interpreter::suggestion;
+use crate::interpreter::transfer::Transfer;
 use std::cell::RefCell;
-use std::collections::HashMap;
-use std::fmt::Display;
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::fmt::{Debug, Display};
 use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Clone, Debug, Default, Error, PartialEq)]
 pub enum ExprResult {
     Number(f64),
-    String(String),
+    String(Rc<str>),
     Boolean(bool),
-    Function(LoxFunction),
-    Class(LoxClass),
+    Function(Rc<LoxFunction>),
+    NativeFunction(NativeFunction),
+    Class(Rc<LoxClass>),
+    Trait(LoxTrait),
     Instance(LoxInstance),
+    List(LoxList),
+    Map(LoxMap),
+    Task(LoxTask),
+    Channel(LoxChannel),
+    Promise(LoxPromise),
+    Generator(LoxGenerator),
+    Coroutine(LoxCoroutine),
     #[default]
     None,
 }
@@ -27,7 +45,7 @@ impl ExprResult {
     }
 
     pub fn string(value: String) -> Self {
-        ExprResult::String(value)
+        ExprResult::String(value.into())
     }
 
     pub fn boolean(value: bool) -> Self {
@@ -35,21 +53,67 @@ impl ExprResult {
     }
 
     pub fn function(function: LoxFunction) -> Self {
-        ExprResult::Function(function)
+        ExprResult::Function(Rc::new(function))
+    }
+
+    pub fn native_function(function: NativeFunction) -> Self {
+        ExprResult::NativeFunction(function)
     }
 
     pub fn class(class: LoxClass) -> Self {
-        ExprResult::Class(class)
+        ExprResult::Class(Rc::new(class))
+    }
+
+    pub fn trait_value(lox_trait: LoxTrait) -> Self {
+        ExprResult::Trait(lox_trait)
     }
 
     pub fn instance(instance: LoxInstance) -> Self {
         ExprResult::Instance(instance)
     }
 
+    pub fn list(list: LoxList) -> Self {
+        ExprResult::List(list)
+    }
+
+    pub fn map(map: LoxMap) -> Self {
+        ExprResult::Map(map)
+    }
+
+    pub fn task(task: LoxTask) -> Self {
+        ExprResult::Task(task)
+    }
+
+    pub fn channel(channel: LoxChannel) -> Self {
+        ExprResult::Channel(channel)
+    }
+
+    pub fn promise(promise: LoxPromise) -> Self {
+        ExprResult::Promise(promise)
+    }
+
+    pub fn generator(generator: LoxGenerator) -> Self {
+        ExprResult::Generator(generator)
+    }
+
+    pub fn coroutine(coroutine: LoxCoroutine) -> Self {
+        ExprResult::Coroutine(coroutine)
+    }
+
     pub fn none() -> Self {
         ExprResult::None
     }
 
+    /// Returns the value as a callable, if it is a function or class.
+    pub fn as_callable(&self) -> Option<&dyn Callable> {
+        match self {
+            ExprResult::Function(function) => Some(function.as_ref()),
+            ExprResult::NativeFunction(function) => Some(function),
+            ExprResult::Class(class) => Some(class),
+            _ => None,
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match *self {
             ExprResult::Boolean(value) => value,
@@ -59,47 +123,159 @@ impl ExprResult {
     }
 }
 
+/// Formats a number the way it is printed by scripts. Integral numbers print without a
+/// fractional part, and very large or small magnitudes use scientific notation instead of
+/// spelling out every digit.
+pub fn format_number(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if value != 0.0 && !(1e-7..1e21).contains(&value.abs()) {
+        format!("{value:e}")
+    } else {
+        value.to_string()
+    }
+}
+
 impl Display for ExprResult {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let result = match self {
-            ExprResult::Number(value) => value.to_string(),
+        write!(f, "{}", self.format_plain(false))
+    }
+}
+
+impl ExprResult {
+    /// Formats a value contained in a list or map, where strings are quoted.
+    pub(crate) fn to_element_string(&self) -> String {
+        self.format_plain(true)
+    }
+
+    /// Formats the value like [`Display`], but formats instances, also those contained in lists
+    /// and maps, with `instance`, e.g. by calling a user-defined `toString` method. Instances
+    /// for which `instance` returns `None` are formatted as usual.
+    pub(crate) fn format_with<E>(
+        &self,
+        instance: &mut impl FnMut(&LoxInstance) -> Result<Option<String>, E>,
+    ) -> Result<String, E> {
+        self.format(false, &mut Vec::new(), instance)
+    }
+
+    fn format_plain(&self, element: bool) -> String {
+        let Ok(formatted) =
+            self.format(element, &mut Vec::new(), &mut |_| Ok::<_, Infallible>(None));
+        formatted
+    }
+
+    /// Formats the value, quoting strings if it is an `element` of a list or map. `enclosing`
+    /// holds the lists and maps being formatted that the value is contained in, so that a list
+    /// or map containing itself is printed as `[...]` or `{...}` instead of recursing forever.
+    fn format<E>(
+        &self,
+        element: bool,
+        enclosing: &mut Vec<*const ()>,
+        instance: &mut impl FnMut(&LoxInstance) -> Result<Option<String>, E>,
+    ) -> Result<String, E> {
+        Ok(match self {
+            ExprResult::Number(value) => format_number(*value),
+            ExprResult::String(value) if element => format!("{:?}", value),
             ExprResult::String(value) => value.to_string(),
             ExprResult::Boolean(value) => value.to_string(),
             ExprResult::Function(function) => format!("<fn {}>", function.name.lexeme),
+            ExprResult::NativeFunction(_) => String::from("<native fn>"),
             ExprResult::Class(class) => class.name.lexeme.to_string(),
-            ExprResult::Instance(instance) => format!("{} instance", instance.class.name.lexeme),
-            ExprResult::None => String::from("nil"),
-        };
+            ExprResult::Trait(lox_trait) => lox_trait.name.lexeme.to_string(),
+            ExprResult::Instance(lox_instance) => match instance(lox_instance)? {
+                Some(formatted) => formatted,
+                None => format!("{} instance", lox_instance.class.name.lexeme),
+            },
+            ExprResult::List(list) => {
+                let id = Rc::as_ptr(&list.elements) as *const ();
+                if enclosing.contains(&id) {
+                    return Ok(String::from("[...]"));
+                }
+
+                /* formatting an instance may run code that modifies the list. */
+                let elements = list.elements.borrow().clone();
+                enclosing.push(id);
+                let elements = elements
+                    .iter()
+                    .map(|element| element.format(true, enclosing, instance))
+                    .collect::<Result<Vec<_>, _>>();
+                enclosing.pop();
+                format!("[{}]", elements?.join(", "))
+            }
+            ExprResult::Map(map) => {
+                let id = Rc::as_ptr(&map.entries) as *const ();
+                if enclosing.contains(&id) {
+                    return Ok(String::from("{...}"));
+                }
 
-        write!(f, "{}", result)
+                let entries = map.entries.borrow().clone();
+                enclosing.push(id);
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = key.format(true, enclosing, instance)?;
+                        Ok(format!(
+                            "{}: {}",
+                            key,
+                            value.format(true, enclosing, instance)?
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>();
+                enclosing.pop();
+                format!("{{{}}}", entries?.join(", "))
+            }
+            ExprResult::Task(_) => String::from("<task>"),
+            ExprResult::Channel(_) => String::from("<channel>"),
+            ExprResult::Promise(_) => String::from("<promise>"),
+            ExprResult::Generator(generator) => format!("<generator {}>", generator.name()),
+            ExprResult::Coroutine(_) => String::from("<coroutine>"),
+            ExprResult::None => String::from("nil"),
+        })
     }
 }
 
 pub trait Callable {
     fn arity(&self) -> usize;
+    /// Whether the callable accepts any number of arguments beyond its arity.
+    fn is_variadic(&self) -> bool {
+        false
+    }
+    /// Whether the callable can be called with `count` arguments.
+    fn accepts(&self, count: usize) -> bool {
+        count == self.arity() || (self.is_variadic() && count > self.arity())
+    }
     fn call(
         &self,
         interpreter: &Interpreter,
+        paren: &Token,
         arguments: &[ExprResult],
     ) -> Result<ExprResult, RuntimeError>;
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct LoxFunction {
-    name: Token,
-    params: Vec<Token>,
-    body: Vec<Stmt>,
-    closure: Rc<RefCell<Environment>>,
-    is_initializer: bool,
+    pub(crate) name: Token,
+    pub(crate) params: Arc<[Token]>,
+    pub(crate) body: Body,
+    pub(crate) closure: Rc<RefCell<Environment>>,
+    pub(crate) is_initializer: bool,
+    pub(crate) is_async: bool,
+    pub(crate) is_variadic: bool,
+    pub(crate) is_generator: bool,
+    pub(crate) compiled: Option<CompiledFunction>,
 }
 
 impl LoxFunction {
     pub fn new(
         name: Token,
-        params: Vec<Token>,
-        body: Vec<Stmt>,
+        params: Arc<[Token]>,
+        body: Body,
         closure: Rc<RefCell<Environment>>,
         is_initializer: bool,
+        is_async: bool,
+        is_variadic: bool,
     ) -> Self {
         Self {
             name,
@@ -107,9 +283,26 @@ impl LoxFunction {
             body,
             closure,
             is_initializer,
+            is_async,
+            is_variadic,
+            is_generator: false,
+            compiled: None,
         }
     }
 
+    /// Marks the function as a generator function, whose calls return a [`LoxGenerator`]
+    /// instead of executing the body.
+    pub fn with_generator(mut self, is_generator: bool) -> Self {
+        self.is_generator = is_generator;
+        self
+    }
+
+    /// Lets calls with numbers as arguments run the compiled function instead of the body.
+    pub fn with_compiled(mut self, compiled: Option<CompiledFunction>) -> Self {
+        self.compiled = compiled;
+        self
+    }
+
     pub fn bind(&self, instance: &LoxInstance) -> ExprResult {
         let environment = Environment::new_enclosing(Rc::clone(&self.closure));
 
@@ -117,47 +310,73 @@ impl LoxFunction {
             .borrow_mut()
             .define("this", ExprResult::instance(instance.to_owned()));
 
-        ExprResult::function(LoxFunction::new(
-            self.name.to_owned(),
-            self.params.to_owned(),
-            self.body.to_owned(),
-            environment,
-            self.is_initializer,
-        ))
-    }
-}
-
-impl Callable for LoxFunction {
-    fn arity(&self) -> usize {
-        self.params.len()
+        ExprResult::function(
+            LoxFunction::new(
+                self.name.to_owned(),
+                Arc::clone(&self.params),
+                self.body.clone(),
+                environment,
+                self.is_initializer,
+                self.is_async,
+                self.is_variadic,
+            )
+            .with_generator(self.is_generator),
+        )
     }
 
-    fn call(
+    /// Creates the environment of a call, with the parameters bound to the arguments.
+    fn call_environment(
         &self,
         interpreter: &Interpreter,
         arguments: &[ExprResult],
-    ) -> Result<ExprResult, RuntimeError> {
-        let scoped_interpreter =
-            interpreter.fork(Environment::new_enclosing(Rc::clone(&self.closure)));
+    ) -> Result<Rc<RefCell<Environment>>, RuntimeError> {
+        heap::collect_if_due(interpreter.options.gc_stress);
+        interpreter.allocate(size_of::<Environment>())?;
+
+        let environment = Environment::new_enclosing(Rc::clone(&self.closure));
 
         for (i, token) in self.params.iter().enumerate() {
-            if let Some(argument) = arguments.get(i) {
-                scoped_interpreter.define(token, argument.clone());
+            let value = if self.is_variadic && i == self.arity() {
+                let rest = arguments.get(i..).unwrap_or_default().to_vec();
+                ExprResult::list(LoxList::new(rest))
+            } else if let Some(argument) = arguments.get(i) {
+                argument.clone()
             } else {
-                return Err(RuntimeError::InvalidArgument { line: token.line });
-            }
+                return Err(RuntimeError::InvalidArgument {
+                    line: token.line,
+                    column: token.column,
+                });
+            };
+            environment.borrow_mut().define(&token.lexeme, value);
         }
 
-        if let Err(e) = scoped_interpreter.execute_block(&self.body) {
+        Ok(environment)
+    }
+
+    /// Executes the function body, regardless of whether the function is async.
+    pub(crate) fn invoke(
+        &self,
+        interpreter: &Interpreter,
+        arguments: &[ExprResult],
+    ) -> Result<ExprResult, RuntimeError> {
+        let scoped_interpreter = interpreter.fork_in(
+            &self.body.ast,
+            self.call_environment(interpreter, arguments)?,
+        );
+
+        if let Err(e) = scoped_interpreter.execute_block(&self.body.statements) {
             return match e {
                 RuntimeError::Return { ret_val } => {
                     if self.is_initializer {
-                        Ok(self.closure.borrow().get_at(0, "this").unwrap())
+                        Ok(self.closure.borrow().values[0].get())
                     } else {
-                        Ok(ret_val)
+                        Ok(*ret_val)
                     }
                 }
-                _ => Err(e),
+                _ => match &self.name.file {
+                    Some(file) => Err(e.in_file(file)),
+                    None => Err(e),
+                },
             };
         }
 
@@ -165,29 +384,227 @@ impl Callable for LoxFunction {
     }
 }
 
+impl Callable for LoxFunction {
+    fn arity(&self) -> usize {
+        if self.is_variadic {
+            self.params.len() - 1
+        } else {
+            self.params.len()
+        }
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.is_variadic
+    }
+
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        paren: &Token,
+        arguments: &[ExprResult],
+    ) -> Result<ExprResult, RuntimeError> {
+        if let Some(result) = self.compiled.as_ref().and_then(|c| c.call(arguments)) {
+            Ok(ExprResult::Number(result))
+        } else if self.is_async {
+            Ok(ExprResult::promise(
+                interpreter.event_loop.borrow_mut().schedule(
+                    self.to_owned(),
+                    arguments.to_vec(),
+                    paren,
+                ),
+            ))
+        } else if self.is_generator {
+            let environment = self.call_environment(interpreter, arguments)?;
+            Ok(ExprResult::generator(LoxGenerator::new(
+                self.to_owned(),
+                environment,
+            )))
+        } else {
+            self.invoke(interpreter, arguments)
+        }
+    }
+}
+
+/// Machine code of a function that only computes with numbers. Returns `None` if the code gives
+/// up, e.g. on deep recursion, so that the body of the function runs instead.
+pub type NumericFn = dyn Fn(&[f64]) -> Option<f64>;
+
+#[derive(Clone)]
+pub struct CompiledFunction(Rc<NumericFn>);
+
+impl CompiledFunction {
+    pub fn new<F>(function: F) -> Self
+    where
+        F: Fn(&[f64]) -> Option<f64> + 'static,
+    {
+        Self(Rc::new(function))
+    }
+
+    /// Runs the compiled function, if all arguments are numbers.
+    fn call(&self, arguments: &[ExprResult]) -> Option<f64> {
+        let numbers = arguments
+            .iter()
+            .map(|argument| match argument {
+                ExprResult::Number(number) => Some(*number),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        (self.0)(&numbers)
+    }
+}
+
+impl Debug for CompiledFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompiledFunction").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for CompiledFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+pub type NativeFn = dyn Fn(&Interpreter, &Token, &[ExprResult]) -> Result<ExprResult, RuntimeError>;
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub(crate) name: String,
+    arity: usize,
+    is_variadic: bool,
+    function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&Interpreter, &Token, &[ExprResult]) -> Result<ExprResult, RuntimeError> + 'static,
+    {
+        Self {
+            name: name.to_owned(),
+            arity,
+            is_variadic: false,
+            function: Rc::new(function),
+        }
+    }
+
+    /// Creates a native function that accepts any number of arguments beyond its arity.
+    pub fn variadic<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&Interpreter, &Token, &[ExprResult]) -> Result<ExprResult, RuntimeError> + 'static,
+    {
+        Self {
+            is_variadic: true,
+            ..Self::new(name, arity, function)
+        }
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("is_variadic", &self.is_variadic)
+            .finish()
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.is_variadic
+    }
+
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        paren: &Token,
+        arguments: &[ExprResult],
+    ) -> Result<ExprResult, RuntimeError> {
+        let result = (self.function)(interpreter, paren, arguments)?;
+        interpreter.allocate_value(&result)?;
+
+        Ok(result)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct LoxClass {
-    name: Token,
-    superclass: Box<Option<LoxClass>>,
-    methods: HashMap<String, LoxFunction>,
+    pub(crate) name: Token,
+    pub(crate) superclass: Option<Rc<LoxClass>>,
+    /// Initializes the declared fields of a new instance, before `init` is called.
+    pub(crate) fields: Box<Option<LoxFunction>>,
+    pub(crate) methods: HashMap<String, LoxFunction>,
 }
 
 impl LoxClass {
     pub fn new(
         name: Token,
-        superclass: Option<LoxClass>,
+        superclass: Option<Rc<LoxClass>>,
+        fields: Option<LoxFunction>,
         methods: HashMap<String, LoxFunction>,
     ) -> Self {
         Self {
             name,
-            superclass: Box::new(superclass),
+            superclass,
+            fields: Box::new(fields),
             methods,
         }
     }
 
+    /// Runs the field initializers of the class and its superclasses, outermost first.
+    fn initialize_fields(
+        &self,
+        interpreter: &Interpreter,
+        instance: &LoxInstance,
+    ) -> Result<(), RuntimeError> {
+        if let Some(superclass) = self.superclass.as_ref() {
+            superclass.initialize_fields(interpreter, instance)?;
+        }
+
+        if let Some(fields) = self.fields.as_ref() {
+            if let ExprResult::Function(function) = fields.bind(instance) {
+                function.invoke(interpreter, &[])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the class is `class` or inherits from it. Classes are told apart by their
+    /// declaration.
+    pub fn is_subclass_of(&self, class: &LoxClass) -> bool {
+        self.name == class.name
+            || self
+                .superclass
+                .as_ref()
+                .is_some_and(|superclass| superclass.is_subclass_of(class))
+    }
+
+    /// Returns the names of the methods of the class, including inherited ones.
+    pub fn method_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.methods.keys().map(String::as_str).collect();
+        if let Some(sc) = self.superclass.as_ref() {
+            names.extend(sc.method_names());
+        }
+
+        names
+    }
+
     pub fn find_method(&self, name: &str) -> Option<&LoxFunction> {
         if self.methods.contains_key(name) {
-            self.methods.get(&name.to_string())
+            self.methods.get(name)
         } else if let Some(sc) = self.superclass.as_ref() {
             sc.find_method(name)
         } else {
@@ -196,7 +613,7 @@ impl LoxClass {
     }
 }
 
-impl Callable for LoxClass {
+impl Callable for Rc<LoxClass> {
     fn arity(&self) -> usize {
         if let Some(initializer) = self.find_method("init") {
             initializer.arity()
@@ -205,16 +622,24 @@ impl Callable for LoxClass {
         }
     }
 
+    fn is_variadic(&self) -> bool {
+        self.find_method("init")
+            .is_some_and(|initializer| initializer.is_variadic())
+    }
+
     fn call(
         &self,
         interpreter: &Interpreter,
+        paren: &Token,
         arguments: &[ExprResult],
     ) -> Result<ExprResult, RuntimeError> {
-        let instance = LoxInstance::new(self.to_owned());
+        interpreter.allocate(size_of::<LoxInstance>())?;
+        let instance = LoxInstance::new(Rc::clone(self));
+        self.initialize_fields(interpreter, &instance)?;
 
         if let Some(initializer) = self.find_method("init") {
             if let ExprResult::Function(function) = initializer.bind(&instance) {
-                function.call(interpreter, arguments)?;
+                function.call(interpreter, paren, arguments)?;
             }
         }
 
@@ -222,18 +647,31 @@ impl Callable for LoxClass {
     }
 }
 
+/// A set of methods that classes can include with `with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoxTrait {
+    pub(crate) name: Token,
+    pub(crate) methods: HashMap<String, LoxFunction>,
+}
+
+impl LoxTrait {
+    pub fn new(name: Token, methods: HashMap<String, LoxFunction>) -> Self {
+        Self { name, methods }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct LoxInstance {
-    class: LoxClass,
-    fields: Rc<RefCell<HashMap<String, ExprResult>>>,
+    pub(crate) class: Rc<LoxClass>,
+    pub(crate) fields: Rc<RefCell<HashMap<String, ExprResult>>>,
 }
 
 impl LoxInstance {
-    pub fn new(class: LoxClass) -> Self {
-        Self {
-            class,
-            fields: Rc::new(RefCell::new(HashMap::new())),
-        }
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        let fields = Rc::new(RefCell::new(HashMap::new()));
+        heap::track_instance(&fields);
+
+        Self { class, fields }
     }
 
     pub fn get(&self, name: &Token) -> Result<ExprResult, RuntimeError> {
@@ -242,7 +680,16 @@ impl LoxInstance {
         } else if let Some(method) = self.class.find_method(&name.lexeme) {
             Ok(method.bind(self))
         } else {
-            Err(RuntimeError::UndefinedProperty { line: name.line })
+            let fields = self.fields.borrow();
+            let candidates = fields
+                .keys()
+                .map(String::as_str)
+                .chain(self.class.method_names());
+            Err(RuntimeError::UndefinedProperty {
+                line: name.line,
+                column: name.column,
+                suggestion: suggestion::closest(&name.lexeme, candidates),
+            })
         }
     }
 
@@ -252,3 +699,221 @@ impl LoxInstance {
             .insert(name.lexeme.to_owned(), value);
     }
 }
+
+/// A growable sequence of values, shared by reference and compared by identity.
+#[derive(Clone, Debug, Default)]
+pub struct LoxList {
+    pub(crate) elements: Rc<RefCell<Vec<ExprResult>>>,
+}
+
+impl LoxList {
+    pub fn new(elements: Vec<ExprResult>) -> Self {
+        Self {
+            elements: Rc::new(RefCell::new(elements)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.borrow().is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<ExprResult> {
+        self.elements.borrow().get(index).cloned()
+    }
+
+    /// Replaces the element at `index`. Returns `false` if the index is out of bounds.
+    pub fn set(&self, index: usize, value: ExprResult) -> bool {
+        match self.elements.borrow_mut().get_mut(index) {
+            Some(element) => {
+                *element = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a copy of the elements, so that the list can be changed while iterating.
+    pub fn to_vec(&self) -> Vec<ExprResult> {
+        self.elements.borrow().to_owned()
+    }
+}
+
+impl PartialEq for LoxList {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.elements, &other.elements)
+    }
+}
+
+/// A mapping of keys to values that keeps the insertion order, shared by reference and compared
+/// by identity. Keys are compared by equality, like with `==`.
+#[derive(Clone, Debug, Default)]
+pub struct LoxMap {
+    pub(crate) entries: Rc<RefCell<Vec<(ExprResult, ExprResult)>>>,
+}
+
+impl LoxMap {
+    pub fn new(entries: Vec<(ExprResult, ExprResult)>) -> Self {
+        let map = Self::default();
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+
+        map
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn get(&self, key: &ExprResult) -> Option<ExprResult> {
+        self.entries
+            .borrow()
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.to_owned())
+    }
+
+    pub fn insert(&self, key: ExprResult, value: ExprResult) {
+        let mut entries = self.entries.borrow_mut();
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => entries.push((key, value)),
+        }
+    }
+
+    /// Returns a copy of the keys in insertion order.
+    pub fn keys(&self) -> Vec<ExprResult> {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|(key, _)| key.to_owned())
+            .collect()
+    }
+}
+
+impl PartialEq for LoxMap {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.entries, &other.entries)
+    }
+}
+
+pub type TaskResult = Result<(Transfer, Vec<u8>), TaskError>;
+
+/// The reason a task ended without a result.
+#[derive(Debug)]
+pub enum TaskError {
+    /// The task stopped with a runtime error.
+    Failed(String),
+    /// The task called `exit`, after printing `output`.
+    Exit { code: i32, output: Vec<u8> },
+}
+
+#[derive(Clone, Debug)]
+pub struct LoxTask {
+    handle: Rc<RefCell<Option<JoinHandle<TaskResult>>>>,
+}
+
+impl LoxTask {
+    pub fn new(handle: JoinHandle<TaskResult>) -> Self {
+        Self {
+            handle: Rc::new(RefCell::new(Some(handle))),
+        }
+    }
+
+    /// Takes the thread handle out of the task, so that a task can only be joined once.
+    pub fn take(&self) -> Option<JoinHandle<TaskResult>> {
+        self.handle.borrow_mut().take()
+    }
+}
+
+impl PartialEq for LoxTask {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.handle, &other.handle)
+    }
+}
+
+/// An unbounded queue of deep-copied values shared between tasks.
+#[derive(Clone, Debug, Default)]
+pub struct LoxChannel {
+    queue: Arc<(Mutex<VecDeque<Transfer>>, Condvar)>,
+}
+
+impl LoxChannel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(&self, value: Transfer) {
+        let (queue, available) = &*self.queue;
+        queue.lock().unwrap().push_back(value);
+        available.notify_one();
+    }
+
+    /// Blocks until a value is available. Returns `None` if interrupted while waiting.
+    pub fn recv(&self, interrupt: &InterruptHandle) -> Option<Transfer> {
+        let (queue, available) = &*self.queue;
+        let mut queue = queue.lock().unwrap();
+        loop {
+            if let Some(value) = queue.pop_front() {
+                return Some(value);
+            }
+
+            if interrupt.is_interrupted() {
+                return None;
+            }
+
+            queue = available
+                .wait_timeout(queue, Duration::from_millis(10))
+                .unwrap()
+                .0;
+        }
+    }
+}
+
+impl PartialEq for LoxChannel {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.queue, &other.queue)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PromiseState {
+    #[default]
+    Pending,
+    Resolved(ExprResult),
+    Rejected(String),
+}
+
+/// The eventual result of an async function call or an async native.
+#[derive(Clone, Debug, Default)]
+pub struct LoxPromise {
+    state: Rc<RefCell<PromiseState>>,
+}
+
+impl LoxPromise {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> PromiseState {
+        self.state.borrow().to_owned()
+    }
+
+    pub fn settle(&self, state: PromiseState) {
+        self.state.replace(state);
+    }
+}
+
+impl PartialEq for LoxPromise {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
@@ -1,6 +1,10 @@
+pub mod ast;
+pub mod diagnostic;
 pub mod expr;
 pub mod expr_result;
 pub mod parser;
+pub mod program;
 pub mod scanner;
+pub mod source;
 pub mod stmt;
 pub mod visitor;
@@ -1,7 +1,10 @@
+use crate::base::ast::{Ast, ExprRef, StmtRef};
+use crate::base::diagnostic::DiagnosticError;
 use crate::base::expr::{Expr, LiteralValue};
 use crate::base::scanner::{Token, TokenType};
-use crate::base::stmt::Stmt;
-use std::cell::RefCell;
+use crate::base::stmt::{Pattern, Stmt};
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -9,162 +12,882 @@ pub enum ParserError {
     #[error("Error while reading token.")]
     TokenReadError,
     #[error("{line:?}: Unknown token detected.")]
-    MissingExpression { line: usize },
+    MissingExpression { line: usize, column: usize },
     #[error("{line:?}: Expect '(' after function name.")]
-    MissingLeftParenthesisAfterFunctionName { line: usize },
+    MissingLeftParenthesisAfterFunctionName { line: usize, column: usize },
     #[error("{line:?}: Expect '(' after 'if' statement.")]
-    MissingLeftParenthesisAfterIfStatement { line: usize },
+    MissingLeftParenthesisAfterIfStatement { line: usize, column: usize },
     #[error("{line:?}: Expect '(' after 'while' statement.")]
-    MissingLeftParenthesisAfterWhileStatement { line: usize },
+    MissingLeftParenthesisAfterWhileStatement { line: usize, column: usize },
     #[error("{line:?}: Expect '(' after 'for' statement.")]
-    MissingLeftParenthesisAfterForStatement { line: usize },
-    #[error("{line:?}: Expect '{{' before function body.")]
-    MissingLeftBraceBeforeFunctionBody { line: usize },
+    MissingLeftParenthesisAfterForStatement { line: usize, column: usize },
+    #[error("{line:?}: Expect '{{' or '->' before function body.")]
+    MissingLeftBraceBeforeFunctionBody { line: usize, column: usize },
     #[error("{line:?}: Expect '{{' before class body.")]
-    MissingLeftBraceBeforeClassBody { line: usize },
+    MissingLeftBraceBeforeClassBody { line: usize, column: usize },
     #[error("{line:?}: Expect ')' after expression.")]
-    MissingRightParenthesisAfterExpression { line: usize },
+    MissingRightParenthesisAfterExpression { line: usize, column: usize },
     #[error("{line:?}: Expect ')' after condition.")]
-    MissingRightParenthesisAfterCondition { line: usize },
+    MissingRightParenthesisAfterCondition { line: usize, column: usize },
     #[error("{line:?}: Expect ')' after 'for' statement.")]
-    MissingRightParenthesisAfterForStatement { line: usize },
+    MissingRightParenthesisAfterForStatement { line: usize, column: usize },
     #[error("{line:?}: Expect ')' after parameters.")]
-    MissingRightParenthesisAfterParameters { line: usize },
+    MissingRightParenthesisAfterParameters { line: usize, column: usize },
+    #[error("{line:?}: Expect ']' after index.")]
+    MissingRightBracketAfterIndex { line: usize, column: usize },
+    #[error("{line:?}: Expect ']' after list elements.")]
+    MissingRightBracketAfterElements { line: usize, column: usize },
+    #[error("{line:?}: Expect ':' after map key.")]
+    MissingColonAfterKey { line: usize, column: usize },
+    #[error("{line:?}: Expect '}}' after map entries.")]
+    MissingRightBraceAfterEntries { line: usize, column: usize },
+    #[error("{line:?}: Expect ')' after 'for' iterable.")]
+    MissingRightParenthesisAfterIterable { line: usize, column: usize },
     #[error("{line:?}: Expect ')' after arguments.")]
-    MissingRightParenthesisAfterArguments { line: usize },
+    MissingRightParenthesisAfterArguments { line: usize, column: usize },
     #[error("{line:?}: Expect '}}' after block.")]
-    MissingRightBraceAfterBlock { line: usize },
+    MissingRightBraceAfterBlock { line: usize, column: usize },
     #[error("{line:?}: Expect '}}' after class body.")]
-    MissingRightBraceAfterClassBody { line: usize },
+    MissingRightBraceAfterClassBody { line: usize, column: usize },
     #[error("{line:?}: Expect ';' after value.")]
-    MissingSemicolonAfterValue { line: usize },
+    MissingSemicolonAfterValue { line: usize, column: usize },
     #[error("{line:?}: Expect ';' after expression.")]
-    MissingSemicolonAfterExpression { line: usize },
+    MissingSemicolonAfterExpression { line: usize, column: usize },
+    #[error("{line:?}: Expect ';' after field initializer.")]
+    MissingSemicolonAfterField { line: usize, column: usize },
     #[error("{line:?}: Expect ';' after variable declaration.")]
-    MissingSemicolonAfterVariableDeclaration { line: usize },
+    MissingSemicolonAfterVariableDeclaration { line: usize, column: usize },
+    #[error("{line:?}: Expect ';' after 'break'.")]
+    MissingSemicolonAfterBreak { line: usize, column: usize },
     #[error("{line:?}: Expect ';' after loop condition.")]
-    MissingSemicolonAfterLoopCondition { line: usize },
+    MissingSemicolonAfterLoopCondition { line: usize, column: usize },
+    #[error("{line:?}: Expect module path after 'import'.")]
+    MissingModulePath { line: usize, column: usize },
+    #[error("{line:?}: Expect ';' after module path.")]
+    MissingSemicolonAfterModulePath { line: usize, column: usize },
+    #[error("{line:?}: Rest parameter must be the last parameter.")]
+    RestParameterNotLast { line: usize, column: usize },
+    #[error("{line:?}: Expect ';' after 'continue'.")]
+    MissingSemicolonAfterContinue { line: usize, column: usize },
+    #[error("{line:?}: Expect loop after label.")]
+    MissingLoopAfterLabel { line: usize, column: usize },
     #[error("{line:?}: Expect variable name.")]
-    MissingVariableName { line: usize },
+    MissingVariableName { line: usize, column: usize },
+    #[error("{line:?}: Expect ']' after variable names.")]
+    MissingRightBracketAfterNames { line: usize, column: usize },
+    #[error("{line:?}: Expect '}}' after variable names.")]
+    MissingRightBraceAfterNames { line: usize, column: usize },
+    #[error("{line:?}: Expect '=' after destructuring pattern.")]
+    MissingDestructuringInitializer { line: usize, column: usize },
+    #[error("{line:?}: Expect '=' after constant name.")]
+    MissingConstantInitializer { line: usize, column: usize },
     #[error("{line:?}: Expect function name.")]
-    MissingFunctionName { line: usize },
+    MissingFunctionName { line: usize, column: usize },
+    #[error("{line:?}: Expect 'fun' after 'async'.")]
+    MissingFunAfterAsync { line: usize, column: usize },
     #[error("{line:?}: Expect class name.")]
-    MissingClassName { line: usize },
+    MissingClassName { line: usize, column: usize },
     #[error("{line:?}: Expect function name.")]
-    MissingParameterName { line: usize },
+    MissingParameterName { line: usize, column: usize },
     #[error("{line:?}: Expect property name after '.'.")]
-    MissingPropertyName { line: usize },
+    MissingPropertyName { line: usize, column: usize },
+    #[error("{line:?}: Expect trait name.")]
+    MissingTraitName { line: usize, column: usize },
+    #[error("{line:?}: Expect '{{' before trait body.")]
+    MissingLeftBraceBeforeTraitBody { line: usize, column: usize },
+    #[error("{line:?}: Expect '}}' after trait body.")]
+    MissingRightBraceAfterTraitBody { line: usize, column: usize },
     #[error("{line:?}: Expect superclass name.")]
-    MissingSuperclassName { line: usize },
+    MissingSuperclassName { line: usize, column: usize },
     #[error("{line:?}: Expect '.' after 'super'.")]
-    MissingDotAfterSuper { line: usize },
+    MissingDotAfterSuper { line: usize, column: usize },
     #[error("{line:?}: Expect superclass method name.")]
-    MissingSuperclassMethodName { line: usize },
+    MissingSuperclassMethodName { line: usize, column: usize },
+    #[error("{line:?}: Expect ':' after then branch of conditional expression.")]
+    MissingColonInConditional { line: usize, column: usize },
+    #[error("{line:?}: Invalid increment or decrement target.")]
+    InvalidUpdateTarget { line: usize, column: usize },
+    #[error("{line:?}: Expect end of string after interpolated expression.")]
+    UnterminatedInterpolation { line: usize, column: usize },
     #[error("{line:?}: Invalid assignment target.")]
-    InvalidAssignmentTarget { line: usize },
+    InvalidAssignmentTarget { line: usize, column: usize },
+    #[error("{line:?}: Can't have more than 255 arguments.")]
+    TooManyArguments { line: usize, column: usize },
+    #[error("{line:?}: Can't have more than 255 parameters.")]
+    TooManyParameters { line: usize, column: usize },
+    #[error("{line:?}: Expect '=' to initialize variable, '==' compares values.")]
+    ComparisonInDeclaration { line: usize, column: usize },
+    #[error("{line:?}: Use '==' to compare, or parenthesize the assignment used as condition.")]
+    AssignmentInCondition { line: usize, column: usize },
+    #[error("{line:?}: Expect 'fun' before function declaration.")]
+    MissingFunBeforeFunction { line: usize, column: usize },
+    #[error("{line:?}: 'print' is a statement printing a single value, as in 'print a;'.")]
+    PrintCall { line: usize, column: usize },
+    #[error("{line:?}: Expressions and statements are nested too deeply.")]
+    TooDeeplyNested { line: usize, column: usize },
+    #[error("{line:?}: Expect end of expression.")]
+    UnexpectedTokenAfterExpression { line: usize, column: usize },
+}
+
+impl ParserError {
+    /// Returns the line and column of the token at which the error occurred.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            ParserError::TokenReadError => None,
+            ParserError::MissingExpression { line, column, .. }
+            | ParserError::MissingLeftParenthesisAfterFunctionName { line, column, .. }
+            | ParserError::MissingLeftParenthesisAfterIfStatement { line, column, .. }
+            | ParserError::MissingLeftParenthesisAfterWhileStatement { line, column, .. }
+            | ParserError::MissingLeftParenthesisAfterForStatement { line, column, .. }
+            | ParserError::MissingLeftBraceBeforeFunctionBody { line, column, .. }
+            | ParserError::MissingLeftBraceBeforeClassBody { line, column, .. }
+            | ParserError::MissingRightParenthesisAfterExpression { line, column, .. }
+            | ParserError::MissingRightParenthesisAfterCondition { line, column, .. }
+            | ParserError::MissingRightParenthesisAfterForStatement { line, column, .. }
+            | ParserError::MissingRightParenthesisAfterParameters { line, column, .. }
+            | ParserError::MissingRightBracketAfterIndex { line, column, .. }
+            | ParserError::MissingRightBracketAfterElements { line, column, .. }
+            | ParserError::MissingColonAfterKey { line, column, .. }
+            | ParserError::MissingRightBraceAfterEntries { line, column, .. }
+            | ParserError::MissingRightParenthesisAfterIterable { line, column, .. }
+            | ParserError::MissingRightParenthesisAfterArguments { line, column, .. }
+            | ParserError::MissingRightBraceAfterBlock { line, column, .. }
+            | ParserError::MissingRightBraceAfterClassBody { line, column, .. }
+            | ParserError::MissingSemicolonAfterValue { line, column, .. }
+            | ParserError::MissingSemicolonAfterExpression { line, column, .. }
+            | ParserError::MissingSemicolonAfterField { line, column, .. }
+            | ParserError::MissingSemicolonAfterVariableDeclaration { line, column, .. }
+            | ParserError::MissingSemicolonAfterBreak { line, column, .. }
+            | ParserError::MissingSemicolonAfterLoopCondition { line, column, .. }
+            | ParserError::MissingModulePath { line, column, .. }
+            | ParserError::MissingSemicolonAfterModulePath { line, column, .. }
+            | ParserError::RestParameterNotLast { line, column, .. }
+            | ParserError::MissingSemicolonAfterContinue { line, column, .. }
+            | ParserError::MissingLoopAfterLabel { line, column, .. }
+            | ParserError::MissingVariableName { line, column, .. }
+            | ParserError::MissingRightBracketAfterNames { line, column, .. }
+            | ParserError::MissingRightBraceAfterNames { line, column, .. }
+            | ParserError::MissingDestructuringInitializer { line, column, .. }
+            | ParserError::MissingConstantInitializer { line, column, .. }
+            | ParserError::MissingFunctionName { line, column, .. }
+            | ParserError::MissingFunAfterAsync { line, column, .. }
+            | ParserError::MissingClassName { line, column, .. }
+            | ParserError::MissingParameterName { line, column, .. }
+            | ParserError::MissingPropertyName { line, column, .. }
+            | ParserError::MissingTraitName { line, column, .. }
+            | ParserError::MissingLeftBraceBeforeTraitBody { line, column, .. }
+            | ParserError::MissingRightBraceAfterTraitBody { line, column, .. }
+            | ParserError::MissingSuperclassName { line, column, .. }
+            | ParserError::MissingDotAfterSuper { line, column, .. }
+            | ParserError::MissingSuperclassMethodName { line, column, .. }
+            | ParserError::MissingColonInConditional { line, column, .. }
+            | ParserError::InvalidUpdateTarget { line, column, .. }
+            | ParserError::UnterminatedInterpolation { line, column, .. }
+            | ParserError::InvalidAssignmentTarget { line, column, .. }
+            | ParserError::TooManyArguments { line, column, .. }
+            | ParserError::TooManyParameters { line, column, .. }
+            | ParserError::ComparisonInDeclaration { line, column, .. }
+            | ParserError::AssignmentInCondition { line, column, .. }
+            | ParserError::MissingFunBeforeFunction { line, column, .. }
+            | ParserError::PrintCall { line, column, .. }
+            | ParserError::TooDeeplyNested { line, column, .. }
+            | ParserError::UnexpectedTokenAfterExpression { line, column, .. } => {
+                Some((*line, *column))
+            }
+        }
+    }
+}
+
+impl DiagnosticError for ParserError {
+    fn code(&self) -> &'static str {
+        match self {
+            ParserError::TokenReadError => "TokenReadError",
+            ParserError::MissingExpression { .. } => "MissingExpression",
+            ParserError::MissingLeftParenthesisAfterFunctionName { .. } => {
+                "MissingLeftParenthesisAfterFunctionName"
+            }
+            ParserError::MissingLeftParenthesisAfterIfStatement { .. } => {
+                "MissingLeftParenthesisAfterIfStatement"
+            }
+            ParserError::MissingLeftParenthesisAfterWhileStatement { .. } => {
+                "MissingLeftParenthesisAfterWhileStatement"
+            }
+            ParserError::MissingLeftParenthesisAfterForStatement { .. } => {
+                "MissingLeftParenthesisAfterForStatement"
+            }
+            ParserError::MissingLeftBraceBeforeFunctionBody { .. } => {
+                "MissingLeftBraceBeforeFunctionBody"
+            }
+            ParserError::MissingLeftBraceBeforeClassBody { .. } => {
+                "MissingLeftBraceBeforeClassBody"
+            }
+            ParserError::MissingRightParenthesisAfterExpression { .. } => {
+                "MissingRightParenthesisAfterExpression"
+            }
+            ParserError::MissingRightParenthesisAfterCondition { .. } => {
+                "MissingRightParenthesisAfterCondition"
+            }
+            ParserError::MissingRightParenthesisAfterForStatement { .. } => {
+                "MissingRightParenthesisAfterForStatement"
+            }
+            ParserError::MissingRightParenthesisAfterParameters { .. } => {
+                "MissingRightParenthesisAfterParameters"
+            }
+            ParserError::MissingRightBracketAfterIndex { .. } => "MissingRightBracketAfterIndex",
+            ParserError::MissingRightBracketAfterElements { .. } => {
+                "MissingRightBracketAfterElements"
+            }
+            ParserError::MissingColonAfterKey { .. } => "MissingColonAfterKey",
+            ParserError::MissingRightBraceAfterEntries { .. } => "MissingRightBraceAfterEntries",
+            ParserError::MissingRightParenthesisAfterIterable { .. } => {
+                "MissingRightParenthesisAfterIterable"
+            }
+            ParserError::MissingRightParenthesisAfterArguments { .. } => {
+                "MissingRightParenthesisAfterArguments"
+            }
+            ParserError::MissingRightBraceAfterBlock { .. } => "MissingRightBraceAfterBlock",
+            ParserError::MissingRightBraceAfterClassBody { .. } => {
+                "MissingRightBraceAfterClassBody"
+            }
+            ParserError::MissingSemicolonAfterValue { .. } => "MissingSemicolonAfterValue",
+            ParserError::MissingSemicolonAfterExpression { .. } => {
+                "MissingSemicolonAfterExpression"
+            }
+            ParserError::MissingSemicolonAfterField { .. } => "MissingSemicolonAfterField",
+            ParserError::MissingSemicolonAfterVariableDeclaration { .. } => {
+                "MissingSemicolonAfterVariableDeclaration"
+            }
+            ParserError::MissingSemicolonAfterBreak { .. } => "MissingSemicolonAfterBreak",
+            ParserError::MissingSemicolonAfterLoopCondition { .. } => {
+                "MissingSemicolonAfterLoopCondition"
+            }
+            ParserError::MissingModulePath { .. } => "MissingModulePath",
+            ParserError::MissingSemicolonAfterModulePath { .. } => {
+                "MissingSemicolonAfterModulePath"
+            }
+            ParserError::RestParameterNotLast { .. } => "RestParameterNotLast",
+            ParserError::MissingSemicolonAfterContinue { .. } => "MissingSemicolonAfterContinue",
+            ParserError::MissingLoopAfterLabel { .. } => "MissingLoopAfterLabel",
+            ParserError::MissingVariableName { .. } => "MissingVariableName",
+            ParserError::MissingRightBracketAfterNames { .. } => "MissingRightBracketAfterNames",
+            ParserError::MissingRightBraceAfterNames { .. } => "MissingRightBraceAfterNames",
+            ParserError::MissingDestructuringInitializer { .. } => {
+                "MissingDestructuringInitializer"
+            }
+            ParserError::MissingConstantInitializer { .. } => "MissingConstantInitializer",
+            ParserError::MissingFunctionName { .. } => "MissingFunctionName",
+            ParserError::MissingFunAfterAsync { .. } => "MissingFunAfterAsync",
+            ParserError::MissingClassName { .. } => "MissingClassName",
+            ParserError::MissingParameterName { .. } => "MissingParameterName",
+            ParserError::MissingPropertyName { .. } => "MissingPropertyName",
+            ParserError::MissingTraitName { .. } => "MissingTraitName",
+            ParserError::MissingLeftBraceBeforeTraitBody { .. } => {
+                "MissingLeftBraceBeforeTraitBody"
+            }
+            ParserError::MissingRightBraceAfterTraitBody { .. } => {
+                "MissingRightBraceAfterTraitBody"
+            }
+            ParserError::MissingSuperclassName { .. } => "MissingSuperclassName",
+            ParserError::MissingDotAfterSuper { .. } => "MissingDotAfterSuper",
+            ParserError::MissingSuperclassMethodName { .. } => "MissingSuperclassMethodName",
+            ParserError::MissingColonInConditional { .. } => "MissingColonInConditional",
+            ParserError::InvalidUpdateTarget { .. } => "InvalidUpdateTarget",
+            ParserError::UnterminatedInterpolation { .. } => "UnterminatedInterpolation",
+            ParserError::InvalidAssignmentTarget { .. } => "InvalidAssignmentTarget",
+            ParserError::TooManyArguments { .. } => "TooManyArguments",
+            ParserError::TooManyParameters { .. } => "TooManyParameters",
+            ParserError::ComparisonInDeclaration { .. } => "ComparisonInDeclaration",
+            ParserError::AssignmentInCondition { .. } => "AssignmentInCondition",
+            ParserError::MissingFunBeforeFunction { .. } => "MissingFunBeforeFunction",
+            ParserError::PrintCall { .. } => "PrintCall",
+            ParserError::TooDeeplyNested { .. } => "TooDeeplyNested",
+            ParserError::UnexpectedTokenAfterExpression { .. } => "UnexpectedTokenAfterExpression",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.location().map(|(line, _)| line)
+    }
+
+    fn column(&self) -> Option<usize> {
+        self.location().map(|(_, column)| column)
+    }
+}
+
+/// Maximum number of arguments of a call and parameters of a function.
+const MAX_ARITY: usize = 255;
+
+/// Default maximum nesting depth of expressions and statements, which keeps the recursive
+/// descent of the parser and of the backends well within the stack. Chains of left-associative
+/// operators, calls and property accesses count one level per operator.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// How tightly an infix operator binds its operands, from the loosest to the tightest.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum Precedence {
+    Conditional,
+    Or,
+    And,
+    Equality,
+    Comparison,
+    Term,
+    Factor,
+    Unary,
+}
+
+impl Precedence {
+    /// Returns the next tighter precedence.
+    fn next(self) -> Self {
+        match self {
+            Precedence::Conditional => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Term,
+            Precedence::Term => Precedence::Factor,
+            Precedence::Factor | Precedence::Unary => Precedence::Unary,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Associativity {
+    Left,
+    Right,
+}
+
+/// The expression an infix operator forms with its operands.
+#[derive(Clone, Copy, Debug)]
+enum Infix {
+    Binary,
+    Logical,
+    /// `condition ? then : else`, which parses the then branch up to the `:`.
+    Conditional,
+}
+
+struct InfixRule {
+    token_type: TokenType,
+    precedence: Precedence,
+    associativity: Associativity,
+    kind: Infix,
+}
+
+/// A left-associative operator forming a binary expression.
+const fn binary(token_type: TokenType, precedence: Precedence) -> InfixRule {
+    InfixRule {
+        token_type,
+        precedence,
+        associativity: Associativity::Left,
+        kind: Infix::Binary,
+    }
+}
+
+/// A left-associative operator forming a logical expression, which short-circuits.
+const fn logical(token_type: TokenType, precedence: Precedence) -> InfixRule {
+    InfixRule {
+        token_type,
+        precedence,
+        associativity: Associativity::Left,
+        kind: Infix::Logical,
+    }
+}
+
+/// The infix operators of expressions. A new operator only needs an entry here, and a token in
+/// the scanner.
+const INFIX_RULES: &[InfixRule] = &[
+    InfixRule {
+        token_type: TokenType::Question,
+        precedence: Precedence::Conditional,
+        associativity: Associativity::Right,
+        kind: Infix::Conditional,
+    },
+    logical(TokenType::Or, Precedence::Or),
+    logical(TokenType::And, Precedence::And),
+    binary(TokenType::BangEqual, Precedence::Equality),
+    binary(TokenType::EqualEqual, Precedence::Equality),
+    binary(TokenType::Greater, Precedence::Comparison),
+    binary(TokenType::GreaterEqual, Precedence::Comparison),
+    binary(TokenType::Less, Precedence::Comparison),
+    binary(TokenType::LessEqual, Precedence::Comparison),
+    binary(TokenType::Is, Precedence::Comparison),
+    binary(TokenType::Minus, Precedence::Term),
+    binary(TokenType::Plus, Precedence::Term),
+    binary(TokenType::Slash, Precedence::Factor),
+    binary(TokenType::Star, Precedence::Factor),
+];
+
+/// The result of parsing a whole script, with the errors of all statements that failed to
+/// parse. The tree contains the statements that parsed successfully.
+#[derive(Debug)]
+pub struct ParseOutcome {
+    pub ast: Arc<Ast>,
+    pub errors: Vec<ParserError>,
+    /// Whether the first error occurred at the end of the input, as in `fun f() {`, so that
+    /// more input might complete the script.
+    pub incomplete: bool,
+}
+
+/// A single parsed expression, like `1 + 2 * x`, whose nodes are allocated in `ast`.
+#[derive(Debug)]
+pub struct ParsedExpression {
+    pub ast: Arc<Ast>,
+    pub expr: ExprRef,
 }
 
 pub struct Parser {
     tokens: Vec<Token>,
-    current: RefCell<usize>,
+    current: Cell<usize>,
+    ast: RefCell<Ast>,
+    errors: RefCell<Vec<ParserError>>,
+    /// Number of blocks enclosing the current token.
+    blocks: Cell<usize>,
+    /// Whether the first error occurred at the end of the input.
+    incomplete: Cell<bool>,
+    /// Number of nested expressions and statements enclosing the current token.
+    depth: Cell<usize>,
+    max_depth: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         Parser {
             tokens,
-            current: RefCell::new(0),
+            current: Cell::new(0),
+            ast: RefCell::new(Ast::default()),
+            errors: RefCell::new(vec![]),
+            incomplete: Cell::new(false),
+            blocks: Cell::new(0),
+            depth: Cell::new(0),
+            max_depth: DEFAULT_MAX_DEPTH,
         }
     }
-    pub fn parse(&self) -> Result<Vec<Stmt>, ParserError> {
+
+    /// Sets the maximum nesting depth of expressions and statements, past which parsing fails
+    /// with `ParserError::TooDeeplyNested` instead of overflowing the stack.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Parses the script. Returns the first error, if any statement failed to parse.
+    pub fn parse(&self) -> Result<Arc<Ast>, ParserError> {
+        let outcome = self.parse_all();
+        match outcome.errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(outcome.ast),
+        }
+    }
+
+    /// Parses the script, skipping to the next statement after each error, so that all
+    /// errors of the script are reported at once.
+    pub fn parse_all(&self) -> ParseOutcome {
         let mut statements = vec![];
 
-        while !self.is_at_end()? {
-            statements.push(self.declaration()?)
+        loop {
+            match self.is_at_end() {
+                Ok(false) => {}
+                Ok(true) => break,
+                Err(error) => {
+                    self.record_error(error);
+                    break;
+                }
+            }
+
+            if let Some(declaration) = self.declaration_or_recover() {
+                statements.push(self.add_stmt(declaration))
+            }
         }
 
-        Ok(statements)
+        let mut ast = self.ast.take();
+        ast.set_statements(statements);
+        ParseOutcome {
+            ast: Arc::new(ast),
+            errors: self.errors.take(),
+            incomplete: self.incomplete.take(),
+        }
+    }
+
+    /// Runs `parse` one nesting level deeper, failing if that exceeds the maximum depth.
+    fn nested<T>(&self, parse: impl FnOnce() -> Result<T, ParserError>) -> Result<T, ParserError> {
+        self.chain(|| {
+            self.deepen()?;
+            parse()
+        })
+    }
+
+    /// Runs `parse`, which calls [`Parser::deepen`] for each operator of a chain like
+    /// `a + b + c` or `f()()`, and restores the nesting depth afterwards. Left-associative
+    /// chains are parsed in a loop, but nest as deeply as they are long.
+    fn chain<T>(&self, parse: impl FnOnce() -> Result<T, ParserError>) -> Result<T, ParserError> {
+        let depth = self.depth.get();
+        let result = parse();
+        self.depth.set(depth);
+        result
+    }
+
+    /// Goes one nesting level deeper, failing if that exceeds the maximum depth.
+    fn deepen(&self) -> Result<(), ParserError> {
+        if self.depth.get() >= self.max_depth {
+            return Err(ParserError::TooDeeplyNested {
+                line: self.peek()?.line,
+                column: self.peek()?.column,
+            });
+        }
+
+        self.depth.set(self.depth.get() + 1);
+        Ok(())
+    }
+
+    /// Parses a single expression without a trailing `;`, which must make up the whole input.
+    pub fn parse_expression(&self) -> Result<ParsedExpression, ParserError> {
+        let expr = self.expression()?;
+        /* statements in the bodies of lambdas recover from their errors, which are recorded. */
+        if let Some(error) = self.errors.take().into_iter().next() {
+            return Err(error);
+        }
+        if !self.is_at_end()? {
+            return Err(ParserError::UnexpectedTokenAfterExpression {
+                line: self.peek()?.line,
+                column: self.peek()?.column,
+            });
+        }
+
+        let expr = self.add_expr(expr);
+        Ok(ParsedExpression {
+            ast: Arc::new(self.ast.take()),
+            expr,
+        })
+    }
+
+    /// Allocates an expression in the tree of the script.
+    fn add_expr(&self, expr: Expr) -> ExprRef {
+        self.ast.borrow_mut().add_expr(expr)
+    }
+
+    /// Allocates a statement in the tree of the script.
+    fn add_stmt(&self, stmt: Stmt) -> StmtRef {
+        self.ast.borrow_mut().add_stmt(stmt)
+    }
+
+    /// Parses a declaration. On an error, records it and skips to the next statement.
+    fn declaration_or_recover(&self) -> Option<Stmt> {
+        match self.declaration() {
+            Ok(declaration) => Some(declaration),
+            Err(error) => {
+                self.record_error(error);
+                self.synchronize();
+                None
+            }
+        }
+    }
+
+    /// Records an error, remembering whether the first one occurred at the end of the input.
+    fn record_error(&self, error: ParserError) {
+        let mut errors = self.errors.borrow_mut();
+        if errors.is_empty() {
+            self.incomplete.set(self.is_at_end().unwrap_or(true));
+        }
+        errors.push(error);
+    }
+
+    /// Skips tokens until the boundary of a statement: after a `;`, or before a keyword
+    /// starting a statement or the end of the enclosing block.
+    fn synchronize(&self) {
+        let _ = self.advance();
+
+        while let Ok(false) = self.is_at_end() {
+            if self
+                .previous()
+                .is_ok_and(|token| token.token_type == TokenType::Semicolon)
+            {
+                return;
+            }
+
+            let boundary = self.peek().is_ok_and(|token| {
+                (token.token_type == TokenType::RightBrace && self.blocks.get() > 0)
+                    || matches!(
+                        token.token_type,
+                        TokenType::Class
+                            | TokenType::Trait
+                            | TokenType::Fun
+                            | TokenType::Async
+                            | TokenType::Var
+                            | TokenType::Const
+                            | TokenType::For
+                            | TokenType::If
+                            | TokenType::While
+                            | TokenType::Print
+                            | TokenType::Return
+                            | TokenType::Import
+                    )
+            });
+            if boundary {
+                return;
+            }
+
+            let _ = self.advance();
+        }
     }
 
     fn declaration(&self) -> Result<Stmt, ParserError> {
         if self.match_token_types(&[TokenType::Class])? {
             self.class_declaration()
-        } else if self.match_token_types(&[TokenType::Fun])? {
-            self.function()
+        } else if self.match_token_types(&[TokenType::Trait])? {
+            self.trait_declaration()
+        } else if self.check(TokenType::Fun)? && !self.check_next(TokenType::LeftParen)? {
+            self.advance()?;
+            self.function(false)
+        } else if self.match_token_types(&[TokenType::Async])? {
+            self.consume(
+                TokenType::Fun,
+                ParserError::MissingFunAfterAsync {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            )?;
+            self.function(true)
         } else if self.match_token_types(&[TokenType::Var])? {
             self.variable_declaration()
+        } else if self.match_token_types(&[TokenType::Const])? {
+            self.constant_declaration()
         } else {
             self.statement()
         }
     }
 
     fn class_declaration(&self) -> Result<Stmt, ParserError> {
-        let name = self.consume(
-            TokenType::Identifier,
-            ParserError::MissingClassName {
-                line: self.peek().unwrap().line,
-            },
-        )?;
+        let name = self
+            .consume(
+                TokenType::Identifier,
+                ParserError::MissingClassName {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            )?
+            .clone();
 
         let superclass = if self.match_token_types(&[TokenType::Less])? {
             self.consume(
                 TokenType::Identifier,
                 ParserError::MissingSuperclassName {
                     line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
                 },
             )?;
 
-            Some(Expr::variable(self.previous()?))
+            Some(self.add_expr(Expr::variable(self.previous()?.clone())))
         } else {
             None
         };
 
+        let mut traits = vec![];
+        if self.match_token_types(&[TokenType::With])? {
+            loop {
+                let name = self
+                    .consume(
+                        TokenType::Identifier,
+                        ParserError::MissingTraitName {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone();
+                traits.push(self.add_expr(Expr::variable(name)));
+
+                if !self.match_token_types(&[TokenType::Comma])? {
+                    break;
+                }
+            }
+        }
+
         self.consume(
             TokenType::LeftBrace,
             ParserError::MissingLeftBraceBeforeClassBody {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
+        let mut fields = vec![];
         let mut methods = vec![];
         while !self.check(TokenType::RightBrace)? && !self.is_at_end()? {
-            methods.push(self.function()?);
+            if self.check(TokenType::Identifier)? && self.check_next(TokenType::Equal)? {
+                let field = self.field()?;
+                fields.push(self.add_stmt(field));
+                continue;
+            }
+
+            let is_async = self.match_token_types(&[TokenType::Async])?;
+            let method = self.function(is_async)?;
+            methods.push(self.add_stmt(method));
         }
 
         self.consume(
             TokenType::RightBrace,
             ParserError::MissingRightBraceAfterClassBody {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        Ok(Stmt::class(name, superclass, methods))
+        Ok(Stmt::class(name, superclass, traits, fields, methods))
     }
 
-    fn function(&self) -> Result<Stmt, ParserError> {
-        let name = self.consume(
-            TokenType::Identifier,
-            ParserError::MissingFunctionName {
+    fn trait_declaration(&self) -> Result<Stmt, ParserError> {
+        let name = self
+            .consume(
+                TokenType::Identifier,
+                ParserError::MissingTraitName {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            )?
+            .clone();
+
+        self.consume(
+            TokenType::LeftBrace,
+            ParserError::MissingLeftBraceBeforeTraitBody {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
+
+        let mut methods = vec![];
+        while !self.check(TokenType::RightBrace)? && !self.is_at_end()? {
+            let is_async = self.match_token_types(&[TokenType::Async])?;
+            let method = self.function(is_async)?;
+            methods.push(self.add_stmt(method));
+        }
+
+        self.consume(
+            TokenType::RightBrace,
+            ParserError::MissingRightBraceAfterTraitBody {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::trait_stmt(name, methods))
+    }
+
+    /// Parses a field initializer `name = value;` into the statement `this.name = value;`.
+    fn field(&self) -> Result<Stmt, ParserError> {
+        let name = self.advance()?.clone();
+        self.advance()?;
+
+        let value = self.expression()?;
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterField {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        let this = Token::new(
+            TokenType::This,
+            String::from("this"),
+            name.line,
+            name.column,
+            name.file.clone(),
+        );
+
+        let this = self.add_expr(Expr::this(this));
+        let value = self.add_expr(value);
+        Ok(Stmt::expression(
+            self.add_expr(Expr::set(this, name, value)),
+        ))
+    }
+
+    fn function(&self, is_async: bool) -> Result<Stmt, ParserError> {
+        let name = self
+            .consume(
+                TokenType::Identifier,
+                ParserError::MissingFunctionName {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            )?
+            .clone();
+        let (parameters, is_variadic, body) = self.function_body(true)?;
+        let is_generator = self.contains_yield(&body);
+
+        Ok(Stmt::function(
+            name,
+            parameters,
+            body,
+            is_async,
+            is_variadic,
+            is_generator,
+        ))
+    }
+
+    /// Parses an anonymous function following the `fun` keyword.
+    fn lambda(&self) -> Result<Expr, ParserError> {
+        let keyword = self.previous()?;
+        let name = Token::new(
+            TokenType::Identifier,
+            String::from("lambda"),
+            keyword.line,
+            keyword.column,
+            keyword.file.clone(),
+        );
+        let (parameters, is_variadic, body) = self.function_body(false)?;
+        let is_generator = self.contains_yield(&body);
+
+        Ok(Expr::lambda(self.add_stmt(Stmt::function(
+            name,
+            parameters,
+            body,
+            false,
+            is_variadic,
+            is_generator,
+        ))))
+    }
+
+    /// Parses the parameters and the body of a function. A body of the form `-> expression`
+    /// is a shorthand for `{ return expression; }`, followed by a semicolon in declarations.
+    /// Returns whether the last parameter is a rest parameter, as in `fun f(a, ...rest)`.
+    fn function_body(
+        &self,
+        is_declaration: bool,
+    ) -> Result<(Vec<Token>, bool, Vec<StmtRef>), ParserError> {
         self.consume(
             TokenType::LeftParen,
             ParserError::MissingLeftParenthesisAfterFunctionName {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
         let mut parameters = vec![];
+        let mut is_variadic = false;
 
         if !self.check(TokenType::RightParen)? {
             loop {
-                let parameter = self.consume(
-                    TokenType::Identifier,
-                    ParserError::MissingParameterName {
+                if is_variadic {
+                    return Err(ParserError::RestParameterNotLast {
+                        line: self.previous()?.line,
+                        column: self.previous()?.column,
+                    });
+                }
+                if parameters.len() >= MAX_ARITY {
+                    return Err(ParserError::TooManyParameters {
                         line: self.peek().unwrap().line,
-                    },
-                )?;
+                        column: self.peek().unwrap().column,
+                    });
+                }
+                is_variadic = self.match_token_types(&[TokenType::DotDotDot])?;
+
+                let parameter = self
+                    .consume(
+                        TokenType::Identifier,
+                        ParserError::MissingParameterName {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone();
 
                 parameters.push(parameter);
 
@@ -178,54 +901,231 @@ impl Parser {
             TokenType::RightParen,
             ParserError::MissingRightParenthesisAfterParameters {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
+
+        if self.match_token_types(&[TokenType::Arrow])? {
+            let arrow = self.previous()?.clone();
+            let value = self.expression()?;
+            let value = self.add_expr(value);
+            if is_declaration {
+                self.consume(
+                    TokenType::Semicolon,
+                    ParserError::MissingSemicolonAfterExpression {
+                        line: self.peek().unwrap().line,
+                        column: self.peek().unwrap().column,
+                    },
+                )?;
+            }
+
+            return Ok((
+                parameters,
+                is_variadic,
+                vec![self.add_stmt(Stmt::return_stmt(arrow, Some(value)))],
+            ));
+        }
+
         self.consume(
             TokenType::LeftBrace,
             ParserError::MissingLeftBraceBeforeFunctionBody {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        let body = self.block()?;
-
-        Ok(Stmt::function(name, parameters, body))
+        Ok((parameters, is_variadic, self.block()?))
     }
 
     fn variable_declaration(&self) -> Result<Stmt, ParserError> {
-        let name = self.consume(
-            TokenType::Identifier,
-            ParserError::MissingVariableName {
-                line: self.peek().unwrap().line,
-            },
-        )?;
+        let keyword = self.previous()?.clone();
+        if self.match_token_types(&[TokenType::LeftBracket])? {
+            return self.destructuring(
+                keyword,
+                Pattern::List,
+                TokenType::RightBracket,
+                ParserError::MissingRightBracketAfterNames {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            );
+        }
+        if self.match_token_types(&[TokenType::LeftBrace])? {
+            return self.destructuring(
+                keyword,
+                Pattern::Fields,
+                TokenType::RightBrace,
+                ParserError::MissingRightBraceAfterNames {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            );
+        }
+
+        let name = self
+            .consume(
+                TokenType::Identifier,
+                ParserError::MissingVariableName {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            )?
+            .clone();
+        if self.match_token_types(&[TokenType::Comma])? {
+            let mut names = vec![name];
+            loop {
+                names.push(
+                    self.consume(
+                        TokenType::Identifier,
+                        ParserError::MissingVariableName {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone(),
+                );
+
+                if !self.match_token_types(&[TokenType::Comma])? {
+                    break;
+                }
+            }
+
+            return self.destructuring_initializer(keyword, Pattern::Values, names);
+        }
+
         let initializer = if self.match_token_types(&[TokenType::Equal])? {
-            self.expression()?
+            Some(self.add_expr(self.yield_or_expression()?))
+        } else if self.check(TokenType::EqualEqual)? {
+            return Err(ParserError::ComparisonInDeclaration {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            });
         } else {
-            Expr::literal(LiteralValue::None)
+            None
         };
 
         self.consume(
             TokenType::Semicolon,
             ParserError::MissingSemicolonAfterVariableDeclaration {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
         Ok(Stmt::var(name, initializer))
     }
 
+    fn destructuring(
+        &self,
+        keyword: Token,
+        pattern: Pattern,
+        closing: TokenType,
+        error: ParserError,
+    ) -> Result<Stmt, ParserError> {
+        let mut names = vec![];
+
+        if !self.check(closing.to_owned())? {
+            loop {
+                names.push(
+                    self.consume(
+                        TokenType::Identifier,
+                        ParserError::MissingVariableName {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone(),
+                );
+
+                if !self.match_token_types(&[TokenType::Comma])? {
+                    break;
+                }
+            }
+        }
+
+        self.consume(closing, error)?;
+        self.destructuring_initializer(keyword, pattern, names)
+    }
+
+    /// Parses the initializer of a destructuring declaration. `var a, b = value;` is a
+    /// shorthand for `var [a, b] = value;`.
+    fn destructuring_initializer(
+        &self,
+        keyword: Token,
+        pattern: Pattern,
+        names: Vec<Token>,
+    ) -> Result<Stmt, ParserError> {
+        self.consume(
+            TokenType::Equal,
+            ParserError::MissingDestructuringInitializer {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+        let initializer = self.add_expr(self.expression()?);
+
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterVariableDeclaration {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::destructure(keyword, pattern, names, initializer))
+    }
+
+    fn constant_declaration(&self) -> Result<Stmt, ParserError> {
+        let name = self
+            .consume(
+                TokenType::Identifier,
+                ParserError::MissingVariableName {
+                    line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
+                },
+            )?
+            .clone();
+        self.consume(
+            TokenType::Equal,
+            ParserError::MissingConstantInitializer {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+        let initializer = self.add_expr(self.expression()?);
+
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterVariableDeclaration {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::constant(name, initializer))
+    }
+
     fn statement(&self) -> Result<Stmt, ParserError> {
-        if self.match_token_types(&[TokenType::For])? {
-            self.for_statement()
+        if self.check(TokenType::Identifier)? && self.check_next(TokenType::Colon)? {
+            self.labeled_statement()
+        } else if self.match_token_types(&[TokenType::Break])? {
+            self.break_statement()
+        } else if self.match_token_types(&[TokenType::Continue])? {
+            self.continue_statement()
+        } else if self.match_token_types(&[TokenType::For])? {
+            self.for_statement(None)
         } else if self.match_token_types(&[TokenType::If])? {
             self.if_statement()
+        } else if self.match_token_types(&[TokenType::Import])? {
+            self.import_statement()
         } else if self.match_token_types(&[TokenType::Print])? {
             self.print_statement()
         } else if self.match_token_types(&[TokenType::Return])? {
             self.return_statement()
         } else if self.match_token_types(&[TokenType::While])? {
-            self.while_statement()
+            self.while_statement(None)
+        } else if self.match_token_types(&[TokenType::Yield])? {
+            self.yield_statement()
         } else if self.match_token_types(&[TokenType::LeftBrace])? {
             let block = self.block()?;
             Ok(Stmt::block(block))
@@ -234,37 +1134,98 @@ impl Parser {
         }
     }
 
-    fn for_statement(&self) -> Result<Stmt, ParserError> {
+    /// Parses a loop preceded by a label, as in `outer: while (...) { ... }`.
+    fn labeled_statement(&self) -> Result<Stmt, ParserError> {
+        let label = self.advance()?.clone();
+        self.advance()?;
+
+        if self.match_token_types(&[TokenType::For])? {
+            self.for_statement(Some(label))
+        } else if self.match_token_types(&[TokenType::While])? {
+            self.while_statement(Some(label))
+        } else {
+            Err(ParserError::MissingLoopAfterLabel {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            })
+        }
+    }
+
+    fn break_statement(&self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
+        let label = self.loop_label()?;
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterBreak {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::break_stmt(keyword, label))
+    }
+
+    fn continue_statement(&self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
+        let label = self.loop_label()?;
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterContinue {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::continue_stmt(keyword, label))
+    }
+
+    /// Parses the optional label of a `break` or `continue` statement.
+    fn loop_label(&self) -> Result<Option<Token>, ParserError> {
+        if self.match_token_types(&[TokenType::Identifier])? {
+            Ok(Some(self.previous()?.clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn for_statement(&self, label: Option<Token>) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
         self.consume(
             TokenType::LeftParen,
             ParserError::MissingLeftParenthesisAfterForStatement {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
+        if self.check(TokenType::Identifier)? && self.check_next(TokenType::In)? {
+            return self.for_in_statement(keyword, label);
+        }
+
         let initializer = if self.match_token_types(&[TokenType::Semicolon])? {
             None
         } else if self.match_token_types(&[TokenType::Var])? {
-            Some(self.variable_declaration()?)
+            Some(self.add_stmt(self.variable_declaration()?))
         } else {
-            Some(self.expression_statement()?)
+            Some(self.add_stmt(self.expression_statement()?))
         };
 
         let condition = if !self.check(TokenType::Semicolon)? {
-            self.expression()?
+            self.add_expr(self.condition()?)
         } else {
-            Expr::literal(LiteralValue::Boolean(true))
+            self.add_expr(Expr::literal(LiteralValue::Boolean(true)))
         };
 
         self.consume(
             TokenType::Semicolon,
             ParserError::MissingSemicolonAfterLoopCondition {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
         let increment = if !self.check(TokenType::RightParen)? {
-            Some(self.expression()?)
+            Some(self.add_expr(self.expression()?))
         } else {
             None
         };
@@ -273,65 +1234,131 @@ impl Parser {
             TokenType::RightParen,
             ParserError::MissingRightParenthesisAfterForStatement {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        let mut body = self.statement()?;
-
-        if let Some(inc) = increment {
-            body = Stmt::block(vec![body, Stmt::expression(inc)])
-        }
-
-        body = Stmt::while_stmt(condition, body);
+        let body = self.add_stmt(self.nested(|| self.statement())?);
+        let mut body = Stmt::while_stmt(keyword, label, condition, body, increment);
 
         if let Some(init) = initializer {
-            body = Stmt::block(vec![init, body])
+            body = Stmt::block(vec![init, self.add_stmt(body)])
         }
 
         Ok(body)
     }
 
+    fn for_in_statement(&self, keyword: Token, label: Option<Token>) -> Result<Stmt, ParserError> {
+        let variable = self.advance()?.clone();
+        self.advance()?;
+
+        let iterable = self.add_expr(self.expression()?);
+        self.consume(
+            TokenType::RightParen,
+            ParserError::MissingRightParenthesisAfterIterable {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        let body = self.add_stmt(self.nested(|| self.statement())?);
+
+        Ok(Stmt::for_in(keyword, label, variable, iterable, body))
+    }
+
     fn if_statement(&self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
         self.consume(
             TokenType::LeftParen,
             ParserError::MissingLeftParenthesisAfterIfStatement {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        let condition = self.expression()?;
+        let condition = self.add_expr(self.condition()?);
         self.consume(
             TokenType::RightParen,
             ParserError::MissingRightParenthesisAfterCondition {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        let then_branch = self.statement()?;
+        let then_branch = self.add_stmt(self.nested(|| self.statement())?);
         let else_branch = if self.match_token_types(&[TokenType::Else])? {
-            Some(self.statement()?)
+            Some(self.add_stmt(self.nested(|| self.statement())?))
         } else {
             None
         };
 
-        Ok(Stmt::if_stmt(condition, then_branch, else_branch))
+        Ok(Stmt::if_stmt(keyword, condition, then_branch, else_branch))
+    }
+
+    fn import_statement(&self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
+        let path = self.advance()?.clone();
+        if !matches!(path.token_type, TokenType::String { .. }) {
+            return Err(ParserError::MissingModulePath {
+                line: path.line,
+                column: path.column,
+            });
+        }
+
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterModulePath {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::import(keyword, path))
     }
 
     fn print_statement(&self) -> Result<Stmt, ParserError> {
-        let value = self.expression()?;
+        let parenthesized = self.check(TokenType::LeftParen)?;
+        let value = match self.expression() {
+            Ok(value) => self.add_expr(value),
+            /* `print(a, b);` and `print();` call it like a function. */
+            Err(error) => {
+                let empty = self.previous()?.token_type == TokenType::LeftParen
+                    && self.check(TokenType::RightParen)?;
+                return if parenthesized && (empty || self.check(TokenType::Comma)?) {
+                    Err(ParserError::PrintCall {
+                        line: self.peek().unwrap().line,
+                        column: self.peek().unwrap().column,
+                    })
+                } else {
+                    Err(error)
+                };
+            }
+        };
         self.consume(
             TokenType::Semicolon,
             ParserError::MissingSemicolonAfterValue {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
         Ok(Stmt::print(value))
     }
 
+    /// Parses a return statement. Returning several values, as in `return a, b;`, returns
+    /// them as a list.
     fn return_statement(&self) -> Result<Stmt, ParserError> {
-        let keyword = self.previous()?;
+        let keyword = self.previous()?.clone();
         let expr = if !self.check(TokenType::Semicolon)? {
-            Some(self.expression()?)
+            let value = self.add_expr(self.expression()?);
+            if self.check(TokenType::Comma)? {
+                let mut values = vec![value];
+                while self.match_token_types(&[TokenType::Comma])? {
+                    values.push(self.add_expr(self.expression()?));
+                }
+                Some(self.add_expr(Expr::list(keyword.to_owned(), values)))
+            } else {
+                Some(value)
+            }
         } else {
             None
         };
@@ -340,44 +1367,91 @@ impl Parser {
             TokenType::Semicolon,
             ParserError::MissingSemicolonAfterExpression {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
         Ok(Stmt::return_stmt(keyword, expr))
     }
 
-    fn while_statement(&self) -> Result<Stmt, ParserError> {
+    fn yield_statement(&self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
+        let value = self.yield_value()?;
+
+        self.consume(
+            TokenType::Semicolon,
+            ParserError::MissingSemicolonAfterExpression {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Stmt::yield_stmt(keyword, value))
+    }
+
+    /// Parses the value of a variable or an assignment, which may also be a `yield` that
+    /// receives the value the generator is resumed with, as in `var answer = yield question;`.
+    fn yield_or_expression(&self) -> Result<Expr, ParserError> {
+        if self.match_token_types(&[TokenType::Yield])? {
+            let keyword = self.previous()?.clone();
+            Ok(Expr::yield_expr(keyword, self.yield_value()?))
+        } else {
+            self.assignment()
+        }
+    }
+
+    fn yield_value(&self) -> Result<Option<ExprRef>, ParserError> {
+        if self.check(TokenType::Semicolon)? {
+            Ok(None)
+        } else {
+            Ok(Some(self.add_expr(self.expression()?)))
+        }
+    }
+
+    fn while_statement(&self, label: Option<Token>) -> Result<Stmt, ParserError> {
+        let keyword = self.previous()?.clone();
         self.consume(
             TokenType::LeftParen,
             ParserError::MissingLeftParenthesisAfterWhileStatement {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        let condition = self.expression()?;
+        let condition = self.add_expr(self.condition()?);
         self.consume(
             TokenType::RightParen,
             ParserError::MissingRightParenthesisAfterCondition {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
-        let body = self.statement()?;
+        let body = self.add_stmt(self.nested(|| self.statement())?);
 
-        Ok(Stmt::while_stmt(condition, body))
+        Ok(Stmt::while_stmt(keyword, label, condition, body, None))
     }
 
-    fn block(&self) -> Result<Vec<Stmt>, ParserError> {
+    fn block(&self) -> Result<Vec<StmtRef>, ParserError> {
+        self.nested(|| self.block_body())
+    }
+
+    fn block_body(&self) -> Result<Vec<StmtRef>, ParserError> {
         let mut statements = vec![];
 
+        self.blocks.set(self.blocks.get() + 1);
         while !self.check(TokenType::RightBrace)? && !self.is_at_end()? {
-            statements.push(self.declaration()?)
+            if let Some(declaration) = self.declaration_or_recover() {
+                statements.push(self.add_stmt(declaration))
+            }
         }
+        self.blocks.set(self.blocks.get() - 1);
 
         self.consume(
             TokenType::RightBrace,
             ParserError::MissingRightBraceAfterBlock {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
 
@@ -386,34 +1460,72 @@ impl Parser {
 
     fn expression_statement(&self) -> Result<Stmt, ParserError> {
         let value = self.expression()?;
+        if matches!(value, Expr::Call { .. }) && self.check(TokenType::LeftBrace)? {
+            return Err(ParserError::MissingFunBeforeFunction {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            });
+        }
+
+        let value = self.add_expr(value);
         self.consume(
             TokenType::Semicolon,
             ParserError::MissingSemicolonAfterExpression {
                 line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
             },
         )?;
         Ok(Stmt::expression(value))
     }
 
     fn expression(&self) -> Result<Expr, ParserError> {
-        self.assignment()
+        self.nested(|| self.assignment())
+    }
+
+    /// Parses the condition of an `if` statement or a loop, which must not be an assignment
+    /// unless it is wrapped in parentheses, as it is most likely a mistyped comparison.
+    fn condition(&self) -> Result<Expr, ParserError> {
+        let condition = self.expression()?;
+        if matches!(
+            condition,
+            Expr::Assign { .. } | Expr::Set { .. } | Expr::IndexSet { .. }
+        ) {
+            return Err(ParserError::AssignmentInCondition {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            });
+        }
+
+        Ok(condition)
     }
 
     fn assignment(&self) -> Result<Expr, ParserError> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         if self.match_token_types(&[TokenType::Equal])? {
-            let value = self.assignment()?;
+            let value = if matches!(expr, Expr::Variable { .. }) {
+                self.nested(|| self.yield_or_expression())?
+            } else {
+                self.nested(|| self.assignment())?
+            };
+            let value = self.add_expr(value);
 
             return match expr {
-                Expr::Variable { uuid: _uuid, name } => Ok(Expr::assign(*name, value)),
+                Expr::Variable { id: _id, name } => Ok(Expr::assign(*name, value)),
                 Expr::Get {
-                    uuid: _name,
+                    id: _name,
                     object,
                     name,
-                } => Ok(Expr::set(*object, *name, value)),
+                } => Ok(Expr::set(object, *name, value)),
+                Expr::Index {
+                    id: _id,
+                    object,
+                    bracket,
+                    index,
+                } => Ok(Expr::index_set(object, *bracket, index, value)),
                 _ => Err(ParserError::InvalidAssignmentTarget {
                     line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
                 }),
             };
         }
@@ -421,102 +1533,138 @@ impl Parser {
         Ok(expr)
     }
 
-    fn or(&self) -> Result<Expr, ParserError> {
-        let mut expr = self.and()?;
-
-        while self.match_token_types(&[TokenType::Or])? {
-            let operator = self.previous()?;
-            let right = self.and()?;
-            expr = Expr::logical(expr, operator, right);
-        }
+    /// Parses a ternary conditional or any expression binding tighter.
+    fn conditional(&self) -> Result<Expr, ParserError> {
+        self.infix(Precedence::Conditional)
+    }
 
-        Ok(expr)
+    /// Parses a chain of infix operators binding at least as tightly as `precedence`, with the
+    /// operators and their precedence looked up in [`INFIX_RULES`].
+    fn infix(&self, precedence: Precedence) -> Result<Expr, ParserError> {
+        self.chain(|| self.infix_chain(precedence))
     }
 
-    fn and(&self) -> Result<Expr, ParserError> {
-        let mut expr = self.equality()?;
+    fn infix_chain(&self, precedence: Precedence) -> Result<Expr, ParserError> {
+        let mut expr = self.unary()?;
+
+        while let Some(rule) = self.infix_rule()? {
+            if rule.precedence < precedence {
+                break;
+            }
 
-        while self.match_token_types(&[TokenType::And])? {
-            let operator = self.previous()?;
-            let right = self.equality()?;
-            expr = Expr::logical(expr, operator, right);
+            self.deepen()?;
+            let operator = self.advance()?.clone();
+            let left = self.add_expr(expr);
+            expr = match rule.kind {
+                Infix::Binary => Expr::binary(left, operator, self.right_operand(rule)?),
+                Infix::Logical => Expr::logical(left, operator, self.right_operand(rule)?),
+                Infix::Conditional => {
+                    let then_branch = self.add_expr(self.expression()?);
+                    self.consume(
+                        TokenType::Colon,
+                        ParserError::MissingColonInConditional {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?;
+                    Expr::ternary(left, operator, then_branch, self.right_operand(rule)?)
+                }
+            };
         }
 
         Ok(expr)
     }
 
-    fn equality(&self) -> Result<Expr, ParserError> {
-        let mut expr = self.comparison()?;
-
-        while self.match_token_types(&[TokenType::BangEqual, TokenType::EqualEqual])? {
-            let operator = self.previous()?;
-            let right = self.comparison()?;
-            expr = Expr::binary(expr, operator, right)
-        }
+    /// Parses the operand to the right of an infix operator. Operands of left-associative
+    /// operators only contain operators binding tighter, so that `a - b - c` is `(a - b) - c`.
+    fn right_operand(&self, rule: &InfixRule) -> Result<ExprRef, ParserError> {
+        let operand = match rule.associativity {
+            Associativity::Left => self.infix(rule.precedence.next())?,
+            Associativity::Right => self.nested(|| self.infix(rule.precedence))?,
+        };
 
-        Ok(expr)
+        Ok(self.add_expr(operand))
     }
 
-    fn comparison(&self) -> Result<Expr, ParserError> {
-        let mut expr = self.term()?;
+    /// Returns the rule of the infix operator at the current token, if it is one.
+    fn infix_rule(&self) -> Result<Option<&'static InfixRule>, ParserError> {
+        let token_type = &self.peek()?.token_type;
+        Ok(INFIX_RULES
+            .iter()
+            .find(|rule| rule.token_type == *token_type))
+    }
 
-        while self.match_token_types(&[
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ])? {
-            let operator = self.previous()?;
-            let right = self.term()?;
-            expr = Expr::binary(expr, operator, right)
+    fn unary(&self) -> Result<Expr, ParserError> {
+        if self.match_token_types(&[TokenType::Bang, TokenType::Minus])? {
+            let operator = self.previous()?.clone();
+            let right = self.add_expr(self.nested(|| self.unary())?);
+            return Ok(Expr::unary(operator, right));
         }
 
-        Ok(expr)
-    }
-
-    fn term(&self) -> Result<Expr, ParserError> {
-        let mut expr = self.factor()?;
+        if self.match_token_types(&[TokenType::PlusPlus, TokenType::MinusMinus])? {
+            let operator = self.previous()?.clone();
+            let target = self.nested(|| self.unary())?;
+            return self.update(operator, target, true);
+        }
 
-        while self.match_token_types(&[TokenType::Minus, TokenType::Plus])? {
-            let operator = self.previous()?;
-            let right = self.factor()?;
-            expr = Expr::binary(expr, operator, right)
+        if self.match_token_types(&[TokenType::Await])? {
+            let keyword = self.previous()?.clone();
+            let value = self.add_expr(self.nested(|| self.unary())?);
+            return Ok(Expr::await_expr(keyword, value));
         }
 
-        Ok(expr)
+        self.postfix()
     }
 
-    fn factor(&self) -> Result<Expr, ParserError> {
-        let mut expr = self.unary()?;
+    fn postfix(&self) -> Result<Expr, ParserError> {
+        let expr = self.call()?;
 
-        while self.match_token_types(&[TokenType::Slash, TokenType::Star])? {
-            let operator = self.previous()?;
-            let right = self.unary()?;
-            expr = Expr::binary(expr, operator, right)
+        if self.match_token_types(&[TokenType::PlusPlus, TokenType::MinusMinus])? {
+            let operator = self.previous()?.clone();
+            return self.update(operator, expr, false);
         }
 
         Ok(expr)
     }
 
-    fn unary(&self) -> Result<Expr, ParserError> {
-        if self.match_token_types(&[TokenType::Bang, TokenType::Minus])? {
-            let operator = self.previous()?;
-            let right = self.unary()?;
-            return Ok(Expr::unary(operator, right));
+    fn update(&self, operator: Token, target: Expr, prefix: bool) -> Result<Expr, ParserError> {
+        match target {
+            Expr::Variable { .. } | Expr::Get { .. } => {
+                Ok(Expr::update(operator, self.add_expr(target), prefix))
+            }
+            _ => Err(ParserError::InvalidUpdateTarget {
+                line: operator.line,
+                column: operator.column,
+            }),
         }
-
-        self.call()
     }
 
     fn call(&self) -> Result<Expr, ParserError> {
+        self.chain(|| self.call_chain())
+    }
+
+    fn call_chain(&self) -> Result<Expr, ParserError> {
         let mut expr = self.primary()?;
 
         loop {
+            if self.check(TokenType::LeftParen)?
+                || self.check(TokenType::Dot)?
+                || self.check(TokenType::LeftBracket)?
+            {
+                self.deepen()?;
+            }
+
             if self.match_token_types(&[TokenType::LeftParen])? {
                 let mut arguments = vec![];
                 if !self.check(TokenType::RightParen)? {
                     loop {
-                        arguments.push(self.expression()?);
+                        if arguments.len() >= MAX_ARITY {
+                            return Err(ParserError::TooManyArguments {
+                                line: self.peek().unwrap().line,
+                                column: self.peek().unwrap().column,
+                            });
+                        }
+                        arguments.push(self.add_expr(self.expression()?));
 
                         if !self.match_token_types(&[TokenType::Comma])? {
                             break;
@@ -524,23 +1672,42 @@ impl Parser {
                     }
                 }
 
-                let paren = self.consume(
-                    TokenType::RightParen,
-                    ParserError::MissingRightParenthesisAfterArguments {
-                        line: self.peek().unwrap().line,
-                    },
-                )?;
-
-                expr = Expr::call(paren, expr, arguments);
+                let paren = self
+                    .consume(
+                        TokenType::RightParen,
+                        ParserError::MissingRightParenthesisAfterArguments {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone();
+
+                expr = Expr::call(paren, self.add_expr(expr), arguments);
             } else if self.match_token_types(&[TokenType::Dot])? {
-                let name = self.consume(
-                    TokenType::Identifier,
-                    ParserError::MissingPropertyName {
-                        line: self.peek().unwrap().line,
-                    },
-                )?;
-
-                expr = Expr::get(expr, name)
+                let name = self
+                    .consume(
+                        TokenType::Identifier,
+                        ParserError::MissingPropertyName {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone();
+
+                expr = Expr::get(self.add_expr(expr), name)
+            } else if self.match_token_types(&[TokenType::LeftBracket])? {
+                let index = self.add_expr(self.expression()?);
+                let bracket = self
+                    .consume(
+                        TokenType::RightBracket,
+                        ParserError::MissingRightBracketAfterIndex {
+                            line: self.peek().unwrap().line,
+                            column: self.peek().unwrap().column,
+                        },
+                    )?
+                    .clone();
+
+                expr = Expr::index(self.add_expr(expr), bracket, index)
             } else {
                 break;
             }
@@ -569,41 +1736,59 @@ impl Parser {
                 self.advance()?;
                 return Ok(Expr::literal(LiteralValue::String(value.clone())));
             }
+            TokenType::Interpolation { .. } => return self.interpolation(),
             _ => {}
         }
 
         if self.match_token_types(&[TokenType::Super])? {
-            let keyword = self.previous()?;
+            let keyword = self.previous()?.clone();
             self.consume(
                 TokenType::Dot,
                 ParserError::MissingDotAfterSuper {
                     line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
                 },
             )?;
-            let method = self.consume(
-                TokenType::Identifier,
-                ParserError::MissingSuperclassMethodName {
-                    line: self.peek().unwrap().line,
-                },
-            )?;
+            let method = self
+                .consume(
+                    TokenType::Identifier,
+                    ParserError::MissingSuperclassMethodName {
+                        line: self.peek().unwrap().line,
+                        column: self.peek().unwrap().column,
+                    },
+                )?
+                .clone();
 
             return Ok(Expr::super_expr(keyword, method));
         }
 
         if self.match_token_types(&[TokenType::This])? {
-            return Ok(Expr::this(self.previous()?));
+            return Ok(Expr::this(self.previous()?.clone()));
+        }
+
+        if self.match_token_types(&[TokenType::Fun])? {
+            return self.lambda();
+        }
+
+        if self.match_token_types(&[TokenType::LeftBracket])? {
+            return self.list();
+        }
+
+        if self.match_token_types(&[TokenType::LeftBrace])? {
+            return self.map();
         }
 
         if self.match_token_types(&[TokenType::Identifier])? {
-            return Ok(Expr::variable(self.previous()?));
+            return Ok(Expr::variable(self.previous()?.clone()));
         }
 
         if self.match_token_types(&[TokenType::LeftParen])? {
-            let expr = self.expression()?;
+            let expr = self.add_expr(self.expression()?);
             self.consume(
                 TokenType::RightParen,
                 ParserError::MissingRightParenthesisAfterExpression {
                     line: self.peek().unwrap().line,
+                    column: self.peek().unwrap().column,
                 },
             )?;
             return Ok(Expr::grouping(expr));
@@ -611,31 +1796,140 @@ impl Parser {
 
         Err(ParserError::MissingExpression {
             line: self.peek().unwrap().line,
+            column: self.peek().unwrap().column,
         })
     }
 
-    fn peek(&self) -> Result<Token, ParserError> {
-        match self.tokens.get(*self.current.borrow()) {
-            None => Err(ParserError::TokenReadError),
-            Some(token) => Ok((*token).clone()),
+    fn list(&self) -> Result<Expr, ParserError> {
+        let bracket = self.previous()?.clone();
+        let mut elements = vec![];
+
+        if !self.check(TokenType::RightBracket)? {
+            loop {
+                elements.push(self.add_expr(self.expression()?));
+
+                if !self.match_token_types(&[TokenType::Comma])? {
+                    break;
+                }
+            }
+        }
+
+        self.consume(
+            TokenType::RightBracket,
+            ParserError::MissingRightBracketAfterElements {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Expr::list(bracket, elements))
+    }
+
+    fn map(&self) -> Result<Expr, ParserError> {
+        let brace = self.previous()?.clone();
+        let mut entries = vec![];
+
+        if !self.check(TokenType::RightBrace)? {
+            loop {
+                let key = self.add_expr(self.expression()?);
+                self.consume(
+                    TokenType::Colon,
+                    ParserError::MissingColonAfterKey {
+                        line: self.peek().unwrap().line,
+                        column: self.peek().unwrap().column,
+                    },
+                )?;
+                let value = self.add_expr(self.expression()?);
+                entries.push((key, value));
+
+                if !self.match_token_types(&[TokenType::Comma])? {
+                    break;
+                }
+            }
         }
+
+        self.consume(
+            TokenType::RightBrace,
+            ParserError::MissingRightBraceAfterEntries {
+                line: self.peek().unwrap().line,
+                column: self.peek().unwrap().column,
+            },
+        )?;
+
+        Ok(Expr::map(brace, entries))
     }
 
-    fn previous(&self) -> Result<Token, ParserError> {
-        match self.tokens.get(*self.current.borrow() - 1) {
-            None => Err(ParserError::TokenReadError),
-            Some(token) => Ok((*token).clone()),
+    /// Parses an interpolated string into a concatenation of its parts, where each embedded
+    /// expression is converted to a string.
+    fn interpolation(&self) -> Result<Expr, ParserError> {
+        let mut expr: Option<Expr> = None;
+
+        loop {
+            let token = self.advance()?;
+            let (value, is_last) = match &token.token_type {
+                TokenType::Interpolation { value } => (value.clone(), false),
+                TokenType::String { value } => (value.clone(), true),
+                _ => {
+                    return Err(ParserError::UnterminatedInterpolation {
+                        line: token.line,
+                        column: token.column,
+                    });
+                }
+            };
+
+            let plus = Token::new(
+                TokenType::Plus,
+                String::from("+"),
+                token.line,
+                token.column,
+                token.file.clone(),
+            );
+            let mut concat = |part: Expr| {
+                expr = Some(match expr.take() {
+                    Some(left) => {
+                        Expr::binary(self.add_expr(left), plus.clone(), self.add_expr(part))
+                    }
+                    None => part,
+                });
+            };
+
+            concat(Expr::literal(LiteralValue::String(value)));
+            if is_last {
+                break;
+            }
+            let part = self.add_expr(self.expression()?);
+            concat(Expr::stringify(part));
         }
+
+        Ok(expr.unwrap())
+    }
+
+    /// Returns whether one of the statements of a function body is or contains a `yield`.
+    fn contains_yield(&self, body: &[StmtRef]) -> bool {
+        let ast = self.ast.borrow();
+        body.iter().any(|statement| ast.contains_yield(*statement))
     }
 
-    fn advance(&self) -> Result<Token, ParserError> {
+    fn peek(&self) -> Result<&Token, ParserError> {
+        self.tokens
+            .get(self.current.get())
+            .ok_or(ParserError::TokenReadError)
+    }
+
+    fn previous(&self) -> Result<&Token, ParserError> {
+        self.tokens
+            .get(self.current.get() - 1)
+            .ok_or(ParserError::TokenReadError)
+    }
+
+    fn advance(&self) -> Result<&Token, ParserError> {
         if !self.is_at_end()? {
-            *self.current.borrow_mut() += 1
+            self.current.set(self.current.get() + 1)
         }
         self.previous()
     }
 
-    fn consume(&self, token_type: TokenType, error: ParserError) -> Result<Token, ParserError> {
+    fn consume(&self, token_type: TokenType, error: ParserError) -> Result<&Token, ParserError> {
         if self.check(token_type)? {
             self.advance()
         } else {
@@ -661,6 +1955,13 @@ impl Parser {
         }
     }
 
+    fn check_next(&self, token_type: TokenType) -> Result<bool, ParserError> {
+        match self.tokens.get(self.current.get() + 1) {
+            Some(token) => Ok(token.token_type == token_type),
+            None => Ok(false),
+        }
+    }
+
     fn match_token_types(&self, token_types: &[TokenType]) -> Result<bool, ParserError> {
         for token_type in token_types {
             if self.check(token_type.clone())? {
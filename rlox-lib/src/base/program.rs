@@ -0,0 +1,69 @@
+use crate::base::ast::Ast;
+use crate::base::scanner::Pragma;
+use crate::interpreter::options::InterpreterOptions;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Marker at the start of a compiled program, directly followed by the format version.
+const PROGRAM_MAGIC: &[u8; 4] = b"LOXC";
+
+/// Version of the compiled format. It changes whenever the syntax tree or the options change.
+pub const PROGRAM_VERSION: u32 = 1;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ProgramError {
+    #[error("Not a compiled Lox program!")]
+    InvalidFormat,
+    #[error("Compiled Lox program has version {version:?}, but version {PROGRAM_VERSION:?} is supported!")]
+    UnsupportedVersion { version: u32 },
+    #[error("Invalid compiled Lox program: {message}!")]
+    InvalidProgram { message: String },
+    #[error("Program can't be compiled: {message}!")]
+    Unserializable { message: String },
+}
+
+/// A script that was scanned, parsed and checked ahead of time, as embedded into standalone
+/// executables by `rlox build`. It keeps the pragmas of the script and the options it was
+/// checked with, so that it runs the same way wherever it is loaded. The program still has to
+/// be resolved before it runs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Program {
+    pub ast: Arc<Ast>,
+    pub pragmas: Vec<Pragma>,
+    pub options: InterpreterOptions,
+}
+
+impl Program {
+    /// Writes the program in the compiled format: [`PROGRAM_MAGIC`], the format version as
+    /// little-endian `u32` and the serialized syntax tree, pragmas and options.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut bytes = PROGRAM_MAGIC.to_vec();
+        bytes.extend_from_slice(&PROGRAM_VERSION.to_le_bytes());
+        serde_json::to_writer(&mut bytes, self).map_err(|error| ProgramError::Unserializable {
+            message: error.to_string(),
+        })?;
+
+        Ok(bytes)
+    }
+
+    /// Reads a program written by [`Program::to_bytes`]. Nodes of the syntax tree get fresh
+    /// ids, so a program can be loaded next to other scripts in the same process.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        let bytes = bytes
+            .strip_prefix(PROGRAM_MAGIC)
+            .ok_or(ProgramError::InvalidFormat)?;
+        let (version, bytes) = bytes
+            .split_first_chunk::<4>()
+            .ok_or(ProgramError::InvalidFormat)?;
+
+        let version = u32::from_le_bytes(*version);
+        if version != PROGRAM_VERSION {
+            return Err(ProgramError::UnsupportedVersion { version });
+        }
+
+        serde_json::from_slice(bytes).map_err(|error| ProgramError::InvalidProgram {
+            message: error.to_string(),
+        })
+    }
+}
@@ -1,19 +1,32 @@
+use crate::base::diagnostic::DiagnosticError;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use thiserror::Error;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TokenType {
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    DotDotDot,
     Minus,
+    MinusMinus,
+    Arrow,
     Plus,
+    PlusPlus,
     Semicolon,
     Slash,
+    Question,
+    Colon,
     Star,
 
     Bang,
@@ -26,101 +39,357 @@ pub enum TokenType {
     LessEqual,
 
     Identifier,
-    String { value: String },
-    Number { value: f64 },
+    String {
+        value: String,
+    },
+    /// A part of an interpolated string that is followed by an embedded expression. The last
+    /// part of the string is a [`TokenType::String`].
+    Interpolation {
+        value: String,
+    },
+    Number {
+        value: f64,
+    },
 
     And,
+    Async,
+    Await,
+    Break,
     Class,
+    Const,
+    Continue,
     Else,
     False,
     Fun,
     For,
     If,
+    Import,
+    In,
+    Is,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Trait,
     True,
     Var,
     While,
+    With,
+    Yield,
+
+    /// A run of spaces, tabs and line breaks, only scanned in trivia mode.
+    Whitespace,
+    /// A line comment, block comment or shebang line, only scanned in trivia mode.
+    Comment,
 
     Eof,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Token {
     pub(crate) token_type: TokenType,
     pub(crate) lexeme: String,
     pub(crate) line: usize,
+    /// 1-based position of the first character of the token in its line, counted in
+    /// characters. See [`display_column`](crate::base::diagnostic::display_column) for the
+    /// column shown to the user.
+    pub(crate) column: usize,
+    pub(crate) file: Option<Arc<str>>,
 }
 
 impl Token {
-    pub(crate) fn new(token_type: TokenType, lexeme: String, line: usize) -> Self {
+    pub(crate) fn new(
+        token_type: TokenType,
+        lexeme: String,
+        line: usize,
+        column: usize,
+        file: Option<Arc<str>>,
+    ) -> Self {
         Token {
             token_type,
             lexeme,
             line,
+            column,
+            file,
         }
     }
+
+    /// Creates an identifier that doesn't appear in the source, for nodes added to a parsed
+    /// tree, like the variable the REPL binds results to.
+    pub fn identifier(name: &str, line: usize) -> Self {
+        Token::new(TokenType::Identifier, name.to_owned(), line, 1, None)
+    }
+
+    pub fn token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum ScannerError {
     #[error("{line:?}: Unknown symbol {symbol:?} detected!")]
-    UnknownSymbol { line: usize, symbol: char },
+    UnknownSymbol {
+        line: usize,
+        column: usize,
+        symbol: char,
+    },
     #[error("{line:?}: Unterminated string!")]
-    UnterminatedString { line: usize },
-    #[error("{line:?}: Error while parsing number {number_string:?}!")]
-    NumberParsingError { line: usize, number_string: String },
+    UnterminatedString { line: usize, column: usize },
+    #[error("{line:?}: Unterminated block comment!")]
+    UnterminatedComment { line: usize, column: usize },
+    #[error("{line:?}: Invalid escape sequence!")]
+    InvalidEscapeSequence { line: usize, column: usize },
+    #[error("{line:?}: Digit separator '_' must be placed between digits!")]
+    InvalidDigitSeparator { line: usize, column: usize },
+    #[error("{line:?}: Error while parsing {format} number {number_string:?}!")]
+    NumberParsingError {
+        line: usize,
+        column: usize,
+        format: NumberFormat,
+        number_string: String,
+    },
+}
+
+/// The notation of a number literal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberFormat {
+    /// `42`, `3.14` or `1.5e-3`.
+    Decimal,
+    /// `0xFF`.
+    Hexadecimal,
+    /// `0b1010`.
+    Binary,
+}
+
+impl NumberFormat {
+    fn radix(&self) -> u32 {
+        match self {
+            NumberFormat::Decimal => 10,
+            NumberFormat::Hexadecimal => 16,
+            NumberFormat::Binary => 2,
+        }
+    }
+}
+
+impl Display for NumberFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberFormat::Decimal => write!(f, "decimal"),
+            NumberFormat::Hexadecimal => write!(f, "hexadecimal"),
+            NumberFormat::Binary => write!(f, "binary"),
+        }
+    }
 }
 
-pub struct Scanner {
-    source: Vec<char>,
-    tokens: Vec<Token>,
+impl ScannerError {
+    /// Returns the line and column at which the error occurred.
+    pub fn location(&self) -> (usize, usize) {
+        match self {
+            ScannerError::UnknownSymbol { line, column, .. }
+            | ScannerError::UnterminatedString { line, column }
+            | ScannerError::UnterminatedComment { line, column }
+            | ScannerError::InvalidEscapeSequence { line, column }
+            | ScannerError::InvalidDigitSeparator { line, column }
+            | ScannerError::NumberParsingError { line, column, .. } => (*line, *column),
+        }
+    }
+}
+
+impl DiagnosticError for ScannerError {
+    fn code(&self) -> &'static str {
+        match self {
+            ScannerError::UnknownSymbol { .. } => "UnknownSymbol",
+            ScannerError::UnterminatedString { .. } => "UnterminatedString",
+            ScannerError::UnterminatedComment { .. } => "UnterminatedComment",
+            ScannerError::InvalidEscapeSequence { .. } => "InvalidEscapeSequence",
+            ScannerError::InvalidDigitSeparator { .. } => "InvalidDigitSeparator",
+            ScannerError::NumberParsingError { .. } => "NumberParsingError",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        Some(self.location().0)
+    }
+
+    fn column(&self) -> Option<usize> {
+        Some(self.location().1)
+    }
+}
+
+/// The result of scanning a whole script, with all errors found along the way. The tokens
+/// skip the source of each error and always end with a [`TokenType::Eof`] token.
+#[derive(Debug, Default)]
+pub struct ScanOutcome {
+    pub tokens: Vec<Token>,
+    pub errors: Vec<ScannerError>,
+}
+
+/// A `// rlox: ...` comment. The directive is the text after `rlox:`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pragma {
+    pub line: usize,
+    pub directive: String,
+}
+
+/// Turns source code into tokens. Positions in the source are byte offsets, so lexemes are
+/// copied straight out of the source.
+///
+/// The scanner is an iterator that scans one token at a time and ends after the
+/// [`TokenType::Eof`] token or the first error. [`Scanner::scan_all`] continues after errors.
+pub struct Scanner<'a> {
+    file: Option<Arc<str>>,
+    source: &'a str,
+    /// The token found by the last call of `scan_token`, which skips whitespace and comments
+    /// without finding one.
+    token: Option<Token>,
+    is_done: bool,
+    /// Whether whitespace and comments are scanned as tokens.
+    trivia: bool,
+    pragmas: Vec<Pragma>,
+    /// Number of unclosed braces in each embedded expression of the interpolated strings
+    /// being scanned, innermost last.
+    interpolations: Vec<usize>,
     start_pos: usize,
+    start_column: usize,
+    /// Line of the first character of the token being scanned.
+    start_line: usize,
     current_pos: usize,
     current_line: usize,
+    /// 1-based column of the current position, counted in characters, so that columns don't
+    /// have to be counted from the start of the line for every token.
+    current_column: usize,
 }
 
-impl Scanner {
-    pub fn new(input: &str) -> Self {
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
         Scanner {
-            source: input.chars().collect(),
-            tokens: vec![],
+            file: None,
+            source: input,
+            token: None,
+            is_done: false,
+            trivia: false,
+            pragmas: vec![],
+            interpolations: vec![],
             start_pos: 0,
+            start_column: 1,
+            start_line: 1,
             current_pos: 0,
             current_line: 1,
+            current_column: 1,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, ScannerError> {
-        while !self.is_at_end() {
-            self.start_pos = self.current_pos;
-            self.scan_token()?;
+    /// Creates a scanner whose tokens refer to the given script file in diagnostics.
+    pub fn new_with_file(input: &'a str, file: &str) -> Self {
+        Scanner {
+            file: Some(Arc::from(file)),
+            ..Scanner::new(input)
         }
+    }
 
-        self.tokens.push(Token::new(
-            TokenType::Eof,
-            String::from(""),
-            self.current_line,
-        ));
+    /// Scans whitespace and comments as [`TokenType::Whitespace`] and [`TokenType::Comment`]
+    /// tokens, so that the lexemes of all tokens add up to the source. This is meant for tools
+    /// like formatters, as the parser doesn't accept these tokens.
+    pub fn with_trivia(mut self) -> Self {
+        self.trivia = true;
+        self
+    }
 
-        Ok(self.tokens.clone())
+    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, ScannerError> {
+        self.by_ref().collect()
+    }
+
+    /// Scans all tokens, skipping the source of each error and continuing after it, so that all
+    /// lexical errors of the script are reported at once.
+    pub fn scan_all(&mut self) -> ScanOutcome {
+        let mut outcome = ScanOutcome::default();
+
+        loop {
+            match self.next() {
+                Some(Ok(token)) => outcome.tokens.push(token),
+                Some(Err(error)) => {
+                    outcome.errors.push(error);
+                    self.is_done = false;
+                }
+                None => return outcome,
+            }
+        }
+    }
+
+    /// Returns the pragmas found in the source scanned so far.
+    pub fn pragmas(&self) -> &[Pragma] {
+        &self.pragmas
     }
 
     fn scan_token(&mut self) -> Result<(), ScannerError> {
         match self.advance() {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.match_string()
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            '.' => {
+                let t = if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    TokenType::DotDotDot
+                } else {
+                    TokenType::Dot
+                };
+                self.add_token(t)
+            }
+            '-' => {
+                let t = if self.match_char('-') {
+                    TokenType::MinusMinus
+                } else if self.match_char('>') {
+                    TokenType::Arrow
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(t)
+            }
+            '+' => {
+                let t = if self.match_char('+') {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(t)
+            }
             ';' => self.add_token(TokenType::Semicolon),
+            '?' => self.add_token(TokenType::Question),
+            ':' => self.add_token(TokenType::Colon),
             '*' => self.add_token(TokenType::Star),
             '!' => {
                 let t = if self.match_char('=') {
@@ -156,32 +425,68 @@ impl Scanner {
             }
             '/' => {
                 if self.match_char('/') {
-                    while self.peek() != '\n' && !self.is_at_end() {
+                    while !matches!(self.peek(), '\n' | '\r') && !self.is_at_end() {
                         self.advance();
                     }
 
-                    Ok(())
+                    let comment = &self.source[self.start_pos + 2..self.current_pos];
+                    if let Some(directive) = comment.trim().strip_prefix("rlox:") {
+                        self.pragmas.push(Pragma {
+                            line: self.current_line,
+                            directive: directive.trim().to_owned(),
+                        });
+                    }
+
+                    self.add_trivia(TokenType::Comment)
+                } else if self.match_char('*') {
+                    self.match_block_comment()?;
+                    self.add_trivia(TokenType::Comment)
                 } else {
                     self.add_token(TokenType::Slash)
                 }
             }
             '"' => self.match_string(),
             '\n' => {
-                self.current_line += 1;
-                Ok(())
+                self.new_line();
+                self.match_whitespace()
+            }
+            '\r' => {
+                /* count lone carriage returns as line breaks, CRLF is counted at '\n'. */
+                if self.peek() != '\n' {
+                    self.new_line();
+                }
+                self.match_whitespace()
+            }
+            '\u{FEFF}' if self.start_pos == 0 => {
+                /* ignore byte order mark. */
+                self.match_whitespace()
             }
-            ' ' | '\r' | '\t' => {
+            '#' if self.peek() == '!'
+                && self.source[..self.start_pos]
+                    .trim_start_matches('\u{FEFF}')
+                    .is_empty() =>
+            {
+                /* ignore the interpreter line of executable scripts, as in `#!/usr/bin/env rlox`. */
+                while !matches!(self.peek(), '\n' | '\r') && !self.is_at_end() {
+                    self.advance();
+                }
+                self.add_trivia(TokenType::Comment)
+            }
+            ' ' | '\t' => {
                 /* ignore whitespaces. */
-                Ok(())
+                self.match_whitespace()
             }
             c => {
                 if c.is_ascii_digit() {
                     self.match_number()
-                } else if c.is_ascii_alphabetic() || c == '_' {
+                } else if c == 'r' && matches!(self.peek(), '"' | '#') && self.is_raw_string() {
+                    self.match_raw_string()
+                } else if c == '_' || is_xid_start(c) {
                     self.match_identifier()
                 } else {
                     Err(ScannerError::UnknownSymbol {
                         line: self.current_line,
+                        column: self.start_column,
                         symbol: c,
                     })
                 }
@@ -190,133 +495,424 @@ impl Scanner {
     }
 
     fn add_token(&mut self, token_type: TokenType) -> Result<(), ScannerError> {
-        let token_string: String = self.source[self.start_pos..self.current_pos]
-            .iter()
-            .collect();
-        self.tokens
-            .push(Token::new(token_type, token_string, self.current_line));
+        self.token = Some(Token::new(
+            token_type,
+            self.lexeme().to_owned(),
+            self.current_line,
+            self.start_column,
+            self.file.clone(),
+        ));
+
+        Ok(())
+    }
+
+    /// Adds a whitespace or comment token in trivia mode, and skips it otherwise. The token is
+    /// placed on the line it starts on, also if it spans line breaks.
+    fn add_trivia(&mut self, token_type: TokenType) -> Result<(), ScannerError> {
+        if !self.trivia {
+            return Ok(());
+        }
+
+        self.token = Some(Token::new(
+            token_type,
+            self.lexeme().to_owned(),
+            self.start_line,
+            self.start_column,
+            self.file.clone(),
+        ));
 
         Ok(())
     }
 
-    fn add_string_token(&mut self, value: String) -> Result<(), ScannerError> {
-        let token_string: String = self.source[self.start_pos..self.current_pos]
-            .iter()
-            .collect();
-        self.tokens.push(Token::new(
-            TokenType::String { value },
-            token_string,
+    /// Skips whitespace. In trivia mode, consumes the rest of the run of whitespace, line breaks
+    /// included, as one token.
+    fn match_whitespace(&mut self) -> Result<(), ScannerError> {
+        if !self.trivia {
+            return Ok(());
+        }
+
+        loop {
+            match self.peek() {
+                ' ' | '\t' => {
+                    self.advance();
+                }
+                '\n' => {
+                    self.advance();
+                    self.new_line();
+                }
+                '\r' => {
+                    self.advance();
+                    if self.peek() != '\n' {
+                        self.new_line();
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        self.add_trivia(TokenType::Whitespace)
+    }
+
+    fn add_string_token(&mut self, token_type: TokenType) -> Result<(), ScannerError> {
+        self.token = Some(Token::new(
+            token_type,
+            self.lexeme().to_owned(),
             self.current_line,
+            self.start_column,
+            self.file.clone(),
         ));
 
         Ok(())
     }
 
     fn add_number_token(&mut self, value: f64) -> Result<(), ScannerError> {
-        let token_string: String = self.source[self.start_pos..self.current_pos]
-            .iter()
-            .collect();
-        self.tokens.push(Token::new(
-            TokenType::Number {
-                value,
-            },
-            token_string,
+        self.token = Some(Token::new(
+            TokenType::Number { value },
+            self.lexeme().to_owned(),
             self.current_line,
+            self.start_column,
+            self.file.clone(),
         ));
 
         Ok(())
     }
 
+    /// Returns the source of the token being scanned.
+    fn lexeme(&self) -> &'a str {
+        &self.source[self.start_pos..self.current_pos]
+    }
+
+    /// Counts a line break at the current position.
+    fn new_line(&mut self) {
+        self.current_line += 1;
+        self.current_column = 1;
+    }
+
     fn match_char(&mut self, expected: char) -> bool {
-        if self.is_at_end() || self.source[self.current_pos] != expected {
+        if self.is_at_end() || self.peek() != expected {
             return false;
         }
 
-        self.current_pos += 1;
+        self.current_pos += expected.len_utf8();
+        self.current_column += 1;
 
         true
     }
 
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn match_block_comment(&mut self) -> Result<(), ScannerError> {
+        let start_line = self.current_line;
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.is_at_end() {
+                return Err(ScannerError::UnterminatedComment {
+                    line: start_line,
+                    column: self.start_column,
+                });
+            }
+
+            let is_line_break =
+                self.peek() == '\n' || (self.peek() == '\r' && self.peek_next() != '\n');
+
+            if self.peek() == '/' && self.peek_next() == '*' {
+                self.advance();
+                self.advance();
+                depth += 1;
+            } else if self.peek() == '*' && self.peek_next() == '/' {
+                self.advance();
+                self.advance();
+                depth -= 1;
+            } else {
+                self.advance();
+                if is_line_break {
+                    self.new_line();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Scans a string literal, or the part of an interpolated string up to the next embedded
+    /// expression.
     fn match_string(&mut self) -> Result<(), ScannerError> {
         let start_line = self.current_line;
+        let mut value = String::new();
 
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.current_line += 1;
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+
+                let value = value.replace("\r\n", "\n").replace('\r', "\n");
+                return self.add_string_token(TokenType::Interpolation { value });
             }
 
-            self.advance();
+            let is_line_break =
+                self.peek() == '\n' || (self.peek() == '\r' && self.peek_next() != '\n');
+
+            match self.advance() {
+                '\\' => match self.match_escape_sequence() {
+                    Ok(c) => value.push(c),
+                    Err(error) => {
+                        self.skip_string();
+                        return Err(error);
+                    }
+                },
+                c => value.push(c),
+            }
+            if is_line_break {
+                self.new_line();
+            }
         }
 
         if self.is_at_end() {
-            return Err(ScannerError::UnterminatedString { line: start_line });
+            /* the strings this one is interpolated into end here as well, without another error. */
+            self.interpolations.clear();
+            return Err(ScannerError::UnterminatedString {
+                line: start_line,
+                column: self.start_column,
+            });
         }
 
         self.advance();
-        self.add_string_token(
-            self.source[self.start_pos + 1..self.current_pos - 1]
-                .iter()
-                .collect(),
-        )
+
+        let value = value.replace("\r\n", "\n").replace('\r', "\n");
+        self.add_string_token(TokenType::String { value })
     }
 
-    fn match_number(&mut self) -> Result<(), ScannerError> {
-        while self.peek().is_ascii_digit() {
+    /// Skips the rest of a string literal with an error, up to and including its closing quote.
+    fn skip_string(&mut self) {
+        while self.peek() != '"' && !self.is_at_end() {
+            let is_line_break =
+                self.peek() == '\n' || (self.peek() == '\r' && self.peek_next() != '\n');
             self.advance();
+            if is_line_break {
+                self.new_line();
+            }
         }
+        self.match_char('"');
+    }
 
-        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
-            self.advance();
+    /// Checks whether the `r` just consumed starts a raw string, i.e. is followed by any number
+    /// of `#` and a double quote.
+    fn is_raw_string(&self) -> bool {
+        self.source[self.current_pos..]
+            .trim_start_matches('#')
+            .starts_with('"')
+    }
+
+    /// Scans a raw string literal like `r"C:\path"` or `r#"say "hi""#`. Backslashes and `${` are
+    /// kept as they are and line breaks are preserved verbatim. The string ends at the first
+    /// double quote followed by as many `#` as preceded the opening quote.
+    fn match_raw_string(&mut self) -> Result<(), ScannerError> {
+        let start_line = self.current_line;
+        let mut hashes = 0;
+        while self.match_char('#') {
+            hashes += 1;
+        }
+        self.advance();
+
+        let mut value = String::new();
+        loop {
+            if self.is_at_end() {
+                self.interpolations.clear();
+                return Err(ScannerError::UnterminatedString {
+                    line: start_line,
+                    column: self.start_column,
+                });
+            }
+
+            if self.peek() == '"'
+                && self.source[self.current_pos + 1..]
+                    .bytes()
+                    .take_while(|c| *c == b'#')
+                    .count()
+                    >= hashes
+            {
+                self.current_pos += hashes + 1;
+                self.current_column += hashes + 1;
+                break;
+            }
+
+            let is_line_break =
+                self.peek() == '\n' || (self.peek() == '\r' && self.peek_next() != '\n');
+            value.push(self.advance());
+            if is_line_break {
+                self.new_line();
+            }
         }
 
-        while self.peek().is_ascii_digit() {
+        self.add_string_token(TokenType::String { value })
+    }
+
+    /// Consumes the escape sequence following a backslash and returns the escaped character.
+    fn match_escape_sequence(&mut self) -> Result<char, ScannerError> {
+        let error = ScannerError::InvalidEscapeSequence {
+            line: self.current_line,
+            column: self.current_column - 1,
+        };
+
+        if self.is_at_end() {
+            return Err(error);
+        }
+
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' if self.match_char('{') => {
+                let start = self.current_pos;
+                while self.peek().is_ascii_hexdigit() {
+                    self.advance();
+                }
+
+                let digits = &self.source[start..self.current_pos];
+                if digits.is_empty() || digits.len() > 6 || !self.match_char('}') {
+                    return Err(error);
+                }
+
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(error)
+            }
+            _ => Err(error),
+        }
+    }
+
+    fn match_number(&mut self) -> Result<(), ScannerError> {
+        let zero = self.lexeme() == "0";
+        let format = match self.peek() {
+            'x' | 'X' if zero => NumberFormat::Hexadecimal,
+            'b' | 'B' if zero => NumberFormat::Binary,
+            _ => NumberFormat::Decimal,
+        };
+
+        let digits = self.match_number_digits(format);
+
+        /* letters directly after a number, as in `0xFG` or `1e`, are part of a malformed literal. */
+        while is_xid_continue(self.peek()) {
             self.advance();
         }
+        digits?;
+
+        let number_string = self.lexeme().to_owned();
+        let digits = number_string.replace('_', "");
+        let number = match format {
+            NumberFormat::Decimal => f64::from_str(&digits).ok(),
+            _ => u64::from_str_radix(&digits[2..], format.radix())
+                .ok()
+                .map(|number| number as f64),
+        };
 
-        let number_string: String = self.source[self.start_pos..self.current_pos]
-            .iter()
-            .collect();
-        let number = f64::from_str(number_string.as_str()).map_err(|_| {
-            ScannerError::NumberParsingError {
+        match number {
+            Some(number) => self.add_number_token(number),
+            None => Err(ScannerError::NumberParsingError {
                 line: self.current_line,
+                column: self.start_column,
+                format,
                 number_string,
+            }),
+        }
+    }
+
+    /// Consumes the digits of a number literal after its first digit, up to its suffix.
+    fn match_number_digits(&mut self, format: NumberFormat) -> Result<(), ScannerError> {
+        if format == NumberFormat::Decimal {
+            self.match_digits(format)?;
+
+            if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+                self.advance();
+                self.match_digits(format)?;
             }
-        })?;
 
-        self.add_number_token(number)
+            if matches!(self.peek(), 'e' | 'E') {
+                self.advance();
+                if matches!(self.peek(), '+' | '-') {
+                    self.advance();
+                }
+                self.match_leading_digits(format)?;
+            }
+        } else {
+            self.advance();
+            self.match_leading_digits(format)?;
+        }
+
+        Ok(())
     }
 
-    fn match_identifier(&mut self) -> Result<(), ScannerError> {
+    /// Consumes the digits following a prefix or an exponent marker, which must not start with
+    /// an underscore.
+    fn match_leading_digits(&mut self, format: NumberFormat) -> Result<(), ScannerError> {
+        if self.peek() == '_' {
+            return Err(ScannerError::InvalidDigitSeparator {
+                line: self.current_line,
+                column: self.current_column,
+            });
+        }
+
+        self.match_digits(format)
+    }
+
+    /// Consumes digits of the given format, which may be separated by single underscores.
+    fn match_digits(&mut self, format: NumberFormat) -> Result<(), ScannerError> {
         loop {
-            let c = self.peek();
-            if c.is_ascii_alphabetic() || c.is_ascii_digit() || c == '_' {
+            if self.peek().is_digit(format.radix()) {
+                self.advance();
+            } else if self.peek() == '_' {
+                if !self.peek_next().is_digit(format.radix()) {
+                    return Err(ScannerError::InvalidDigitSeparator {
+                        line: self.current_line,
+                        column: self.current_column,
+                    });
+                }
                 self.advance();
             } else {
-                break;
+                return Ok(());
             }
         }
+    }
 
-        let identifier_string: String = self.source[self.start_pos..self.current_pos]
-            .iter()
-            .collect();
+    /// Consumes an identifier, which follows the Unicode rules for identifiers like Rust's do:
+    /// a letter or `_`, followed by letters, digits, `_` and combining marks.
+    fn match_identifier(&mut self) -> Result<(), ScannerError> {
+        while is_xid_continue(self.peek()) {
+            self.advance();
+        }
 
-        let t = match identifier_string.as_str() {
+        let t = match self.lexeme() {
             "and" => TokenType::And,
+            "async" => TokenType::Async,
+            "await" => TokenType::Await,
+            "break" => TokenType::Break,
             "class" => TokenType::Class,
+            "const" => TokenType::Const,
+            "continue" => TokenType::Continue,
             "else" => TokenType::Else,
             "false" => TokenType::False,
             "for" => TokenType::For,
             "fun" => TokenType::Fun,
             "if" => TokenType::If,
+            "import" => TokenType::Import,
+            "in" => TokenType::In,
+            "is" => TokenType::Is,
             "nil" => TokenType::Nil,
             "or" => TokenType::Or,
             "print" => TokenType::Print,
             "return" => TokenType::Return,
             "super" => TokenType::Super,
             "this" => TokenType::This,
+            "trait" => TokenType::Trait,
             "true" => TokenType::True,
             "var" => TokenType::Var,
             "while" => TokenType::While,
+            "with" => TokenType::With,
+            "yield" => TokenType::Yield,
             _ => TokenType::Identifier,
         };
 
@@ -328,25 +924,65 @@ impl Scanner {
     }
 
     fn advance(&mut self) -> char {
-        let c = self.source[self.current_pos];
-        self.current_pos += 1;
+        let c = self.peek();
+        self.current_pos += c.len_utf8();
+        self.current_column += 1;
 
         c
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source[self.current_pos]
-        }
+        self.source[self.current_pos..]
+            .chars()
+            .next()
+            .unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.current_pos + 1 >= self.source.len() {
-            '\0'
-        } else {
-            self.source[self.current_pos + 1]
+        let mut chars = self.source[self.current_pos..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Result<Token, ScannerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_done {
+            if self.is_at_end() {
+                self.is_done = true;
+
+                if !self.interpolations.is_empty() {
+                    self.interpolations.clear();
+                    return Some(Err(ScannerError::UnterminatedString {
+                        line: self.current_line,
+                        column: self.current_column,
+                    }));
+                }
+
+                return Some(Ok(Token::new(
+                    TokenType::Eof,
+                    String::from(""),
+                    self.current_line,
+                    self.current_column,
+                    self.file.clone(),
+                )));
+            }
+
+            self.start_pos = self.current_pos;
+            self.start_column = self.current_column;
+            self.start_line = self.current_line;
+            if let Err(error) = self.scan_token() {
+                self.is_done = true;
+                return Some(Err(error));
+            }
+
+            if let Some(token) = self.token.take() {
+                return Some(Ok(token));
+            }
         }
+
+        None
     }
 }
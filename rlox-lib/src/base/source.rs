@@ -0,0 +1,43 @@
+use crate::base::diagnostic::DiagnosticError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SourceError {
+    #[error("{line:?}: Invalid UTF-8 at byte offset {offset:?}!")]
+    InvalidUtf8 { line: usize, offset: usize },
+}
+
+impl DiagnosticError for SourceError {
+    fn code(&self) -> &'static str {
+        match self {
+            SourceError::InvalidUtf8 { .. } => "InvalidUtf8",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        match self {
+            SourceError::InvalidUtf8 { line, .. } => Some(*line),
+        }
+    }
+
+    fn column(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Decodes the raw content of a script file. Invalid UTF-8 is either reported with the line it
+/// occurs in or, if `lossy` is set, replaced by U+FFFD.
+pub fn decode_source(bytes: &[u8], lossy: bool) -> Result<String, SourceError> {
+    if lossy {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+
+    std::str::from_utf8(bytes)
+        .map(str::to_owned)
+        .map_err(|error| {
+            let offset = error.valid_up_to();
+            let line = bytes[..offset].iter().filter(|b| **b == b'\n').count() + 1;
+
+            SourceError::InvalidUtf8 { line, offset }
+        })
+}
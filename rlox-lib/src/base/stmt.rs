@@ -1,106 +1,279 @@
-use crate::base::expr::Expr;
+use crate::base::ast::{ExprRef, StmtRef};
+use crate::base::expr::NodeId;
 use crate::base::scanner::Token;
 use crate::base::visitor::Visitor;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Clone, Debug, PartialEq)]
+/// The shape of a destructuring declaration.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Pattern {
+    /// `var [a, b] = list;` binds the elements of a list by position.
+    List,
+    /// `var {x, y} = instance;` binds the properties of an instance by name.
+    Fields,
+    /// `var a, b = f();` binds the values returned by a function, which must be exactly as
+    /// many as there are names.
+    Values,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Stmt {
     Block {
-        statements: Vec<Stmt>,
+        statements: Vec<StmtRef>,
+    },
+    Break {
+        keyword: Box<Token>,
+        label: Box<Option<Token>>,
     },
     Class {
+        /// Identifies the field initializer, which captures variables like a method.
+        id: NodeId,
         name: Box<Token>,
-        superclass: Box<Option<Expr>>,
-        methods: Vec<Stmt>,
+        superclass: Option<ExprRef>,
+        /// Traits whose methods are copied into the class.
+        traits: Vec<ExprRef>,
+        /// Field initializers, as `this.field = value;` expression statements.
+        fields: Arc<[StmtRef]>,
+        methods: Vec<StmtRef>,
+    },
+    Continue {
+        keyword: Box<Token>,
+        label: Box<Option<Token>>,
+    },
+    Destructure {
+        keyword: Box<Token>,
+        pattern: Pattern,
+        names: Vec<Token>,
+        initializer: ExprRef,
     },
     Expression {
-        expression: Box<Expr>,
+        expression: ExprRef,
+    },
+    ForIn {
+        keyword: Box<Token>,
+        label: Box<Option<Token>>,
+        variable: Box<Token>,
+        iterable: ExprRef,
+        body: StmtRef,
     },
     Function {
+        /// Identifies the function, so that the resolver can record the variables it captures.
+        id: NodeId,
         name: Box<Token>,
-        params: Vec<Token>,
-        body: Vec<Stmt>,
+        /// Shared with the functions created from the declaration.
+        params: Arc<[Token]>,
+        body: Arc<[StmtRef]>,
+        is_async: bool,
+        /// Whether the last parameter collects the remaining arguments into a list.
+        is_variadic: bool,
+        /// Whether the body contains a `yield` statement, which makes calls return a generator.
+        is_generator: bool,
     },
     If {
-        condition: Box<Expr>,
-        then_branch: Box<Stmt>,
-        else_branch: Box<Option<Stmt>>,
+        keyword: Box<Token>,
+        condition: ExprRef,
+        then_branch: StmtRef,
+        else_branch: Option<StmtRef>,
+    },
+    Import {
+        keyword: Box<Token>,
+        path: Box<Token>,
     },
     Print {
-        expression: Box<Expr>,
+        expression: ExprRef,
     },
     Return {
         keyword: Box<Token>,
-        value: Box<Option<Expr>>,
+        value: Option<ExprRef>,
+    },
+    Trait {
+        name: Box<Token>,
+        methods: Vec<StmtRef>,
     },
     Var {
         name: Box<Token>,
-        initializer: Box<Expr>,
+        initializer: Option<ExprRef>,
+        is_const: bool,
     },
     While {
-        condition: Box<Expr>,
-        body: Box<Stmt>,
+        keyword: Box<Token>,
+        label: Box<Option<Token>>,
+        condition: ExprRef,
+        body: StmtRef,
+        /// The increment of a desugared `for` loop, which also runs after `continue`.
+        increment: Option<ExprRef>,
+    },
+    Yield {
+        keyword: Box<Token>,
+        value: Option<ExprRef>,
     },
 }
 
 impl Stmt {
-    pub fn block(statements: Vec<Stmt>) -> Self {
+    pub fn block(statements: Vec<StmtRef>) -> Self {
         Stmt::Block { statements }
     }
 
-    pub fn class(name: Token, superclass: Option<Expr>, methods: Vec<Stmt>) -> Self {
+    pub fn break_stmt(keyword: Token, label: Option<Token>) -> Self {
+        Stmt::Break {
+            keyword: Box::new(keyword),
+            label: Box::new(label),
+        }
+    }
+
+    pub fn continue_stmt(keyword: Token, label: Option<Token>) -> Self {
+        Stmt::Continue {
+            keyword: Box::new(keyword),
+            label: Box::new(label),
+        }
+    }
+
+    pub fn class(
+        name: Token,
+        superclass: Option<ExprRef>,
+        traits: Vec<ExprRef>,
+        fields: Vec<StmtRef>,
+        methods: Vec<StmtRef>,
+    ) -> Self {
         Stmt::Class {
+            id: NodeId::next(),
             name: Box::new(name),
-            superclass: Box::new(superclass),
+            superclass,
+            traits,
+            fields: fields.into(),
             methods,
         }
     }
 
-    pub fn function(name: Token, params: Vec<Token>, body: Vec<Stmt>) -> Self {
+    pub fn for_in(
+        keyword: Token,
+        label: Option<Token>,
+        variable: Token,
+        iterable: ExprRef,
+        body: StmtRef,
+    ) -> Self {
+        Stmt::ForIn {
+            keyword: Box::new(keyword),
+            label: Box::new(label),
+            variable: Box::new(variable),
+            iterable,
+            body,
+        }
+    }
+
+    pub fn function(
+        name: Token,
+        params: Vec<Token>,
+        body: Vec<StmtRef>,
+        is_async: bool,
+        is_variadic: bool,
+        is_generator: bool,
+    ) -> Self {
         Stmt::Function {
+            id: NodeId::next(),
             name: Box::new(name),
-            params,
-            body,
+            params: params.into(),
+            body: body.into(),
+            is_async,
+            is_variadic,
+            is_generator,
         }
     }
 
-    pub fn expression(expression: Expr) -> Self {
-        Stmt::Expression {
-            expression: Box::new(expression),
+    pub fn destructure(
+        keyword: Token,
+        pattern: Pattern,
+        names: Vec<Token>,
+        initializer: ExprRef,
+    ) -> Self {
+        Stmt::Destructure {
+            keyword: Box::new(keyword),
+            pattern,
+            names,
+            initializer,
         }
     }
 
-    pub fn if_stmt(condition: Expr, then_branch: Stmt, else_branch: Option<Stmt>) -> Self {
+    pub fn expression(expression: ExprRef) -> Self {
+        Stmt::Expression { expression }
+    }
+
+    pub fn if_stmt(
+        keyword: Token,
+        condition: ExprRef,
+        then_branch: StmtRef,
+        else_branch: Option<StmtRef>,
+    ) -> Self {
         Stmt::If {
-            condition: Box::new(condition),
-            then_branch: Box::new(then_branch),
-            else_branch: Box::new(else_branch),
+            keyword: Box::new(keyword),
+            condition,
+            then_branch,
+            else_branch,
         }
     }
 
-    pub fn print(expression: Expr) -> Self {
-        Stmt::Print {
-            expression: Box::new(expression),
+    pub fn import(keyword: Token, path: Token) -> Self {
+        Stmt::Import {
+            keyword: Box::new(keyword),
+            path: Box::new(path),
         }
     }
 
-    pub fn return_stmt(keyword: Token, value: Option<Expr>) -> Self {
+    pub fn print(expression: ExprRef) -> Self {
+        Stmt::Print { expression }
+    }
+
+    pub fn return_stmt(keyword: Token, value: Option<ExprRef>) -> Self {
         Stmt::Return {
             keyword: Box::new(keyword),
-            value: Box::new(value),
+            value,
+        }
+    }
+
+    pub fn trait_stmt(name: Token, methods: Vec<StmtRef>) -> Self {
+        Stmt::Trait {
+            name: Box::new(name),
+            methods,
+        }
+    }
+
+    pub fn var(name: Token, initializer: Option<ExprRef>) -> Self {
+        Stmt::Var {
+            name: Box::new(name),
+            initializer,
+            is_const: false,
         }
     }
 
-    pub fn var(name: Token, initializer: Expr) -> Self {
+    pub fn constant(name: Token, initializer: ExprRef) -> Self {
         Stmt::Var {
             name: Box::new(name),
-            initializer: Box::new(initializer),
+            initializer: Some(initializer),
+            is_const: true,
         }
     }
 
-    pub fn while_stmt(condition: Expr, body: Stmt) -> Self {
+    pub fn while_stmt(
+        keyword: Token,
+        label: Option<Token>,
+        condition: ExprRef,
+        body: StmtRef,
+        increment: Option<ExprRef>,
+    ) -> Self {
         Stmt::While {
-            condition: Box::new(condition),
-            body: Box::new(body),
+            keyword: Box::new(keyword),
+            label: Box::new(label),
+            condition,
+            body,
+            increment,
+        }
+    }
+
+    pub fn yield_stmt(keyword: Token, value: Option<ExprRef>) -> Self {
+        Stmt::Yield {
+            keyword: Box::new(keyword),
+            value,
         }
     }
 
@@ -0,0 +1,174 @@
+use crate::base::expr_result::format_number;
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// Instructions of the bytecode backend. Operands follow the opcode byte in the chunk.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum OpCode {
+    Constant,
+    Nil,
+    True,
+    False,
+    Pop,
+    Dup,
+    GetLocal,
+    SetLocal,
+    GetGlobal,
+    DefineGlobal,
+    SetGlobal,
+    GetUpvalue,
+    SetUpvalue,
+    GetProperty,
+    SetProperty,
+    GetSuper,
+    Equal,
+    Greater,
+    Less,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Not,
+    Negate,
+    Stringify,
+    Print,
+    Jump,
+    JumpIfFalse,
+    Loop,
+    Call,
+    Invoke,
+    SuperInvoke,
+    Closure,
+    CloseUpvalue,
+    Return,
+    Class,
+    Inherit,
+    Method,
+}
+
+impl OpCode {
+    const ALL: [OpCode; 39] = [
+        OpCode::Constant,
+        OpCode::Nil,
+        OpCode::True,
+        OpCode::False,
+        OpCode::Pop,
+        OpCode::Dup,
+        OpCode::GetLocal,
+        OpCode::SetLocal,
+        OpCode::GetGlobal,
+        OpCode::DefineGlobal,
+        OpCode::SetGlobal,
+        OpCode::GetUpvalue,
+        OpCode::SetUpvalue,
+        OpCode::GetProperty,
+        OpCode::SetProperty,
+        OpCode::GetSuper,
+        OpCode::Equal,
+        OpCode::Greater,
+        OpCode::Less,
+        OpCode::Add,
+        OpCode::Subtract,
+        OpCode::Multiply,
+        OpCode::Divide,
+        OpCode::Not,
+        OpCode::Negate,
+        OpCode::Stringify,
+        OpCode::Print,
+        OpCode::Jump,
+        OpCode::JumpIfFalse,
+        OpCode::Loop,
+        OpCode::Call,
+        OpCode::Invoke,
+        OpCode::SuperInvoke,
+        OpCode::Closure,
+        OpCode::CloseUpvalue,
+        OpCode::Return,
+        OpCode::Class,
+        OpCode::Inherit,
+        OpCode::Method,
+    ];
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.get(byte as usize).copied()
+    }
+}
+
+/// A compiled function. The top-level script is compiled into a function without a name.
+#[derive(Debug, Default, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub arity: usize,
+    pub upvalue_count: usize,
+    pub chunk: Chunk,
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "<script>")
+        } else {
+            write!(f, "<fn {}>", self.name)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Number(f64),
+    String(Rc<str>),
+    Function(Rc<Function>),
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Constant::Number(value) => write!(f, "{}", format_number(*value)),
+            Constant::String(value) => write!(f, "{}", value),
+            Constant::Function(function) => write!(f, "{}", function),
+        }
+    }
+}
+
+/// A sequence of instructions with the source line and column of every byte and the constants
+/// the instructions refer to.
+#[derive(Debug, Default, PartialEq)]
+pub struct Chunk {
+    pub code: Vec<u8>,
+    pub lines: Vec<usize>,
+    pub columns: Vec<usize>,
+    pub constants: Vec<Constant>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, byte: u8, line: usize, column: usize) {
+        self.code.push(byte);
+        self.lines.push(line);
+        self.columns.push(column);
+    }
+
+    pub fn write_op(&mut self, op: OpCode, line: usize, column: usize) {
+        self.write(op as u8, line, column);
+    }
+
+    /// Adds a constant and returns its index. Equal numbers and strings are stored only once.
+    pub fn add_constant(&mut self, value: Constant) -> usize {
+        let existing = match &value {
+            Constant::Function(_) => None,
+            _ => self
+                .constants
+                .iter()
+                .position(|constant| *constant == value),
+        };
+
+        existing.unwrap_or_else(|| {
+            self.constants.push(value);
+            self.constants.len() - 1
+        })
+    }
+}
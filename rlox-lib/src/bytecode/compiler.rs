@@ -0,0 +1,1049 @@
+use crate::base::ast::{Ast, ExprRef, StmtRef};
+use crate::base::diagnostic::DiagnosticError;
+use crate::base::expr::{Expr, LiteralValue};
+use crate::base::scanner::{Token, TokenType};
+use crate::base::stmt::Stmt;
+use crate::base::visitor::Visitor;
+use crate::bytecode::chunk::{Constant, Function, OpCode};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CompilerError {
+    #[error("{line:?}: Too many constants in one chunk!")]
+    TooManyConstants { line: usize },
+    #[error("{line:?}: Too many local variables in function!")]
+    TooManyLocals { line: usize },
+    #[error("{line:?}: Too many closure variables in function!")]
+    TooManyUpvalues { line: usize },
+    #[error("{line:?}: Can't have more than 255 parameters!")]
+    TooManyParameters { line: usize },
+    #[error("{line:?}: Can't have more than 255 arguments!")]
+    TooManyArguments { line: usize },
+    #[error("{line:?}: Too much code to jump over!")]
+    JumpTooLarge { line: usize },
+    #[error("{line:?}: Loop body too large!")]
+    LoopTooLarge { line: usize },
+    #[error("{line:?}: {feature} not supported by the bytecode backend!")]
+    Unsupported { line: usize, feature: String },
+}
+
+impl DiagnosticError for CompilerError {
+    fn code(&self) -> &'static str {
+        match self {
+            CompilerError::TooManyConstants { .. } => "TooManyConstants",
+            CompilerError::TooManyLocals { .. } => "TooManyLocals",
+            CompilerError::TooManyUpvalues { .. } => "TooManyUpvalues",
+            CompilerError::TooManyParameters { .. } => "TooManyParameters",
+            CompilerError::TooManyArguments { .. } => "TooManyArguments",
+            CompilerError::JumpTooLarge { .. } => "JumpTooLarge",
+            CompilerError::LoopTooLarge { .. } => "LoopTooLarge",
+            CompilerError::Unsupported { .. } => "Unsupported",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        match self {
+            CompilerError::TooManyConstants { line, .. }
+            | CompilerError::TooManyLocals { line, .. }
+            | CompilerError::TooManyUpvalues { line, .. }
+            | CompilerError::TooManyParameters { line, .. }
+            | CompilerError::TooManyArguments { line, .. }
+            | CompilerError::JumpTooLarge { line, .. }
+            | CompilerError::LoopTooLarge { line, .. }
+            | CompilerError::Unsupported { line, .. } => Some(*line),
+        }
+    }
+
+    fn column(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FunctionType {
+    Script,
+    Function,
+    Method,
+    Initializer,
+}
+
+struct Local {
+    name: String,
+    depth: Option<usize>,
+    is_captured: bool,
+}
+
+struct Upvalue {
+    index: u8,
+    is_local: bool,
+}
+
+/// An enclosing loop and the jumps of its `break` and `continue` statements, patched once the
+/// loop ends and once its body ends.
+struct Loop {
+    scope_depth: usize,
+    label: Option<String>,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+struct FunctionState {
+    function: Function,
+    function_type: FunctionType,
+    locals: Vec<Local>,
+    upvalues: Vec<Upvalue>,
+    loops: Vec<Loop>,
+    scope_depth: usize,
+}
+
+impl FunctionState {
+    fn new(name: &str, function_type: FunctionType) -> Self {
+        let receiver = match function_type {
+            FunctionType::Method | FunctionType::Initializer => "this",
+            FunctionType::Script | FunctionType::Function => "",
+        };
+
+        Self {
+            function: Function {
+                name: name.to_owned(),
+                ..Function::default()
+            },
+            function_type,
+            locals: vec![Local {
+                name: receiver.to_owned(),
+                depth: Some(0),
+                is_captured: false,
+            }],
+            upvalues: vec![],
+            loops: vec![],
+            scope_depth: 0,
+        }
+    }
+}
+
+/// Lowers a resolved AST into bytecode, in the style of clox: locals live in stack slots and
+/// closures capture variables through upvalues.
+///
+/// Expects statements that passed the [`Resolver`](crate::interpreter::resolver::Resolver),
+/// which reports misplaced `return`, `this` and `super`.
+pub struct Compiler {
+    ast: RefCell<Arc<Ast>>,
+    functions: RefCell<Vec<FunctionState>>,
+    line: RefCell<usize>,
+    column: RefCell<usize>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            ast: RefCell::new(Arc::new(Ast::default())),
+            functions: RefCell::new(vec![]),
+            line: RefCell::new(1),
+            column: RefCell::new(1),
+        }
+    }
+
+    /// Compiles a script into a function without name and parameters.
+    pub fn compile(&self, ast: &Arc<Ast>) -> Result<Function, CompilerError> {
+        self.ast.replace(Arc::clone(ast));
+        self.functions
+            .borrow_mut()
+            .push(FunctionState::new("", FunctionType::Script));
+
+        for statement in ast.statements() {
+            self.compile_stmt(*statement)?;
+        }
+
+        self.emit_return();
+        let (function, _) = self.end_function();
+
+        Ok(function)
+    }
+
+    fn compile_stmt(&self, statement: StmtRef) -> Result<(), CompilerError> {
+        self.ast.borrow()[statement].accept(self)
+    }
+
+    fn compile_expr(&self, expression: ExprRef) -> Result<(), CompilerError> {
+        self.ast.borrow()[expression].accept(self)
+    }
+
+    fn line(&self) -> usize {
+        *self.line.borrow()
+    }
+
+    fn column(&self) -> usize {
+        *self.column.borrow()
+    }
+
+    fn at(&self, token: &Token) {
+        self.line.replace(token.line);
+        self.column.replace(token.column);
+    }
+
+    fn current<R>(&self, f: impl FnOnce(&mut FunctionState) -> R) -> R {
+        f(self.functions.borrow_mut().last_mut().unwrap())
+    }
+
+    fn code_len(&self) -> usize {
+        self.current(|state| state.function.chunk.code.len())
+    }
+
+    fn emit_byte(&self, byte: u8) {
+        let (line, column) = (self.line(), self.column());
+        self.current(|state| state.function.chunk.write(byte, line, column));
+    }
+
+    fn emit_op(&self, op: OpCode) {
+        self.emit_byte(op as u8);
+    }
+
+    fn emit_op_with(&self, op: OpCode, operand: u8) {
+        self.emit_op(op);
+        self.emit_byte(operand);
+    }
+
+    fn emit_return(&self) {
+        if self.current(|state| state.function_type) == FunctionType::Initializer {
+            self.emit_op_with(OpCode::GetLocal, 0);
+        } else {
+            self.emit_op(OpCode::Nil);
+        }
+
+        self.emit_op(OpCode::Return);
+    }
+
+    fn make_constant(&self, value: Constant) -> Result<u8, CompilerError> {
+        let index = self.current(|state| state.function.chunk.add_constant(value));
+        u8::try_from(index).map_err(|_| CompilerError::TooManyConstants { line: self.line() })
+    }
+
+    fn identifier_constant(&self, name: &Token) -> Result<u8, CompilerError> {
+        self.make_constant(Constant::String(Rc::from(name.lexeme.as_str())))
+    }
+
+    fn emit_jump(&self, op: OpCode) -> usize {
+        self.emit_op(op);
+        self.emit_byte(0xff);
+        self.emit_byte(0xff);
+
+        self.code_len() - 2
+    }
+
+    fn patch_jump(&self, offset: usize) -> Result<(), CompilerError> {
+        let jump = self.code_len() - offset - 2;
+        let jump =
+            u16::try_from(jump).map_err(|_| CompilerError::JumpTooLarge { line: self.line() })?;
+
+        self.current(|state| {
+            state.function.chunk.code[offset..offset + 2].copy_from_slice(&jump.to_be_bytes())
+        });
+
+        Ok(())
+    }
+
+    fn emit_loop(&self, loop_start: usize) -> Result<(), CompilerError> {
+        self.emit_op(OpCode::Loop);
+
+        let offset = self.code_len() - loop_start + 2;
+        let offset =
+            u16::try_from(offset).map_err(|_| CompilerError::LoopTooLarge { line: self.line() })?;
+        for byte in offset.to_be_bytes() {
+            self.emit_byte(byte);
+        }
+
+        Ok(())
+    }
+
+    /// Emits a `break` or `continue`: discards the locals of the loop body without ending their
+    /// scopes and jumps to the end of the loop or of its body.
+    fn jump_out_of_loop(&self, keyword: &Token, label: &Option<Token>, is_continue: bool) {
+        self.at(keyword);
+
+        let captured: Vec<bool> = self.current(|state| {
+            let depth = state
+                .loops
+                .iter()
+                .rev()
+                .find(|l| Self::targets(l, label))
+                .map_or(0, |l| l.scope_depth);
+            state
+                .locals
+                .iter()
+                .rev()
+                .take_while(|local| local.depth.is_none_or(|d| d > depth))
+                .map(|local| local.is_captured)
+                .collect()
+        });
+        for is_captured in captured {
+            if is_captured {
+                self.emit_op(OpCode::CloseUpvalue);
+            } else {
+                self.emit_op(OpCode::Pop);
+            }
+        }
+
+        let jump = self.emit_jump(OpCode::Jump);
+        self.current(|state| {
+            if let Some(target) = state
+                .loops
+                .iter_mut()
+                .rev()
+                .find(|l| Self::targets(l, label))
+            {
+                if is_continue {
+                    target.continues.push(jump);
+                } else {
+                    target.breaks.push(jump);
+                }
+            }
+        });
+    }
+
+    /// Whether a `break` or `continue` with the given label applies to a loop.
+    fn targets(enclosing: &Loop, label: &Option<Token>) -> bool {
+        match label {
+            Some(label) => enclosing.label.as_deref() == Some(label.lexeme.as_str()),
+            None => true,
+        }
+    }
+
+    fn begin_scope(&self) {
+        self.current(|state| state.scope_depth += 1);
+    }
+
+    fn end_scope(&self) {
+        let captured: Vec<bool> = self.current(|state| {
+            state.scope_depth -= 1;
+
+            let depth = state.scope_depth;
+            let mut captured = vec![];
+            while let Some(local) = state.locals.last() {
+                if local.depth.is_some_and(|local_depth| local_depth <= depth) {
+                    break;
+                }
+                captured.push(local.is_captured);
+                state.locals.pop();
+            }
+
+            captured
+        });
+
+        for is_captured in captured {
+            if is_captured {
+                self.emit_op(OpCode::CloseUpvalue);
+            } else {
+                self.emit_op(OpCode::Pop);
+            }
+        }
+    }
+
+    /// Declares a variable in the current scope and returns the constant of its name for
+    /// global variables.
+    fn declare_variable(&self, name: &Token) -> Result<u8, CompilerError> {
+        if self.current(|state| state.scope_depth) == 0 {
+            return self.identifier_constant(name);
+        }
+
+        if self.current(|state| state.locals.len()) > u8::MAX as usize {
+            return Err(CompilerError::TooManyLocals { line: name.line });
+        }
+
+        self.current(|state| {
+            state.locals.push(Local {
+                name: name.lexeme.to_owned(),
+                depth: None,
+                is_captured: false,
+            })
+        });
+
+        Ok(0)
+    }
+
+    fn mark_initialized(&self) {
+        self.current(|state| {
+            if state.scope_depth > 0 {
+                if let Some(local) = state.locals.last_mut() {
+                    local.depth = Some(state.scope_depth);
+                }
+            }
+        });
+    }
+
+    fn define_variable(&self, global: u8) {
+        if self.current(|state| state.scope_depth) > 0 {
+            self.mark_initialized();
+        } else {
+            self.emit_op_with(OpCode::DefineGlobal, global);
+        }
+    }
+
+    fn resolve_local(&self, function: usize, name: &str) -> Option<u8> {
+        self.functions.borrow()[function]
+            .locals
+            .iter()
+            .rposition(|local| local.name == name)
+            .map(|index| index as u8)
+    }
+
+    fn add_upvalue(&self, function: usize, index: u8, is_local: bool) -> Result<u8, CompilerError> {
+        let mut functions = self.functions.borrow_mut();
+        let state = &mut functions[function];
+
+        if let Some(existing) = state
+            .upvalues
+            .iter()
+            .position(|upvalue| upvalue.index == index && upvalue.is_local == is_local)
+        {
+            return Ok(existing as u8);
+        }
+
+        if state.upvalues.len() > u8::MAX as usize {
+            return Err(CompilerError::TooManyUpvalues { line: self.line() });
+        }
+
+        state.upvalues.push(Upvalue { index, is_local });
+        state.function.upvalue_count = state.upvalues.len();
+
+        Ok((state.upvalues.len() - 1) as u8)
+    }
+
+    fn resolve_upvalue(&self, function: usize, name: &str) -> Result<Option<u8>, CompilerError> {
+        if function == 0 {
+            return Ok(None);
+        }
+
+        if let Some(local) = self.resolve_local(function - 1, name) {
+            self.functions.borrow_mut()[function - 1].locals[local as usize].is_captured = true;
+            return self.add_upvalue(function, local, true).map(Some);
+        }
+
+        match self.resolve_upvalue(function - 1, name)? {
+            Some(upvalue) => self.add_upvalue(function, upvalue, false).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the instructions reading and writing the variable `name` and their operand.
+    fn variable_ops(&self, name: &Token) -> Result<(OpCode, OpCode, u8), CompilerError> {
+        let function = self.functions.borrow().len() - 1;
+        if let Some(local) = self.resolve_local(function, &name.lexeme) {
+            Ok((OpCode::GetLocal, OpCode::SetLocal, local))
+        } else if let Some(upvalue) = self.resolve_upvalue(function, &name.lexeme)? {
+            Ok((OpCode::GetUpvalue, OpCode::SetUpvalue, upvalue))
+        } else {
+            Ok((
+                OpCode::GetGlobal,
+                OpCode::SetGlobal,
+                self.identifier_constant(name)?,
+            ))
+        }
+    }
+
+    /// Emits a read of the variable `name`, or an assignment of `value` to it.
+    fn named_variable(&self, name: &Token, value: Option<ExprRef>) -> Result<(), CompilerError> {
+        self.at(name);
+        let (get_op, set_op, operand) = self.variable_ops(name)?;
+
+        match value {
+            Some(value) => {
+                self.compile_expr(value)?;
+                self.at(name);
+                self.emit_op_with(set_op, operand);
+            }
+            None => self.emit_op_with(get_op, operand),
+        }
+
+        Ok(())
+    }
+
+    fn synthetic_at(&self, name: &str, token: &Token) -> Token {
+        Token::new(
+            TokenType::Identifier,
+            name.to_owned(),
+            token.line,
+            token.column,
+            None,
+        )
+    }
+
+    fn end_function(&self) -> (Function, Vec<Upvalue>) {
+        let state = self.functions.borrow_mut().pop().unwrap();
+        (state.function, state.upvalues)
+    }
+
+    fn function(&self, statement: &Stmt, function_type: FunctionType) -> Result<(), CompilerError> {
+        let Stmt::Function {
+            name,
+            params,
+            body,
+            is_async,
+            is_variadic,
+            is_generator,
+            ..
+        } = statement
+        else {
+            return Ok(());
+        };
+
+        self.at(name);
+        if *is_async {
+            return Err(CompilerError::Unsupported {
+                line: name.line,
+                feature: String::from("Async functions are"),
+            });
+        }
+        if *is_variadic {
+            return Err(CompilerError::Unsupported {
+                line: name.line,
+                feature: String::from("Rest parameters are"),
+            });
+        }
+        if *is_generator {
+            return Err(CompilerError::Unsupported {
+                line: name.line,
+                feature: String::from("Generators are"),
+            });
+        }
+
+        if params.len() > u8::MAX as usize {
+            return Err(CompilerError::TooManyParameters { line: name.line });
+        }
+
+        self.functions
+            .borrow_mut()
+            .push(FunctionState::new(&name.lexeme, function_type));
+        self.current(|state| state.function.arity = params.len());
+        self.begin_scope();
+
+        for param in params.iter() {
+            let constant = self.declare_variable(param)?;
+            self.define_variable(constant);
+        }
+
+        for statement in body.iter() {
+            self.compile_stmt(*statement)?;
+        }
+
+        self.emit_return();
+        let (function, upvalues) = self.end_function();
+
+        self.at(name);
+        let constant = self.make_constant(Constant::Function(Rc::new(function)))?;
+        self.emit_op_with(OpCode::Closure, constant);
+        for upvalue in upvalues {
+            self.emit_byte(upvalue.is_local as u8);
+            self.emit_byte(upvalue.index);
+        }
+
+        Ok(())
+    }
+
+    fn arguments(&self, arguments: &[ExprRef]) -> Result<u8, CompilerError> {
+        for argument in arguments {
+            self.compile_expr(*argument)?;
+        }
+
+        u8::try_from(arguments.len())
+            .map_err(|_| CompilerError::TooManyArguments { line: self.line() })
+    }
+}
+
+impl Visitor<Stmt, (), CompilerError> for Compiler {
+    fn visit(&self, input: &Stmt) -> Result<(), CompilerError> {
+        let ast = self.ast.borrow();
+        match input {
+            Stmt::Block { statements } => {
+                self.begin_scope();
+                for statement in statements {
+                    self.compile_stmt(*statement)?;
+                }
+                self.end_scope();
+            }
+            Stmt::Break { keyword, label } => self.jump_out_of_loop(keyword, label, false),
+            Stmt::Continue { keyword, label } => self.jump_out_of_loop(keyword, label, true),
+            Stmt::Class {
+                name,
+                superclass,
+                traits,
+                fields,
+                methods,
+                ..
+            } => {
+                if !traits.is_empty() {
+                    return Err(CompilerError::Unsupported {
+                        line: name.line,
+                        feature: String::from("Traits are"),
+                    });
+                }
+
+                if !fields.is_empty() {
+                    return Err(CompilerError::Unsupported {
+                        line: name.line,
+                        feature: String::from("Field initializers are"),
+                    });
+                }
+
+                self.at(name);
+                let name_constant = self.identifier_constant(name)?;
+                let global = self.declare_variable(name)?;
+
+                self.emit_op_with(OpCode::Class, name_constant);
+                self.define_variable(global);
+
+                let has_superclass = superclass.is_some();
+                if let Some(Expr::Variable {
+                    name: superclass_name,
+                    ..
+                }) = superclass.map(|superclass| &ast[superclass])
+                {
+                    self.named_variable(superclass_name, None)?;
+
+                    self.begin_scope();
+                    let super_token = self.synthetic_at("super", name);
+                    self.declare_variable(&super_token)?;
+                    self.define_variable(0);
+
+                    self.named_variable(name, None)?;
+                    self.emit_op(OpCode::Inherit);
+                }
+
+                self.named_variable(name, None)?;
+                for method in methods {
+                    if let Stmt::Function {
+                        name: method_name, ..
+                    } = &ast[*method]
+                    {
+                        let constant = self.identifier_constant(method_name)?;
+                        let function_type = if method_name.lexeme == "init" {
+                            FunctionType::Initializer
+                        } else {
+                            FunctionType::Method
+                        };
+
+                        self.function(&ast[*method], function_type)?;
+                        self.emit_op_with(OpCode::Method, constant);
+                    }
+                }
+                self.emit_op(OpCode::Pop);
+
+                if has_superclass {
+                    self.end_scope();
+                }
+            }
+            Stmt::Destructure { keyword, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: keyword.line,
+                    feature: String::from("Destructuring is"),
+                })
+            }
+            Stmt::Expression { expression } => {
+                self.compile_expr(*expression)?;
+                self.emit_op(OpCode::Pop);
+            }
+            Stmt::ForIn { keyword, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: keyword.line,
+                    feature: String::from("'for-in' loops are"),
+                })
+            }
+            Stmt::Function { name, .. } => {
+                let global = self.declare_variable(name)?;
+                self.mark_initialized();
+                self.function(input, FunctionType::Function)?;
+                self.define_variable(global);
+            }
+            Stmt::If {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.compile_expr(*condition)?;
+
+                self.at(keyword);
+                let then_jump = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit_op(OpCode::Pop);
+                self.compile_stmt(*then_branch)?;
+
+                let else_jump = self.emit_jump(OpCode::Jump);
+                self.patch_jump(then_jump)?;
+                self.emit_op(OpCode::Pop);
+
+                if let Some(else_branch) = else_branch {
+                    self.compile_stmt(*else_branch)?;
+                }
+                self.patch_jump(else_jump)?;
+            }
+            Stmt::Import { keyword, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: keyword.line,
+                    feature: String::from("Imports are"),
+                })
+            }
+            Stmt::Print { expression } => {
+                self.compile_expr(*expression)?;
+                self.emit_op(OpCode::Print);
+            }
+            Stmt::Return { keyword, value } => {
+                self.at(keyword);
+                match value {
+                    Some(value) => {
+                        self.compile_expr(*value)?;
+                        self.emit_op(OpCode::Return);
+                    }
+                    None => self.emit_return(),
+                }
+            }
+            Stmt::Trait { name, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: name.line,
+                    feature: String::from("Traits are"),
+                })
+            }
+            Stmt::Yield { keyword, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: keyword.line,
+                    feature: String::from("Generators are"),
+                })
+            }
+            Stmt::Var {
+                name, initializer, ..
+            } => {
+                let global = self.declare_variable(name)?;
+                match initializer {
+                    Some(initializer) => self.compile_expr(*initializer)?,
+                    None => {
+                        self.at(name);
+                        self.emit_op(OpCode::Nil);
+                    }
+                }
+                self.at(name);
+                self.define_variable(global);
+            }
+            Stmt::While {
+                keyword,
+                label,
+                condition,
+                body,
+                increment,
+            } => {
+                let loop_start = self.code_len();
+                self.compile_expr(*condition)?;
+
+                self.at(keyword);
+                let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit_op(OpCode::Pop);
+
+                self.current(|state| {
+                    state.loops.push(Loop {
+                        scope_depth: state.scope_depth,
+                        label: label.as_ref().as_ref().map(|label| label.lexeme.to_owned()),
+                        breaks: vec![],
+                        continues: vec![],
+                    })
+                });
+                self.compile_stmt(*body)?;
+
+                let continues = self.current(|state| {
+                    std::mem::take(&mut state.loops.last_mut().unwrap().continues)
+                });
+                for jump in continues {
+                    self.patch_jump(jump)?;
+                }
+                if let Some(increment) = increment {
+                    self.compile_expr(*increment)?;
+                    self.emit_op(OpCode::Pop);
+                }
+                self.at(keyword);
+                self.emit_loop(loop_start)?;
+
+                self.patch_jump(exit_jump)?;
+                self.emit_op(OpCode::Pop);
+
+                let breaks = self.current(|state| state.loops.pop().unwrap().breaks);
+                for jump in breaks {
+                    self.patch_jump(jump)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Visitor<Expr, (), CompilerError> for Compiler {
+    fn visit(&self, input: &Expr) -> Result<(), CompilerError> {
+        let ast = self.ast.borrow();
+        match input {
+            Expr::Assign { name, value, .. } => self.named_variable(name, Some(*value))?,
+            Expr::Await { keyword, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: keyword.line,
+                    feature: String::from("'await' is"),
+                })
+            }
+            Expr::Yield { keyword, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: keyword.line,
+                    feature: String::from("Generators are"),
+                })
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                if operator.token_type == TokenType::Is {
+                    return Err(CompilerError::Unsupported {
+                        line: operator.line,
+                        feature: String::from("'is' checks are"),
+                    });
+                }
+
+                self.compile_expr(*left)?;
+                self.compile_expr(*right)?;
+
+                self.at(operator);
+                match operator.token_type {
+                    TokenType::BangEqual => {
+                        self.emit_op(OpCode::Equal);
+                        self.emit_op(OpCode::Not);
+                    }
+                    TokenType::EqualEqual => self.emit_op(OpCode::Equal),
+                    TokenType::Greater => self.emit_op(OpCode::Greater),
+                    TokenType::GreaterEqual => {
+                        self.emit_op(OpCode::Less);
+                        self.emit_op(OpCode::Not);
+                    }
+                    TokenType::Less => self.emit_op(OpCode::Less),
+                    TokenType::LessEqual => {
+                        self.emit_op(OpCode::Greater);
+                        self.emit_op(OpCode::Not);
+                    }
+                    TokenType::Plus => self.emit_op(OpCode::Add),
+                    TokenType::Minus => self.emit_op(OpCode::Subtract),
+                    TokenType::Star => self.emit_op(OpCode::Multiply),
+                    TokenType::Slash => self.emit_op(OpCode::Divide),
+                    _ => {}
+                }
+            }
+            Expr::Call {
+                paren,
+                callee,
+                arguments,
+                ..
+            } => match &ast[*callee] {
+                Expr::Get { object, name, .. } => {
+                    self.compile_expr(*object)?;
+                    let argument_count = self.arguments(arguments)?;
+
+                    self.at(paren);
+                    let constant = self.identifier_constant(name)?;
+                    self.emit_op_with(OpCode::Invoke, constant);
+                    self.emit_byte(argument_count);
+                }
+                Expr::Super {
+                    keyword, method, ..
+                } => {
+                    self.named_variable(&self.synthetic_at("this", keyword), None)?;
+                    let argument_count = self.arguments(arguments)?;
+                    self.named_variable(&self.synthetic_at("super", keyword), None)?;
+
+                    self.at(paren);
+                    let constant = self.identifier_constant(method)?;
+                    self.emit_op_with(OpCode::SuperInvoke, constant);
+                    self.emit_byte(argument_count);
+                }
+                _ => {
+                    self.compile_expr(*callee)?;
+                    let argument_count = self.arguments(arguments)?;
+
+                    self.at(paren);
+                    self.emit_op_with(OpCode::Call, argument_count);
+                }
+            },
+            Expr::Get { object, name, .. } => {
+                self.compile_expr(*object)?;
+                self.at(name);
+                let constant = self.identifier_constant(name)?;
+                self.emit_op_with(OpCode::GetProperty, constant);
+            }
+            Expr::Grouping { expression, .. } => self.compile_expr(*expression)?,
+            Expr::Literal { value, .. } => match value {
+                LiteralValue::Number(value) => {
+                    let constant = self.make_constant(Constant::Number(*value))?;
+                    self.emit_op_with(OpCode::Constant, constant);
+                }
+                LiteralValue::String(value) => {
+                    let constant =
+                        self.make_constant(Constant::String(Rc::from(value.as_str())))?;
+                    self.emit_op_with(OpCode::Constant, constant);
+                }
+                LiteralValue::Boolean(true) => self.emit_op(OpCode::True),
+                LiteralValue::Boolean(false) => self.emit_op(OpCode::False),
+                LiteralValue::None => self.emit_op(OpCode::Nil),
+            },
+            Expr::Index { bracket, .. } | Expr::IndexSet { bracket, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: bracket.line,
+                    feature: String::from("Indexing is"),
+                })
+            }
+            Expr::List { bracket, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: bracket.line,
+                    feature: String::from("Lists are"),
+                })
+            }
+            Expr::Map { brace, .. } => {
+                return Err(CompilerError::Unsupported {
+                    line: brace.line,
+                    feature: String::from("Maps are"),
+                })
+            }
+            Expr::Lambda { function, .. } => {
+                self.function(&ast[*function], FunctionType::Function)?
+            }
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.compile_expr(*left)?;
+                self.at(operator);
+
+                if operator.token_type == TokenType::Or {
+                    let else_jump = self.emit_jump(OpCode::JumpIfFalse);
+                    let end_jump = self.emit_jump(OpCode::Jump);
+
+                    self.patch_jump(else_jump)?;
+                    self.emit_op(OpCode::Pop);
+                    self.compile_expr(*right)?;
+                    self.patch_jump(end_jump)?;
+                } else {
+                    let end_jump = self.emit_jump(OpCode::JumpIfFalse);
+
+                    self.emit_op(OpCode::Pop);
+                    self.compile_expr(*right)?;
+                    self.patch_jump(end_jump)?;
+                }
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+                ..
+            } => {
+                self.compile_expr(*object)?;
+                self.compile_expr(*value)?;
+                self.at(name);
+                let constant = self.identifier_constant(name)?;
+                self.emit_op_with(OpCode::SetProperty, constant);
+            }
+            Expr::Stringify { expression, .. } => {
+                self.compile_expr(*expression)?;
+                self.emit_op(OpCode::Stringify);
+            }
+            Expr::Super {
+                keyword, method, ..
+            } => {
+                self.named_variable(&self.synthetic_at("this", keyword), None)?;
+                self.named_variable(&self.synthetic_at("super", keyword), None)?;
+
+                self.at(method);
+                let constant = self.identifier_constant(method)?;
+                self.emit_op_with(OpCode::GetSuper, constant);
+            }
+            Expr::Ternary {
+                condition,
+                question,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.compile_expr(*condition)?;
+
+                self.at(question);
+                let then_jump = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit_op(OpCode::Pop);
+                self.compile_expr(*then_branch)?;
+
+                let else_jump = self.emit_jump(OpCode::Jump);
+                self.patch_jump(then_jump)?;
+                self.emit_op(OpCode::Pop);
+                self.compile_expr(*else_branch)?;
+                self.patch_jump(else_jump)?;
+            }
+            Expr::This { keyword, .. } => self.named_variable(keyword, None)?,
+            Expr::Unary {
+                operator, right, ..
+            } => {
+                self.compile_expr(*right)?;
+                self.at(operator);
+                match operator.token_type {
+                    TokenType::Minus => self.emit_op(OpCode::Negate),
+                    TokenType::Bang => self.emit_op(OpCode::Not),
+                    _ => {}
+                }
+            }
+            Expr::Update {
+                operator,
+                target,
+                prefix,
+                ..
+            } => {
+                let (apply_op, revert_op) = if operator.token_type == TokenType::PlusPlus {
+                    (OpCode::Add, OpCode::Subtract)
+                } else {
+                    (OpCode::Subtract, OpCode::Add)
+                };
+                let one = self.make_constant(Constant::Number(1.0))?;
+
+                match &ast[*target] {
+                    Expr::Variable { name, .. } => {
+                        self.at(name);
+                        let (get_op, set_op, operand) = self.variable_ops(name)?;
+                        self.emit_op_with(get_op, operand);
+                        self.at(operator);
+                        self.emit_op_with(OpCode::Constant, one);
+                        self.emit_op(apply_op);
+                        self.emit_op_with(set_op, operand);
+                    }
+                    Expr::Get { object, name, .. } => {
+                        self.compile_expr(*object)?;
+                        self.at(name);
+                        let constant = self.identifier_constant(name)?;
+                        self.emit_op(OpCode::Dup);
+                        self.emit_op_with(OpCode::GetProperty, constant);
+                        self.at(operator);
+                        self.emit_op_with(OpCode::Constant, one);
+                        self.emit_op(apply_op);
+                        self.emit_op_with(OpCode::SetProperty, constant);
+                    }
+                    _ => {}
+                }
+
+                /* the stored value is left on the stack, postfix forms evaluate to the old one. */
+                if !*prefix {
+                    self.emit_op_with(OpCode::Constant, one);
+                    self.emit_op(revert_op);
+                }
+            }
+            Expr::Variable { name, .. } => self.named_variable(name, None)?,
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::bytecode::chunk::{Chunk, Constant, Function, OpCode};
+use std::fmt::Write;
+
+/// Disassembles a compiled function and every function nested in its constants, in the format
+/// of clox's `debug.c`.
+pub fn disassemble(function: &Function) -> String {
+    let mut output = disassemble_chunk(&function.chunk, &function.to_string());
+
+    for constant in &function.chunk.constants {
+        if let Constant::Function(nested) = constant {
+            output.push('\n');
+            output.push_str(&disassemble(nested));
+        }
+    }
+
+    output
+}
+
+/// Disassembles all instructions of a chunk under a `== name ==` header.
+pub fn disassemble_chunk(chunk: &Chunk, name: &str) -> String {
+    let mut output = format!("== {} ==\n", name);
+
+    let mut offset = 0;
+    while offset < chunk.code.len() {
+        offset = disassemble_instruction(chunk, offset, &mut output);
+    }
+
+    output
+}
+
+/// Appends the instruction at `offset` to `output` and returns the offset of the next
+/// instruction.
+pub fn disassemble_instruction(chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+    let _ = write!(output, "{:04} ", offset);
+    if offset > 0 && chunk.lines[offset] == chunk.lines[offset - 1] {
+        output.push_str("   | ");
+    } else {
+        let _ = write!(output, "{:4} ", chunk.lines[offset]);
+    }
+
+    let Some(op) = OpCode::from_byte(chunk.code[offset]) else {
+        let _ = writeln!(output, "Unknown opcode {}", chunk.code[offset]);
+        return offset + 1;
+    };
+
+    match op {
+        OpCode::Constant
+        | OpCode::GetGlobal
+        | OpCode::DefineGlobal
+        | OpCode::SetGlobal
+        | OpCode::GetProperty
+        | OpCode::SetProperty
+        | OpCode::GetSuper
+        | OpCode::Class
+        | OpCode::Method => constant_instruction(op, chunk, offset, output),
+        OpCode::GetLocal
+        | OpCode::SetLocal
+        | OpCode::GetUpvalue
+        | OpCode::SetUpvalue
+        | OpCode::Call => byte_instruction(op, chunk, offset, output),
+        OpCode::Jump | OpCode::JumpIfFalse => jump_instruction(op, 1, chunk, offset, output),
+        OpCode::Loop => jump_instruction(op, -1, chunk, offset, output),
+        OpCode::Invoke | OpCode::SuperInvoke => invoke_instruction(op, chunk, offset, output),
+        OpCode::Closure => closure_instruction(chunk, offset, output),
+        _ => {
+            let _ = writeln!(output, "{}", op_name(op));
+            offset + 1
+        }
+    }
+}
+
+/// Returns the clox name of an opcode, e.g. `OP_JUMP_IF_FALSE`.
+fn op_name(op: OpCode) -> String {
+    let mut name = String::from("OP");
+    for c in format!("{:?}", op).chars() {
+        if c.is_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+
+    name
+}
+
+fn constant_instruction(op: OpCode, chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+    let constant = chunk.code[offset + 1];
+    let _ = writeln!(
+        output,
+        "{:<16} {:4} '{}'",
+        op_name(op),
+        constant,
+        chunk.constants[constant as usize]
+    );
+
+    offset + 2
+}
+
+fn byte_instruction(op: OpCode, chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+    let _ = writeln!(output, "{:<16} {:4}", op_name(op), chunk.code[offset + 1]);
+
+    offset + 2
+}
+
+fn jump_instruction(
+    op: OpCode,
+    sign: i64,
+    chunk: &Chunk,
+    offset: usize,
+    output: &mut String,
+) -> usize {
+    let jump = u16::from_be_bytes([chunk.code[offset + 1], chunk.code[offset + 2]]);
+    let target = offset as i64 + 3 + sign * jump as i64;
+    let _ = writeln!(output, "{:<16} {:4} -> {}", op_name(op), offset, target);
+
+    offset + 3
+}
+
+fn invoke_instruction(op: OpCode, chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+    let constant = chunk.code[offset + 1];
+    let argument_count = chunk.code[offset + 2];
+    let _ = writeln!(
+        output,
+        "{:<16} ({} args) {:4} '{}'",
+        op_name(op),
+        argument_count,
+        constant,
+        chunk.constants[constant as usize]
+    );
+
+    offset + 3
+}
+
+fn closure_instruction(chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+    let constant = chunk.code[offset + 1];
+    let value = &chunk.constants[constant as usize];
+    let _ = writeln!(
+        output,
+        "{:<16} {:4} {}",
+        op_name(OpCode::Closure),
+        constant,
+        value
+    );
+
+    let mut offset = offset + 2;
+    if let Constant::Function(function) = value {
+        for _ in 0..function.upvalue_count {
+            let kind = match chunk.code[offset] {
+                0 => "upvalue",
+                _ => "local",
+            };
+            let _ = writeln!(
+                output,
+                "{:04}      |                     {} {}",
+                offset,
+                kind,
+                chunk.code[offset + 1]
+            );
+            offset += 2;
+        }
+    }
+
+    offset
+}
@@ -0,0 +1,135 @@
+use crate::base::expr_result::format_number;
+use crate::bytecode::chunk::{Constant, Function};
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// A value on the stack of the [`Vm`](crate::bytecode::vm::Vm). Objects are shared by
+/// reference and compared by identity, except for strings.
+#[derive(Clone, Debug, Default)]
+pub enum Value {
+    #[default]
+    Nil,
+    Boolean(bool),
+    Number(f64),
+    String(Rc<str>),
+    Closure(Rc<Closure>),
+    Native(Rc<Native>),
+    Class(Rc<Class>),
+    Instance(Rc<Instance>),
+    BoundMethod(Rc<BoundMethod>),
+}
+
+impl Value {
+    pub fn is_falsey(&self) -> bool {
+        matches!(self, Value::Nil | Value::Boolean(false))
+    }
+
+    /// Formats the value for error messages, with strings in quotes.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Value::String(value) => format!("{:?}", value),
+            value => value.to_string(),
+        }
+    }
+}
+
+impl From<&Constant> for Value {
+    fn from(constant: &Constant) -> Self {
+        match constant {
+            Constant::Number(value) => Value::Number(*value),
+            Constant::String(value) => Value::String(Rc::clone(value)),
+            Constant::Function(function) => Value::Closure(Rc::new(Closure {
+                function: Rc::clone(function),
+                upvalues: vec![],
+            })),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            (Value::Native(a), Value::Native(b)) => Rc::ptr_eq(a, b),
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::BoundMethod(a), Value::BoundMethod(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", format_number(*value)),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Closure(closure) => write!(f, "{}", closure.function),
+            Value::Native(_) => write!(f, "<native fn>"),
+            Value::Class(class) => write!(f, "{}", class.name),
+            Value::Instance(instance) => write!(f, "{} instance", instance.class.name),
+            Value::BoundMethod(method) => write!(f, "{}", method.method.function),
+        }
+    }
+}
+
+/// A compiled function together with the variables it captured.
+#[derive(Debug)]
+pub struct Closure {
+    pub function: Rc<Function>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+/// A captured variable. It refers to a stack slot while the variable is in scope and holds
+/// the value itself once the scope has ended.
+#[derive(Debug)]
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
+}
+
+pub type NativeFn = fn(&[Value]) -> Result<Value, RuntimeError>;
+
+/// A function implemented in Rust.
+pub struct Native {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: NativeFn,
+}
+
+impl std::fmt::Debug for Native {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Native")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: Rc<str>,
+    pub methods: RefCell<HashMap<Rc<str>, Rc<Closure>>>,
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: RefCell<HashMap<Rc<str>, Value>>,
+}
+
+/// A method together with the instance it was accessed on.
+#[derive(Debug)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: Rc<Closure>,
+}
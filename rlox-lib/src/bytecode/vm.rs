@@ -0,0 +1,855 @@
+use crate::bytecode::chunk::{Constant, Function, OpCode};
+use crate::bytecode::disassembler::disassemble_instruction;
+use crate::bytecode::value::{
+    BoundMethod, Class, Closure, Instance, Native, NativeFn, Upvalue, Value,
+};
+use crate::interpreter::budget::Budget;
+use crate::interpreter::interrupt::InterruptHandle;
+use crate::interpreter::options::InterpreterOptions;
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum depth of nested calls before a script is aborted with a stack overflow.
+const FRAMES_MAX: usize = 1024;
+
+/// Native functions available to scripts running on the VM.
+const NATIVES: &[(&str, usize, NativeFn)] = &[("clock", 0, clock)];
+
+struct CallFrame {
+    closure: Rc<Closure>,
+    ip: usize,
+    /// Index of the stack slot holding the callee, followed by the arguments and locals.
+    slots: usize,
+}
+
+/// Stack-based virtual machine executing functions compiled by the
+/// [`Compiler`](crate::bytecode::compiler::Compiler), in the style of clox.
+///
+/// Global variables persist across calls of [`Vm::interpret`], like in the
+/// [`Interpreter`](crate::interpreter::interpreter::Interpreter).
+pub struct Vm<'a> {
+    frames: Vec<CallFrame>,
+    stack: Vec<Value>,
+    globals: HashMap<Rc<str>, Value>,
+    /// Upvalues still referring to stack slots, ordered by slot.
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    options: InterpreterOptions,
+    budget: Budget,
+    trace: bool,
+    interrupt: InterruptHandle,
+    output_stream: Rc<RefCell<dyn Write + 'a>>,
+    output_written: usize,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new<OutputWriter>(output_stream: Rc<RefCell<OutputWriter>>) -> Self
+    where
+        OutputWriter: Write + 'a,
+    {
+        let globals = NATIVES
+            .iter()
+            .map(|&(name, arity, function)| {
+                (
+                    Rc::from(name),
+                    Value::Native(Rc::new(Native {
+                        name,
+                        arity,
+                        function,
+                    })),
+                )
+            })
+            .collect();
+
+        Self {
+            frames: vec![],
+            stack: vec![],
+            globals,
+            open_upvalues: vec![],
+            options: InterpreterOptions::default(),
+            budget: Budget::new(),
+            trace: false,
+            interrupt: InterruptHandle::new(),
+            output_stream,
+            output_written: 0,
+        }
+    }
+
+    pub fn with_options(mut self, options: InterpreterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Writes the stack and the disassembled instruction to the output stream before executing
+    /// each instruction, like clox's `DEBUG_TRACE_EXECUTION`.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Returns a handle that stops the currently running script when triggered. Each call of
+    /// [`Vm::interpret`] starts uninterrupted.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.to_owned()
+    }
+
+    /// Runs a compiled script. After an error, the stack is reset, but global variables
+    /// defined so far are kept.
+    pub fn interpret(&mut self, function: Function) -> Result<(), RuntimeError> {
+        self.interrupt.reset();
+        self.budget.reset();
+
+        let closure = Rc::new(Closure {
+            function: Rc::new(function),
+            upvalues: vec![],
+        });
+        self.stack.push(Value::Closure(Rc::clone(&closure)));
+
+        let result = self
+            .call_closure(closure, 0)
+            .and_then(|_| self.run(0))
+            .map(|_| ());
+
+        if result.is_err() {
+            self.frames.clear();
+            self.stack.clear();
+            self.open_upvalues.clear();
+        }
+
+        result
+    }
+
+    /// Executes instructions until the frame at index `depth` returns, and returns its result.
+    fn run(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        loop {
+            if self.trace {
+                self.trace_instruction()?;
+            }
+
+            let byte = self.read_byte();
+            let Some(op) = OpCode::from_byte(byte) else {
+                return Err(RuntimeError::InvalidValue {
+                    line: self.line(),
+                    column: self.column(),
+                });
+            };
+
+            match op {
+                OpCode::Constant => {
+                    let value = Value::from(self.read_constant());
+                    self.push(value);
+                }
+                OpCode::Nil => self.push(Value::Nil),
+                OpCode::True => self.push(Value::Boolean(true)),
+                OpCode::False => self.push(Value::Boolean(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::Dup => self.push(self.peek(0).to_owned()),
+                OpCode::GetLocal => {
+                    let slot = self.frame().slots + self.read_byte() as usize;
+                    self.push(self.stack[slot].to_owned());
+                }
+                OpCode::SetLocal => {
+                    let slot = self.frame().slots + self.read_byte() as usize;
+                    self.stack[slot] = self.peek(0).to_owned();
+                }
+                OpCode::GetGlobal => {
+                    let name = self.read_string();
+                    match self.globals.get(&name) {
+                        Some(value) => self.push(value.to_owned()),
+                        None => return Err(self.undefined_variable(&name)),
+                    }
+                }
+                OpCode::DefineGlobal => {
+                    let name = self.read_string();
+                    let value = self.pop();
+                    self.globals.insert(name, value);
+                }
+                OpCode::SetGlobal => {
+                    let name = self.read_string();
+                    let value = self.peek(0).to_owned();
+                    match self.globals.get_mut(&name) {
+                        Some(global) => *global = value,
+                        None => return Err(self.undefined_variable(&name)),
+                    }
+                }
+                OpCode::GetUpvalue => {
+                    let index = self.read_byte() as usize;
+                    let upvalue = Rc::clone(&self.frame().closure.upvalues[index]);
+                    let value = match &*upvalue.borrow() {
+                        Upvalue::Open(slot) => self.stack[*slot].to_owned(),
+                        Upvalue::Closed(value) => value.to_owned(),
+                    };
+                    self.push(value);
+                }
+                OpCode::SetUpvalue => {
+                    let index = self.read_byte() as usize;
+                    let upvalue = Rc::clone(&self.frame().closure.upvalues[index]);
+                    let value = self.peek(0).to_owned();
+                    match &mut *upvalue.borrow_mut() {
+                        Upvalue::Open(slot) => self.stack[*slot] = value,
+                        Upvalue::Closed(closed) => *closed = value,
+                    };
+                }
+                OpCode::GetProperty => {
+                    let name = self.read_string();
+                    let Value::Instance(instance) = self.peek(0).to_owned() else {
+                        return Err(RuntimeError::InvalidPropertyAccess {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+
+                    let field = instance.fields.borrow().get(&name).cloned();
+                    match field {
+                        Some(value) => {
+                            self.pop();
+                            self.push(value);
+                        }
+                        None => self.bind_method(&instance.class, &name)?,
+                    }
+                }
+                OpCode::SetProperty => {
+                    let name = self.read_string();
+                    let Value::Instance(instance) = self.peek(1).to_owned() else {
+                        return Err(RuntimeError::InvalidFieldAccess {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+
+                    let value = self.pop();
+                    instance.fields.borrow_mut().insert(name, value.to_owned());
+                    self.pop();
+                    self.push(value);
+                }
+                OpCode::GetSuper => {
+                    let name = self.read_string();
+                    let Value::Class(superclass) = self.pop() else {
+                        return Err(RuntimeError::SuperclassInvalidType {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+                    self.bind_method(&superclass, &name)?;
+                }
+                OpCode::Equal => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.check_mismatch(&a, &b)?;
+                    self.push(Value::Boolean(a == b));
+                }
+                OpCode::Greater => {
+                    let operator = self.comparison_operator(">", "<=");
+                    self.binary_number(operator, |a, b| Value::Boolean(a > b))?
+                }
+                OpCode::Less => {
+                    let operator = self.comparison_operator("<", ">=");
+                    self.binary_number(operator, |a, b| Value::Boolean(a < b))?
+                }
+                OpCode::Add => self.add()?,
+                OpCode::Subtract => self.binary_number("-", |a, b| Value::Number(a - b))?,
+                OpCode::Multiply => self.binary_number("*", |a, b| Value::Number(a * b))?,
+                OpCode::Divide => {
+                    if self.options.checked_division && self.peek(0) == &Value::Number(0.0) {
+                        if let Value::Number(_) = self.peek(1) {
+                            return Err(RuntimeError::DivisionByZero {
+                                line: self.line(),
+                                column: self.column(),
+                            });
+                        }
+                    }
+                    self.binary_number("/", |a, b| Value::Number(a / b))?
+                }
+                OpCode::Not => {
+                    let value = self.pop();
+                    let is_true = self.is_true(&value)?;
+                    self.push(Value::Boolean(!is_true));
+                }
+                OpCode::Negate => match self.pop() {
+                    Value::Number(value) => self.push(Value::Number(-value)),
+                    value => return Err(self.number_expected("-", &[&value])),
+                },
+                OpCode::Stringify => {
+                    let value = self.pop();
+                    let string = self.stringify(value)?;
+                    self.allocate(string.len())?;
+                    self.push(Value::String(Rc::from(string)));
+                }
+                OpCode::Print => {
+                    let value = self.pop();
+                    let string = self.stringify(value)?;
+                    self.write_output(format!("{}\n", string).as_bytes())?;
+                }
+                OpCode::Jump => {
+                    let offset = self.read_short();
+                    self.frame_mut().ip += offset as usize;
+                }
+                OpCode::JumpIfFalse => {
+                    let offset = self.read_short();
+                    if !self.is_true(self.peek(0))? {
+                        self.frame_mut().ip += offset as usize;
+                    }
+                }
+                OpCode::Loop => {
+                    let offset = self.read_short();
+                    self.frame_mut().ip -= offset as usize;
+                    if self.interrupt.is_interrupted() {
+                        return Err(RuntimeError::Interrupted);
+                    }
+                    self.budget.spend(&self.options)?;
+                }
+                OpCode::Call => {
+                    let argument_count = self.read_byte() as usize;
+                    let callee = self.peek(argument_count).to_owned();
+                    self.call_value(callee, argument_count)?;
+                }
+                OpCode::Invoke => {
+                    let name = self.read_string();
+                    let argument_count = self.read_byte() as usize;
+                    self.invoke(&name, argument_count)?;
+                }
+                OpCode::SuperInvoke => {
+                    let name = self.read_string();
+                    let argument_count = self.read_byte() as usize;
+                    let Value::Class(superclass) = self.pop() else {
+                        return Err(RuntimeError::SuperclassInvalidType {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+                    self.invoke_from_class(&superclass, &name, argument_count)?;
+                }
+                OpCode::Closure => {
+                    let Constant::Function(function) = self.read_constant().to_owned() else {
+                        return Err(RuntimeError::InvalidValue {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+
+                    let mut upvalues = Vec::with_capacity(function.upvalue_count);
+                    for _ in 0..function.upvalue_count {
+                        let is_local = self.read_byte() == 1;
+                        let index = self.read_byte() as usize;
+                        if is_local {
+                            let slot = self.frame().slots + index;
+                            upvalues.push(self.capture_upvalue(slot));
+                        } else {
+                            upvalues.push(Rc::clone(&self.frame().closure.upvalues[index]));
+                        }
+                    }
+
+                    self.push(Value::Closure(Rc::new(Closure { function, upvalues })));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    let frame = self.frames.pop().unwrap();
+                    self.close_upvalues(frame.slots);
+                    self.stack.truncate(frame.slots);
+
+                    if self.frames.len() == depth {
+                        return Ok(result);
+                    }
+                    self.push(result);
+                }
+                OpCode::Class => {
+                    let name = self.read_string();
+                    self.push(Value::Class(Rc::new(Class {
+                        name,
+                        methods: RefCell::new(HashMap::new()),
+                    })));
+                }
+                OpCode::Inherit => {
+                    let Value::Class(superclass) = self.peek(1).to_owned() else {
+                        return Err(RuntimeError::SuperclassInvalidType {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+                    let Value::Class(subclass) = self.pop() else {
+                        return Err(RuntimeError::InvalidValue {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+
+                    let methods = superclass.methods.borrow().clone();
+                    subclass.methods.borrow_mut().extend(methods);
+                }
+                OpCode::Method => {
+                    let name = self.read_string();
+                    let Value::Closure(method) = self.pop() else {
+                        return Err(RuntimeError::InvalidValue {
+                            line: self.line(),
+                            column: self.column(),
+                        });
+                    };
+                    if let Value::Class(class) = self.peek(0) {
+                        class.methods.borrow_mut().insert(name, method);
+                    }
+                }
+            }
+        }
+    }
+
+    fn trace_instruction(&mut self) -> Result<(), RuntimeError> {
+        let mut trace = String::from("          ");
+        for value in &self.stack {
+            trace.push_str(&format!("[ {} ]", value));
+        }
+        trace.push('\n');
+
+        let frame = self.frame();
+        disassemble_instruction(&frame.closure.function.chunk, frame.ip, &mut trace);
+        self.write_output(trace.as_bytes())
+    }
+
+    fn frame(&self) -> &CallFrame {
+        self.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut CallFrame {
+        self.frames.last_mut().unwrap()
+    }
+
+    /// Returns the source line of the instruction being executed.
+    fn line(&self) -> usize {
+        let frame = self.frame();
+        frame.closure.function.chunk.lines[frame.ip.saturating_sub(1)]
+    }
+
+    /// Returns the source column of the instruction being executed.
+    fn column(&self) -> usize {
+        let frame = self.frame();
+        frame.closure.function.chunk.columns[frame.ip.saturating_sub(1)]
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let frame = self.frame_mut();
+        let byte = frame.closure.function.chunk.code[frame.ip];
+        frame.ip += 1;
+        byte
+    }
+
+    fn read_short(&mut self) -> u16 {
+        u16::from_be_bytes([self.read_byte(), self.read_byte()])
+    }
+
+    fn read_constant(&mut self) -> &Constant {
+        let index = self.read_byte() as usize;
+        &self.frame().closure.function.chunk.constants[index]
+    }
+
+    fn read_string(&mut self) -> Rc<str> {
+        match self.read_constant() {
+            Constant::String(name) => Rc::clone(name),
+            constant => Rc::from(constant.to_string()),
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap_or_default()
+    }
+
+    fn peek(&self, distance: usize) -> &Value {
+        &self.stack[self.stack.len() - 1 - distance]
+    }
+
+    fn undefined_variable(&self, name: &str) -> RuntimeError {
+        RuntimeError::UndefinedVariable {
+            line: self.line(),
+            column: self.column(),
+            name: name.to_owned(),
+            suggestion: None,
+        }
+    }
+
+    fn is_true(&self, value: &Value) -> Result<bool, RuntimeError> {
+        match value {
+            Value::Boolean(value) => Ok(*value),
+            _ if self.options.strict => Err(RuntimeError::BooleanExpected {
+                line: self.line(),
+                column: self.column(),
+            }),
+            _ => Ok(!value.is_falsey()),
+        }
+    }
+
+    /// Rejects comparing values of different types for equality in strict mode, except with
+    /// `nil`.
+    fn check_mismatch(&self, a: &Value, b: &Value) -> Result<(), RuntimeError> {
+        let different = std::mem::discriminant(a) != std::mem::discriminant(b);
+        if self.options.strict && different && *a != Value::Nil && *b != Value::Nil {
+            return Err(RuntimeError::MismatchedTypes {
+                line: self.line(),
+                column: self.column(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn binary_number(
+        &mut self,
+        operator: &str,
+        operation: fn(f64, f64) -> Value,
+    ) -> Result<(), RuntimeError> {
+        match (self.peek(1), self.peek(0)) {
+            (Value::Number(a), Value::Number(b)) => {
+                let result = operation(*a, *b);
+                self.pop();
+                self.pop();
+                self.push(result);
+                Ok(())
+            }
+            (a, b) => Err(self.number_expected(operator, &[a, b])),
+        }
+    }
+
+    /// Returns the operator of a comparison. `>=` and `<=` are compiled to the opposite
+    /// comparison followed by a negation.
+    fn comparison_operator(&self, operator: &'static str, negated: &'static str) -> &'static str {
+        let frame = self.frame();
+        match frame.closure.function.chunk.code.get(frame.ip) {
+            Some(byte) if OpCode::from_byte(*byte) == Some(OpCode::Not) => negated,
+            _ => operator,
+        }
+    }
+
+    /// Reports the first of the operands of `operator` that isn't a number.
+    fn number_expected(&self, operator: &str, operands: &[&Value]) -> RuntimeError {
+        let value = operands
+            .iter()
+            .find(|operand| !matches!(operand, Value::Number(_)))
+            .unwrap_or(&operands[0]);
+
+        RuntimeError::NumberExpected {
+            line: self.line(),
+            column: self.column(),
+            operator: operator.to_owned(),
+            value: value.describe(),
+        }
+    }
+
+    /// Adds two numbers or concatenates two values of which at least one is a string.
+    fn add(&mut self) -> Result<(), RuntimeError> {
+        let b = self.pop();
+        let a = self.pop();
+
+        let result = match (a, b) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+            (Value::String(a), Value::String(b)) => {
+                self.allocate(a.len() + b.len())?;
+                Value::String(Rc::from(format!("{a}{b}")))
+            }
+            (a @ Value::String(_), b) | (a, b @ Value::String(_)) => {
+                if self.options.strict {
+                    return Err(RuntimeError::MismatchedTypes {
+                        line: self.line(),
+                        column: self.column(),
+                    });
+                }
+                let a = self.stringify(a)?;
+                let b = self.stringify(b)?;
+                self.allocate(a.len() + b.len())?;
+                Value::String(Rc::from(a + &b))
+            }
+            (a, b) => {
+                let value = if matches!(a, Value::Number(_)) { b } else { a };
+                return Err(RuntimeError::NumberOrStringExpected {
+                    line: self.line(),
+                    column: self.column(),
+                    operator: String::from("+"),
+                    value: value.describe(),
+                });
+            }
+        };
+
+        self.push(result);
+        Ok(())
+    }
+
+    /// Converts a value to the string printed by `print`, calling a `toString` method without
+    /// parameters of instances.
+    fn stringify(&mut self, value: Value) -> Result<String, RuntimeError> {
+        if let Value::Instance(instance) = &value {
+            let method = instance.class.methods.borrow().get("toString").cloned();
+            if let Some(method) = method.filter(|method| method.function.arity == 0) {
+                self.push(value.to_owned());
+                self.call_closure(method, 0)?;
+                let result = self.run(self.frames.len() - 1)?;
+                return Ok(result.to_string());
+            }
+        }
+
+        Ok(value.to_string())
+    }
+
+    fn call_value(&mut self, callee: Value, argument_count: usize) -> Result<(), RuntimeError> {
+        match callee {
+            Value::Closure(closure) => self.call_closure(closure, argument_count),
+            Value::BoundMethod(bound) => {
+                let slot = self.stack.len() - argument_count - 1;
+                self.stack[slot] = bound.receiver.to_owned();
+                self.call_closure(Rc::clone(&bound.method), argument_count)
+            }
+            Value::Class(class) => {
+                self.allocate(size_of::<Instance>())?;
+                let slot = self.stack.len() - argument_count - 1;
+                self.stack[slot] = Value::Instance(Rc::new(Instance {
+                    class: Rc::clone(&class),
+                    fields: RefCell::new(HashMap::new()),
+                }));
+
+                let initializer = class.methods.borrow().get("init").cloned();
+                match initializer {
+                    Some(initializer) => self.call_closure(initializer, argument_count),
+                    None if argument_count == 0 => Ok(()),
+                    None => Err(RuntimeError::NonMatchingNumberOfArguments {
+                        line: self.line(),
+                        column: self.column(),
+                    }),
+                }
+            }
+            Value::Native(native) => {
+                if argument_count != native.arity {
+                    return Err(RuntimeError::NonMatchingNumberOfArguments {
+                        line: self.line(),
+                        column: self.column(),
+                    });
+                }
+
+                let arguments_start = self.stack.len() - argument_count;
+                let result = (native.function)(&self.stack[arguments_start..])?;
+                self.stack.truncate(arguments_start - 1);
+                self.push(result);
+                Ok(())
+            }
+            _ => Err(RuntimeError::UndefinedCallable {
+                line: self.line(),
+                column: self.column(),
+            }),
+        }
+    }
+
+    fn call_closure(
+        &mut self,
+        closure: Rc<Closure>,
+        argument_count: usize,
+    ) -> Result<(), RuntimeError> {
+        if argument_count != closure.function.arity {
+            return Err(RuntimeError::NonMatchingNumberOfArguments {
+                line: self.line(),
+                column: self.column(),
+            });
+        }
+        if self.frames.len() == FRAMES_MAX {
+            return Err(RuntimeError::StackOverflow {
+                line: self.line(),
+                column: self.column(),
+            });
+        }
+        self.budget.spend(&self.options)?;
+
+        self.frames.push(CallFrame {
+            closure,
+            ip: 0,
+            slots: self.stack.len() - argument_count - 1,
+        });
+
+        Ok(())
+    }
+
+    /// Calls a method of the receiver below the arguments without creating a bound method. A
+    /// field holding a function is called instead, if present.
+    fn invoke(&mut self, name: &Rc<str>, argument_count: usize) -> Result<(), RuntimeError> {
+        let Value::Instance(instance) = self.peek(argument_count).to_owned() else {
+            return Err(RuntimeError::InvalidPropertyAccess {
+                line: self.line(),
+                column: self.column(),
+            });
+        };
+
+        let field = instance.fields.borrow().get(name).cloned();
+        if let Some(field) = field {
+            let slot = self.stack.len() - argument_count - 1;
+            self.stack[slot] = field.to_owned();
+            return self.call_value(field, argument_count);
+        }
+
+        self.invoke_from_class(&instance.class, name, argument_count)
+    }
+
+    fn invoke_from_class(
+        &mut self,
+        class: &Rc<Class>,
+        name: &Rc<str>,
+        argument_count: usize,
+    ) -> Result<(), RuntimeError> {
+        let method = class.methods.borrow().get(name).cloned();
+        match method {
+            Some(method) => self.call_closure(method, argument_count),
+            None => Err(RuntimeError::UndefinedProperty {
+                line: self.line(),
+                column: self.column(),
+                suggestion: None,
+            }),
+        }
+    }
+
+    /// Replaces the instance on top of the stack by its method `name`.
+    fn bind_method(&mut self, class: &Rc<Class>, name: &Rc<str>) -> Result<(), RuntimeError> {
+        let method = class.methods.borrow().get(name).cloned();
+        let Some(method) = method else {
+            return Err(RuntimeError::UndefinedProperty {
+                line: self.line(),
+                column: self.column(),
+                suggestion: None,
+            });
+        };
+
+        let receiver = self.pop();
+        self.push(Value::BoundMethod(Rc::new(BoundMethod {
+            receiver,
+            method,
+        })));
+        Ok(())
+    }
+
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let existing = self
+            .open_upvalues
+            .iter()
+            .find(|upvalue| matches!(*upvalue.borrow(), Upvalue::Open(open) if open == slot));
+        if let Some(upvalue) = existing {
+            return Rc::clone(upvalue);
+        }
+
+        let upvalue = Rc::new(RefCell::new(Upvalue::Open(slot)));
+        let position = self
+            .open_upvalues
+            .iter()
+            .position(|upvalue| matches!(*upvalue.borrow(), Upvalue::Open(open) if open > slot))
+            .unwrap_or(self.open_upvalues.len());
+        self.open_upvalues.insert(position, Rc::clone(&upvalue));
+
+        upvalue
+    }
+
+    /// Moves the values of all upvalues referring to `last` or any slot above into the
+    /// upvalues.
+    fn close_upvalues(&mut self, last: usize) {
+        while let Some(upvalue) = self.open_upvalues.last().cloned() {
+            let slot = match *upvalue.borrow() {
+                Upvalue::Open(slot) if slot >= last => slot,
+                _ => break,
+            };
+
+            upvalue.replace(Upvalue::Closed(self.stack[slot].to_owned()));
+            self.open_upvalues.pop();
+        }
+    }
+
+    /// Accounts for memory allocated by the script, which is limited by
+    /// [`InterpreterOptions::max_memory`].
+    fn allocate(&self, bytes: usize) -> Result<(), RuntimeError> {
+        self.budget
+            .allocate(bytes, &self.options, || self.live_bytes())
+    }
+
+    /// Returns the memory held by the strings and instances reachable from the stack, the
+    /// globals and the open upvalues, estimated the same way as when it is allocated.
+    fn live_bytes(&self) -> usize {
+        let mut pending: Vec<Value> = self.stack.to_owned();
+        pending.extend(self.globals.values().cloned());
+        pending.extend(
+            self.frames
+                .iter()
+                .map(|frame| Value::Closure(Rc::clone(&frame.closure))),
+        );
+
+        let mut visited = HashSet::new();
+        let mut bytes = 0;
+        while let Some(value) = pending.pop() {
+            let id = match &value {
+                Value::String(string) => Rc::as_ptr(string) as *const () as usize,
+                Value::Closure(closure) => Rc::as_ptr(closure) as *const () as usize,
+                Value::Class(class) => Rc::as_ptr(class) as *const () as usize,
+                Value::Instance(instance) => Rc::as_ptr(instance) as *const () as usize,
+                Value::BoundMethod(method) => Rc::as_ptr(method) as *const () as usize,
+                Value::Nil | Value::Boolean(_) | Value::Number(_) | Value::Native(_) => continue,
+            };
+            if !visited.insert(id) {
+                continue;
+            }
+
+            match value {
+                Value::String(string) => bytes += string.len(),
+                Value::Closure(closure) => {
+                    for upvalue in &closure.upvalues {
+                        if let Upvalue::Closed(value) = &*upvalue.borrow() {
+                            pending.push(value.to_owned());
+                        }
+                    }
+                }
+                Value::Class(class) => pending.extend(
+                    class
+                        .methods
+                        .borrow()
+                        .values()
+                        .map(|method| Value::Closure(Rc::clone(method))),
+                ),
+                Value::Instance(instance) => {
+                    bytes += size_of::<Instance>();
+                    pending.push(Value::Class(Rc::clone(&instance.class)));
+                    pending.extend(instance.fields.borrow().values().cloned());
+                }
+                Value::BoundMethod(method) => {
+                    pending.push(method.receiver.to_owned());
+                    pending.push(Value::Closure(Rc::clone(&method.method)));
+                }
+                Value::Nil | Value::Boolean(_) | Value::Number(_) | Value::Native(_) => {}
+            }
+        }
+
+        bytes
+    }
+
+    /// Writes to the output stream, unless the output would exceed
+    /// [`InterpreterOptions::max_output`].
+    fn write_output(&mut self, bytes: &[u8]) -> Result<(), RuntimeError> {
+        let written = self.output_written + bytes.len();
+        if let Some(limit) = self.options.max_output {
+            if written > limit {
+                return Err(RuntimeError::OutputLimitExceeded { limit });
+            }
+        }
+        self.output_written = written;
+
+        let mut stream = self.output_stream.borrow_mut();
+        stream
+            .write_all(bytes)
+            .map_err(|_| RuntimeError::OutputError)?;
+        stream.flush().map_err(|_| RuntimeError::OutputError)
+    }
+}
+
+/// Returns the number of seconds since the Unix epoch.
+fn clock(_arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(Value::Number(elapsed.as_secs_f64()))
+}
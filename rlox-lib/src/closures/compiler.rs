@@ -0,0 +1,1018 @@
+use crate::base::ast::{Ast, ExprRef, StmtRef};
+use crate::base::diagnostic::DiagnosticError;
+use crate::base::expr::{Expr, LiteralValue};
+use crate::base::scanner::{Token, TokenType};
+use crate::base::stmt::Stmt;
+use crate::base::visitor::Visitor;
+use crate::closures::runtime::{bind_method, Expression, Runtime, Statement, Unwind, NATIVES};
+use crate::closures::value::{Capture, Class, Function, Value};
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CompilerError {
+    #[error("{line:?}: {feature} not supported by the closure backend!")]
+    Unsupported { line: usize, feature: String },
+}
+
+impl DiagnosticError for CompilerError {
+    fn code(&self) -> &'static str {
+        match self {
+            CompilerError::Unsupported { .. } => "Unsupported",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        match self {
+            CompilerError::Unsupported { line, .. } => Some(*line),
+        }
+    }
+
+    fn column(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FunctionType {
+    Script,
+    Function,
+    Method,
+    Initializer,
+}
+
+/// A local variable in scope. Its slot is its position among the locals of the function.
+struct Local {
+    name: String,
+    depth: usize,
+}
+
+struct FunctionState {
+    function_type: FunctionType,
+    locals: Vec<Local>,
+    captures: Vec<Capture>,
+    /// Labels of the enclosing loops, innermost last.
+    loops: Vec<Option<String>>,
+    scope_depth: usize,
+}
+
+impl FunctionState {
+    fn new(function_type: FunctionType) -> Self {
+        let locals = match function_type {
+            FunctionType::Method | FunctionType::Initializer => vec![Local {
+                name: String::from("this"),
+                depth: 0,
+            }],
+            FunctionType::Script | FunctionType::Function => vec![],
+        };
+
+        Self {
+            function_type,
+            locals,
+            captures: vec![],
+            loops: vec![],
+            scope_depth: 0,
+        }
+    }
+}
+
+/// Where a variable is stored at runtime.
+#[derive(Clone, Copy, Debug)]
+enum Access {
+    Local(usize),
+    Upvalue(usize),
+    Global(usize),
+}
+
+/// Stores a value in a variable.
+type Store = Box<dyn Fn(&mut Runtime<'_>, Value) -> Result<(), RuntimeError>>;
+
+/// Compiles a resolved AST into a tree of closures, one per node, for the [`Runtime`]. Each
+/// closure calls the closures of its children directly, so running a script neither matches
+/// on nodes nor looks up the slots of variables.
+///
+/// Expects statements that passed the [`Resolver`](crate::interpreter::resolver::Resolver),
+/// which reports misplaced `return`, `break`, `this` and `super`. Global variables keep their
+/// slots across the scripts compiled by the same compiler.
+pub struct Compiler {
+    ast: RefCell<Arc<Ast>>,
+    /// Slots of the global variables by name.
+    globals: RefCell<HashMap<String, usize>>,
+    functions: RefCell<Vec<FunctionState>>,
+    line: RefCell<usize>,
+    column: RefCell<usize>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        let globals = NATIVES
+            .iter()
+            .enumerate()
+            .map(|(slot, (name, _, _))| (name.to_string(), slot))
+            .collect();
+
+        Self {
+            ast: RefCell::new(Arc::new(Ast::default())),
+            globals: RefCell::new(globals),
+            functions: RefCell::new(vec![]),
+            line: RefCell::new(1),
+            column: RefCell::new(1),
+        }
+    }
+
+    /// Compiles a script into a function without name and parameters.
+    pub fn compile(&self, ast: &Arc<Ast>) -> Result<Function, CompilerError> {
+        self.ast.replace(Arc::clone(ast));
+        self.functions
+            .replace(vec![FunctionState::new(FunctionType::Script)]);
+
+        let body = self.block(ast.statements())?;
+        self.functions.borrow_mut().pop();
+
+        Ok(Function {
+            name: Rc::from(""),
+            arity: 0,
+            is_method: false,
+            is_initializer: false,
+            captures: vec![],
+            body,
+        })
+    }
+
+    /// Compiles a statement, which accounts for its execution in the runtime's budget first.
+    fn compile_stmt(&self, statement: StmtRef) -> Result<Statement, CompilerError> {
+        let statement = self.ast.borrow()[statement].accept(self)?;
+
+        Ok(Box::new(move |runtime| {
+            runtime.spend()?;
+            statement(runtime)
+        }))
+    }
+
+    fn compile_expr(&self, expression: ExprRef) -> Result<Expression, CompilerError> {
+        self.ast.borrow()[expression].accept(self)
+    }
+
+    /// Compiles statements into one statement running them in order.
+    fn block(&self, statements: &[StmtRef]) -> Result<Statement, CompilerError> {
+        let statements = statements
+            .iter()
+            .map(|statement| self.compile_stmt(*statement))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Box::new(move |runtime| {
+            for statement in &statements {
+                statement(runtime)?;
+            }
+            Ok(())
+        }))
+    }
+
+    fn arguments(&self, arguments: &[ExprRef]) -> Result<Vec<Expression>, CompilerError> {
+        arguments
+            .iter()
+            .map(|argument| self.compile_expr(*argument))
+            .collect()
+    }
+
+    fn line(&self) -> usize {
+        *self.line.borrow()
+    }
+
+    fn column(&self) -> usize {
+        *self.column.borrow()
+    }
+
+    fn at(&self, token: &Token) {
+        self.line.replace(token.line);
+        self.column.replace(token.column);
+    }
+
+    fn current<R>(&self, f: impl FnOnce(&mut FunctionState) -> R) -> R {
+        f(self.functions.borrow_mut().last_mut().unwrap())
+    }
+
+    fn begin_scope(&self) {
+        self.current(|state| state.scope_depth += 1);
+    }
+
+    fn end_scope(&self) {
+        self.current(|state| {
+            state.scope_depth -= 1;
+
+            let depth = state.scope_depth;
+            while state.locals.last().is_some_and(|local| local.depth > depth) {
+                state.locals.pop();
+            }
+        });
+    }
+
+    fn global_slot(&self, name: &str) -> usize {
+        let mut globals = self.globals.borrow_mut();
+        let slot = globals.len();
+        *globals.entry(name.to_owned()).or_insert(slot)
+    }
+
+    /// Declares a variable in the current scope. Variables of the script outside of blocks are
+    /// global.
+    fn declare_variable(&self, name: &str) -> Access {
+        let is_global = self
+            .current(|state| state.function_type == FunctionType::Script && state.scope_depth == 0);
+        if is_global {
+            return Access::Global(self.global_slot(name));
+        }
+
+        self.current(|state| {
+            state.locals.push(Local {
+                name: name.to_owned(),
+                depth: state.scope_depth,
+            });
+            Access::Local(state.locals.len() - 1)
+        })
+    }
+
+    fn resolve_local(&self, function: usize, name: &str) -> Option<usize> {
+        self.functions.borrow()[function]
+            .locals
+            .iter()
+            .rposition(|local| local.name == name)
+    }
+
+    fn add_capture(&self, function: usize, capture: Capture) -> usize {
+        let captures = &mut self.functions.borrow_mut()[function].captures;
+        match captures.iter().position(|existing| *existing == capture) {
+            Some(index) => index,
+            None => {
+                captures.push(capture);
+                captures.len() - 1
+            }
+        }
+    }
+
+    fn resolve_upvalue(&self, function: usize, name: &str) -> Option<usize> {
+        if function == 0 {
+            return None;
+        }
+
+        let capture = match self.resolve_local(function - 1, name) {
+            Some(slot) => Capture::Local(slot),
+            None => Capture::Upvalue(self.resolve_upvalue(function - 1, name)?),
+        };
+        Some(self.add_capture(function, capture))
+    }
+
+    fn access(&self, name: &str) -> Access {
+        let function = self.functions.borrow().len() - 1;
+        if let Some(slot) = self.resolve_local(function, name) {
+            Access::Local(slot)
+        } else if let Some(index) = self.resolve_upvalue(function, name) {
+            Access::Upvalue(index)
+        } else {
+            Access::Global(self.global_slot(name))
+        }
+    }
+
+    /// Compiles a read of the variable `name`.
+    fn variable(&self, name: &str) -> Expression {
+        match self.access(name) {
+            Access::Local(slot) => Box::new(move |runtime| Ok(runtime.local(slot))),
+            Access::Upvalue(index) => Box::new(move |runtime| Ok(runtime.upvalue(index))),
+            Access::Global(index) => {
+                let name = name.to_owned();
+                let (line, column) = (self.line(), self.column());
+                Box::new(move |runtime| runtime.global(index, &name, line, column))
+            }
+        }
+    }
+
+    /// Compiles an assignment to the variable `name`.
+    fn store(&self, name: &str) -> Store {
+        match self.access(name) {
+            Access::Local(slot) => Box::new(move |runtime, value| {
+                runtime.assign_local(slot, value);
+                Ok(())
+            }),
+            Access::Upvalue(index) => Box::new(move |runtime, value| {
+                runtime.assign_upvalue(index, value);
+                Ok(())
+            }),
+            Access::Global(index) => {
+                let name = name.to_owned();
+                let (line, column) = (self.line(), self.column());
+                Box::new(move |runtime, value| {
+                    runtime.assign_global(index, &name, value, line, column)
+                })
+            }
+        }
+    }
+
+    /// Compiles a function declaration into the function its closures are created from.
+    fn function(
+        &self,
+        statement: &Stmt,
+        function_type: FunctionType,
+    ) -> Result<Rc<Function>, CompilerError> {
+        let Stmt::Function {
+            name,
+            params,
+            body,
+            is_async,
+            is_variadic,
+            is_generator,
+            ..
+        } = statement
+        else {
+            unreachable!("functions are compiled from function declarations")
+        };
+
+        self.at(name);
+        if *is_async {
+            return Err(unsupported(name, "Async functions are"));
+        }
+        if *is_variadic {
+            return Err(unsupported(name, "Rest parameters are"));
+        }
+        if *is_generator {
+            return Err(unsupported(name, "Generators are"));
+        }
+
+        self.functions
+            .borrow_mut()
+            .push(FunctionState::new(function_type));
+        self.begin_scope();
+
+        for param in params.iter() {
+            self.declare_variable(&param.lexeme);
+        }
+        let body = self.block(body)?;
+
+        let state = self.functions.borrow_mut().pop().unwrap();
+        Ok(Rc::new(Function {
+            name: Rc::from(name.lexeme.as_str()),
+            arity: params.len(),
+            is_method: matches!(
+                function_type,
+                FunctionType::Method | FunctionType::Initializer
+            ),
+            is_initializer: function_type == FunctionType::Initializer,
+            captures: state.captures,
+            body,
+        }))
+    }
+
+    /// Compiles a `break` or `continue`, which unwinds to the loop it targets.
+    fn jump_out_of_loop(
+        &self,
+        label: &Option<Token>,
+        unwind: fn(usize) -> Unwind,
+    ) -> Result<Statement, CompilerError> {
+        let distance = self.current(|state| {
+            state
+                .loops
+                .iter()
+                .rev()
+                .position(|enclosing| match label {
+                    Some(label) => enclosing.as_deref() == Some(label.lexeme.as_str()),
+                    None => true,
+                })
+                .unwrap_or_default()
+        });
+
+        Ok(Box::new(move |_| Err(unwind(distance))))
+    }
+}
+
+fn unsupported(token: &Token, feature: &str) -> CompilerError {
+    CompilerError::Unsupported {
+        line: token.line,
+        feature: String::from(feature),
+    }
+}
+
+fn define(runtime: &mut Runtime<'_>, access: Access, value: Value) {
+    match access {
+        Access::Global(index) => runtime.define_global(index, value),
+        Access::Local(slot) => runtime.define_local(slot, value),
+        Access::Upvalue(_) => unreachable!("variables are declared as locals or globals"),
+    }
+}
+
+fn evaluate_all(
+    runtime: &mut Runtime<'_>,
+    expressions: &[Expression],
+) -> Result<Vec<Value>, RuntimeError> {
+    expressions
+        .iter()
+        .map(|expression| expression(runtime))
+        .collect()
+}
+
+/// Compiles an operator on two numbers.
+fn numeric(
+    left: Expression,
+    right: Expression,
+    operator: &Token,
+    operation: fn(f64, f64) -> Value,
+) -> Expression {
+    let (line, column) = (operator.line, operator.column);
+    let operator = operator.lexeme.clone();
+    Box::new(move |runtime| match (left(runtime)?, right(runtime)?) {
+        (Value::Number(a), Value::Number(b)) => Ok(operation(a, b)),
+        (a, b) => Err(number_expected(line, column, &operator, &[&a, &b])),
+    })
+}
+
+/// Creates the error for an operand of `operator` that is not a number, reporting the first
+/// operand that isn't.
+fn number_expected(
+    line: usize,
+    column: usize,
+    operator: &str,
+    operands: &[&Value],
+) -> RuntimeError {
+    let value = operands
+        .iter()
+        .find(|operand| !matches!(operand, Value::Number(_)))
+        .unwrap_or(&operands[0]);
+
+    RuntimeError::NumberExpected {
+        line,
+        column,
+        operator: operator.to_owned(),
+        value: value.describe(),
+    }
+}
+
+impl Visitor<Stmt, Statement, CompilerError> for Compiler {
+    fn visit(&self, input: &Stmt) -> Result<Statement, CompilerError> {
+        let ast = self.ast.borrow();
+        match input {
+            Stmt::Block { statements } => {
+                self.begin_scope();
+                let block = self.block(statements)?;
+                self.end_scope();
+
+                Ok(block)
+            }
+            Stmt::Break { label, .. } => self.jump_out_of_loop(label, Unwind::Break),
+            Stmt::Continue { label, .. } => self.jump_out_of_loop(label, Unwind::Continue),
+            Stmt::Class {
+                name,
+                superclass,
+                traits,
+                fields,
+                methods,
+                ..
+            } => {
+                if !traits.is_empty() {
+                    return Err(unsupported(name, "Traits are"));
+                }
+                if !fields.is_empty() {
+                    return Err(unsupported(name, "Field initializers are"));
+                }
+
+                self.at(name);
+                let (line, column) = (name.line, name.column);
+                let class_name: Rc<str> = Rc::from(name.lexeme.as_str());
+                let access = self.declare_variable(&name.lexeme);
+
+                let superclass = match superclass {
+                    Some(superclass) => {
+                        let superclass = self.compile_expr(*superclass)?;
+                        self.begin_scope();
+                        let Access::Local(slot) = self.declare_variable("super") else {
+                            unreachable!("'super' is declared in a block")
+                        };
+                        Some((superclass, slot))
+                    }
+                    None => None,
+                };
+
+                let methods = methods
+                    .iter()
+                    .map(|method| {
+                        let function_type = match &ast[*method] {
+                            Stmt::Function { name, .. } if name.lexeme == "init" => {
+                                FunctionType::Initializer
+                            }
+                            _ => FunctionType::Method,
+                        };
+                        let function = self.function(&ast[*method], function_type)?;
+                        Ok((Rc::clone(&function.name), function))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+
+                Ok(Box::new(move |runtime| {
+                    let class = Rc::new(Class {
+                        name: Rc::clone(&class_name),
+                        methods: RefCell::new(HashMap::new()),
+                    });
+                    define(runtime, access, Value::Class(Rc::clone(&class)));
+
+                    if let Some((superclass, slot)) = &superclass {
+                        let superclass = superclass(runtime)?;
+                        let Value::Class(inherited) = &superclass else {
+                            return Err(RuntimeError::SuperclassInvalidType { line, column }.into());
+                        };
+
+                        let inherited = inherited.methods.borrow().clone();
+                        class.methods.borrow_mut().extend(inherited);
+                        runtime.define_local(*slot, superclass);
+                    }
+
+                    for (name, function) in &methods {
+                        let method = runtime.closure(function);
+                        class.methods.borrow_mut().insert(Rc::clone(name), method);
+                    }
+
+                    Ok(())
+                }))
+            }
+            Stmt::Destructure { keyword, .. } => Err(unsupported(keyword, "Destructuring is")),
+            Stmt::Expression { expression } => {
+                let expression = self.compile_expr(*expression)?;
+                Ok(Box::new(move |runtime| {
+                    expression(runtime)?;
+                    Ok(())
+                }))
+            }
+            Stmt::ForIn { keyword, .. } => Err(unsupported(keyword, "'for-in' loops are")),
+            Stmt::Function { name, .. } => {
+                let access = self.declare_variable(&name.lexeme);
+                let function = self.function(input, FunctionType::Function)?;
+
+                // The local exists before the closure, so that recursive functions capture it.
+                Ok(Box::new(move |runtime| {
+                    if let Access::Local(slot) = access {
+                        runtime.define_local(slot, Value::Nil);
+                        let closure = Value::Closure(runtime.closure(&function));
+                        runtime.assign_local(slot, closure);
+                    } else {
+                        let closure = Value::Closure(runtime.closure(&function));
+                        define(runtime, access, closure);
+                    }
+                    Ok(())
+                }))
+            }
+            Stmt::If {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let (line, column) = (keyword.line, keyword.column);
+                let condition = self.compile_expr(*condition)?;
+                let then_branch = self.compile_stmt(*then_branch)?;
+                let else_branch = else_branch
+                    .map(|branch| self.compile_stmt(branch))
+                    .transpose()?;
+
+                Ok(Box::new(move |runtime| {
+                    let condition = condition(runtime)?;
+                    if runtime.is_true(&condition, line, column)? {
+                        then_branch(runtime)
+                    } else if let Some(else_branch) = &else_branch {
+                        else_branch(runtime)
+                    } else {
+                        Ok(())
+                    }
+                }))
+            }
+            Stmt::Import { keyword, .. } => Err(unsupported(keyword, "Imports are")),
+            Stmt::Print { expression } => {
+                let expression = self.compile_expr(*expression)?;
+                let (line, column) = (self.line(), self.column());
+
+                Ok(Box::new(move |runtime| {
+                    let value = expression(runtime)?;
+                    let value = runtime.stringify(value, line, column)?;
+                    runtime.write_output(format!("{}\n", value).as_bytes())?;
+                    Ok(())
+                }))
+            }
+            Stmt::Return { keyword, value } => {
+                self.at(keyword);
+                let value = value.map(|value| self.compile_expr(value)).transpose()?;
+
+                Ok(Box::new(move |runtime| {
+                    let value = match &value {
+                        Some(value) => value(runtime)?,
+                        None => Value::Nil,
+                    };
+                    Err(Unwind::Return(value))
+                }))
+            }
+            Stmt::Trait { name, .. } => Err(unsupported(name, "Traits are")),
+            Stmt::Yield { keyword, .. } => Err(unsupported(keyword, "Generators are")),
+            Stmt::Var {
+                name, initializer, ..
+            } => {
+                self.at(name);
+                let initializer = initializer
+                    .map(|initializer| self.compile_expr(initializer))
+                    .transpose()?;
+                let access = self.declare_variable(&name.lexeme);
+
+                Ok(Box::new(move |runtime| {
+                    let value = match &initializer {
+                        Some(initializer) => initializer(runtime)?,
+                        None => Value::Nil,
+                    };
+                    define(runtime, access, value);
+                    Ok(())
+                }))
+            }
+            Stmt::While {
+                keyword,
+                label,
+                condition,
+                body,
+                increment,
+            } => {
+                let (line, column) = (keyword.line, keyword.column);
+                let condition = self.compile_expr(*condition)?;
+
+                self.current(|state| {
+                    state
+                        .loops
+                        .push(label.as_ref().as_ref().map(|label| label.lexeme.to_owned()))
+                });
+                let body = self.compile_stmt(*body);
+                self.current(|state| state.loops.pop());
+                let body = body?;
+
+                let increment = increment
+                    .map(|increment| self.compile_expr(increment))
+                    .transpose()?;
+
+                Ok(Box::new(move |runtime| {
+                    loop {
+                        let condition = condition(runtime)?;
+                        if !runtime.is_true(&condition, line, column)? {
+                            break;
+                        }
+
+                        match body(runtime) {
+                            Ok(()) | Err(Unwind::Continue(0)) => {}
+                            Err(Unwind::Break(0)) => break,
+                            Err(Unwind::Break(distance)) => {
+                                return Err(Unwind::Break(distance - 1))
+                            }
+                            Err(Unwind::Continue(distance)) => {
+                                return Err(Unwind::Continue(distance - 1))
+                            }
+                            Err(unwind) => return Err(unwind),
+                        }
+
+                        if let Some(increment) = &increment {
+                            increment(runtime)?;
+                        }
+                        runtime.check_interrupt()?;
+                    }
+
+                    Ok(())
+                }))
+            }
+        }
+    }
+}
+
+impl Visitor<Expr, Expression, CompilerError> for Compiler {
+    fn visit(&self, input: &Expr) -> Result<Expression, CompilerError> {
+        let ast = self.ast.borrow();
+        match input {
+            Expr::Assign { name, value, .. } => {
+                let value = self.compile_expr(*value)?;
+                self.at(name);
+                let store = self.store(&name.lexeme);
+
+                Ok(Box::new(move |runtime| {
+                    let value = value(runtime)?;
+                    store(runtime, value.to_owned())?;
+                    Ok(value)
+                }))
+            }
+            Expr::Await { keyword, .. } => Err(unsupported(keyword, "'await' is")),
+            Expr::Yield { keyword, .. } => Err(unsupported(keyword, "Generators are")),
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                if operator.token_type == TokenType::Is {
+                    return Err(unsupported(operator, "'is' checks are"));
+                }
+
+                let left = self.compile_expr(*left)?;
+                let right = self.compile_expr(*right)?;
+                self.at(operator);
+                let (line, column) = (operator.line, operator.column);
+
+                Ok(match operator.token_type {
+                    TokenType::BangEqual | TokenType::EqualEqual => {
+                        let equal = operator.token_type == TokenType::EqualEqual;
+                        Box::new(move |runtime| {
+                            let a = left(runtime)?;
+                            let b = right(runtime)?;
+                            runtime.check_mismatch(&a, &b, line, column)?;
+                            Ok(Value::Boolean((a == b) == equal))
+                        })
+                    }
+                    TokenType::Greater => {
+                        numeric(left, right, operator, |a, b| Value::Boolean(a > b))
+                    }
+                    TokenType::GreaterEqual => {
+                        numeric(left, right, operator, |a, b| Value::Boolean(a >= b))
+                    }
+                    TokenType::Less => numeric(left, right, operator, |a, b| Value::Boolean(a < b)),
+                    TokenType::LessEqual => {
+                        numeric(left, right, operator, |a, b| Value::Boolean(a <= b))
+                    }
+                    TokenType::Minus => numeric(left, right, operator, |a, b| Value::Number(a - b)),
+                    TokenType::Star => numeric(left, right, operator, |a, b| Value::Number(a * b)),
+                    TokenType::Slash => {
+                        let operator = operator.lexeme.clone();
+                        Box::new(move |runtime| match (left(runtime)?, right(runtime)?) {
+                            (Value::Number(_), Value::Number(b))
+                                if b == 0.0 && runtime.options().checked_division =>
+                            {
+                                Err(RuntimeError::DivisionByZero { line, column })
+                            }
+                            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a / b)),
+                            (a, b) => Err(number_expected(line, column, &operator, &[&a, &b])),
+                        })
+                    }
+                    TokenType::Plus => Box::new(move |runtime| {
+                        let a = left(runtime)?;
+                        let b = right(runtime)?;
+                        runtime.add(a, b, line, column)
+                    }),
+                    _ => return Err(unsupported(operator, "The operator is")),
+                })
+            }
+            Expr::Call {
+                paren,
+                callee,
+                arguments,
+                ..
+            } => {
+                let (line, column) = (paren.line, paren.column);
+                match &ast[*callee] {
+                    Expr::Get { object, name, .. } => {
+                        let object = self.compile_expr(*object)?;
+                        let arguments = self.arguments(arguments)?;
+                        let name: Rc<str> = Rc::from(name.lexeme.as_str());
+
+                        Ok(Box::new(move |runtime| {
+                            let receiver = object(runtime)?;
+                            let arguments = evaluate_all(runtime, &arguments)?;
+                            runtime.invoke(receiver, &name, arguments, line, column)
+                        }))
+                    }
+                    Expr::Super { method, .. } => {
+                        let this = self.variable("this");
+                        let arguments = self.arguments(arguments)?;
+                        let superclass = self.variable("super");
+                        let method: Rc<str> = Rc::from(method.lexeme.as_str());
+
+                        Ok(Box::new(move |runtime| {
+                            let receiver = this(runtime)?;
+                            let arguments = evaluate_all(runtime, &arguments)?;
+                            let superclass = superclass(runtime)?;
+                            runtime.invoke_super(
+                                &superclass,
+                                receiver,
+                                &method,
+                                arguments,
+                                line,
+                                column,
+                            )
+                        }))
+                    }
+                    _ => {
+                        let callee = self.compile_expr(*callee)?;
+                        let arguments = self.arguments(arguments)?;
+
+                        Ok(Box::new(move |runtime| {
+                            let callee = callee(runtime)?;
+                            let arguments = evaluate_all(runtime, &arguments)?;
+                            runtime.call(callee, arguments, line, column)
+                        }))
+                    }
+                }
+            }
+            Expr::Get { object, name, .. } => {
+                let object = self.compile_expr(*object)?;
+                let (line, column) = (name.line, name.column);
+                let name: Rc<str> = Rc::from(name.lexeme.as_str());
+
+                Ok(Box::new(move |runtime| {
+                    let object = object(runtime)?;
+                    runtime.get_property(object, &name, line, column)
+                }))
+            }
+            Expr::Grouping { expression, .. } => self.compile_expr(*expression),
+            Expr::Literal { value, .. } => {
+                let value = match value {
+                    LiteralValue::Number(value) => Value::Number(*value),
+                    LiteralValue::String(value) => Value::String(Rc::from(value.as_str())),
+                    LiteralValue::Boolean(value) => Value::Boolean(*value),
+                    LiteralValue::None => Value::Nil,
+                };
+
+                Ok(Box::new(move |_| Ok(value.to_owned())))
+            }
+            Expr::Index { bracket, .. } | Expr::IndexSet { bracket, .. } => {
+                Err(unsupported(bracket, "Indexing is"))
+            }
+            Expr::List { bracket, .. } => Err(unsupported(bracket, "Lists are")),
+            Expr::Map { brace, .. } => Err(unsupported(brace, "Maps are")),
+            Expr::Lambda { function, .. } => {
+                let function = self.function(&ast[*function], FunctionType::Function)?;
+                Ok(Box::new(move |runtime| {
+                    Ok(Value::Closure(runtime.closure(&function)))
+                }))
+            }
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.compile_expr(*left)?;
+                let right = self.compile_expr(*right)?;
+                let (line, column) = (operator.line, operator.column);
+                let is_or = operator.token_type == TokenType::Or;
+
+                Ok(Box::new(move |runtime| {
+                    let left = left(runtime)?;
+                    if runtime.is_true(&left, line, column)? == is_or {
+                        Ok(left)
+                    } else {
+                        right(runtime)
+                    }
+                }))
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+                ..
+            } => {
+                let object = self.compile_expr(*object)?;
+                let value = self.compile_expr(*value)?;
+                let (line, column) = (name.line, name.column);
+                let name: Rc<str> = Rc::from(name.lexeme.as_str());
+
+                Ok(Box::new(move |runtime| {
+                    let object = object(runtime)?;
+                    let value = value(runtime)?;
+                    runtime.set_property(object, &name, value, line, column)
+                }))
+            }
+            Expr::Stringify { expression, .. } => {
+                let expression = self.compile_expr(*expression)?;
+                let (line, column) = (self.line(), self.column());
+
+                Ok(Box::new(move |runtime| {
+                    let value = expression(runtime)?;
+                    let string = runtime.stringify(value, line, column)?;
+                    runtime.allocate(string.len())?;
+                    Ok(Value::String(Rc::from(string)))
+                }))
+            }
+            Expr::Super {
+                keyword, method, ..
+            } => {
+                self.at(keyword);
+                let this = self.variable("this");
+                let superclass = self.variable("super");
+                let (line, column) = (method.line, method.column);
+                let method: Rc<str> = Rc::from(method.lexeme.as_str());
+
+                Ok(Box::new(move |runtime| {
+                    let receiver = this(runtime)?;
+                    let Value::Class(superclass) = superclass(runtime)? else {
+                        return Err(RuntimeError::SuperclassInvalidType { line, column });
+                    };
+                    bind_method(&superclass, receiver, &method, line, column)
+                }))
+            }
+            Expr::Ternary {
+                condition,
+                question,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let condition = self.compile_expr(*condition)?;
+                let then_branch = self.compile_expr(*then_branch)?;
+                let else_branch = self.compile_expr(*else_branch)?;
+                let (line, column) = (question.line, question.column);
+
+                Ok(Box::new(move |runtime| {
+                    let condition = condition(runtime)?;
+                    if runtime.is_true(&condition, line, column)? {
+                        then_branch(runtime)
+                    } else {
+                        else_branch(runtime)
+                    }
+                }))
+            }
+            Expr::This { keyword, .. } => {
+                self.at(keyword);
+                Ok(self.variable(&keyword.lexeme))
+            }
+            Expr::Unary {
+                operator, right, ..
+            } => {
+                let right = self.compile_expr(*right)?;
+                let (line, column) = (operator.line, operator.column);
+
+                match operator.token_type {
+                    TokenType::Minus => Ok(Box::new(move |runtime| match right(runtime)? {
+                        Value::Number(value) => Ok(Value::Number(-value)),
+                        value => Err(number_expected(line, column, "-", &[&value])),
+                    })),
+                    TokenType::Bang => Ok(Box::new(move |runtime| {
+                        let value = right(runtime)?;
+                        Ok(Value::Boolean(!runtime.is_true(&value, line, column)?))
+                    })),
+                    _ => Err(unsupported(operator, "The operator is")),
+                }
+            }
+            Expr::Update {
+                operator,
+                target,
+                prefix,
+                ..
+            } => {
+                let delta = if operator.token_type == TokenType::PlusPlus {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let prefix = *prefix;
+                let (line, column) = (operator.line, operator.column);
+                let lexeme = operator.lexeme.clone();
+                let updated = move |value: Value| match value {
+                    Value::Number(old) => {
+                        let new = old + delta;
+                        Ok((
+                            Value::Number(new),
+                            Value::Number(if prefix { new } else { old }),
+                        ))
+                    }
+                    value => Err(number_expected(line, column, &lexeme, &[&value])),
+                };
+
+                match &ast[*target] {
+                    Expr::Variable { name, .. } => {
+                        self.at(name);
+                        let read = self.variable(&name.lexeme);
+                        let store = self.store(&name.lexeme);
+
+                        Ok(Box::new(move |runtime| {
+                            let (new, result) = updated(read(runtime)?)?;
+                            store(runtime, new)?;
+                            Ok(result)
+                        }))
+                    }
+                    Expr::Get { object, name, .. } => {
+                        let object = self.compile_expr(*object)?;
+                        let name: Rc<str> = Rc::from(name.lexeme.as_str());
+
+                        Ok(Box::new(move |runtime| {
+                            let object = object(runtime)?;
+                            let value =
+                                runtime.get_property(object.to_owned(), &name, line, column)?;
+                            let (new, result) = updated(value)?;
+                            runtime.set_property(object, &name, new, line, column)?;
+                            Ok(result)
+                        }))
+                    }
+                    _ => Err(unsupported(operator, "The operator is")),
+                }
+            }
+            Expr::Variable { name, .. } => {
+                self.at(name);
+                Ok(self.variable(&name.lexeme))
+            }
+        }
+    }
+}
@@ -0,0 +1,607 @@
+use crate::closures::value::{
+    BoundMethod, Capture, Class, Closure, Function, Instance, Native, NativeFn, Value, Variable,
+};
+use crate::interpreter::budget::Budget;
+use crate::interpreter::interrupt::InterruptHandle;
+use crate::interpreter::options::InterpreterOptions;
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum depth of nested calls before a script is aborted with a stack overflow. Compiled
+/// functions call each other on the native stack, so the limit is lower than the VM's.
+const FRAMES_MAX: usize = 256;
+
+/// Native functions available to scripts, in the order of their global slots.
+pub(crate) const NATIVES: &[(&str, usize, NativeFn)] = &[("clock", 0, clock)];
+
+/// A compiled expression.
+pub type Expression = Box<dyn Fn(&mut Runtime<'_>) -> Result<Value, RuntimeError>>;
+
+/// A compiled statement.
+pub type Statement = Box<dyn Fn(&mut Runtime<'_>) -> Result<(), Unwind>>;
+
+/// Why a statement ended before its last instruction.
+#[derive(Debug)]
+pub enum Unwind {
+    Error(RuntimeError),
+    Return(Value),
+    /// Leaves the loop that many loops outside of the innermost one.
+    Break(usize),
+    /// Continues the loop that many loops outside of the innermost one.
+    Continue(usize),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+/// The variables of the running function.
+#[derive(Default)]
+struct Frame {
+    locals: Vec<Variable>,
+    upvalues: Rc<[Variable]>,
+}
+
+/// Runs scripts that the [`Compiler`](crate::closures::compiler::Compiler) turned into trees of
+/// closures. Variables are resolved to slots during compilation, so only fields and methods are
+/// looked up by name while running.
+///
+/// Global variables persist across calls of [`Runtime::interpret`], as long as the scripts are
+/// compiled by the same compiler.
+pub struct Runtime<'a> {
+    globals: Vec<Option<Value>>,
+    frame: Frame,
+    /// Frames of the functions waiting for the running function to return.
+    callers: Vec<Frame>,
+    options: InterpreterOptions,
+    budget: Budget,
+    interrupt: InterruptHandle,
+    output_stream: Rc<RefCell<dyn Write + 'a>>,
+    output_written: usize,
+}
+
+impl<'a> Runtime<'a> {
+    pub fn new<OutputWriter>(output_stream: Rc<RefCell<OutputWriter>>) -> Self
+    where
+        OutputWriter: Write + 'a,
+    {
+        let globals = NATIVES
+            .iter()
+            .map(|&(name, arity, function)| {
+                Some(Value::Native(Rc::new(Native {
+                    name,
+                    arity,
+                    function,
+                })))
+            })
+            .collect();
+
+        Self {
+            globals,
+            frame: Frame::default(),
+            callers: vec![],
+            options: InterpreterOptions::default(),
+            budget: Budget::new(),
+            interrupt: InterruptHandle::new(),
+            output_stream,
+            output_written: 0,
+        }
+    }
+
+    pub fn with_options(mut self, options: InterpreterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns a handle that stops the currently running script when triggered. Each call of
+    /// [`Runtime::interpret`] starts uninterrupted.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.to_owned()
+    }
+
+    /// Runs a compiled script. After an error, global variables defined so far are kept.
+    pub fn interpret(&mut self, script: Function) -> Result<(), RuntimeError> {
+        self.interrupt.reset();
+        self.budget.reset();
+
+        let closure = Rc::new(Closure {
+            function: Rc::new(script),
+            upvalues: Rc::from([]),
+        });
+        self.call_closure(&closure, None, Vec::new(), 0, 0)
+            .map(|_| ())
+    }
+
+    pub(crate) fn options(&self) -> &InterpreterOptions {
+        &self.options
+    }
+
+    pub(crate) fn local(&self, slot: usize) -> Value {
+        self.frame.locals[slot].borrow().to_owned()
+    }
+
+    pub(crate) fn assign_local(&self, slot: usize, value: Value) {
+        *self.frame.locals[slot].borrow_mut() = value;
+    }
+
+    /// Creates the variable of a declaration, replacing the variable of a scope that ended.
+    pub(crate) fn define_local(&mut self, slot: usize, value: Value) {
+        let variable = Rc::new(RefCell::new(value));
+        if slot < self.frame.locals.len() {
+            self.frame.locals[slot] = variable;
+        } else {
+            self.frame.locals.resize_with(slot, Variable::default);
+            self.frame.locals.push(variable);
+        }
+    }
+
+    pub(crate) fn upvalue(&self, index: usize) -> Value {
+        self.frame.upvalues[index].borrow().to_owned()
+    }
+
+    pub(crate) fn assign_upvalue(&self, index: usize, value: Value) {
+        *self.frame.upvalues[index].borrow_mut() = value;
+    }
+
+    pub(crate) fn global(
+        &self,
+        index: usize,
+        name: &str,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        match self.globals.get(index) {
+            Some(Some(value)) => Ok(value.to_owned()),
+            _ => Err(undefined_variable(name, line, column)),
+        }
+    }
+
+    pub(crate) fn assign_global(
+        &mut self,
+        index: usize,
+        name: &str,
+        value: Value,
+        line: usize,
+        column: usize,
+    ) -> Result<(), RuntimeError> {
+        match self.globals.get_mut(index) {
+            Some(Some(global)) => {
+                *global = value;
+                Ok(())
+            }
+            _ => Err(undefined_variable(name, line, column)),
+        }
+    }
+
+    pub(crate) fn define_global(&mut self, index: usize, value: Value) {
+        if index >= self.globals.len() {
+            self.globals.resize(index + 1, None);
+        }
+        self.globals[index] = Some(value);
+    }
+
+    /// Creates a closure of a function declared in the running function.
+    pub(crate) fn closure(&self, function: &Rc<Function>) -> Rc<Closure> {
+        let upvalues = function
+            .captures
+            .iter()
+            .map(|capture| match capture {
+                Capture::Local(slot) => Rc::clone(&self.frame.locals[*slot]),
+                Capture::Upvalue(index) => Rc::clone(&self.frame.upvalues[*index]),
+            })
+            .collect();
+
+        Rc::new(Closure {
+            function: Rc::clone(function),
+            upvalues,
+        })
+    }
+
+    pub(crate) fn call(
+        &mut self,
+        callee: Value,
+        arguments: Vec<Value>,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        match callee {
+            Value::Closure(closure) => self.call_closure(&closure, None, arguments, line, column),
+            Value::BoundMethod(bound) => self.call_closure(
+                &bound.method,
+                Some(bound.receiver.to_owned()),
+                arguments,
+                line,
+                column,
+            ),
+            Value::Class(class) => {
+                self.allocate(size_of::<Instance>())?;
+                let instance = Value::Instance(Rc::new(Instance {
+                    class: Rc::clone(&class),
+                    fields: RefCell::new(HashMap::new()),
+                }));
+
+                let initializer = class.methods.borrow().get("init").cloned();
+                match initializer {
+                    Some(initializer) => {
+                        self.call_closure(&initializer, Some(instance), arguments, line, column)
+                    }
+                    None if arguments.is_empty() => Ok(instance),
+                    None => Err(RuntimeError::NonMatchingNumberOfArguments { line, column }),
+                }
+            }
+            Value::Native(native) => {
+                if arguments.len() != native.arity {
+                    return Err(RuntimeError::NonMatchingNumberOfArguments { line, column });
+                }
+
+                (native.function)(&arguments)
+            }
+            _ => Err(RuntimeError::UndefinedCallable { line, column }),
+        }
+    }
+
+    /// Runs the body of a closure in a frame of its own. Methods find the instance they are
+    /// bound to in slot 0, followed by the arguments.
+    fn call_closure(
+        &mut self,
+        closure: &Rc<Closure>,
+        receiver: Option<Value>,
+        arguments: Vec<Value>,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        let function = &closure.function;
+        if arguments.len() != function.arity {
+            return Err(RuntimeError::NonMatchingNumberOfArguments { line, column });
+        }
+        if self.callers.len() == FRAMES_MAX {
+            return Err(RuntimeError::StackOverflow { line, column });
+        }
+
+        let locals = receiver
+            .into_iter()
+            .chain(arguments)
+            .map(|value| Rc::new(RefCell::new(value)))
+            .collect();
+        let enclosing = std::mem::replace(
+            &mut self.frame,
+            Frame {
+                locals,
+                upvalues: Rc::clone(&closure.upvalues),
+            },
+        );
+
+        self.callers.push(enclosing);
+        let result = (function.body)(self);
+        let enclosing = self.callers.pop().unwrap_or_default();
+        let frame = std::mem::replace(&mut self.frame, enclosing);
+
+        match result {
+            Ok(()) | Err(Unwind::Return(_)) if function.is_initializer => {
+                Ok(frame.locals[0].borrow().to_owned())
+            }
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+            /* the resolver rejects `break` and `continue` outside of loops. */
+            Err(Unwind::Break(_) | Unwind::Continue(_)) => Ok(Value::Nil),
+        }
+    }
+
+    /// Calls a method of an instance without creating a bound method. A field holding a
+    /// function is called instead, if present.
+    pub(crate) fn invoke(
+        &mut self,
+        receiver: Value,
+        name: &str,
+        arguments: Vec<Value>,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        let Value::Instance(instance) = &receiver else {
+            return Err(RuntimeError::InvalidPropertyAccess { line, column });
+        };
+
+        let field = instance.fields.borrow().get(name).cloned();
+        if let Some(field) = field {
+            return self.call(field, arguments, line, column);
+        }
+
+        let method = instance.class.methods.borrow().get(name).cloned();
+        match method {
+            Some(method) => self.call_closure(&method, Some(receiver), arguments, line, column),
+            None => Err(RuntimeError::UndefinedProperty {
+                line,
+                column,
+                suggestion: None,
+            }),
+        }
+    }
+
+    /// Calls a method of the superclass on the instance a method is bound to.
+    pub(crate) fn invoke_super(
+        &mut self,
+        superclass: &Value,
+        receiver: Value,
+        name: &str,
+        arguments: Vec<Value>,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        let Value::Class(superclass) = superclass else {
+            return Err(RuntimeError::SuperclassInvalidType { line, column });
+        };
+
+        let method = superclass.methods.borrow().get(name).cloned();
+        match method {
+            Some(method) => self.call_closure(&method, Some(receiver), arguments, line, column),
+            None => Err(RuntimeError::UndefinedProperty {
+                line,
+                column,
+                suggestion: None,
+            }),
+        }
+    }
+
+    pub(crate) fn get_property(
+        &self,
+        object: Value,
+        name: &str,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        let Value::Instance(instance) = &object else {
+            return Err(RuntimeError::InvalidPropertyAccess { line, column });
+        };
+
+        let field = instance.fields.borrow().get(name).cloned();
+        match field {
+            Some(value) => Ok(value),
+            None => bind_method(&instance.class, object.to_owned(), name, line, column),
+        }
+    }
+
+    pub(crate) fn set_property(
+        &self,
+        object: Value,
+        name: &Rc<str>,
+        value: Value,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        let Value::Instance(instance) = object else {
+            return Err(RuntimeError::InvalidFieldAccess { line, column });
+        };
+
+        instance
+            .fields
+            .borrow_mut()
+            .insert(Rc::clone(name), value.to_owned());
+        Ok(value)
+    }
+
+    pub(crate) fn is_true(
+        &self,
+        value: &Value,
+        line: usize,
+        column: usize,
+    ) -> Result<bool, RuntimeError> {
+        match value {
+            Value::Boolean(value) => Ok(*value),
+            _ if self.options.strict => Err(RuntimeError::BooleanExpected { line, column }),
+            _ => Ok(!value.is_falsey()),
+        }
+    }
+
+    /// Rejects comparing values of different types for equality in strict mode, except with
+    /// `nil`.
+    pub(crate) fn check_mismatch(
+        &self,
+        a: &Value,
+        b: &Value,
+        line: usize,
+        column: usize,
+    ) -> Result<(), RuntimeError> {
+        let different = std::mem::discriminant(a) != std::mem::discriminant(b);
+        if self.options.strict && different && *a != Value::Nil && *b != Value::Nil {
+            return Err(RuntimeError::MismatchedTypes { line, column });
+        }
+
+        Ok(())
+    }
+
+    /// Adds two numbers or concatenates two values of which at least one is a string.
+    pub(crate) fn add(
+        &mut self,
+        a: Value,
+        b: Value,
+        line: usize,
+        column: usize,
+    ) -> Result<Value, RuntimeError> {
+        match (a, b) {
+            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + b)),
+            (Value::String(a), Value::String(b)) => {
+                self.allocate(a.len() + b.len())?;
+                Ok(Value::String(Rc::from(format!("{a}{b}"))))
+            }
+            (a @ Value::String(_), b) | (a, b @ Value::String(_)) => {
+                if self.options.strict {
+                    return Err(RuntimeError::MismatchedTypes { line, column });
+                }
+                let a = self.stringify(a, line, column)?;
+                let b = self.stringify(b, line, column)?;
+                self.allocate(a.len() + b.len())?;
+                Ok(Value::String(Rc::from(a + &b)))
+            }
+            (a, b) => {
+                let value = if matches!(a, Value::Number(_)) { b } else { a };
+                Err(RuntimeError::NumberOrStringExpected {
+                    line,
+                    column,
+                    operator: String::from("+"),
+                    value: value.describe(),
+                })
+            }
+        }
+    }
+
+    /// Converts a value to the string printed by `print`, calling a `toString` method without
+    /// parameters of instances.
+    pub(crate) fn stringify(
+        &mut self,
+        value: Value,
+        line: usize,
+        column: usize,
+    ) -> Result<String, RuntimeError> {
+        if let Value::Instance(instance) = &value {
+            let method = instance.class.methods.borrow().get("toString").cloned();
+            if let Some(method) = method.filter(|method| method.function.arity == 0) {
+                let result = self.call_closure(&method, Some(value), Vec::new(), line, column)?;
+                return Ok(result.to_string());
+            }
+        }
+
+        Ok(value.to_string())
+    }
+
+    /// Stops a loop of the running script if it was interrupted.
+    pub(crate) fn check_interrupt(&self) -> Result<(), RuntimeError> {
+        if self.interrupt.is_interrupted() {
+            return Err(RuntimeError::Interrupted);
+        }
+
+        Ok(())
+    }
+
+    /// Accounts for executing a statement, which is limited by
+    /// [`InterpreterOptions::max_steps`] and [`InterpreterOptions::max_duration`].
+    pub(crate) fn spend(&self) -> Result<(), RuntimeError> {
+        self.budget.spend(&self.options)
+    }
+
+    /// Accounts for memory allocated by the script, which is limited by
+    /// [`InterpreterOptions::max_memory`].
+    pub(crate) fn allocate(&self, bytes: usize) -> Result<(), RuntimeError> {
+        self.budget
+            .allocate(bytes, &self.options, || self.live_bytes())
+    }
+
+    /// Returns the memory held by the strings and instances reachable from the globals and the
+    /// frames of the running functions, estimated the same way as when it is allocated.
+    fn live_bytes(&self) -> usize {
+        let mut pending: Vec<Value> = self.globals.iter().flatten().cloned().collect();
+        for frame in self.callers.iter().chain([&self.frame]) {
+            for variable in frame.locals.iter().chain(frame.upvalues.iter()) {
+                pending.push(variable.borrow().to_owned());
+            }
+        }
+
+        let mut visited = HashSet::new();
+        let mut bytes = 0;
+        while let Some(value) = pending.pop() {
+            let id = match &value {
+                Value::String(string) => Rc::as_ptr(string) as *const () as usize,
+                Value::Closure(closure) => Rc::as_ptr(closure) as *const () as usize,
+                Value::Class(class) => Rc::as_ptr(class) as *const () as usize,
+                Value::Instance(instance) => Rc::as_ptr(instance) as *const () as usize,
+                Value::BoundMethod(method) => Rc::as_ptr(method) as *const () as usize,
+                Value::Nil | Value::Boolean(_) | Value::Number(_) | Value::Native(_) => continue,
+            };
+            if !visited.insert(id) {
+                continue;
+            }
+
+            match value {
+                Value::String(string) => bytes += string.len(),
+                Value::Closure(closure) => pending.extend(
+                    closure
+                        .upvalues
+                        .iter()
+                        .map(|variable| variable.borrow().to_owned()),
+                ),
+                Value::Class(class) => pending.extend(
+                    class
+                        .methods
+                        .borrow()
+                        .values()
+                        .map(|method| Value::Closure(Rc::clone(method))),
+                ),
+                Value::Instance(instance) => {
+                    bytes += size_of::<Instance>();
+                    pending.push(Value::Class(Rc::clone(&instance.class)));
+                    pending.extend(instance.fields.borrow().values().cloned());
+                }
+                Value::BoundMethod(method) => {
+                    pending.push(method.receiver.to_owned());
+                    pending.push(Value::Closure(Rc::clone(&method.method)));
+                }
+                Value::Nil | Value::Boolean(_) | Value::Number(_) | Value::Native(_) => {}
+            }
+        }
+
+        bytes
+    }
+
+    /// Writes to the output stream, unless the output would exceed
+    /// [`InterpreterOptions::max_output`].
+    pub(crate) fn write_output(&mut self, bytes: &[u8]) -> Result<(), RuntimeError> {
+        let written = self.output_written + bytes.len();
+        if let Some(limit) = self.options.max_output {
+            if written > limit {
+                return Err(RuntimeError::OutputLimitExceeded { limit });
+            }
+        }
+        self.output_written = written;
+
+        let mut stream = self.output_stream.borrow_mut();
+        stream
+            .write_all(bytes)
+            .map_err(|_| RuntimeError::OutputError)?;
+        stream.flush().map_err(|_| RuntimeError::OutputError)
+    }
+}
+
+/// Returns the method `name` of a class, bound to the receiver.
+pub(crate) fn bind_method(
+    class: &Rc<Class>,
+    receiver: Value,
+    name: &str,
+    line: usize,
+    column: usize,
+) -> Result<Value, RuntimeError> {
+    let method = class.methods.borrow().get(name).cloned();
+    match method {
+        Some(method) => Ok(Value::BoundMethod(Rc::new(BoundMethod {
+            receiver,
+            method,
+        }))),
+        None => Err(RuntimeError::UndefinedProperty {
+            line,
+            column,
+            suggestion: None,
+        }),
+    }
+}
+
+fn undefined_variable(name: &str, line: usize, column: usize) -> RuntimeError {
+    RuntimeError::UndefinedVariable {
+        line,
+        column,
+        name: name.to_owned(),
+        suggestion: None,
+    }
+}
+
+/// Returns the number of seconds since the Unix epoch.
+fn clock(_arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(Value::Number(elapsed.as_secs_f64()))
+}
@@ -0,0 +1,159 @@
+use crate::base::expr_result::format_number;
+use crate::closures::runtime::Statement;
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// A value of a script running on the [`Runtime`](crate::closures::runtime::Runtime). Objects
+/// are shared by reference and compared by identity, except for strings.
+#[derive(Clone, Debug, Default)]
+pub enum Value {
+    #[default]
+    Nil,
+    Boolean(bool),
+    Number(f64),
+    String(Rc<str>),
+    Closure(Rc<Closure>),
+    Native(Rc<Native>),
+    Class(Rc<Class>),
+    Instance(Rc<Instance>),
+    BoundMethod(Rc<BoundMethod>),
+}
+
+impl Value {
+    pub fn is_falsey(&self) -> bool {
+        matches!(self, Value::Nil | Value::Boolean(false))
+    }
+
+    /// Formats the value for error messages, with strings in quotes.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Value::String(value) => format!("{:?}", value),
+            value => value.to_string(),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            (Value::Native(a), Value::Native(b)) => Rc::ptr_eq(a, b),
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::BoundMethod(a), Value::BoundMethod(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", format_number(*value)),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Closure(closure) => write!(f, "{}", closure.function),
+            Value::Native(_) => write!(f, "<native fn>"),
+            Value::Class(class) => write!(f, "{}", class.name),
+            Value::Instance(instance) => write!(f, "{} instance", instance.class.name),
+            Value::BoundMethod(method) => write!(f, "{}", method.method.function),
+        }
+    }
+}
+
+/// A local variable. Each declaration creates a new one, so that closures created in a loop
+/// capture the variable of their iteration.
+pub type Variable = Rc<RefCell<Value>>;
+
+/// Where a closure finds a variable it captures when it is created.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Capture {
+    /// A local of the function creating the closure.
+    Local(usize),
+    /// A variable captured by the function creating the closure.
+    Upvalue(usize),
+}
+
+/// A compiled function. Its body reads parameters and locals from the slots of its frame.
+pub struct Function {
+    pub name: Rc<str>,
+    pub arity: usize,
+    /// Whether the instance the function is bound to is passed in slot 0.
+    pub is_method: bool,
+    /// Whether calls return the bound instance instead of the result of the body.
+    pub is_initializer: bool,
+    pub captures: Vec<Capture>,
+    pub(crate) body: Statement,
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("captures", &self.captures)
+            .finish()
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "<script>")
+        } else {
+            write!(f, "<fn {}>", self.name)
+        }
+    }
+}
+
+/// A compiled function together with the variables it captured.
+#[derive(Debug)]
+pub struct Closure {
+    pub function: Rc<Function>,
+    pub upvalues: Rc<[Variable]>,
+}
+
+pub type NativeFn = fn(&[Value]) -> Result<Value, RuntimeError>;
+
+/// A function implemented in Rust.
+pub struct Native {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: NativeFn,
+}
+
+impl std::fmt::Debug for Native {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Native")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: Rc<str>,
+    pub methods: RefCell<HashMap<Rc<str>, Rc<Closure>>>,
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: RefCell<HashMap<Rc<str>, Value>>,
+}
+
+/// A method together with the instance it was accessed on.
+#[derive(Debug)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: Rc<Closure>,
+}
@@ -0,0 +1,77 @@
+use crate::interpreter::options::InterpreterOptions;
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Counts the statements a script executed, measures how long it has been running and keeps
+/// track of the memory it holds, so that [`InterpreterOptions::max_steps`],
+/// [`InterpreterOptions::max_duration`] and [`InterpreterOptions::max_memory`] can be
+/// enforced. Forks of an interpreter share the budget.
+#[derive(Clone, Debug)]
+pub(crate) struct Budget {
+    steps: Rc<Cell<usize>>,
+    started: Rc<Cell<Instant>>,
+    allocated: Rc<Cell<usize>>,
+}
+
+impl Budget {
+    pub(crate) fn new() -> Self {
+        Self {
+            steps: Rc::new(Cell::new(0)),
+            started: Rc::new(Cell::new(Instant::now())),
+            allocated: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Starts a new budget, e.g. for the next script run in the REPL.
+    pub(crate) fn reset(&self) {
+        self.steps.set(0);
+        self.started.set(Instant::now());
+        self.allocated.set(0);
+    }
+
+    /// Accounts for executing a statement.
+    pub(crate) fn spend(&self, options: &InterpreterOptions) -> Result<(), RuntimeError> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+
+        if options.max_steps.is_some_and(|max_steps| steps > max_steps) {
+            return Err(RuntimeError::BudgetExceeded);
+        }
+        if let Some(max_duration) = options.max_duration {
+            if self.started.get().elapsed() > max_duration {
+                return Err(RuntimeError::BudgetExceeded);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Accounts for allocating `bytes` of memory.
+    ///
+    /// Allocations are summed up without noticing memory being freed. Once the sum exceeds the
+    /// limit, `live` is called to count the memory the script still holds, which the sum
+    /// starts over from, and the allocation only fails if that exceeds the limit as well.
+    pub(crate) fn allocate(
+        &self,
+        bytes: usize,
+        options: &InterpreterOptions,
+        live: impl FnOnce() -> usize,
+    ) -> Result<(), RuntimeError> {
+        let mut allocated = self.allocated.get() + bytes;
+        if let Some(limit) = options.max_memory {
+            if allocated > limit {
+                allocated = live() + bytes;
+            }
+            self.allocated.set(allocated);
+            if allocated > limit {
+                return Err(RuntimeError::MemoryLimitExceeded { limit });
+            }
+        } else {
+            self.allocated.set(allocated);
+        }
+
+        Ok(())
+    }
+}
@@ -1,96 +1,296 @@
 use crate::base::expr_result::ExprResult;
 use crate::base::scanner::Token;
+use crate::interpreter::heap;
 use crate::interpreter::runtime_error::RuntimeError;
+use crate::interpreter::suggestion;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
+/// Where a local variable is stored: the number of scopes between its use and its
+/// declaration, and its position among the values of the declaring scope.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
+/// A local variable captured by a closure. The scope that declared the variable and the closures
+/// share the cell, so that they see each other's assignments.
+pub type Upvalue = Rc<RefCell<ExprResult>>;
+
+/// The value of a variable in a scope.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Binding {
+    Value(ExprResult),
+    /// The variable was captured by a closure, the value lives in the shared cell.
+    Captured(Upvalue),
+}
+
+impl Binding {
+    pub fn get(&self) -> ExprResult {
+        match self {
+            Binding::Value(value) => value.to_owned(),
+            Binding::Captured(upvalue) => upvalue.borrow().to_owned(),
+        }
+    }
+
+    fn set(&mut self, value: ExprResult) {
+        match self {
+            Binding::Value(stored) => *stored = value,
+            Binding::Captured(upvalue) => *upvalue.borrow_mut() = value,
+        }
+    }
+}
+
+/// The values of a scope, in declaration order. Locals are accessed through the [`Slot`] the
+/// resolver assigned to them, globals by name. Only global scopes, i.e. the global scope of the
+/// interpreter and of modules, remember the names of their values.
+///
+/// Functions don't keep the scopes they were declared in alive. Their closure is a scope of its
+/// own that only holds the variables they capture, in the order of their slots.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Environment {
-    enclosing: Option<Rc<RefCell<Environment>>>,
-    values: HashMap<String, ExprResult>,
+    pub(crate) enclosing: Option<Rc<RefCell<Environment>>>,
+    pub(crate) values: Vec<Binding>,
+    /// Slots of the values by name, for global scopes.
+    pub(crate) slots: Option<HashMap<String, usize>>,
+    /// Names of the values that were declared with `const`.
+    pub(crate) constants: HashSet<String>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
             enclosing: None,
-            values: HashMap::new(),
+            values: Vec::new(),
+            slots: Some(HashMap::new()),
+            constants: HashSet::new(),
         }
     }
 
+    /// Creates a local scope.
     pub fn new_enclosing(enclosing: Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
-        Rc::new(RefCell::new(Self {
+        Self::track(Self {
+            enclosing: Some(enclosing),
+            values: Vec::new(),
+            slots: None,
+            constants: HashSet::new(),
+        })
+    }
+
+    /// Creates the scope of a closure, which holds the variables it captured.
+    pub fn new_closure(upvalues: Vec<Upvalue>) -> Rc<RefCell<Self>> {
+        Self::track(Self {
+            enclosing: None,
+            values: upvalues.into_iter().map(Binding::Captured).collect(),
+            slots: None,
+            constants: HashSet::new(),
+        })
+    }
+
+    /// Creates the global scope of a module, which falls back to `enclosing` for names it
+    /// doesn't define.
+    pub fn new_module(enclosing: Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
+        Self::track(Self {
             enclosing: Some(enclosing),
-            values: HashMap::new(),
-        }))
+            ..Self::new()
+        })
+    }
+
+    fn track(environment: Self) -> Rc<RefCell<Self>> {
+        let environment = Rc::new(RefCell::new(environment));
+        heap::track_environment(&environment);
+
+        environment
     }
 
+    /// Defines a value in the next free slot. Redefining a global reuses its slot.
     pub fn define(&mut self, name: &str, value: ExprResult) {
-        self.values.insert(name.to_owned(), value);
+        self.constants.remove(name);
+        self.insert(name, value);
     }
 
-    pub fn get(&self, name: &Token) -> Result<ExprResult, RuntimeError> {
-        if let Some(value) = self.values.get(&name.lexeme) {
-            return Ok(value.to_owned());
+    pub fn define_constant(&mut self, name: &str, value: ExprResult) {
+        if self.slots.is_some() {
+            self.constants.insert(name.to_owned());
         }
+        self.insert(name, value);
+    }
 
-        if let Some(enclosing) = &self.enclosing {
-            return enclosing.borrow().get(name);
+    /// Reserves the slot of a local that closures capture before its value exists, like a
+    /// recursive function. Global scopes look their values up by name and reserve nothing.
+    pub fn reserve(&mut self) -> Option<usize> {
+        if self.slots.is_some() {
+            return None;
         }
 
-        Err(RuntimeError::UndefinedVariable {
-            line: name.line,
-            name: name.lexeme.to_owned(),
-        })
+        self.values.push(Binding::Value(ExprResult::none()));
+        Some(self.values.len() - 1)
     }
 
-    pub fn get_at(&self, distance: usize, name: &str) -> Option<ExprResult> {
-        self.get_at_helper(distance, 0, name)
+    /// Defines a value in the slot returned by [`Environment::reserve`].
+    pub fn define_reserved(&mut self, slot: Option<usize>, name: &str, value: ExprResult) {
+        match slot {
+            Some(slot) => self.values[slot].set(value),
+            None => self.define(name, value),
+        }
     }
 
-    fn get_at_helper(&self, distance: usize, index: usize, name: &str) -> Option<ExprResult> {
-        if index < distance {
-            if let Some(enclosing) = &self.enclosing {
-                return enclosing.borrow().get_at_helper(distance, index + 1, name);
+    fn insert(&mut self, name: &str, value: ExprResult) {
+        let Some(slots) = self.slots.as_mut() else {
+            self.values.push(Binding::Value(value));
+            return;
+        };
+
+        match slots.get(name) {
+            Some(slot) => self.values[*slot].set(value),
+            None => {
+                slots.insert(name.to_owned(), self.values.len());
+                self.values.push(Binding::Value(value));
             }
-        } else if let Some(value) = self.values.get(name) {
-            return Some(value.to_owned());
         }
+    }
 
-        None
+    fn slot(&self, name: &str) -> Option<usize> {
+        self.slots.as_ref()?.get(name).copied()
     }
 
-    pub fn assign(&mut self, name: &Token, value: &ExprResult) -> Result<(), RuntimeError> {
-        if self.values.contains_key(&name.lexeme) {
-            self.values.insert(name.lexeme.to_owned(), value.to_owned());
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.slot(name).is_some()
+    }
+
+    /// Returns the names and values of a global scope in declaration order.
+    pub fn entries(&self) -> Vec<(String, ExprResult)> {
+        let mut entries: Vec<(&String, &usize)> = self.slots.iter().flatten().collect();
+        entries.sort_by_key(|(_, slot)| **slot);
 
-            return Ok(());
+        entries
+            .into_iter()
+            .map(|(name, slot)| (name.to_owned(), self.values[*slot].get()))
+            .collect()
+    }
+
+    /// Keeps only the values for which `keep` returns `true`. The remaining values are moved
+    /// to new slots, so this must not be used for scopes with resolved locals.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &ExprResult) -> bool) {
+        let entries = self.entries();
+        self.values.clear();
+        self.slots = Some(HashMap::new());
+        for (name, value) in entries {
+            if keep(&name, &value) {
+                self.insert(&name, value);
+            } else {
+                self.constants.remove(&name);
+            }
         }
+    }
 
-        if let Some(enclosing) = &self.enclosing {
-            return enclosing.borrow_mut().assign(name, value);
+    pub fn get(&self, name: &Token) -> Result<ExprResult, RuntimeError> {
+        self.lookup(&name.lexeme)
+            .ok_or_else(|| self.undefined_variable(name))
+    }
+
+    fn lookup(&self, name: &str) -> Option<ExprResult> {
+        if let Some(slot) = self.slot(name) {
+            return Some(self.values[slot].get());
         }
 
-        Err(RuntimeError::UndefinedVariable {
+        self.enclosing.as_ref()?.borrow().lookup(name)
+    }
+
+    /// Reports an undefined variable, suggesting the most similar name of the global scopes.
+    fn undefined_variable(&self, name: &Token) -> RuntimeError {
+        let mut names = vec![];
+        self.collect_names(&mut names);
+
+        RuntimeError::UndefinedVariable {
             line: name.line,
+            column: name.column,
             name: name.lexeme.to_owned(),
-        })
+            suggestion: suggestion::closest(&name.lexeme, names.iter().map(String::as_str)),
+        }
+    }
+
+    fn collect_names(&self, names: &mut Vec<String>) {
+        names.extend(self.slots.iter().flatten().map(|(name, _)| name.to_owned()));
+        if let Some(enclosing) = &self.enclosing {
+            enclosing.borrow().collect_names(names);
+        }
     }
 
-    pub fn assign_at(&mut self, distance: usize, name: &str, value: &ExprResult) {
-        self.assign_at_helper(distance, 0, name, value)
+    pub fn get_at(&self, slot: Slot) -> Option<ExprResult> {
+        if slot.depth > 0 {
+            let enclosing = self.enclosing.as_ref()?;
+            return enclosing.borrow().get_at(Slot {
+                depth: slot.depth - 1,
+                ..slot
+            });
+        }
+
+        self.values.get(slot.index).map(Binding::get)
+    }
+
+    /// Returns the local at `slot` as an upvalue. The value is moved into a cell the first time
+    /// it is captured.
+    pub fn capture(&mut self, slot: Slot) -> Option<Upvalue> {
+        if slot.depth > 0 {
+            let enclosing = self.enclosing.as_ref()?;
+            return enclosing.borrow_mut().capture(Slot {
+                depth: slot.depth - 1,
+                ..slot
+            });
+        }
+
+        let binding = self.values.get_mut(slot.index)?;
+        if let Binding::Value(value) = binding {
+            *binding = Binding::Captured(Rc::new(RefCell::new(std::mem::take(value))));
+        }
+
+        match binding {
+            Binding::Captured(upvalue) => Some(Rc::clone(upvalue)),
+            Binding::Value(_) => None,
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: &ExprResult) -> Result<(), RuntimeError> {
+        if self.assign_named(&name.lexeme, value) {
+            Ok(())
+        } else {
+            Err(self.undefined_variable(name))
+        }
+    }
+
+    fn assign_named(&mut self, name: &str, value: &ExprResult) -> bool {
+        if let Some(slot) = self.slot(name) {
+            self.values[slot].set(value.to_owned());
+
+            return true;
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign_named(name, value),
+            None => false,
+        }
     }
 
-    fn assign_at_helper(&mut self, distance: usize, index: usize, name: &str, value: &ExprResult) {
-        if index < distance {
+    pub fn assign_at(&mut self, slot: Slot, value: &ExprResult) {
+        if slot.depth > 0 {
             if let Some(enclosing) = &self.enclosing {
-                return enclosing
-                    .borrow_mut()
-                    .assign_at_helper(distance, index + 1, name, value);
+                enclosing.borrow_mut().assign_at(
+                    Slot {
+                        depth: slot.depth - 1,
+                        ..slot
+                    },
+                    value,
+                );
             }
-        } else if self.values.contains_key(name) {
-            self.values.insert(name.to_owned(), value.to_owned());
+        } else if let Some(stored) = self.values.get_mut(slot.index) {
+            stored.set(value.to_owned());
         }
     }
 }
@@ -0,0 +1,282 @@
+use crate::base::expr_result::{ExprResult, LoxFunction, LoxPromise, PromiseState};
+use crate::base::scanner::Token;
+use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// Interval in which the event loop checks whether a background read has finished.
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+struct Job {
+    function: LoxFunction,
+    arguments: Vec<ExprResult>,
+    promise: LoxPromise,
+    line: usize,
+    column: usize,
+}
+
+struct Timer {
+    deadline: Instant,
+    promise: LoxPromise,
+}
+
+struct Read {
+    receiver: Receiver<Result<String, String>>,
+    promise: LoxPromise,
+    line: usize,
+    column: usize,
+}
+
+/// Single-threaded scheduler for async function calls, timers and background reads.
+///
+/// Jobs run to completion. Awaiting a pending promise runs other jobs, timers and finished reads
+/// from within the awaiting job until the promise is settled.
+#[derive(Default)]
+pub struct EventLoop {
+    jobs: VecDeque<Job>,
+    timers: Vec<Timer>,
+    reads: Vec<Read>,
+    rejections: Vec<(LoxPromise, usize, usize)>,
+}
+
+impl EventLoop {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a call of an async function and returns the promise for its result.
+    pub fn schedule(
+        &mut self,
+        function: LoxFunction,
+        arguments: Vec<ExprResult>,
+        paren: &Token,
+    ) -> LoxPromise {
+        let promise = LoxPromise::new();
+        self.jobs.push_back(Job {
+            function,
+            arguments,
+            promise: promise.to_owned(),
+            line: paren.line,
+            column: paren.column,
+        });
+
+        promise
+    }
+
+    /// Returns a promise that is resolved with `nil` once `deadline` has passed.
+    pub fn timer(&mut self, deadline: Instant) -> LoxPromise {
+        let promise = LoxPromise::new();
+        self.timers.push(Timer {
+            deadline,
+            promise: promise.to_owned(),
+        });
+
+        promise
+    }
+
+    /// Returns a promise that is resolved with the result `receiver` gets from a background
+    /// thread, or rejected with the message of its error.
+    pub fn read(
+        &mut self,
+        receiver: Receiver<Result<String, String>>,
+        paren: &Token,
+    ) -> LoxPromise {
+        let promise = LoxPromise::new();
+        self.reads.push(Read {
+            receiver,
+            promise: promise.to_owned(),
+            line: paren.line,
+            column: paren.column,
+        });
+
+        promise
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Removes the timer with the earliest deadline, if that has passed.
+    fn expired_timer(&mut self) -> Option<Timer> {
+        let now = Instant::now();
+        let index = self
+            .timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.deadline <= now)
+            .min_by_key(|(_, timer)| timer.deadline)
+            .map(|(index, _)| index)?;
+
+        Some(self.timers.remove(index))
+    }
+
+    /// Removes a read whose thread has finished and returns it with its result.
+    fn finished_read(&mut self) -> Option<(Read, Result<String, String>)> {
+        for index in 0..self.reads.len() {
+            let result = match self.reads[index].receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Disconnected) => Err("Read was aborted".to_owned()),
+                Err(TryRecvError::Empty) => continue,
+            };
+
+            return Some((self.reads.remove(index), result));
+        }
+
+        None
+    }
+}
+
+/// Returns the message of the rejection of a job that failed with `error`. A job that failed
+/// because it awaited a rejected promise passes on the message of that rejection, so that it
+/// isn't wrapped again for each await.
+fn rejection_message(error: RuntimeError) -> String {
+    match error {
+        RuntimeError::PromiseRejected { message, .. } => message,
+        RuntimeError::InFile { error, .. }
+            if matches!(*error, RuntimeError::PromiseRejected { .. }) =>
+        {
+            rejection_message(*error)
+        }
+        error => error.to_string(),
+    }
+}
+
+/// Runs the next queued job, or settles the next finished read or expired timer if no job is
+/// queued, waiting for one if necessary. Returns `false` if there was nothing left to do.
+fn run_once(
+    event_loop: &RefCell<EventLoop>,
+    interpreter: &Interpreter,
+) -> Result<bool, RuntimeError> {
+    let job = event_loop.borrow_mut().jobs.pop_front();
+    if let Some(job) = job {
+        match job.function.invoke(interpreter, &job.arguments) {
+            Ok(value) => job.promise.settle(PromiseState::Resolved(value)),
+            Err(
+                error @ (RuntimeError::Exit { .. }
+                | RuntimeError::Interrupted
+                | RuntimeError::BudgetExceeded
+                | RuntimeError::MemoryLimitExceeded { .. }
+                | RuntimeError::OutputLimitExceeded { .. }),
+            ) => return Err(error),
+            Err(error) => {
+                job.promise
+                    .settle(PromiseState::Rejected(rejection_message(error)));
+                event_loop
+                    .borrow_mut()
+                    .rejections
+                    .push((job.promise, job.line, job.column));
+            }
+        }
+
+        return Ok(true);
+    }
+
+    let read = event_loop.borrow_mut().finished_read();
+    if let Some((read, result)) = read {
+        match result {
+            Ok(contents) => read
+                .promise
+                .settle(PromiseState::Resolved(ExprResult::string(contents))),
+            Err(message) => {
+                read.promise.settle(PromiseState::Rejected(message));
+                event_loop
+                    .borrow_mut()
+                    .rejections
+                    .push((read.promise, read.line, read.column));
+            }
+        }
+
+        return Ok(true);
+    }
+
+    let timer = event_loop.borrow_mut().expired_timer();
+    if let Some(timer) = timer {
+        timer
+            .promise
+            .settle(PromiseState::Resolved(ExprResult::none()));
+
+        return Ok(true);
+    }
+
+    let (deadline, reading) = {
+        let event_loop = event_loop.borrow();
+        (event_loop.next_deadline(), !event_loop.reads.is_empty())
+    };
+    let remaining = match (deadline, reading) {
+        (None, false) => return Ok(false),
+        (None, true) => READ_POLL_INTERVAL,
+        (Some(deadline), reading) => {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if reading {
+                remaining.min(READ_POLL_INTERVAL)
+            } else {
+                remaining
+            }
+        }
+    };
+
+    if !interpreter.interrupt.sleep(remaining) {
+        return Err(RuntimeError::Interrupted);
+    }
+
+    Ok(true)
+}
+
+/// Runs the event loop until `promise` is settled and returns its value.
+pub fn wait_for(
+    event_loop: &RefCell<EventLoop>,
+    interpreter: &Interpreter,
+    promise: &LoxPromise,
+    keyword: &Token,
+) -> Result<ExprResult, RuntimeError> {
+    loop {
+        match promise.state() {
+            PromiseState::Pending => {
+                if !run_once(event_loop, interpreter)? {
+                    return Err(RuntimeError::PromiseNeverSettled {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
+                }
+            }
+            PromiseState::Resolved(value) => return Ok(value),
+            PromiseState::Rejected(message) => {
+                event_loop
+                    .borrow_mut()
+                    .rejections
+                    .retain(|(rejected, ..)| rejected != promise);
+
+                return Err(RuntimeError::PromiseRejected {
+                    line: keyword.line,
+                    column: keyword.column,
+                    message,
+                });
+            }
+        }
+    }
+}
+
+/// Runs the event loop until no jobs and timers are left. Fails with the first rejection that
+/// was never awaited.
+pub fn run_to_completion(
+    event_loop: &RefCell<EventLoop>,
+    interpreter: &Interpreter,
+) -> Result<(), RuntimeError> {
+    while run_once(event_loop, interpreter)? {}
+
+    let rejection = event_loop.borrow_mut().rejections.drain(..).next();
+    if let Some((promise, line, column)) = rejection {
+        if let PromiseState::Rejected(message) = promise.state() {
+            return Err(RuntimeError::PromiseRejected {
+                line,
+                column,
+                message,
+            });
+        }
+    }
+
+    Ok(())
+}
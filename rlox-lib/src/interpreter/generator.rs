@@ -0,0 +1,540 @@
+use crate::base::ast::{ExprRef, StmtRef};
+use crate::base::expr::Expr;
+use crate::base::expr_result::{ExprResult, LoxFunction};
+use crate::base::scanner::Token;
+use crate::base::stmt::Stmt;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{is_loop_target, Interpreter};
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The result of calling a generator function. The body runs up to the next `yield` whenever
+/// the generator is resumed, either with `next()` or by a `for-in` loop.
+///
+/// A generator is suspended by remembering where each statement on the path to the `yield`
+/// left off, innermost first. Statements that don't contain a `yield` are executed by the
+/// interpreter as usual.
+#[derive(Clone, Debug)]
+pub struct LoxGenerator {
+    function: LoxFunction,
+    state: Rc<RefCell<GeneratorState>>,
+}
+
+#[derive(Debug)]
+enum GeneratorState {
+    Suspended(Vec<Frame>),
+    Running,
+    Done,
+}
+
+/// The position in a statement at which a suspended generator continues.
+#[derive(Debug)]
+enum Frame {
+    Block {
+        index: usize,
+        environment: Rc<RefCell<Environment>>,
+    },
+    If {
+        then_branch: bool,
+    },
+    While,
+    ForIn {
+        items: Items,
+        environment: Rc<RefCell<Environment>>,
+    },
+    Yield,
+}
+
+impl LoxGenerator {
+    /// Creates a generator that executes the body of `function` in `environment`, which holds
+    /// the arguments of the call.
+    pub fn new(function: LoxFunction, environment: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            function,
+            state: Rc::new(RefCell::new(GeneratorState::Suspended(vec![
+                Frame::Block {
+                    index: 0,
+                    environment,
+                },
+            ]))),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.function.name.lexeme
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(*self.state.borrow(), GeneratorState::Done)
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(*self.state.borrow(), GeneratorState::Running)
+    }
+
+    /// Runs the body up to the next `yield`. The `yield` the generator was suspended at
+    /// evaluates to `sent`.
+    pub(crate) fn resume(
+        &self,
+        interpreter: &Interpreter,
+        token: &Token,
+        sent: ExprResult,
+    ) -> Result<Resumed, RuntimeError> {
+        let mut frames = match self.state.replace(GeneratorState::Running) {
+            GeneratorState::Suspended(frames) => frames,
+            GeneratorState::Running => {
+                return Err(RuntimeError::GeneratorRunning {
+                    line: token.line,
+                    column: token.column,
+                })
+            }
+            GeneratorState::Done => {
+                self.state.replace(GeneratorState::Done);
+                return Ok(Resumed::Returned(ExprResult::none()));
+            }
+        };
+
+        let Some(Frame::Block { index, environment }) = frames.pop() else {
+            unreachable!("generators are suspended in the function body")
+        };
+        let interpreter = interpreter.fork_in(&self.function.body.ast, Rc::clone(&environment));
+        let mut executor = Executor {
+            interpreter: &interpreter,
+            resume: frames,
+            suspend: vec![],
+            sent,
+        };
+        let result = executor.block(&self.function.body.statements, index, environment);
+
+        match result {
+            Ok(Some(value)) => {
+                self.state
+                    .replace(GeneratorState::Suspended(executor.suspend));
+                Ok(Resumed::Yielded(value))
+            }
+            Ok(None) => {
+                self.state.replace(GeneratorState::Done);
+                Ok(Resumed::Returned(ExprResult::none()))
+            }
+            Err(RuntimeError::Return { ret_val }) => {
+                self.state.replace(GeneratorState::Done);
+                Ok(Resumed::Returned(*ret_val))
+            }
+            Err(error) => {
+                self.state.replace(GeneratorState::Done);
+                match &self.function.name.file {
+                    Some(file) => Err(error.in_file(file)),
+                    None => Err(error),
+                }
+            }
+        }
+    }
+}
+
+/// The outcome of resuming a generator.
+pub(crate) enum Resumed {
+    Yielded(ExprResult),
+    /// The body has finished, with the value of its `return` statement or `nil`.
+    Returned(ExprResult),
+}
+
+impl PartialEq for LoxGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+/// A Lua-style coroutine created by `coroutine(function)`. The first `resume()` calls the
+/// function with the given arguments, later calls continue it after the `yield` it is
+/// suspended at, which evaluates to the argument of `resume()`.
+///
+/// Coroutines are generators underneath, so a `yield` has to appear in the body of the
+/// coroutine function itself and not in a function called by it.
+#[derive(Clone, Debug)]
+pub struct LoxCoroutine {
+    state: Rc<RefCell<CoroutineState>>,
+}
+
+#[derive(Debug)]
+enum CoroutineState {
+    Created(ExprResult),
+    Suspended(LoxGenerator),
+    Dead,
+}
+
+impl LoxCoroutine {
+    pub fn new(function: ExprResult) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(CoroutineState::Created(function))),
+        }
+    }
+
+    /// Returns `"suspended"`, `"running"` or `"dead"`.
+    pub fn status(&self) -> &'static str {
+        match &*self.state.borrow() {
+            CoroutineState::Created(_) => "suspended",
+            CoroutineState::Suspended(generator) if generator.is_running() => "running",
+            CoroutineState::Suspended(_) => "suspended",
+            CoroutineState::Dead => "dead",
+        }
+    }
+
+    /// Runs the coroutine up to the next `yield` and returns the yielded value, or the
+    /// returned value once the function has finished.
+    pub(crate) fn resume(
+        &self,
+        interpreter: &Interpreter,
+        paren: &Token,
+        arguments: &[ExprResult],
+    ) -> Result<ExprResult, RuntimeError> {
+        let state = std::mem::replace(&mut *self.state.borrow_mut(), CoroutineState::Dead);
+        let generator = match state {
+            CoroutineState::Created(function) => {
+                let callable = function
+                    .as_callable()
+                    .ok_or(RuntimeError::InvalidArgument {
+                        line: paren.line,
+                        column: paren.column,
+                    })?;
+                if !callable.accepts(arguments.len()) {
+                    return Err(RuntimeError::NonMatchingNumberOfArguments {
+                        line: paren.line,
+                        column: paren.column,
+                    });
+                }
+                match callable.call(interpreter, paren, arguments)? {
+                    ExprResult::Generator(generator) => generator,
+                    value => return Ok(value),
+                }
+            }
+            CoroutineState::Suspended(generator) => generator,
+            CoroutineState::Dead => {
+                return Err(RuntimeError::DeadCoroutine {
+                    line: paren.line,
+                    column: paren.column,
+                })
+            }
+        };
+
+        self.state
+            .replace(CoroutineState::Suspended(generator.to_owned()));
+        let sent = arguments.first().cloned().unwrap_or_default();
+        match generator.resume(interpreter, paren, sent) {
+            Ok(Resumed::Yielded(value)) => Ok(value),
+            Ok(Resumed::Returned(value)) => {
+                self.state.replace(CoroutineState::Dead);
+                Ok(value)
+            }
+            Err(error) => {
+                self.state.replace(CoroutineState::Dead);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl PartialEq for LoxCoroutine {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+/// The values a `for-in` loop iterates over.
+#[derive(Debug)]
+pub(crate) enum Items {
+    Values(std::vec::IntoIter<ExprResult>),
+    Generator(LoxGenerator),
+}
+
+impl Items {
+    pub(crate) fn new(iterable: ExprResult, keyword: &Token) -> Result<Self, RuntimeError> {
+        let values = match iterable {
+            ExprResult::List(list) => list.to_vec(),
+            ExprResult::Map(map) => map.keys(),
+            ExprResult::String(value) => value
+                .chars()
+                .map(|c| ExprResult::string(c.to_string()))
+                .collect(),
+            ExprResult::Generator(generator) => return Ok(Items::Generator(generator)),
+            _ => {
+                return Err(RuntimeError::NotIterable {
+                    line: keyword.line,
+                    column: keyword.column,
+                })
+            }
+        };
+
+        Ok(Items::Values(values.into_iter()))
+    }
+
+    pub(crate) fn next(
+        &mut self,
+        interpreter: &Interpreter,
+        keyword: &Token,
+    ) -> Result<Option<ExprResult>, RuntimeError> {
+        match self {
+            Items::Values(values) => Ok(values.next()),
+            Items::Generator(generator) => {
+                match generator.resume(interpreter, keyword, ExprResult::none())? {
+                    Resumed::Yielded(value) => Ok(Some(value)),
+                    Resumed::Returned(_) => Ok(None),
+                }
+            }
+        }
+    }
+}
+
+/// How a loop continues after its body.
+enum Flow {
+    Next,
+    Break,
+    Yield(ExprResult),
+}
+
+/// Whether a `yield` suspended the generator or was resumed.
+enum Step {
+    Suspended(ExprResult),
+    Resumed(ExprResult),
+}
+
+/// Executes a generator body until it yields.
+struct Executor<'i, 'a> {
+    interpreter: &'i Interpreter<'a>,
+    /// Positions to continue at, outermost last.
+    resume: Vec<Frame>,
+    /// Positions at which the body was suspended, innermost first.
+    suspend: Vec<Frame>,
+    /// The value the generator was resumed with.
+    sent: ExprResult,
+}
+
+impl Executor<'_, '_> {
+    /// Executes a statement and returns the value if it yielded.
+    fn execute(
+        &mut self,
+        statement: StmtRef,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<Option<ExprResult>, RuntimeError> {
+        let ast = &self.interpreter.ast;
+        if self.resume.is_empty() && !ast.contains_yield(statement) {
+            self.interpreter
+                .fork(Rc::clone(environment))
+                .execute_block(&[statement])?;
+            return Ok(None);
+        }
+
+        match &ast[statement] {
+            Stmt::Yield { value, .. } => match self.suspend_at(*value, environment)? {
+                Step::Suspended(value) => Ok(Some(value)),
+                Step::Resumed(_) => Ok(None),
+            },
+            Stmt::Var {
+                name, initializer, ..
+            } => {
+                let Some(Expr::Yield { value, .. }) = initializer.map(|i| &ast[i]) else {
+                    unreachable!("only a yield initializer is resumed")
+                };
+                match self.suspend_at(*value, environment)? {
+                    Step::Suspended(value) => Ok(Some(value)),
+                    Step::Resumed(sent) => {
+                        environment.borrow_mut().define(&name.lexeme, sent);
+                        Ok(None)
+                    }
+                }
+            }
+            Stmt::Expression { expression } => {
+                let Expr::Assign { id, name, value } = &ast[*expression] else {
+                    unreachable!("only an assignment of a yield is resumed")
+                };
+                let Expr::Yield { value, .. } = &ast[*value] else {
+                    unreachable!("only an assignment of a yield is resumed")
+                };
+                match self.suspend_at(*value, environment)? {
+                    Step::Suspended(value) => Ok(Some(value)),
+                    Step::Resumed(sent) => {
+                        self.interpreter
+                            .fork(Rc::clone(environment))
+                            .assign_variable(id, name, &sent)?;
+                        Ok(None)
+                    }
+                }
+            }
+            Stmt::Block { statements } => match self.resume.pop() {
+                Some(Frame::Block { index, environment }) => {
+                    self.block(statements, index, environment)
+                }
+                _ => self.block(
+                    statements,
+                    0,
+                    Environment::new_enclosing(Rc::clone(environment)),
+                ),
+            },
+            Stmt::If {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let take_then_branch = match self.resume.pop() {
+                    Some(Frame::If { then_branch }) => then_branch,
+                    _ => self.condition(*condition, keyword, environment)?,
+                };
+                let branch = if take_then_branch {
+                    Some(*then_branch)
+                } else {
+                    *else_branch
+                };
+
+                if let Some(value) = branch.map_or(Ok(None), |b| self.execute(b, environment))? {
+                    self.suspend.push(Frame::If {
+                        then_branch: take_then_branch,
+                    });
+                    return Ok(Some(value));
+                }
+                Ok(None)
+            }
+            Stmt::While {
+                keyword,
+                label,
+                condition,
+                body,
+                increment,
+            } => {
+                let mut resuming = self.resume.pop().is_some();
+                while resuming || self.condition(*condition, keyword, environment)? {
+                    resuming = false;
+                    match self.loop_body(*body, label, environment)? {
+                        Flow::Yield(value) => {
+                            self.suspend.push(Frame::While);
+                            return Ok(Some(value));
+                        }
+                        Flow::Break => break,
+                        Flow::Next => {}
+                    }
+                    if let Some(increment) = increment {
+                        self.interpreter
+                            .fork(Rc::clone(environment))
+                            .evaluate(*increment)?;
+                    }
+                }
+                Ok(None)
+            }
+            Stmt::ForIn {
+                keyword,
+                label,
+                variable,
+                iterable,
+                body,
+            } => {
+                let (mut items, mut resumed) = match self.resume.pop() {
+                    Some(Frame::ForIn { items, environment }) => (items, Some(environment)),
+                    _ => {
+                        let iterable = self
+                            .interpreter
+                            .fork(Rc::clone(environment))
+                            .evaluate(*iterable)?;
+                        (Items::new(iterable, keyword)?, None)
+                    }
+                };
+
+                loop {
+                    let scope = match resumed.take() {
+                        Some(scope) => scope,
+                        None => match items.next(self.interpreter, keyword)? {
+                            Some(item) => {
+                                let scope = Environment::new_enclosing(Rc::clone(environment));
+                                scope.borrow_mut().define(&variable.lexeme, item);
+                                scope
+                            }
+                            None => break,
+                        },
+                    };
+
+                    match self.loop_body(*body, label, &scope)? {
+                        Flow::Yield(value) => {
+                            self.suspend.push(Frame::ForIn {
+                                items,
+                                environment: scope,
+                            });
+                            return Ok(Some(value));
+                        }
+                        Flow::Break => break,
+                        Flow::Next => {}
+                    }
+                }
+                Ok(None)
+            }
+            _ => unreachable!("only blocks, branches and loops can contain 'yield'"),
+        }
+    }
+
+    /// Suspends the generator with the value of a `yield`, or returns the value the generator
+    /// was resumed with if it was suspended at this `yield`.
+    fn suspend_at(
+        &mut self,
+        value: Option<ExprRef>,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<Step, RuntimeError> {
+        if self.resume.pop().is_some() {
+            return Ok(Step::Resumed(std::mem::take(&mut self.sent)));
+        }
+
+        let value = match value {
+            Some(value) => self
+                .interpreter
+                .fork(Rc::clone(environment))
+                .evaluate(value)?,
+            None => ExprResult::none(),
+        };
+        self.suspend.push(Frame::Yield);
+        Ok(Step::Suspended(value))
+    }
+
+    /// Executes the statements of a block, starting at `index`.
+    fn block(
+        &mut self,
+        statements: &[StmtRef],
+        index: usize,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Option<ExprResult>, RuntimeError> {
+        for (index, statement) in statements.iter().enumerate().skip(index) {
+            if let Some(value) = self.execute(*statement, &environment)? {
+                self.suspend.push(Frame::Block { index, environment });
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn loop_body(
+        &mut self,
+        body: StmtRef,
+        label: &Option<Token>,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<Flow, RuntimeError> {
+        match self.execute(body, environment) {
+            Ok(Some(value)) => Ok(Flow::Yield(value)),
+            Ok(None) => Ok(Flow::Next),
+            Err(RuntimeError::Break { label: target }) if is_loop_target(label, &target) => {
+                Ok(Flow::Break)
+            }
+            Err(RuntimeError::Continue { label: target }) if is_loop_target(label, &target) => {
+                Ok(Flow::Next)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn condition(
+        &self,
+        condition: ExprRef,
+        keyword: &Token,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<bool, RuntimeError> {
+        let interpreter = self.interpreter.fork(Rc::clone(environment));
+        let value = interpreter.evaluate(condition)?;
+        interpreter.is_true(&value, keyword)
+    }
+}
@@ -0,0 +1,418 @@
+use crate::base::expr_result::{ExprResult, LoxClass, LoxFunction, LoxInstance};
+use crate::interpreter::environment::{Binding, Environment, Upvalue};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+
+/// Number of tracked objects after which the first collection runs.
+const INITIAL_THRESHOLD: usize = 1024;
+
+type Fields = RefCell<HashMap<String, ExprResult>>;
+type Elements = RefCell<Vec<ExprResult>>;
+type Entries = RefCell<Vec<(ExprResult, ExprResult)>>;
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap::new());
+}
+
+/// Registry of the environments and instances created on the current thread.
+///
+/// Values are still reference counted, so everything that is not part of a reference cycle is
+/// freed as soon as it becomes unreachable. The collector only has to find cycles, such as an
+/// instance storing a closure that captures the instance, and break them.
+struct Heap {
+    environments: Vec<Weak<RefCell<Environment>>>,
+    instances: Vec<Weak<Fields>>,
+    threshold: usize,
+}
+
+impl Heap {
+    fn new() -> Self {
+        Self {
+            environments: vec![],
+            instances: vec![],
+            threshold: INITIAL_THRESHOLD,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.environments.len() + self.instances.len()
+    }
+}
+
+/// Registers an environment with the collector.
+pub(crate) fn track_environment(environment: &Rc<RefCell<Environment>>) {
+    HEAP.with(|heap| {
+        heap.borrow_mut()
+            .environments
+            .push(Rc::downgrade(environment))
+    });
+}
+
+/// Registers the fields of an instance with the collector.
+pub(crate) fn track_instance(fields: &Rc<Fields>) {
+    HEAP.with(|heap| heap.borrow_mut().instances.push(Rc::downgrade(fields)));
+}
+
+/// Returns the number of tracked environments and instances that are still alive.
+pub fn live_objects() -> usize {
+    HEAP.with(|heap| {
+        let heap = heap.borrow();
+        let environments = heap.environments.iter().filter(|e| e.strong_count() > 0);
+        let instances = heap.instances.iter().filter(|i| i.strong_count() > 0);
+
+        environments.count() + instances.count()
+    })
+}
+
+/// Returns the memory held by the live environments and instances of the current thread and
+/// by the lists, maps and strings reachable from them, estimated the same way as when it is
+/// allocated.
+pub(crate) fn live_bytes() -> usize {
+    let mut pending: Vec<Object> = HEAP.with(|heap| {
+        let heap = heap.borrow();
+        let environments = heap
+            .environments
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(Object::Environment);
+        let instances = heap
+            .instances
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(Object::Instance);
+
+        environments.chain(instances).collect()
+    });
+
+    let mut visited = HashSet::new();
+    let mut strings = HashSet::new();
+    let mut bytes = 0;
+    while let Some(object) = pending.pop() {
+        if !visited.insert(object.id()) {
+            continue;
+        }
+
+        bytes += object.size(&mut strings);
+        pending.extend(object.children().into_iter().flatten());
+    }
+
+    bytes
+}
+
+/// Collects garbage if enough objects were created since the last collection, or always if
+/// `stress` is set.
+pub(crate) fn collect_if_due(stress: bool) {
+    if stress || HEAP.with(|heap| heap.borrow().len() >= heap.borrow().threshold) {
+        collect();
+    }
+}
+
+/// Frees the objects of the current thread that are only kept alive by reference cycles and
+/// returns how many objects were freed.
+///
+/// An object is a root if it has more strong references than the collector found in other
+/// objects, because the remaining references are held by the interpreter itself. Everything
+/// that is not reachable from a root is garbage, and clearing it breaks its cycles. Values the
+/// collector cannot look into, like natives and promises, only ever add roots, so it errs on
+/// the side of keeping objects alive.
+pub fn collect() -> usize {
+    let mut objects: HashMap<usize, Object> = HashMap::new();
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.environments.retain(|environment| {
+            if let Some(environment) = environment.upgrade() {
+                let object = Object::Environment(environment);
+                objects.insert(object.id(), object);
+                true
+            } else {
+                false
+            }
+        });
+        heap.instances.retain(|fields| {
+            if let Some(fields) = fields.upgrade() {
+                let object = Object::Instance(fields);
+                objects.insert(object.id(), object);
+                true
+            } else {
+                false
+            }
+        });
+    });
+
+    let mut edges: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut opaque = HashSet::new();
+    let mut pending: Vec<usize> = objects.keys().copied().collect();
+    while let Some(id) = pending.pop() {
+        let Some(children) = objects[&id].children() else {
+            opaque.insert(id);
+            continue;
+        };
+
+        let mut ids = vec![];
+        for child in children {
+            let child_id = child.id();
+            if let Entry::Vacant(entry) = objects.entry(child_id) {
+                entry.insert(child);
+                pending.push(child_id);
+            }
+            ids.push(child_id);
+        }
+        edges.insert(id, ids);
+    }
+
+    let mut references: HashMap<usize, usize> = HashMap::new();
+    for child in edges.values().flatten() {
+        *references.entry(*child).or_default() += 1;
+    }
+
+    // Every object is referenced once by `objects`, which does not count.
+    let mut reachable = HashSet::new();
+    let mut roots: Vec<usize> = objects
+        .iter()
+        .filter(|(id, object)| {
+            opaque.contains(*id)
+                || object.strong_count() - 1 > references.get(*id).copied().unwrap_or_default()
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    while let Some(id) = roots.pop() {
+        if reachable.insert(id) {
+            roots.extend(edges.get(&id).into_iter().flatten());
+        }
+    }
+
+    let garbage: Vec<Object> = objects
+        .into_iter()
+        .filter(|(id, _)| !reachable.contains(id))
+        .map(|(_, object)| object)
+        .collect();
+    let freed = garbage.len();
+    for object in &garbage {
+        object.clear();
+    }
+    drop(garbage);
+
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.environments.retain(|e| e.strong_count() > 0);
+        heap.instances.retain(|i| i.strong_count() > 0);
+        heap.threshold = INITIAL_THRESHOLD.max(heap.len() * 2);
+    });
+
+    freed
+}
+
+/// An object with identity that may be part of a reference cycle.
+enum Object {
+    Environment(Rc<RefCell<Environment>>),
+    /// A variable captured by closures, which is shared by several environments.
+    Upvalue(Upvalue),
+    Instance(Rc<Fields>),
+    List(Rc<Elements>),
+    Map(Rc<Entries>),
+    Function(Rc<LoxFunction>),
+    Class(Rc<LoxClass>),
+}
+
+impl Object {
+    fn id(&self) -> usize {
+        match self {
+            Object::Environment(environment) => Rc::as_ptr(environment) as *const () as usize,
+            Object::Upvalue(upvalue) => Rc::as_ptr(upvalue) as *const () as usize,
+            Object::Instance(fields) => Rc::as_ptr(fields) as *const () as usize,
+            Object::List(elements) => Rc::as_ptr(elements) as *const () as usize,
+            Object::Map(entries) => Rc::as_ptr(entries) as *const () as usize,
+            Object::Function(function) => Rc::as_ptr(function) as *const () as usize,
+            Object::Class(class) => Rc::as_ptr(class) as *const () as usize,
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Object::Environment(environment) => Rc::strong_count(environment),
+            Object::Upvalue(upvalue) => Rc::strong_count(upvalue),
+            Object::Instance(fields) => Rc::strong_count(fields),
+            Object::List(elements) => Rc::strong_count(elements),
+            Object::Map(entries) => Rc::strong_count(entries),
+            Object::Function(function) => Rc::strong_count(function),
+            Object::Class(class) => Rc::strong_count(class),
+        }
+    }
+
+    /// Returns the objects this object holds a reference to, once per reference, or `None` if
+    /// the object is currently being modified.
+    fn children(&self) -> Option<Vec<Object>> {
+        let mut children = vec![];
+        match self {
+            Object::Environment(environment) => {
+                let environment = environment.try_borrow().ok()?;
+                if let Some(enclosing) = &environment.enclosing {
+                    children.push(Object::Environment(Rc::clone(enclosing)));
+                }
+                for binding in environment.values.iter() {
+                    match binding {
+                        Binding::Value(value) => value_children(value, &mut children),
+                        Binding::Captured(upvalue) => {
+                            children.push(Object::Upvalue(Rc::clone(upvalue)))
+                        }
+                    }
+                }
+            }
+            Object::Upvalue(upvalue) => {
+                let value = upvalue.try_borrow().ok()?;
+                value_children(&value, &mut children);
+            }
+            Object::Instance(fields) => {
+                for value in fields.try_borrow().ok()?.values() {
+                    value_children(value, &mut children);
+                }
+            }
+            Object::List(elements) => {
+                for value in elements.try_borrow().ok()?.iter() {
+                    value_children(value, &mut children);
+                }
+            }
+            Object::Map(entries) => {
+                for (key, value) in entries.try_borrow().ok()?.iter() {
+                    value_children(key, &mut children);
+                    value_children(value, &mut children);
+                }
+            }
+            Object::Function(function) => {
+                children.push(Object::Environment(Rc::clone(&function.closure)))
+            }
+            Object::Class(class) => class_children(class, &mut children),
+        }
+
+        Some(children)
+    }
+
+    /// Returns the memory of the object itself and of the strings it holds that are not in
+    /// `strings` yet, which are added to it.
+    fn size(&self, strings: &mut HashSet<usize>) -> usize {
+        let mut bytes = match self {
+            Object::Environment(_) => size_of::<Environment>(),
+            Object::Instance(_) => size_of::<LoxInstance>(),
+            Object::List(elements) => elements
+                .try_borrow()
+                .map_or(0, |elements| elements.len() * size_of::<ExprResult>()),
+            Object::Map(entries) => entries
+                .try_borrow()
+                .map_or(0, |entries| entries.len() * 2 * size_of::<ExprResult>()),
+            Object::Upvalue(_) | Object::Function(_) | Object::Class(_) => 0,
+        };
+        for value in self.values() {
+            if let ExprResult::String(string) = value {
+                if strings.insert(Rc::as_ptr(&string) as *const () as usize) {
+                    bytes += string.len();
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Returns the values the object holds directly, or none if it is being modified.
+    fn values(&self) -> Vec<ExprResult> {
+        match self {
+            Object::Environment(environment) => environment.try_borrow().map_or(vec![], |e| {
+                e.values
+                    .iter()
+                    .filter_map(|binding| match binding {
+                        Binding::Value(value) => Some(value.to_owned()),
+                        Binding::Captured(_) => None,
+                    })
+                    .collect()
+            }),
+            Object::Upvalue(upvalue) => upvalue
+                .try_borrow()
+                .map_or(vec![], |value| vec![value.to_owned()]),
+            Object::Instance(fields) => fields
+                .try_borrow()
+                .map_or(vec![], |fields| fields.values().cloned().collect()),
+            Object::List(elements) => elements
+                .try_borrow()
+                .map_or(vec![], |elements| elements.to_owned()),
+            Object::Map(entries) => entries.try_borrow().map_or(vec![], |entries| {
+                entries
+                    .iter()
+                    .flat_map(|(key, value)| [key.to_owned(), value.to_owned()])
+                    .collect()
+            }),
+            Object::Function(_) | Object::Class(_) => vec![],
+        }
+    }
+
+    /// Drops the references held by the object. The values are dropped after the object is no
+    /// longer borrowed, because dropping them may free further objects.
+    fn clear(&self) {
+        match self {
+            Object::Environment(environment) => {
+                if let Ok(mut environment) = environment.try_borrow_mut() {
+                    let values = std::mem::take(&mut environment.values);
+                    let enclosing = environment.enclosing.take();
+                    drop(environment);
+                    drop((values, enclosing));
+                }
+            }
+            Object::Upvalue(upvalue) => {
+                let value = upvalue
+                    .try_borrow_mut()
+                    .map(|mut v| std::mem::take(&mut *v));
+                drop(value);
+            }
+            Object::Instance(fields) => {
+                let fields = fields.try_borrow_mut().map(|mut f| std::mem::take(&mut *f));
+                drop(fields);
+            }
+            Object::List(elements) => {
+                let elements = elements
+                    .try_borrow_mut()
+                    .map(|mut e| std::mem::take(&mut *e));
+                drop(elements);
+            }
+            Object::Map(entries) => {
+                let entries = entries
+                    .try_borrow_mut()
+                    .map(|mut e| std::mem::take(&mut *e));
+                drop(entries);
+            }
+            // Functions and classes are immutable, clearing the environments they refer to
+            // breaks their cycles.
+            Object::Function(_) | Object::Class(_) => {}
+        }
+    }
+}
+
+fn value_children(value: &ExprResult, children: &mut Vec<Object>) {
+    match value {
+        ExprResult::Function(function) => children.push(Object::Function(Rc::clone(function))),
+        ExprResult::Class(class) => children.push(Object::Class(Rc::clone(class))),
+        ExprResult::Trait(lox_trait) => {
+            for method in lox_trait.methods.values() {
+                children.push(Object::Environment(Rc::clone(&method.closure)));
+            }
+        }
+        ExprResult::Instance(instance) => {
+            children.push(Object::Instance(Rc::clone(&instance.fields)));
+            children.push(Object::Class(Rc::clone(&instance.class)));
+        }
+        ExprResult::List(list) => children.push(Object::List(Rc::clone(&list.elements))),
+        ExprResult::Map(map) => children.push(Object::Map(Rc::clone(&map.entries))),
+        _ => {}
+    }
+}
+
+fn class_children(class: &LoxClass, children: &mut Vec<Object>) {
+    if let Some(superclass) = class.superclass.as_ref() {
+        children.push(Object::Class(Rc::clone(superclass)));
+    }
+    if let Some(fields) = class.fields.as_ref() {
+        children.push(Object::Environment(Rc::clone(&fields.closure)));
+    }
+    for method in class.methods.values() {
+        children.push(Object::Environment(Rc::clone(&method.closure)));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::base::scanner::{Token, TokenType};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The lexemes of each top-level function, class and trait declaration of a script, by name.
+pub type Declarations = HashMap<String, Vec<String>>;
+
+/// Collects the top-level function, class and trait declarations of a scanned script.
+///
+/// Declarations are compared by their lexemes, so a change in whitespace or comments alone
+/// does not count as a change.
+pub fn declarations(tokens: &[Token]) -> Declarations {
+    let mut declarations = Declarations::new();
+    let mut depth = 0usize;
+    let mut current = 0;
+
+    while current < tokens.len() {
+        let token = &tokens[current];
+        let is_declaration = depth == 0
+            && matches!(
+                token.token_type,
+                TokenType::Class | TokenType::Fun | TokenType::Trait
+            )
+            && tokens
+                .get(current + 1)
+                .is_some_and(|name| name.token_type == TokenType::Identifier);
+
+        if !is_declaration {
+            match token.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            current += 1;
+            continue;
+        }
+
+        let start = match current
+            .checked_sub(1)
+            .map(|index| &tokens[index].token_type)
+        {
+            Some(TokenType::Async) => current - 1,
+            _ => current,
+        };
+
+        /* a function with an arrow body ends at the semicolon instead of a closing brace. */
+        let mut end = current;
+        let mut body_depth = 0usize;
+        let mut is_arrow = false;
+        while end < tokens.len() {
+            match tokens[end].token_type {
+                TokenType::LeftBrace => body_depth += 1,
+                TokenType::RightBrace => {
+                    body_depth = body_depth.saturating_sub(1);
+                    if body_depth == 0 && !is_arrow {
+                        break;
+                    }
+                }
+                TokenType::Arrow if body_depth == 0 => is_arrow = true,
+                TokenType::Semicolon if body_depth == 0 && is_arrow => break,
+                _ => {}
+            }
+            end += 1;
+        }
+
+        let end = end.min(tokens.len() - 1);
+        declarations.insert(
+            tokens[current + 1].lexeme.to_owned(),
+            tokens[start..=end]
+                .iter()
+                .map(|token| token.lexeme.to_owned())
+                .collect(),
+        );
+        current = end + 1;
+    }
+
+    declarations
+}
+
+/// Handle for handing a changed script to a running interpreter from another thread, e.g.
+/// from a file watcher.
+///
+/// The interpreter checks the handle between statements and redefines the changed top-level
+/// functions and classes in its global environment.
+#[derive(Clone, Debug, Default)]
+pub struct ReloadHandle {
+    pending: Arc<AtomicBool>,
+    source: Arc<Mutex<Option<(String, String)>>>,
+}
+
+impl ReloadHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests a reload of `file` with the new `source`. Replaces a pending request.
+    pub fn request(&self, source: String, file: &str) {
+        if let Ok(mut pending) = self.source.lock() {
+            *pending = Some((source, file.to_owned()));
+            self.pending.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn take(&self) -> Option<(String, String)> {
+        let mut pending = self.source.lock().ok()?;
+        self.pending.store(false, Ordering::SeqCst);
+        pending.take()
+    }
+}
@@ -1,60 +1,273 @@
-use crate::base::expr::{Expr, LiteralValue};
-use crate::base::expr_result::{Callable, LoxFunction};
-use crate::base::expr_result::{ExprResult, LoxClass};
-use crate::base::scanner::{Token, TokenType};
-use crate::base::stmt::Stmt;
+use crate::base::ast::{Ast, Body, ExprRef, StmtRef};
+use crate::base::expr::{Expr, LiteralValue, NodeId, NodeMap};
+use crate::base::expr_result::{Callable, CompiledFunction, LoxFunction, NativeFunction};
+use crate::base::expr_result::{ExprResult, LoxClass, LoxList, LoxMap, LoxTrait};
+use crate::base::parser::Parser;
+use crate::base::scanner::{Scanner, Token, TokenType};
+use crate::base::stmt::{Pattern, Stmt};
 use crate::base::visitor::Visitor;
-use crate::interpreter::environment::Environment;
+use crate::interpreter::budget::Budget;
+use crate::interpreter::environment::{Environment, Slot};
+use crate::interpreter::event_loop;
+use crate::interpreter::event_loop::EventLoop;
+use crate::interpreter::generator::Items;
+use crate::interpreter::heap;
+use crate::interpreter::hot_reload;
+use crate::interpreter::hot_reload::{Declarations, ReloadHandle};
+use crate::interpreter::interrupt::InterruptHandle;
+use crate::interpreter::methods;
+use crate::interpreter::modules::{self, Module, Modules};
+use crate::interpreter::natives;
+use crate::interpreter::options::InterpreterOptions;
+use crate::interpreter::random::Random;
+use crate::interpreter::resolver::Resolver;
 use crate::interpreter::runtime_error::RuntimeError;
-use std::cell::RefCell;
+use crate::interpreter::suggestion;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::io::stdout;
-use std::io::Write;
+use std::error::Error;
+use std::fmt::Display;
+use std::io::{stderr, stdin, stdout};
+use std::io::{ErrorKind, Read, Write};
 use std::rc::Rc;
-use uuid::Uuid;
+use std::sync::Arc;
 
 pub struct Interpreter<'a> {
-    globals: Rc<RefCell<Environment>>,
+    /// The tree of the statements the interpreter executes.
+    pub(crate) ast: Arc<Ast>,
+    pub(crate) globals: Rc<RefCell<Environment>>,
     environment: Rc<RefCell<Environment>>,
-    locals: RefCell<HashMap<Uuid, usize>>,
+    pub(crate) locals: Rc<RefCell<NodeMap<Slot>>>,
+    /// Slots of the variables each function captures, in the order of its closure.
+    pub(crate) captures: Rc<RefCell<NodeMap<Arc<[Slot]>>>>,
+    /// Machine code of function declarations, see [`Interpreter::add_compiled_functions`].
+    compiled: Rc<RefCell<NodeMap<CompiledFunction>>>,
+    pub(crate) event_loop: Rc<RefCell<EventLoop>>,
+    pub(crate) interrupt: InterruptHandle,
+    budget: Budget,
+    pub(crate) options: Rc<InterpreterOptions>,
+    pub(crate) random: Random,
+    reload: ReloadHandle,
+    declarations: Rc<RefCell<Declarations>>,
+    modules: Rc<RefCell<Modules>>,
+    input_stream: Rc<RefCell<dyn Read + 'a>>,
     output_stream: Rc<RefCell<dyn Write + 'a>>,
+    output_written: Rc<Cell<usize>>,
+    error_stream: Rc<RefCell<dyn Write + 'a>>,
 }
 
 impl<'a> Interpreter<'a> {
     pub fn new<OutputWriter>(output_stream: Rc<RefCell<OutputWriter>>) -> Self
     where
         OutputWriter: Write + 'a,
+    {
+        Self::new_with_error_stream(output_stream, Rc::new(RefCell::new(stderr())))
+    }
+
+    pub fn new_with_error_stream<OutputWriter, ErrorWriter>(
+        output_stream: Rc<RefCell<OutputWriter>>,
+        error_stream: Rc<RefCell<ErrorWriter>>,
+    ) -> Self
+    where
+        OutputWriter: Write + 'a,
+        ErrorWriter: Write + 'a,
     {
         let globals = Rc::new(RefCell::new(Environment::new()));
+        heap::track_environment(&globals);
+        natives::define_builtins(&globals);
+
         let env = Rc::clone(&globals);
         Self {
+            ast: Arc::new(Ast::default()),
             globals,
             environment: env,
-            locals: RefCell::new(HashMap::new()),
+            locals: Rc::new(RefCell::new(NodeMap::default())),
+            captures: Rc::new(RefCell::new(NodeMap::default())),
+            compiled: Rc::new(RefCell::new(NodeMap::default())),
+            event_loop: Rc::new(RefCell::new(EventLoop::new())),
+            interrupt: InterruptHandle::new(),
+            budget: Budget::new(),
+            options: Rc::new(InterpreterOptions::default()),
+            random: Random::new(),
+            reload: ReloadHandle::new(),
+            declarations: Rc::new(RefCell::new(Declarations::new())),
+            modules: Rc::new(RefCell::new(Modules::new())),
+            input_stream: Rc::new(RefCell::new(stdin())),
             output_stream,
+            output_written: Rc::new(Cell::new(0)),
+            error_stream,
         }
     }
 
     pub fn fork(&self, environment: Rc<RefCell<Environment>>) -> Self {
+        self.fork_in(&self.ast, environment)
+    }
+
+    /// Forks the interpreter to execute statements of another tree, like the body of a
+    /// function that was declared in another script.
+    pub(crate) fn fork_in(&self, ast: &Arc<Ast>, environment: Rc<RefCell<Environment>>) -> Self {
         Self {
+            ast: Arc::clone(ast),
             globals: Rc::clone(&self.globals),
             environment,
-            locals: self.locals.clone(),
+            locals: Rc::clone(&self.locals),
+            captures: Rc::clone(&self.captures),
+            compiled: Rc::clone(&self.compiled),
+            event_loop: Rc::clone(&self.event_loop),
+            interrupt: self.interrupt.to_owned(),
+            budget: self.budget.to_owned(),
+            options: Rc::clone(&self.options),
+            random: self.random.to_owned(),
+            reload: self.reload.to_owned(),
+            declarations: Rc::clone(&self.declarations),
+            modules: Rc::clone(&self.modules),
+            input_stream: Rc::clone(&self.input_stream),
             output_stream: Rc::clone(&self.output_stream),
+            output_written: Rc::clone(&self.output_written),
+            error_stream: Rc::clone(&self.error_stream),
         }
     }
 
-    pub fn interpret(&self, statements: &[Stmt]) -> Result<(), RuntimeError> {
-        for statement in statements {
-            self.execute(statement)?;
+    pub fn with_options(mut self, options: InterpreterOptions) -> Self {
+        self.options = Rc::new(options);
+        self
+    }
+
+    /// Sets the arguments passed to the script, which it reads from the `ARGS` global.
+    pub fn with_arguments(self, arguments: &[String]) -> Self {
+        natives::define_arguments(&self.globals, arguments);
+        self
+    }
+
+    /// Replaces the input stream `readLine()` reads from, which defaults to standard input.
+    pub fn with_input_stream<InputReader>(mut self, input_stream: Rc<RefCell<InputReader>>) -> Self
+    where
+        InputReader: Read + 'a,
+    {
+        self.input_stream = input_stream;
+        self
+    }
+
+    /// Defines a global native function implemented in Rust, which scripts call with exactly
+    /// `arity` arguments. Host functions are not available inside tasks.
+    pub fn define_native<F>(&self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[ExprResult]) -> Result<ExprResult, RuntimeError> + 'static,
+    {
+        let native = NativeFunction::new(name, arity, move |_, _, arguments| function(arguments));
+        self.globals
+            .borrow_mut()
+            .define(name, ExprResult::native_function(native));
+    }
+
+    /// Removes the native functions of all standard library features that are not listed.
+    pub fn restrict_stdlib(&self, features: &[String]) {
+        self.globals.borrow_mut().retain(|name, value| {
+            !matches!(value, ExprResult::NativeFunction(_))
+                || natives::feature(name)
+                    .is_none_or(|feature| features.iter().any(|f| f == feature))
+        });
+    }
+
+    /// Returns a handle that stops the currently running script when triggered. Each call of
+    /// [`Interpreter::interpret`] starts uninterrupted.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.to_owned()
+    }
+
+    /// Returns a handle for hot reloading the running script. See [`Interpreter::reload`].
+    pub fn reload_handle(&self) -> ReloadHandle {
+        self.reload.to_owned()
+    }
+
+    /// Remembers the top-level declarations of a script, which a later reload is compared to.
+    pub fn track_declarations(&self, tokens: &[Token]) {
+        self.declarations.replace(hot_reload::declarations(tokens));
+    }
+
+    /// Redefines all top-level functions and classes of `source` in the global environment that
+    /// differ from the tracked declarations, without running any other statement. Returns the
+    /// names of the redefined declarations.
+    pub fn reload(&self, source: &str, file: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let tokens = Scanner::new_with_file(source, file).scan_tokens()?;
+        let declarations = hot_reload::declarations(&tokens);
+        let ast = Parser::new(tokens).parse()?;
+
+        let changed: Vec<StmtRef> = ast
+            .statements()
+            .iter()
+            .copied()
+            .filter(|statement| match &ast[*statement] {
+                Stmt::Function { name, .. }
+                | Stmt::Class { name, .. }
+                | Stmt::Trait { name, .. } => {
+                    self.declarations.borrow().get(&name.lexeme) != declarations.get(&name.lexeme)
+                }
+                _ => false,
+            })
+            .collect();
+
+        let global = Rc::new(self.fork_in(&ast, Rc::clone(&self.globals)));
+        Resolver::new(Rc::clone(&global)).resolve_statements(&ast, &changed)?;
+        global.execute_block(&changed)?;
+        self.declarations.replace(declarations);
+
+        Ok(changed
+            .iter()
+            .filter_map(|statement| match &ast[*statement] {
+                Stmt::Function { name, .. }
+                | Stmt::Class { name, .. }
+                | Stmt::Trait { name, .. } => Some(name.lexeme.to_owned()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Applies a reload requested through the [`ReloadHandle`]. A script that fails to reload
+    /// is reported to the error stream and the previous declarations stay in place.
+    pub fn apply_pending_reload(&self) -> Result<(), RuntimeError> {
+        let Some((source, file)) = self.reload.take() else {
+            return Ok(());
+        };
+
+        match self.reload(&source, &file) {
+            Ok(names) if names.is_empty() => Ok(()),
+            Ok(names) => self.report_error(&format!("{}: Reloaded {}.", file, names.join(", "))),
+            Err(error) => self.report_error(&format!("{}:{}", file, error)),
         }
+    }
 
-        Ok(())
+    pub fn interpret(&self, ast: &Arc<Ast>) -> Result<(), RuntimeError> {
+        self.interrupt.reset();
+        self.budget.reset();
+
+        self.fork_in(ast, Rc::clone(&self.environment))
+            .execute_block(ast.statements())?;
+
+        event_loop::run_to_completion(&self.event_loop, self)
     }
 
-    pub fn execute_block(&self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+    /// Evaluates an expression parsed on its own, as by [`Parser::parse_expression`], in the
+    /// current environment and returns its value.
+    pub fn evaluate_expression(
+        &self,
+        ast: &Arc<Ast>,
+        expr: ExprRef,
+    ) -> Result<ExprResult, RuntimeError> {
+        self.interrupt.reset();
+        self.budget.reset();
+
+        let value = self
+            .fork_in(ast, Rc::clone(&self.environment))
+            .evaluate(expr)?;
+
+        event_loop::run_to_completion(&self.event_loop, self)?;
+        Ok(value)
+    }
+
+    pub fn execute_block(&self, statements: &[StmtRef]) -> Result<(), RuntimeError> {
         for statement in statements {
-            self.execute(statement)?;
+            self.execute(*statement)?;
         }
 
         Ok(())
@@ -64,26 +277,739 @@ impl<'a> Interpreter<'a> {
         self.environment.borrow_mut().define(&name.lexeme, value);
     }
 
-    pub fn resolve(&self, uuid: &Uuid, depth: usize) {
-        self.locals.borrow_mut().insert(uuid.to_owned(), depth);
+    pub fn resolve(&self, id: &NodeId, slot: Slot) {
+        self.locals.borrow_mut().insert(id.to_owned(), slot);
+    }
+
+    /// Registers machine code for function declarations by their id. Functions declared by these
+    /// statements run the code when they are called with numbers.
+    pub fn add_compiled_functions(&self, functions: NodeMap<CompiledFunction>) {
+        self.compiled.borrow_mut().extend(functions);
     }
 
-    fn execute(&self, stmt: &Stmt) -> Result<(), RuntimeError> {
-        stmt.accept(self)
+    /// Records the variables a function captures from the scopes enclosing its declaration.
+    pub fn resolve_captures(&self, id: &NodeId, slots: Vec<Slot>) {
+        self.captures
+            .borrow_mut()
+            .insert(id.to_owned(), Arc::from(slots));
     }
 
-    fn evaluate(&self, expr: &Expr) -> Result<ExprResult, RuntimeError> {
-        expr.accept(self)
+    /// Creates the closure of the function with the given id, capturing its variables from
+    /// `environment`. Functions that capture nothing share the global scope.
+    fn closure(
+        &self,
+        id: &NodeId,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Rc<RefCell<Environment>> {
+        let Some(slots) = self.captures.borrow().get(id).cloned() else {
+            return Rc::clone(&self.globals);
+        };
+
+        let upvalues = slots
+            .iter()
+            .map(|slot| environment.borrow_mut().capture(*slot).unwrap_or_default())
+            .collect();
+        Environment::new_closure(upvalues)
+    }
+
+    /// Writes to the output stream, unless the output would exceed
+    /// [`InterpreterOptions::max_output`].
+    pub(crate) fn write_output(&self, bytes: &[u8]) -> Result<(), RuntimeError> {
+        let written = self.output_written.get() + bytes.len();
+        if let Some(limit) = self.options.max_output {
+            if written > limit {
+                return Err(RuntimeError::OutputLimitExceeded { limit });
+            }
+        }
+        self.output_written.set(written);
+
+        let mut stream = self.output_stream.borrow_mut();
+        stream
+            .write_all(bytes)
+            .map_err(|_| RuntimeError::OutputError)?;
+        stream.flush().map_err(|_| RuntimeError::OutputError)
+    }
+
+    /// Accounts for memory allocated by the script, which is limited by
+    /// [`InterpreterOptions::max_memory`].
+    pub(crate) fn allocate(&self, bytes: usize) -> Result<(), RuntimeError> {
+        self.budget.allocate(bytes, &self.options, || {
+            heap::collect();
+            heap::live_bytes()
+        })
+    }
+
+    /// Accounts for the memory of a value created by a native function.
+    pub(crate) fn allocate_value(&self, value: &ExprResult) -> Result<(), RuntimeError> {
+        match value {
+            ExprResult::String(value) => self.allocate(value.len()),
+            ExprResult::List(list) => {
+                self.allocate(list.elements.borrow().len() * size_of::<ExprResult>())
+            }
+            ExprResult::Map(map) => {
+                self.allocate(map.entries.borrow().len() * 2 * size_of::<ExprResult>())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates a string value and accounts for its memory.
+    fn new_string(&self, value: String) -> Result<ExprResult, RuntimeError> {
+        self.allocate(value.len())?;
+        Ok(ExprResult::string(value))
+    }
+
+    /// Reads a line from the input stream without the line terminator. Returns `None` at the
+    /// end of the stream. Bytes are read one at a time, so no input after the line is consumed.
+    pub(crate) fn read_line(&self, token: &Token) -> Result<Option<String>, RuntimeError> {
+        let mut stream = self.input_stream.borrow_mut();
+        let mut bytes = vec![];
+        let mut byte = [0u8];
+
+        loop {
+            match stream.read(&mut byte) {
+                Ok(0) if bytes.is_empty() => return Ok(None),
+                Ok(0) => break,
+                Ok(_) if byte[0] == b'\n' => break,
+                Ok(_) => bytes.push(byte[0]),
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(_) => {
+                    return Err(RuntimeError::InputError {
+                        line: token.line,
+                        column: token.column,
+                    })
+                }
+            }
+        }
+        if bytes.last() == Some(&b'\r') {
+            bytes.pop();
+        }
+
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|_| RuntimeError::InputError {
+                line: token.line,
+                column: token.column,
+            })
+    }
+
+    /// Writes to the error stream.
+    pub(crate) fn write_error(&self, bytes: &[u8]) -> Result<(), RuntimeError> {
+        let mut stream = self.error_stream.borrow_mut();
+        stream
+            .write_all(bytes)
+            .map_err(|_| RuntimeError::OutputError)?;
+        stream.flush().map_err(|_| RuntimeError::OutputError)
+    }
+
+    /// Writes an error of any stage (scanning, parsing, resolving or interpreting) to the error
+    /// stream.
+    pub fn report_error(&self, error: &dyn Display) -> Result<(), RuntimeError> {
+        let mut stream = self.error_stream.borrow_mut();
+        writeln!(stream, "{}", error).map_err(|_| RuntimeError::OutputError)?;
+        stream.flush().map_err(|_| RuntimeError::OutputError)
+    }
+
+    fn execute(&self, stmt: StmtRef) -> Result<(), RuntimeError> {
+        if self.interrupt.is_interrupted() {
+            return Err(RuntimeError::Interrupted);
+        }
+
+        self.budget.spend(&self.options)?;
+
+        if self.reload.is_pending() {
+            self.apply_pending_reload()?;
+        }
+
+        self.ast[stmt].accept(self)
+    }
+
+    pub(crate) fn evaluate(&self, expr: ExprRef) -> Result<ExprResult, RuntimeError> {
+        self.ast[expr].accept(self)
+    }
+
+    /// Assigns a value to the variable an assignment expression refers to.
+    pub(crate) fn assign_variable(
+        &self,
+        id: &NodeId,
+        name: &Token,
+        value: &ExprResult,
+    ) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.locals.borrow().get(id) {
+            self.environment.borrow_mut().assign_at(*slot, value);
+        } else {
+            self.globals.borrow_mut().assign(name, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether a tested value counts as true. In strict mode only booleans may be
+    /// tested.
+    pub(crate) fn is_true(&self, value: &ExprResult, token: &Token) -> Result<bool, RuntimeError> {
+        match value {
+            ExprResult::Boolean(value) => Ok(*value),
+            _ if self.options.strict => Err(RuntimeError::BooleanExpected {
+                line: token.line,
+                column: token.column,
+            }),
+            _ => Ok(value.is_truthy()),
+        }
     }
 
-    fn lookup_variable(&self, name: &Token, uuid: &Uuid) -> Result<ExprResult, RuntimeError> {
-        if let Some(distance) = self.locals.borrow().get(uuid) {
+    /// Defines a variable for each name of a destructuring declaration.
+    fn destructure(
+        &self,
+        keyword: &Token,
+        pattern: Pattern,
+        names: &[Token],
+        value: ExprResult,
+    ) -> Result<(), RuntimeError> {
+        let values = match (pattern, value) {
+            (Pattern::List, ExprResult::List(list)) => {
+                if list.len() < names.len() {
+                    return Err(RuntimeError::IndexOutOfBounds {
+                        line: keyword.line,
+                        column: keyword.column,
+                        index: list.len(),
+                        length: list.len(),
+                    });
+                }
+                list.to_vec()
+            }
+            (Pattern::List, _) => {
+                return Err(RuntimeError::ListExpected {
+                    line: keyword.line,
+                    column: keyword.column,
+                })
+            }
+            (Pattern::Fields, ExprResult::Instance(instance)) => names
+                .iter()
+                .map(|name| instance.get(name))
+                .collect::<Result<Vec<ExprResult>, RuntimeError>>()?,
+            (Pattern::Fields, _) => {
+                return Err(RuntimeError::InvalidPropertyAccess {
+                    line: keyword.line,
+                    column: keyword.column,
+                })
+            }
+            (Pattern::Values, value) => {
+                let values = match value {
+                    ExprResult::List(list) => list.to_vec(),
+                    value => vec![value],
+                };
+                if values.len() != names.len() {
+                    return Err(RuntimeError::ValueCountMismatch {
+                        line: keyword.line,
+                        column: keyword.column,
+                        expected: names.len(),
+                        actual: values.len(),
+                    });
+                }
+                values
+            }
+        };
+
+        for (name, value) in names.iter().zip(values) {
+            self.environment.borrow_mut().define(&name.lexeme, value);
+        }
+
+        Ok(())
+    }
+
+    /// Defines the top-level declarations of an imported module in the current environment.
+    fn import(&self, keyword: &Token, path: &Token) -> Result<(), RuntimeError> {
+        let TokenType::String { value } = &path.token_type else {
+            return Err(RuntimeError::InvalidValue {
+                line: path.line,
+                column: path.column,
+            });
+        };
+
+        let module = self.import_module(keyword, value)?;
+        for (name, value) in module.borrow().entries() {
+            self.environment.borrow_mut().define(&name, value);
+        }
+
+        Ok(())
+    }
+
+    /// Executes a module once and returns its top-level declarations. The module runs in its
+    /// own global scope, enclosed by the global scope of the interpreter.
+    fn import_module(
+        &self,
+        keyword: &Token,
+        path: &str,
+    ) -> Result<Rc<RefCell<Environment>>, RuntimeError> {
+        let path = modules::module_path(keyword, path, &self.options.import_paths)?;
+        match self.modules.borrow().get(&path) {
+            Some(Module::Loaded(environment)) => return Ok(Rc::clone(environment)),
+            Some(Module::Loading) => {
+                return Err(RuntimeError::CyclicImport {
+                    line: keyword.line,
+                    column: keyword.column,
+                    path: path.display().to_string(),
+                })
+            }
+            None => {}
+        }
+
+        self.modules
+            .borrow_mut()
+            .insert(path.to_owned(), Module::Loading);
+        let result = self.execute_module(keyword, &path);
+        match &result {
+            Ok(environment) => self
+                .modules
+                .borrow_mut()
+                .insert(path, Module::Loaded(Rc::clone(environment))),
+            Err(_) => self.modules.borrow_mut().remove(&path),
+        };
+
+        result
+    }
+
+    fn execute_module(
+        &self,
+        keyword: &Token,
+        path: &std::path::Path,
+    ) -> Result<Rc<RefCell<Environment>>, RuntimeError> {
+        let ast = modules::parse_module(keyword, path, self.options.lossy_utf8)?;
+
+        let environment = Environment::new_module(Rc::clone(&self.globals));
+        let module = Rc::new(Interpreter {
+            globals: Rc::clone(&environment),
+            ..self.fork_in(&ast, Rc::clone(&environment))
+        });
+
+        Resolver::new(Rc::clone(&module)).resolve_stmts(&ast)?;
+        module
+            .execute_block(ast.statements())
+            .map_err(|error| error.in_file(&Arc::from(path.display().to_string())))?;
+
+        Ok(environment)
+    }
+
+    /// Evaluates a binary expression.
+    fn binary(
+        &self,
+        left: ExprRef,
+        operator: &Token,
+        right: ExprRef,
+    ) -> Result<ExprResult, RuntimeError> {
+        let left = self.evaluate(left)?;
+        let right = self.evaluate(right)?;
+
+        if self.options.strict && is_mismatched(&operator.token_type, &left, &right) {
+            return Err(RuntimeError::MismatchedTypes {
+                line: operator.line,
+                column: operator.column,
+            });
+        }
+
+        match &operator.token_type {
+            TokenType::Greater => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::boolean(v1 > v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::GreaterEqual => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::boolean(v1 >= v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::Less => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::boolean(v1 < v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::LessEqual => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::boolean(v1 <= v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::Is => match (&left, &right) {
+                (ExprResult::Instance(instance), ExprResult::Class(class)) => {
+                    Ok(ExprResult::boolean(instance.class.is_subclass_of(class)))
+                }
+                (_, ExprResult::Class(_)) => Ok(ExprResult::boolean(false)),
+                _ => Err(RuntimeError::ClassExpected {
+                    line: operator.line,
+                    column: operator.column,
+                }),
+            },
+            TokenType::BangEqual => Ok(ExprResult::boolean(left != right)),
+            TokenType::EqualEqual => Ok(ExprResult::boolean(left == right)),
+            TokenType::Minus => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::number(v1 - v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::Slash => match (&left, &right) {
+                (ExprResult::Number(_), &ExprResult::Number(v2))
+                    if v2 == 0.0 && self.options.checked_division =>
+                {
+                    Err(RuntimeError::DivisionByZero {
+                        line: operator.line,
+                        column: operator.column,
+                    })
+                }
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::number(v1 / v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::Star => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::number(v1 * v2))
+                }
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            TokenType::Plus => match (&left, &right) {
+                (&ExprResult::Number(v1), &ExprResult::Number(v2)) => {
+                    Ok(ExprResult::number(v1 + v2))
+                }
+                (ExprResult::String(v1), ExprResult::String(v2)) => {
+                    self.new_string(format!("{v1}{v2}"))
+                }
+                (ExprResult::String(v1), v2) => {
+                    self.new_string(format!("{v1}{}", self.stringify(v2)?))
+                }
+                (v1, ExprResult::String(v2)) => self.new_string(self.stringify(v1)? + v2),
+                _ => Err(number_expected(operator, &[&left, &right])),
+            },
+            _ => Err(RuntimeError::InvalidValue {
+                line: operator.line,
+                column: operator.column,
+            }),
+        }
+    }
+
+    /// Converts a value into the string that `print` shows. Instances whose class defines a
+    /// `toString()` method are converted by calling it, also inside lists and maps.
+    pub(crate) fn stringify(&self, value: &ExprResult) -> Result<String, RuntimeError> {
+        value.format_with(&mut |instance| {
+            if let Some(method) = instance.class.find_method("toString") {
+                if method.arity() == 0 {
+                    if let ExprResult::Function(method) = method.bind(instance) {
+                        return Ok(Some(method.invoke(self, &[])?.to_string()));
+                    }
+                }
+            }
+
+            Ok(None)
+        })
+    }
+
+    /// Evaluates a call of a function, native function or class.
+    fn call(
+        &self,
+        paren: &Token,
+        callee: ExprRef,
+        arguments: &[ExprRef],
+    ) -> Result<ExprResult, RuntimeError> {
+        let call = self.evaluate(callee)?;
+
+        let callable = call.as_callable().ok_or(RuntimeError::UndefinedCallable {
+            line: paren.line,
+            column: paren.column,
+        })?;
+
+        if !callable.accepts(arguments.len()) {
+            return Err(RuntimeError::NonMatchingNumberOfArguments {
+                line: paren.line,
+                column: paren.column,
+            });
+        }
+
+        let args = arguments
+            .iter()
+            .map(|argument| self.evaluate(*argument))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        callable.call(self, paren, &args)
+    }
+
+    /// Evaluates reading an element of a list, map or string.
+    fn index(
+        &self,
+        object: ExprRef,
+        bracket: &Token,
+        index: ExprRef,
+    ) -> Result<ExprResult, RuntimeError> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+
+        match object {
+            ExprResult::List(list) => {
+                let index = self.element_index(&index, list.len(), bracket)?;
+                Ok(list.get(index).unwrap_or_default())
+            }
+            ExprResult::Map(map) => Ok(map.get(&index).unwrap_or_default()),
+            ExprResult::String(value) => {
+                let index = self.element_index(&index, value.chars().count(), bracket)?;
+                Ok(ExprResult::string(
+                    value.chars().nth(index).unwrap_or_default().to_string(),
+                ))
+            }
+            _ => Err(RuntimeError::NotIndexable {
+                line: bracket.line,
+                column: bracket.column,
+            }),
+        }
+    }
+
+    /// Evaluates assigning an element of a list or map.
+    fn index_set(
+        &self,
+        object: ExprRef,
+        bracket: &Token,
+        index: ExprRef,
+        value: ExprRef,
+    ) -> Result<ExprResult, RuntimeError> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(value)?;
+
+        match object {
+            ExprResult::List(list) => {
+                let index = self.element_index(&index, list.len(), bracket)?;
+                list.set(index, value.clone());
+            }
+            ExprResult::Map(map) => map.insert(index, value.clone()),
+            _ => {
+                return Err(RuntimeError::InvalidIndexAssignment {
+                    line: bracket.line,
+                    column: bracket.column,
+                })
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Creates the function of an anonymous function expression.
+    fn lambda(&self, function: StmtRef) -> Result<ExprResult, RuntimeError> {
+        let Stmt::Function {
+            id,
+            name,
+            params,
+            body,
+            is_variadic,
+            is_generator,
+            ..
+        } = &self.ast[function]
+        else {
+            unreachable!("lambdas are parsed into function declarations")
+        };
+
+        Ok(ExprResult::function(
+            LoxFunction::new(
+                name.as_ref().clone(),
+                Arc::clone(params),
+                Body::new(Arc::clone(&self.ast), Arc::clone(body)),
+                self.closure(id, &self.environment),
+                false,
+                false,
+                *is_variadic,
+            )
+            .with_generator(*is_generator),
+        ))
+    }
+
+    /// Looks up a method of the superclass, bound to the current instance.
+    fn super_method(
+        &self,
+        id: &NodeId,
+        this_id: &NodeId,
+        keyword: &Token,
+        method: &Token,
+    ) -> Result<ExprResult, RuntimeError> {
+        let locals = self.locals.borrow();
+        if let (Some(slot), Some(this_slot)) = (locals.get(id), locals.get(this_id)) {
+            let superclass = self.environment.borrow().get_at(*slot);
+            let object = self.environment.borrow().get_at(*this_slot);
+
+            if let Some(ExprResult::Class(sc)) = superclass {
+                if let Some(ExprResult::Instance(obj)) = object {
+                    if let Some(method) = sc.find_method(&method.lexeme) {
+                        return Ok(method.bind(&obj));
+                    }
+                }
+
+                return Err(RuntimeError::UndefinedProperty {
+                    line: keyword.line,
+                    column: keyword.column,
+                    suggestion: suggestion::closest(&method.lexeme, sc.method_names()),
+                });
+            }
+        }
+
+        Err(RuntimeError::UndefinedProperty {
+            line: keyword.line,
+            column: keyword.column,
+            suggestion: None,
+        })
+    }
+
+    /// Evaluates an increment or decrement of a variable or field.
+    fn update(
+        &self,
+        operator: &Token,
+        target: ExprRef,
+        prefix: bool,
+    ) -> Result<ExprResult, RuntimeError> {
+        let delta = if operator.token_type == TokenType::PlusPlus {
+            1.0
+        } else {
+            -1.0
+        };
+        let updated = |value: &ExprResult| match value {
+            ExprResult::Number(value) => Ok(ExprResult::number(value + delta)),
+            _ => Err(number_expected(operator, &[value])),
+        };
+
+        let (old_value, new_value) = match &self.ast[target] {
+            Expr::Variable { id, name } => {
+                let old_value = self.lookup_variable(name, id)?;
+                let new_value = updated(&old_value)?;
+
+                if let Some(slot) = self.locals.borrow().get(id) {
+                    self.environment.borrow_mut().assign_at(*slot, &new_value);
+                } else {
+                    self.globals.borrow_mut().assign(name, &new_value)?;
+                }
+
+                (old_value, new_value)
+            }
+            Expr::Get { object, name, .. } => {
+                let ExprResult::Instance(instance) = self.evaluate(*object)? else {
+                    return Err(RuntimeError::InvalidFieldAccess {
+                        line: name.line,
+                        column: name.column,
+                    });
+                };
+                let old_value = instance.get(name)?;
+                let new_value = updated(&old_value)?;
+                instance.set(name, new_value.clone());
+
+                (old_value, new_value)
+            }
+            _ => {
+                return Err(RuntimeError::InvalidValue {
+                    line: operator.line,
+                    column: operator.column,
+                })
+            }
+        };
+
+        Ok(if prefix { new_value } else { old_value })
+    }
+
+    /// Executes the body of a loop and returns whether the loop continues. A `break` or
+    /// `continue` that names another loop's label is passed on to the enclosing loops.
+    fn execute_loop_body(
+        &self,
+        body: StmtRef,
+        label: &Option<Token>,
+    ) -> Result<bool, RuntimeError> {
+        match self.execute(body) {
+            Err(RuntimeError::Break { label: target }) if is_loop_target(label, &target) => {
+                Ok(false)
+            }
+            Err(RuntimeError::Continue { label: target }) if is_loop_target(label, &target) => {
+                Ok(true)
+            }
+            result => result.map(|_| true),
+        }
+    }
+
+    /// Creates the methods of a class or trait declaration.
+    fn methods(
+        &self,
+        methods: &[StmtRef],
+        closure: &Rc<RefCell<Environment>>,
+    ) -> HashMap<String, LoxFunction> {
+        methods
+            .iter()
+            .filter_map(|method| {
+                if let Stmt::Function {
+                    id,
+                    name,
+                    params,
+                    body,
+                    is_async,
+                    is_variadic,
+                    is_generator,
+                } = &self.ast[*method]
+                {
+                    let function = LoxFunction::new(
+                        name.as_ref().clone(),
+                        Arc::clone(params),
+                        Body::new(Arc::clone(&self.ast), Arc::clone(body)),
+                        self.closure(id, closure),
+                        name.lexeme.eq("this"),
+                        *is_async,
+                        *is_variadic,
+                    )
+                    .with_generator(*is_generator);
+
+                    Some((name.lexeme.to_owned(), function))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Converts an index value into a position in a sequence of the given length.
+    pub(crate) fn element_index(
+        &self,
+        index: &ExprResult,
+        length: usize,
+        token: &Token,
+    ) -> Result<usize, RuntimeError> {
+        let ExprResult::Number(value) = index else {
+            return Err(RuntimeError::InvalidIndex {
+                line: token.line,
+                column: token.column,
+            });
+        };
+        if value.fract() != 0.0 || *value < 0.0 {
+            return Err(RuntimeError::InvalidIndex {
+                line: token.line,
+                column: token.column,
+            });
+        }
+
+        let index = *value as usize;
+        if index >= length {
+            return Err(RuntimeError::IndexOutOfBounds {
+                line: token.line,
+                column: token.column,
+                index,
+                length,
+            });
+        }
+
+        Ok(index)
+    }
+
+    fn lookup_variable(&self, name: &Token, id: &NodeId) -> Result<ExprResult, RuntimeError> {
+        if let Some(slot) = self.locals.borrow().get(id) {
             self.environment
                 .borrow()
-                .get_at(distance.to_owned(), &name.lexeme)
+                .get_at(*slot)
                 .ok_or(RuntimeError::UndefinedVariable {
                     line: name.line,
+                    column: name.column,
                     name: name.lexeme.to_owned(),
+                    suggestion: None,
                 })
         } else {
             self.globals.borrow().get(name)
@@ -97,231 +1023,234 @@ impl Default for Interpreter<'_> {
     }
 }
 
+/// Returns whether a `break` or `continue` with the `target` label applies to the loop with
+/// the given label.
+pub(crate) fn is_loop_target(label: &Option<Token>, target: &Option<String>) -> bool {
+    match target {
+        Some(target) => label.as_ref().is_some_and(|label| label.lexeme == *target),
+        None => true,
+    }
+}
+
+/// Whether the operands of a binary operator have types that only match by implicit
+/// conversion, which strict mode rejects.
+fn is_mismatched(operator: &TokenType, left: &ExprResult, right: &ExprResult) -> bool {
+    let different = std::mem::discriminant(left) != std::mem::discriminant(right);
+    match operator {
+        TokenType::Plus => different,
+        TokenType::EqualEqual | TokenType::BangEqual => {
+            different && *left != ExprResult::None && *right != ExprResult::None
+        }
+        _ => false,
+    }
+}
+
+/// Reports the first of the operands of `operator` that isn't a number.
+fn number_expected(operator: &Token, operands: &[&ExprResult]) -> RuntimeError {
+    let value = operands
+        .iter()
+        .find(|operand| !matches!(operand, ExprResult::Number(_)))
+        .unwrap_or(&operands[0]);
+
+    RuntimeError::NumberExpected {
+        line: operator.line,
+        column: operator.column,
+        operator: operator.lexeme.to_owned(),
+        value: value.to_element_string(),
+    }
+}
+
 impl Visitor<Expr, ExprResult, RuntimeError> for Interpreter<'_> {
     fn visit(&self, input: &Expr) -> Result<ExprResult, RuntimeError> {
         match input {
-            Expr::Assign { uuid, name, value } => {
-                let v = self.evaluate(value)?;
-
-                if let Some(distance) = self.locals.borrow().get(uuid) {
-                    self.environment
-                        .borrow_mut()
-                        .assign_at(distance.to_owned(), &name.lexeme, &v);
-                } else {
-                    self.globals.borrow_mut().assign(name, &v)?;
-                }
+            Expr::Assign { id, name, value } => {
+                let v = self.evaluate(*value)?;
+                self.assign_variable(id, name, &v)?;
 
                 Ok(v)
             }
+            Expr::Await {
+                id: _id,
+                keyword,
+                value,
+            } => match self.evaluate(*value)? {
+                ExprResult::Promise(promise) => {
+                    event_loop::wait_for(&self.event_loop, self, &promise, keyword)
+                }
+                v => Ok(v),
+            },
             Expr::Binary {
-                uuid: _uuid,
+                id: _id,
                 left,
                 operator,
                 right,
-            } => {
-                let left = self.evaluate(left)?;
-                let right = self.evaluate(right)?;
-
-                match &operator.token_type {
-                    TokenType::Greater => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::boolean(v1 > v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::GreaterEqual => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::boolean(v1 >= v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::Less => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::boolean(v1 < v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::LessEqual => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::boolean(v1 <= v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::BangEqual => Ok(ExprResult::boolean(left != right)),
-                    TokenType::EqualEqual => Ok(ExprResult::boolean(left == right)),
-                    TokenType::Minus => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::number(v1 - v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::Slash => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::number(v1 / v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::Star => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::number(v1 * v2))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    TokenType::Plus => match (left, right) {
-                        (ExprResult::Number(v1), ExprResult::Number(v2)) => {
-                            Ok(ExprResult::number(v1 + v2))
-                        }
-                        (ExprResult::String(v1), ExprResult::String(v2)) => {
-                            Ok(ExprResult::string(v1.clone() + v2.clone().as_str()))
-                        }
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
-                    },
-                    _ => Err(RuntimeError::InvalidValue {
-                        line: operator.line,
-                    }),
-                }
-            }
+            } => self.binary(*left, operator, *right),
             Expr::Call {
-                uuid: _uuid,
+                id: _id,
                 paren,
                 callee,
                 arguments,
-            } => {
-                let call = self.evaluate(callee)?;
-
-                if let ExprResult::Function(function) = call {
-                    if arguments.len() != function.arity() {
-                        return Err(RuntimeError::NonMatchingNumberOfArguments {
-                            line: paren.line,
-                        });
-                    }
-
-                    let args = arguments
-                        .iter()
-                        .map(|argument| self.evaluate(argument))
-                        .collect::<Result<Vec<_>, _>>()?;
-
-                    function.call(self, &args)
-                } else if let ExprResult::Class(class) = call {
-                    class.call(self, &[])
-                } else {
-                    Err(RuntimeError::UndefinedCallable { line: paren.line })
-                }
-            }
+            } => self.call(paren, *callee, arguments),
             Expr::Get {
-                uuid: _uuid,
+                id: _id,
                 object,
                 name,
+            } => match self.evaluate(*object)? {
+                ExprResult::Instance(instance) => instance.get(name),
+                ExprResult::List(list) => methods::list_method(&list, name),
+                ExprResult::Generator(generator) => methods::generator_method(&generator, name),
+                ExprResult::Coroutine(coroutine) => methods::coroutine_method(&coroutine, name),
+                _ => Err(RuntimeError::InvalidPropertyAccess {
+                    line: name.line,
+                    column: name.column,
+                }),
+            },
+            Expr::Grouping {
+                id: _id,
+                expression,
+            } => self.evaluate(*expression),
+            Expr::Index {
+                id: _id,
+                object,
+                bracket,
+                index,
+            } => self.index(*object, bracket, *index),
+            Expr::IndexSet {
+                id: _id,
+                object,
+                bracket,
+                index,
+                value,
+            } => self.index_set(*object, bracket, *index, *value),
+            Expr::Lambda { id: _id, function } => self.lambda(*function),
+            Expr::List {
+                id: _id,
+                bracket: _bracket,
+                elements,
             } => {
-                let obj = self.evaluate(object)?;
-                if let ExprResult::Instance(instance) = obj {
-                    instance.get(name)
-                } else {
-                    Err(RuntimeError::InvalidPropertyAccess { line: name.line })
+                let elements = elements
+                    .iter()
+                    .map(|element| self.evaluate(*element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let list = ExprResult::list(LoxList::new(elements));
+                self.allocate_value(&list)?;
+                Ok(list)
+            }
+            Expr::Map {
+                id: _id,
+                brace: _brace,
+                entries,
+            } => {
+                let map = LoxMap::default();
+                for (key, value) in entries {
+                    map.insert(self.evaluate(*key)?, self.evaluate(*value)?);
                 }
+                let map = ExprResult::map(map);
+                self.allocate_value(&map)?;
+                Ok(map)
             }
-            Expr::Grouping {
-                uuid: _uuid,
-                expression,
-            } => self.evaluate(expression),
-            Expr::Literal { uuid: _uuid, value } => match value {
+            Expr::Literal { id: _id, value } => match value {
                 LiteralValue::Number(value) => Ok(ExprResult::number(value.to_owned())),
                 LiteralValue::String(value) => Ok(ExprResult::string(value.clone())),
                 LiteralValue::Boolean(value) => Ok(ExprResult::boolean(*value)),
                 LiteralValue::None => Ok(ExprResult::none()),
             },
             Expr::Logical {
-                uuid: _uuid,
+                id: _id,
                 left,
                 operator,
                 right,
             } => {
-                let left_expr = self.evaluate(left)?;
+                let left_expr = self.evaluate(*left)?;
 
                 if operator.token_type == TokenType::Or {
-                    if left_expr.is_truthy() {
+                    if self.is_true(&left_expr, operator)? {
                         return Ok(left_expr);
                     }
-                } else if !left_expr.is_truthy() {
+                } else if !self.is_true(&left_expr, operator)? {
                     return Ok(left_expr);
                 }
 
-                self.evaluate(right)
+                self.evaluate(*right)
             }
             Expr::Set {
-                uuid: _uuid,
+                id: _id,
                 object,
                 name,
                 value,
             } => {
-                let obj = self.evaluate(object)?;
+                let obj = self.evaluate(*object)?;
                 if let ExprResult::Instance(instance) = obj {
-                    let v = self.evaluate(value)?;
+                    let v = self.evaluate(*value)?;
                     instance.set(name, v.to_owned());
 
                     Ok(v)
                 } else {
-                    Err(RuntimeError::InvalidFieldAccess { line: name.line })
+                    Err(RuntimeError::InvalidFieldAccess {
+                        line: name.line,
+                        column: name.column,
+                    })
                 }
             }
+            Expr::Stringify {
+                id: _id,
+                expression,
+            } => Ok(ExprResult::string(
+                self.stringify(&self.evaluate(*expression)?)?,
+            )),
             Expr::Super {
-                uuid,
+                id,
+                this_id,
                 keyword,
                 method,
+            } => self.super_method(id, this_id, keyword, method),
+            Expr::Ternary {
+                id: _id,
+                condition,
+                question,
+                then_branch,
+                else_branch,
             } => {
-                if let Some(distance) = self.locals.borrow().get(uuid) {
-                    let superclass = self.environment.borrow().get_at(*distance, "super");
-                    let object = self.environment.borrow().get_at(*distance - 1, "this");
-
-                    if let Some(ExprResult::Class(sc)) = superclass {
-                        if let Some(ExprResult::Instance(obj)) = object {
-                            if let Some(method) = sc.find_method(&method.lexeme) {
-                                return Ok(method.bind(&obj));
-                            }
-                        }
-                    }
-                }
+                let condition_result = self.evaluate(*condition)?;
 
-                Err(RuntimeError::UndefinedProperty { line: keyword.line })
+                if self.is_true(&condition_result, question)? {
+                    self.evaluate(*then_branch)
+                } else {
+                    self.evaluate(*else_branch)
+                }
             }
-            Expr::This { uuid, keyword } => self.lookup_variable(keyword, uuid),
+            Expr::This { id, keyword } => self.lookup_variable(keyword, id),
             Expr::Unary {
-                uuid: _uuid,
+                id: _id,
                 operator,
                 right,
             } => {
-                let right = self.evaluate(right)?;
+                let right = self.evaluate(*right)?;
 
                 match &operator.token_type {
                     TokenType::Minus => match right {
                         ExprResult::Number(value) => Ok(ExprResult::number(-value)),
-                        _ => Err(RuntimeError::NumberExpected {
-                            line: operator.line,
-                        }),
+                        _ => Err(number_expected(operator, &[&right])),
                     },
-                    TokenType::Bang => Ok(ExprResult::boolean(!right.is_truthy())),
+                    TokenType::Bang => Ok(ExprResult::boolean(!self.is_true(&right, operator)?)),
                     _ => Err(RuntimeError::InvalidValue {
                         line: operator.line,
+                        column: operator.column,
                     }),
                 }
             }
-            Expr::Variable { uuid, name } => self.lookup_variable(name, uuid),
+            Expr::Update {
+                id: _id,
+                operator,
+                target,
+                prefix,
+            } => self.update(operator, *target, *prefix),
+            Expr::Variable { id, name } => self.lookup_variable(name, id),
+            // Generators resume at yields before the expression is evaluated.
+            Expr::Yield { keyword, .. } => Err(RuntimeError::YieldInsideExpression {
+                line: keyword.line,
+                column: keyword.column,
+            }),
         }
     }
 }
@@ -330,126 +1259,249 @@ impl Visitor<Stmt, (), RuntimeError> for Interpreter<'_> {
     fn visit(&self, input: &Stmt) -> Result<(), RuntimeError> {
         match input {
             Stmt::Block { statements } => {
+                self.allocate(size_of::<Environment>())?;
                 let scoped_interpreter =
                     self.fork(Environment::new_enclosing(Rc::clone(&self.environment)));
                 scoped_interpreter.execute_block(statements)?;
             }
+            Stmt::Break { label, .. } => {
+                return Err(RuntimeError::Break {
+                    label: label.as_ref().as_ref().map(|label| label.lexeme.to_owned()),
+                })
+            }
+            Stmt::Continue { label, .. } => {
+                return Err(RuntimeError::Continue {
+                    label: label.as_ref().as_ref().map(|label| label.lexeme.to_owned()),
+                })
+            }
             Stmt::Class {
+                id,
                 name,
                 superclass,
+                traits,
+                fields,
                 methods,
             } => {
-                let sc_result = if let Some(sc) = superclass.as_ref() {
-                    Some(self.evaluate(sc)?)
+                let slot = self.environment.borrow_mut().reserve();
+                let sc_result = if let Some(sc) = superclass {
+                    Some(self.evaluate(*sc)?)
                 } else {
                     None
                 };
 
-                self.environment
-                    .borrow_mut()
-                    .define(&name.lexeme, ExprResult::none());
-
-                let enclosing_environment = if let Some(sc) = sc_result.to_owned() {
+                let enclosing_environment = if let Some(sc) = &sc_result {
                     let env = Environment::new_enclosing(Rc::clone(&self.environment));
-                    env.borrow_mut().define("super", sc);
+                    env.borrow_mut().define("super", sc.clone());
 
                     env
                 } else {
                     Rc::clone(&self.environment)
                 };
 
-                let functions = methods
-                    .iter()
-                    .filter_map(|method| {
-                        if let Stmt::Function { name, params, body } = method {
-                            let function = LoxFunction::new(
-                                *name.to_owned(),
-                                params.to_owned(),
-                                body.to_owned(),
-                                Rc::clone(&enclosing_environment),
-                                name.lexeme.eq("this"),
-                            );
-
-                            Some((name.lexeme.to_owned(), function))
-                        } else {
-                            None
+                let mut functions = self.methods(methods, &enclosing_environment);
+
+                /* methods of the class take precedence over methods of its traits. */
+                let mut included = HashMap::new();
+                for lox_trait in traits {
+                    let ExprResult::Trait(lox_trait) = self.evaluate(*lox_trait)? else {
+                        return Err(RuntimeError::TraitInvalidType {
+                            line: name.line,
+                            column: name.column,
+                        });
+                    };
+
+                    for (method_name, method) in lox_trait.methods {
+                        if !functions.contains_key(&method_name)
+                            && included.insert(method_name.to_owned(), method).is_some()
+                        {
+                            return Err(RuntimeError::TraitMethodConflict {
+                                line: name.line,
+                                column: name.column,
+                                name: method_name,
+                            });
                         }
-                    })
-                    .collect();
+                    }
+                }
+                functions.extend(included);
 
-                let lox_superclass = if let Some(sc) = sc_result.to_owned() {
-                    if let ExprResult::Class(c) = sc {
-                        Some(c)
-                    } else {
-                        return Err(RuntimeError::SuperclassInvalidType { line: name.line });
+                let lox_superclass = match sc_result {
+                    Some(ExprResult::Class(c)) => Some(c),
+                    Some(_) => {
+                        return Err(RuntimeError::SuperclassInvalidType {
+                            line: name.line,
+                            column: name.column,
+                        });
                     }
-                } else {
-                    None
+                    None => None,
                 };
 
-                let class = LoxClass::new(*name.to_owned(), lox_superclass, functions);
+                let field_initializer = (!fields.is_empty()).then(|| {
+                    LoxFunction::new(
+                        name.as_ref().clone(),
+                        Arc::new([]),
+                        Body::new(Arc::clone(&self.ast), Arc::clone(fields)),
+                        self.closure(id, &enclosing_environment),
+                        false,
+                        false,
+                        false,
+                    )
+                });
 
-                self.environment
-                    .borrow_mut()
-                    .assign(name, &ExprResult::class(class))?;
+                let class = LoxClass::new(
+                    name.as_ref().clone(),
+                    lox_superclass,
+                    field_initializer,
+                    functions,
+                );
+
+                self.environment.borrow_mut().define_reserved(
+                    slot,
+                    &name.lexeme,
+                    ExprResult::class(class),
+                );
+            }
+            Stmt::Destructure {
+                keyword,
+                pattern,
+                names,
+                initializer,
+            } => {
+                let value = self.evaluate(*initializer)?;
+                self.destructure(keyword, *pattern, names, value)?;
             }
             Stmt::Expression { expression } => {
-                self.evaluate(expression)?;
+                self.evaluate(*expression)?;
             }
-            Stmt::Function { name, params, body } => {
-                self.environment
-                    .borrow_mut()
-                    .define(&name.lexeme, ExprResult::none());
+            Stmt::ForIn {
+                keyword,
+                label,
+                variable,
+                iterable,
+                body,
+            } => {
+                let mut items = Items::new(self.evaluate(*iterable)?, keyword)?;
+
+                while let Some(item) = items.next(self, keyword)? {
+                    let scoped_interpreter =
+                        self.fork(Environment::new_enclosing(Rc::clone(&self.environment)));
+                    scoped_interpreter.define(variable, item);
 
+                    if !scoped_interpreter.execute_loop_body(*body, label)? {
+                        break;
+                    }
+                }
+            }
+            Stmt::Function {
+                id,
+                name,
+                params,
+                body,
+                is_async,
+                is_variadic,
+                is_generator,
+            } => {
+                /* the slot exists before the closure, so that recursive functions capture it. */
+                let slot = self.environment.borrow_mut().reserve();
                 let function = LoxFunction::new(
-                    *name.to_owned(),
-                    params.to_owned(),
-                    body.to_owned(),
-                    Rc::clone(&self.environment),
+                    name.as_ref().clone(),
+                    Arc::clone(params),
+                    Body::new(Arc::clone(&self.ast), Arc::clone(body)),
+                    self.closure(id, &self.environment),
                     false,
-                );
+                    *is_async,
+                    *is_variadic,
+                )
+                .with_generator(*is_generator)
+                .with_compiled(self.compiled.borrow().get(id).cloned());
 
-                self.environment
-                    .borrow_mut()
-                    .assign(name, &ExprResult::function(function.clone()))?;
+                self.environment.borrow_mut().define_reserved(
+                    slot,
+                    &name.lexeme,
+                    ExprResult::function(function),
+                );
             }
             Stmt::If {
+                keyword,
                 condition,
                 then_branch,
                 else_branch,
             } => {
-                let condition_result = self.evaluate(condition)?;
+                let condition_result = self.evaluate(*condition)?;
 
-                if condition_result.is_truthy() {
-                    self.execute(then_branch)?
-                } else if let Some(branch) = *else_branch.to_owned() {
-                    self.execute(&branch)?
+                if self.is_true(&condition_result, keyword)? {
+                    self.execute(*then_branch)?
+                } else if let Some(branch) = else_branch {
+                    self.execute(*branch)?
                 }
             }
+            Stmt::Import { keyword, path } => self.import(keyword, path)?,
             Stmt::Print { expression } => {
-                let value = self.evaluate(expression)?;
-                let mut stream = self.output_stream.borrow_mut();
-                writeln!(stream, "{}", value).map_err(|_| RuntimeError::OutputError)?;
-                stream.flush().map_err(|_| RuntimeError::OutputError)?;
+                let value = self.evaluate(*expression)?;
+                let value = self.stringify(&value)?;
+                self.write_output(format!("{}\n", value).as_bytes())?;
             }
             Stmt::Return {
                 keyword: _keyword,
                 value,
             } => {
-                if let Some(expr) = *value.to_owned() {
-                    let ret_val = self.evaluate(&expr)?;
-                    return Err(RuntimeError::Return { ret_val });
-                }
+                let ret_val = match value {
+                    Some(expr) => self.evaluate(*expr)?,
+                    None => ExprResult::none(),
+                };
+                return Err(RuntimeError::Return {
+                    ret_val: Box::new(ret_val),
+                });
             }
-            Stmt::Var { name, initializer } => {
-                let value = self.evaluate(initializer)?;
-                self.environment.borrow_mut().define(&name.lexeme, value);
+            Stmt::Trait { name, methods } => {
+                let lox_trait = LoxTrait::new(
+                    name.as_ref().clone(),
+                    self.methods(methods, &self.environment),
+                );
+
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, ExprResult::trait_value(lox_trait));
+            }
+            Stmt::Var {
+                name,
+                initializer,
+                is_const,
+            } => {
+                let value = match initializer {
+                    Some(initializer) => self.evaluate(*initializer)?,
+                    None => ExprResult::none(),
+                };
+                if *is_const {
+                    self.environment
+                        .borrow_mut()
+                        .define_constant(&name.lexeme, value);
+                } else {
+                    self.environment.borrow_mut().define(&name.lexeme, value);
+                }
             }
-            Stmt::While { condition, body } => {
-                while self.evaluate(condition)?.is_truthy() {
-                    self.execute(body)?;
+            Stmt::While {
+                keyword,
+                label,
+                condition,
+                body,
+                increment,
+            } => {
+                while self.is_true(&self.evaluate(*condition)?, keyword)? {
+                    if !self.execute_loop_body(*body, label)? {
+                        break;
+                    }
+                    if let Some(increment) = increment {
+                        self.evaluate(*increment)?;
+                    }
                 }
             }
+            // Bodies of generator functions are executed by the generator.
+            Stmt::Yield { keyword, .. } => {
+                return Err(RuntimeError::TopLevelYield {
+                    line: keyword.line,
+                    column: keyword.column,
+                })
+            }
         }
 
         Ok(())
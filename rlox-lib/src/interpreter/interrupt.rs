@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval in which sleeping threads check for an interruption.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Handle for stopping a running script from another thread, e.g. from a signal handler.
+///
+/// The interpreter checks the handle between statements and aborts with
+/// [`RuntimeError::Interrupted`](crate::interpreter::runtime_error::RuntimeError::Interrupted).
+#[derive(Clone, Debug, Default)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Returns the address of the flag, for machine code checking for an interruption.
+    #[cfg(feature = "jit")]
+    pub(crate) fn as_ptr(&self) -> *const bool {
+        self.interrupted.as_ptr()
+    }
+
+    pub fn reset(&self) {
+        self.interrupted.store(false, Ordering::SeqCst);
+    }
+
+    /// Sleeps for `duration`, but wakes up early on interruption. Returns `false` if the sleep
+    /// was interrupted. A duration too long to be represented is slept until interrupted.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now().checked_add(duration);
+        loop {
+            if self.is_interrupted() {
+                return false;
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => POLL_INTERVAL,
+            };
+            if remaining.is_zero() {
+                return true;
+            }
+
+            thread::sleep(remaining.min(POLL_INTERVAL));
+        }
+    }
+}
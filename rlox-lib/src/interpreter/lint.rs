@@ -0,0 +1,199 @@
+use crate::base::diagnostic::DiagnosticError;
+use crate::base::scanner::Pragma;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LintError {
+    #[error("Unknown lint {name:?}!")]
+    UnknownLint { name: String },
+    #[error("Unknown lint level {level:?}!")]
+    UnknownLevel { level: String },
+    #[error("{line:?}: Invalid pragma {pragma:?}!")]
+    InvalidPragma { line: usize, pragma: String },
+}
+
+impl DiagnosticError for LintError {
+    fn code(&self) -> &'static str {
+        match self {
+            LintError::UnknownLint { .. } => "UnknownLint",
+            LintError::UnknownLevel { .. } => "UnknownLevel",
+            LintError::InvalidPragma { .. } => "InvalidPragma",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        match self {
+            LintError::InvalidPragma { line, .. } => Some(*line),
+            LintError::UnknownLint { .. } | LintError::UnknownLevel { .. } => None,
+        }
+    }
+
+    fn column(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Diagnostics the resolver reports without failing, unless they are denied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Lint {
+    /// A local variable that is declared but never read.
+    UnusedVariable,
+    /// A function declared in a block or function that is never called or otherwise used.
+    /// Functions at the top level are exempt, as later scripts may call them.
+    UnusedFunction,
+    /// Statements of a block following a `return` that always runs.
+    UnreachableCode,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 3] = [
+        Lint::UnusedVariable,
+        Lint::UnusedFunction,
+        Lint::UnreachableCode,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused_variable",
+            Lint::UnusedFunction => "unused_function",
+            Lint::UnreachableCode => "unreachable_code",
+        }
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warn
+    }
+
+    /// Whether the lint is promoted from a warning to an error in strict mode.
+    fn denied_in_strict_mode(&self) -> bool {
+        matches!(self, Lint::UnreachableCode)
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = LintError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Lint::ALL
+            .into_iter()
+            .find(|lint| lint.name() == name)
+            .ok_or_else(|| LintError::UnknownLint {
+                name: name.to_owned(),
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl FromStr for Severity {
+    type Err = LintError;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level {
+            "allow" => Ok(Severity::Allow),
+            "warn" => Ok(Severity::Warn),
+            "deny" => Ok(Severity::Deny),
+            _ => Err(LintError::UnknownLevel {
+                level: level.to_owned(),
+            }),
+        }
+    }
+}
+
+/// A lint reported at [`Severity::Warn`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub lint: Lint,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: Warning: {} [{}]",
+            self.line, self.message, self.lint
+        )
+    }
+}
+
+/// The severity of every lint. Later settings override earlier ones, so settings from the
+/// manifest, the command line and source pragmas should be applied in that order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LintLevels {
+    levels: HashMap<Lint, Severity>,
+    deny_warnings: bool,
+}
+
+impl LintLevels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, lint: Lint, severity: Severity) {
+        self.levels.insert(lint, severity);
+    }
+
+    /// Sets a level given by name, e.g. `unused_variable = "allow"` in a manifest.
+    pub fn set_by_name(&mut self, lint: &str, level: &str) -> Result<(), LintError> {
+        self.set(lint.parse()?, level.parse()?);
+        Ok(())
+    }
+
+    /// Turns every lint that would be reported as a warning into an error.
+    pub fn deny_warnings(&mut self) {
+        self.deny_warnings = true;
+    }
+
+    /// Applies pragmas like `// rlox: allow(unused_variable, ...)` found by the scanner.
+    pub fn apply_pragmas(&mut self, pragmas: &[Pragma]) -> Result<(), LintError> {
+        for pragma in pragmas {
+            let invalid = || LintError::InvalidPragma {
+                line: pragma.line,
+                pragma: pragma.directive.to_owned(),
+            };
+
+            let (level, lints) = pragma.directive.split_once('(').ok_or_else(invalid)?;
+            let lints = lints.trim_end().strip_suffix(')').ok_or_else(invalid)?;
+            let severity: Severity = level.trim().parse().map_err(|_| invalid())?;
+
+            for lint in lints.split(',') {
+                let lint: Lint = lint.trim().parse().map_err(|_| invalid())?;
+                self.set(lint, severity);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn severity(&self, lint: Lint, strict: bool) -> Severity {
+        let severity = self
+            .levels
+            .get(&lint)
+            .copied()
+            .unwrap_or(lint.default_severity());
+
+        match severity {
+            Severity::Warn if self.deny_warnings => Severity::Deny,
+            Severity::Warn if strict && lint.denied_in_strict_mode() => Severity::Deny,
+            severity => severity,
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use crate::base::expr_result::{ExprResult, LoxList, NativeFunction};
+use crate::base::scanner::Token;
+use crate::interpreter::generator::{LoxCoroutine, LoxGenerator, Resumed};
+use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::runtime_error::RuntimeError;
+use crate::interpreter::suggestion;
+use std::cmp::Ordering;
+
+type ListMethod =
+    fn(&Interpreter, &Token, &LoxList, &[ExprResult]) -> Result<ExprResult, RuntimeError>;
+
+/// The built-in methods of lists, with their arity.
+const LIST_METHODS: [(&str, usize, ListMethod); 10] = [
+    ("push", 1, push),
+    ("pop", 0, pop),
+    ("insert", 2, insert),
+    ("remove", 1, remove),
+    ("contains", 1, contains),
+    ("indexOf", 1, index_of),
+    ("sort", 0, sort),
+    ("map", 1, map),
+    ("filter", 1, filter),
+    ("reduce", 2, reduce),
+];
+
+/// Returns the built-in method of a list with the given name, bound to the list.
+pub fn list_method(list: &LoxList, name: &Token) -> Result<ExprResult, RuntimeError> {
+    let Some((_, arity, method)) = LIST_METHODS
+        .into_iter()
+        .find(|(method, _, _)| *method == name.lexeme)
+    else {
+        return Err(undefined_method(
+            name,
+            &LIST_METHODS.map(|(method, _, _)| method),
+        ));
+    };
+
+    let list = list.to_owned();
+    Ok(ExprResult::native_function(NativeFunction::new(
+        &name.lexeme,
+        arity,
+        move |interpreter, paren, arguments| method(interpreter, paren, &list, arguments),
+    )))
+}
+
+/// Returns the built-in method of a generator with the given name, bound to the generator.
+/// `next()` resumes the generator and returns the next value, or `nil` once it is done.
+pub fn generator_method(
+    generator: &LoxGenerator,
+    name: &Token,
+) -> Result<ExprResult, RuntimeError> {
+    let generator = generator.to_owned();
+    let method = match name.lexeme.as_str() {
+        "next" => NativeFunction::new("next", 0, move |interpreter, paren, _| {
+            match generator.resume(interpreter, paren, ExprResult::none())? {
+                Resumed::Yielded(value) => Ok(value),
+                Resumed::Returned(_) => Ok(ExprResult::none()),
+            }
+        }),
+        "done" => NativeFunction::new("done", 0, move |_, _, _| {
+            Ok(ExprResult::boolean(generator.is_done()))
+        }),
+        _ => return Err(undefined_method(name, &["next", "done"])),
+    };
+
+    Ok(ExprResult::native_function(method))
+}
+
+/// Returns the built-in method of a coroutine with the given name, bound to the coroutine.
+/// `resume(...)` passes its arguments to the function on the first call and its first argument
+/// to the suspended `yield` afterwards.
+pub fn coroutine_method(
+    coroutine: &LoxCoroutine,
+    name: &Token,
+) -> Result<ExprResult, RuntimeError> {
+    let coroutine = coroutine.to_owned();
+    let method = match name.lexeme.as_str() {
+        "resume" => NativeFunction::variadic("resume", 0, move |interpreter, paren, arguments| {
+            coroutine.resume(interpreter, paren, arguments)
+        }),
+        "status" => NativeFunction::new("status", 0, move |_, _, _| {
+            Ok(ExprResult::string(String::from(coroutine.status())))
+        }),
+        _ => return Err(undefined_method(name, &["resume", "status"])),
+    };
+
+    Ok(ExprResult::native_function(method))
+}
+
+/// Reports a call of a built-in method that doesn't exist, suggesting a similar one.
+fn undefined_method(name: &Token, methods: &[&str]) -> RuntimeError {
+    RuntimeError::UndefinedProperty {
+        line: name.line,
+        column: name.column,
+        suggestion: suggestion::closest(&name.lexeme, methods.iter().copied()),
+    }
+}
+
+/// Calls a function passed as an argument to a method.
+fn call_argument(
+    interpreter: &Interpreter,
+    paren: &Token,
+    function: &ExprResult,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let callable = function
+        .as_callable()
+        .ok_or(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        })?;
+    if !callable.accepts(arguments.len()) {
+        return Err(RuntimeError::NonMatchingNumberOfArguments {
+            line: paren.line,
+            column: paren.column,
+        });
+    }
+
+    callable.call(interpreter, paren, arguments)
+}
+
+/// Appends a value to the end of the list.
+fn push(
+    _interpreter: &Interpreter,
+    _paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    list.elements.borrow_mut().push(arguments[0].to_owned());
+    Ok(ExprResult::none())
+}
+
+/// Removes and returns the last element of the list, or nil if the list is empty.
+fn pop(
+    _interpreter: &Interpreter,
+    _paren: &Token,
+    list: &LoxList,
+    _arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(list.elements.borrow_mut().pop().unwrap_or_default())
+}
+
+/// Inserts a value at an index, shifting all following elements. The index may be the length
+/// of the list, which appends the value.
+fn insert(
+    interpreter: &Interpreter,
+    paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let length = list.len();
+    let index = interpreter
+        .element_index(&arguments[0], length + 1, paren)
+        .map_err(|error| match error {
+            RuntimeError::IndexOutOfBounds {
+                line,
+                column,
+                index,
+                ..
+            } => RuntimeError::IndexOutOfBounds {
+                line,
+                column,
+                index,
+                length,
+            },
+            error => error,
+        })?;
+
+    list.elements
+        .borrow_mut()
+        .insert(index, arguments[1].to_owned());
+    Ok(ExprResult::none())
+}
+
+/// Removes and returns the element at an index, shifting all following elements.
+fn remove(
+    interpreter: &Interpreter,
+    paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let index = interpreter.element_index(&arguments[0], list.len(), paren)?;
+    Ok(list.elements.borrow_mut().remove(index))
+}
+
+fn contains(
+    _interpreter: &Interpreter,
+    _paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(ExprResult::boolean(
+        list.elements.borrow().contains(&arguments[0]),
+    ))
+}
+
+/// Returns the index of the first element equal to a value, or -1 if there is none.
+fn index_of(
+    _interpreter: &Interpreter,
+    _paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let index = list
+        .elements
+        .borrow()
+        .iter()
+        .position(|element| *element == arguments[0])
+        .map_or(-1.0, |index| index as f64);
+    Ok(ExprResult::number(index))
+}
+
+/// Sorts a list of numbers or a list of strings in ascending order.
+fn sort(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    list: &LoxList,
+    _arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let mut elements = list.elements.borrow_mut();
+    let comparable = elements
+        .iter()
+        .all(|element| matches!(element, ExprResult::Number(_)))
+        || elements
+            .iter()
+            .all(|element| matches!(element, ExprResult::String(_)));
+    if !comparable {
+        return Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        });
+    }
+
+    elements.sort_by(|a, b| match (a, b) {
+        (ExprResult::Number(a), ExprResult::Number(b)) => a.total_cmp(b),
+        (ExprResult::String(a), ExprResult::String(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    });
+    Ok(ExprResult::none())
+}
+
+/// Returns a new list with the results of calling a function with each element.
+fn map(
+    interpreter: &Interpreter,
+    paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let elements = list
+        .to_vec()
+        .into_iter()
+        .map(|element| call_argument(interpreter, paren, &arguments[0], &[element]))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ExprResult::list(LoxList::new(elements)))
+}
+
+/// Returns a new list with the elements for which a function returns a truthy value.
+fn filter(
+    interpreter: &Interpreter,
+    paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let mut elements = vec![];
+    for element in list.to_vec() {
+        let keep = call_argument(interpreter, paren, &arguments[0], &[element.to_owned()])?;
+        if interpreter.is_true(&keep, paren)? {
+            elements.push(element);
+        }
+    }
+    Ok(ExprResult::list(LoxList::new(elements)))
+}
+
+/// Combines the elements from first to last by calling a function with the result so far,
+/// starting with an initial value, and each element.
+fn reduce(
+    interpreter: &Interpreter,
+    paren: &Token,
+    list: &LoxList,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    list.to_vec()
+        .into_iter()
+        .try_fold(arguments[1].to_owned(), |result, element| {
+            call_argument(interpreter, paren, &arguments[0], &[result, element])
+        })
+}
@@ -1,4 +1,20 @@
+pub mod budget;
 pub mod environment;
+pub mod event_loop;
+pub mod generator;
+pub mod heap;
+pub mod hot_reload;
+#[allow(clippy::module_inception)]
 pub mod interpreter;
+pub mod interrupt;
+pub mod lint;
+pub mod methods;
+pub mod modules;
+pub mod natives;
+pub mod options;
+pub mod random;
 pub mod resolver;
 pub mod runtime_error;
+pub mod suggestion;
+pub mod transfer;
+pub mod worker;
@@ -0,0 +1,88 @@
+use crate::base::ast::Ast;
+use crate::base::parser::Parser;
+use crate::base::scanner::{Scanner, Token};
+use crate::base::source::decode_source;
+use crate::base::stmt::Stmt;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A module in the module cache of an interpreter.
+pub(crate) enum Module {
+    /// The module is being executed. Importing it again means that modules import each other.
+    Loading,
+    /// The top-level declarations of an executed module.
+    Loaded(Rc<RefCell<Environment>>),
+}
+
+/// Modules by canonical path.
+pub(crate) type Modules = HashMap<PathBuf, Module>;
+
+/// Resolves the path of an `import` relative to the directory of the importing script, or to
+/// the working directory for scripts without file. Modules not found there are searched in
+/// `import_paths`, in order.
+pub(crate) fn module_path(
+    keyword: &Token,
+    path: &str,
+    import_paths: &[PathBuf],
+) -> Result<PathBuf, RuntimeError> {
+    let directory = keyword
+        .file
+        .as_deref()
+        .and_then(|file| Path::new(file).parent())
+        .unwrap_or(Path::new(""));
+
+    std::iter::once(directory)
+        .chain(import_paths.iter().map(PathBuf::as_path))
+        .find_map(|directory| directory.join(path).canonicalize().ok())
+        .ok_or_else(|| RuntimeError::ModuleNotFound {
+            line: keyword.line,
+            column: keyword.column,
+            path: path.to_owned(),
+        })
+}
+
+/// Reads and parses a module. Errors are reported with the path of the module, as scanner and
+/// parser errors only know the line.
+pub(crate) fn parse_module(
+    keyword: &Token,
+    path: &Path,
+    lossy_utf8: bool,
+) -> Result<Arc<Ast>, RuntimeError> {
+    let error = |message: String| RuntimeError::ModuleError {
+        line: keyword.line,
+        column: keyword.column,
+        message: format!("{}:{}", path.display(), message),
+    };
+
+    let bytes = std::fs::read(path).map_err(|e| error(e.to_string()))?;
+    let source = decode_source(&bytes, lossy_utf8).map_err(|e| error(e.to_string()))?;
+    let tokens = Scanner::new_with_file(&source, &path.display().to_string())
+        .scan_tokens()
+        .map_err(|e| error(e.to_string()))?;
+
+    Parser::new(tokens)
+        .parse()
+        .map_err(|e| error(e.to_string()))
+}
+
+/// Returns the names declared at the top level of a module.
+pub(crate) fn declared_names(ast: &Ast) -> Vec<String> {
+    ast.statements()
+        .iter()
+        .flat_map(|statement| match &ast[*statement] {
+            Stmt::Class { name, .. }
+            | Stmt::Function { name, .. }
+            | Stmt::Trait { name, .. }
+            | Stmt::Var { name, .. } => vec![name.lexeme.to_owned()],
+            Stmt::Destructure { names, .. } => {
+                names.iter().map(|name| name.lexeme.to_owned()).collect()
+            }
+            _ => vec![],
+        })
+        .collect()
+}
@@ -0,0 +1,820 @@
+use crate::base::expr_result::{
+    Callable, ExprResult, LoxChannel, LoxInstance, LoxList, LoxTask, NativeFunction, TaskError,
+};
+use crate::base::scanner::Token;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::generator::LoxCoroutine;
+use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::runtime_error::RuntimeError;
+use crate::interpreter::suggestion;
+use crate::interpreter::transfer::Transfer;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Stack size of task threads, matching the main thread so that recursive scripts behave
+/// the same inside and outside of tasks.
+pub(crate) const TASK_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Standard library features, each enabling a group of native functions.
+pub const FEATURES: &[&str] = &["tasks", "async"];
+
+/// Returns the standard library feature a built-in native function belongs to.
+pub fn feature(name: &str) -> Option<&'static str> {
+    match name {
+        "spawn" | "join" | "channel" | "send" | "recv" => Some("tasks"),
+        "sleepAsync" | "readFileAsync" => Some("async"),
+        _ => None,
+    }
+}
+
+/// Returns all built-in native functions.
+pub fn builtins() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("clock", 0, clock),
+        NativeFunction::new("sleep", 1, sleep),
+        NativeFunction::new("readLine", 0, read_line),
+        NativeFunction::new("write", 1, write),
+        NativeFunction::new("printErr", 1, print_err),
+        NativeFunction::variadic("format", 1, format),
+        NativeFunction::new("getField", 2, get_field),
+        NativeFunction::new("setField", 3, set_field),
+        NativeFunction::new("hasField", 2, has_field),
+        NativeFunction::new("fields", 1, fields),
+        NativeFunction::new("arity", 1, arity),
+        NativeFunction::new("name", 1, name),
+        NativeFunction::new("env", 1, env),
+        NativeFunction::new("exit", 1, exit),
+        NativeFunction::new("random", 0, random),
+        NativeFunction::new("randomInt", 2, random_int),
+        NativeFunction::new("setRandomSeed", 1, set_random_seed),
+        unary("sqrt", f64::sqrt),
+        unary("abs", f64::abs),
+        unary("floor", f64::floor),
+        unary("ceil", f64::ceil),
+        unary("round", f64::round),
+        unary("sin", f64::sin),
+        unary("cos", f64::cos),
+        unary("log", f64::ln),
+        binary("min", f64::min),
+        binary("max", f64::max),
+        binary("pow", f64::powf),
+        NativeFunction::new("len", 1, len),
+        NativeFunction::new("substring", 3, substring),
+        NativeFunction::new("indexOf", 2, index_of),
+        NativeFunction::new("toUpper", 1, to_upper),
+        NativeFunction::new("toLower", 1, to_lower),
+        NativeFunction::new("trim", 1, trim),
+        NativeFunction::new("split", 2, split),
+        NativeFunction::new("replace", 3, replace),
+        NativeFunction::new("charAt", 2, char_at),
+        NativeFunction::new("coroutine", 1, coroutine),
+        NativeFunction::new("spawn", 1, spawn),
+        NativeFunction::new("join", 1, join),
+        NativeFunction::new("channel", 0, channel),
+        NativeFunction::new("send", 2, send),
+        NativeFunction::new("recv", 1, recv),
+        NativeFunction::new("sleepAsync", 1, sleep_async),
+        NativeFunction::new("readFileAsync", 1, read_file_async),
+    ]
+}
+
+/// Returns the built-in native function with the given name.
+pub fn builtin(name: &str) -> Option<NativeFunction> {
+    builtins()
+        .into_iter()
+        .find(|function| function.name == name)
+}
+
+pub fn define_builtins(globals: &Rc<RefCell<Environment>>) {
+    for function in builtins() {
+        globals.borrow_mut().define(
+            &function.name.to_owned(),
+            ExprResult::native_function(function),
+        );
+    }
+    globals
+        .borrow_mut()
+        .define_constant("PI", ExprResult::number(std::f64::consts::PI));
+    define_arguments(globals, &[]);
+}
+
+/// Defines the `ARGS` global, a list of the arguments passed to the script.
+pub fn define_arguments(globals: &Rc<RefCell<Environment>>, arguments: &[String]) {
+    let arguments = arguments
+        .iter()
+        .map(|argument| ExprResult::string(argument.to_owned()))
+        .collect();
+    globals
+        .borrow_mut()
+        .define_constant("ARGS", ExprResult::list(LoxList::new(arguments)));
+}
+
+/// Writes a value to the output stream like `print`, but without a trailing newline.
+fn write(
+    interpreter: &Interpreter,
+    _paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = interpreter.stringify(&arguments[0])?;
+    interpreter.write_output(value.as_bytes())?;
+    Ok(ExprResult::none())
+}
+
+/// Writes a value followed by a newline to the error stream.
+fn print_err(
+    interpreter: &Interpreter,
+    _paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = interpreter.stringify(&arguments[0])?;
+    interpreter.write_error(format!("{}\n", value).as_bytes())?;
+    Ok(ExprResult::none())
+}
+
+/// Replaces each `{}` placeholder of a format string with the next argument, converted like
+/// by `print`. `{{` and `}}` stand for literal braces.
+fn format(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let template = string(paren, &arguments[0])?;
+    let mut values = arguments[1..].iter();
+    let mut result = String::new();
+    let mut characters = template.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match (character, characters.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                characters.next();
+                result.push(character);
+            }
+            ('{', Some('}')) => {
+                characters.next();
+                let value = values.next().ok_or(RuntimeError::InvalidArgument {
+                    line: paren.line,
+                    column: paren.column,
+                })?;
+                result.push_str(&interpreter.stringify(value)?);
+            }
+            ('{', _) | ('}', _) => {
+                return Err(RuntimeError::InvalidArgument {
+                    line: paren.line,
+                    column: paren.column,
+                })
+            }
+            _ => result.push(character),
+        }
+    }
+    if values.next().is_some() {
+        return Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        });
+    }
+
+    Ok(ExprResult::string(result))
+}
+
+/// Blocks the script for the given number of milliseconds. The sleep ends early with an error
+/// when the interpreter is interrupted.
+fn sleep(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    if interpreter.interrupt.sleep(duration(paren, &arguments[0])?) {
+        Ok(ExprResult::none())
+    } else {
+        Err(RuntimeError::Interrupted)
+    }
+}
+
+/// Returns the value of an instance field by name.
+fn get_field(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let instance = instance(paren, &arguments[0])?;
+    let name = string(paren, &arguments[1])?;
+
+    let fields = instance.fields.borrow();
+    fields
+        .get(name)
+        .cloned()
+        .ok_or_else(|| RuntimeError::UndefinedProperty {
+            line: paren.line,
+            column: paren.column,
+            suggestion: suggestion::closest(name, fields.keys().map(String::as_str)),
+        })
+}
+
+/// Sets an instance field by name and returns the value.
+fn set_field(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let instance = instance(paren, &arguments[0])?;
+    let name = string(paren, &arguments[1])?;
+
+    instance
+        .fields
+        .borrow_mut()
+        .insert(name.to_owned(), arguments[2].to_owned());
+    Ok(arguments[2].to_owned())
+}
+
+fn has_field(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let instance = instance(paren, &arguments[0])?;
+    let name = string(paren, &arguments[1])?;
+
+    Ok(ExprResult::boolean(
+        instance.fields.borrow().contains_key(name),
+    ))
+}
+
+/// Returns the names of the fields of an instance in alphabetical order. Methods are not
+/// included.
+fn fields(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let instance = instance(paren, &arguments[0])?;
+
+    let mut names: Vec<String> = instance.fields.borrow().keys().cloned().collect();
+    names.sort();
+    Ok(ExprResult::list(LoxList::new(
+        names.into_iter().map(ExprResult::string).collect(),
+    )))
+}
+
+/// Returns the number of arguments a function or class expects. For callables with a rest
+/// parameter, this is the minimum number of arguments.
+fn arity(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let callable = arguments[0]
+        .as_callable()
+        .ok_or(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        })?;
+    Ok(ExprResult::number(callable.arity() as f64))
+}
+
+/// Returns the name of a function or class. Anonymous functions are named `lambda`.
+fn name(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let name = match &arguments[0] {
+        ExprResult::Function(function) => function.name.lexeme.to_owned(),
+        ExprResult::NativeFunction(function) => function.name.to_owned(),
+        ExprResult::Class(class) => class.name.lexeme.to_owned(),
+        _ => {
+            return Err(RuntimeError::InvalidArgument {
+                line: paren.line,
+                column: paren.column,
+            })
+        }
+    };
+    Ok(ExprResult::string(name))
+}
+
+/// Reads a line from the input stream, or returns nil at the end of the stream.
+fn read_line(
+    interpreter: &Interpreter,
+    paren: &Token,
+    _arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(interpreter
+        .read_line(paren)?
+        .map(ExprResult::string)
+        .unwrap_or_default())
+}
+
+/// Returns the value of an environment variable, or nil if it isn't set.
+fn env(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(std::env::var(string(paren, &arguments[0])?)
+        .map(ExprResult::string)
+        .unwrap_or_default())
+}
+
+/// Stops the script, which makes the command line interpreter exit with the given code.
+fn exit(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    match number(paren, &arguments[0])? {
+        code if code.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(&code) => {
+            Err(RuntimeError::Exit { code: code as i32 })
+        }
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Returns a pseudo-random number in the range `[0, 1)`.
+fn random(
+    interpreter: &Interpreter,
+    _paren: &Token,
+    _arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(ExprResult::number(interpreter.random.next_f64()))
+}
+
+/// Returns a pseudo-random integer between two integers, including both bounds.
+fn random_int(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let low = number(paren, &arguments[0])?;
+    let high = number(paren, &arguments[1])?;
+    if low.fract() != 0.0 || high.fract() != 0.0 || low > high {
+        return Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        });
+    }
+
+    let range = (high - low) as u64 + 1;
+    let offset = interpreter.random.next_u64() % range;
+    Ok(ExprResult::number(low + offset as f64))
+}
+
+/// Seeds the random number generator, making the following random numbers reproducible.
+fn set_random_seed(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    interpreter
+        .random
+        .seed(number(paren, &arguments[0])?.to_bits());
+    Ok(ExprResult::none())
+}
+
+/// Returns the duration of a number of milliseconds passed as an argument to a native function.
+/// Negative, infinite and durations too long to wait for are rejected.
+fn duration(paren: &Token, argument: &ExprResult) -> Result<Duration, RuntimeError> {
+    Duration::try_from_secs_f64(number(paren, argument)? / 1000.0)
+        .ok()
+        .filter(|duration| Instant::now().checked_add(*duration).is_some())
+        .ok_or(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        })
+}
+
+/// Returns the number passed as an argument to a native function.
+fn number(paren: &Token, argument: &ExprResult) -> Result<f64, RuntimeError> {
+    match argument {
+        ExprResult::Number(value) => Ok(*value),
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Returns a native function applying a math operation to one number.
+fn unary(name: &str, operation: fn(f64) -> f64) -> NativeFunction {
+    NativeFunction::new(name, 1, move |_, paren, arguments| {
+        Ok(ExprResult::number(operation(number(paren, &arguments[0])?)))
+    })
+}
+
+/// Returns a native function applying a math operation to two numbers.
+fn binary(name: &str, operation: fn(f64, f64) -> f64) -> NativeFunction {
+    NativeFunction::new(name, 2, move |_, paren, arguments| {
+        Ok(ExprResult::number(operation(
+            number(paren, &arguments[0])?,
+            number(paren, &arguments[1])?,
+        )))
+    })
+}
+
+/// Returns the number of seconds since the Unix epoch, for measuring elapsed time.
+fn clock(
+    _interpreter: &Interpreter,
+    _paren: &Token,
+    _arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(ExprResult::number(elapsed.as_secs_f64()))
+}
+
+/// Returns the instance passed as an argument to a native function.
+fn instance<'a>(paren: &Token, argument: &'a ExprResult) -> Result<&'a LoxInstance, RuntimeError> {
+    match argument {
+        ExprResult::Instance(instance) => Ok(instance),
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Returns the string passed as an argument to a native function.
+fn string<'a>(paren: &Token, argument: &'a ExprResult) -> Result<&'a str, RuntimeError> {
+    match argument {
+        ExprResult::String(value) => Ok(value),
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Returns the number of characters of a string, or the number of elements of a list or map.
+fn len(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let length = match &arguments[0] {
+        ExprResult::String(value) => value.chars().count(),
+        ExprResult::List(list) => list.len(),
+        ExprResult::Map(map) => map.len(),
+        _ => {
+            return Err(RuntimeError::InvalidArgument {
+                line: paren.line,
+                column: paren.column,
+            })
+        }
+    };
+    Ok(ExprResult::number(length as f64))
+}
+
+/// Returns the characters of a string from a start index up to, but excluding, an end index.
+fn substring(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = string(paren, &arguments[0])?;
+    let length = value.chars().count();
+    let bound = |argument| {
+        interpreter
+            .element_index(argument, length + 1, paren)
+            .map_err(|error| match error {
+                RuntimeError::IndexOutOfBounds {
+                    line,
+                    column,
+                    index,
+                    ..
+                } => RuntimeError::IndexOutOfBounds {
+                    line,
+                    column,
+                    index,
+                    length,
+                },
+                error => error,
+            })
+    };
+    let start = bound(&arguments[1])?;
+    let end = bound(&arguments[2])?;
+    if start > end {
+        return Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        });
+    }
+
+    Ok(ExprResult::string(
+        value.chars().skip(start).take(end - start).collect(),
+    ))
+}
+
+/// Returns the character index of the first occurrence of a substring, or -1 if the string
+/// doesn't contain it.
+fn index_of(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = string(paren, &arguments[0])?;
+    let pattern = string(paren, &arguments[1])?;
+
+    let index = value
+        .find(pattern)
+        .map_or(-1.0, |offset| value[..offset].chars().count() as f64);
+    Ok(ExprResult::number(index))
+}
+
+fn to_upper(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(ExprResult::string(
+        string(paren, &arguments[0])?.to_uppercase(),
+    ))
+}
+
+fn to_lower(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(ExprResult::string(
+        string(paren, &arguments[0])?.to_lowercase(),
+    ))
+}
+
+/// Removes leading and trailing whitespace from a string.
+fn trim(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(ExprResult::string(
+        string(paren, &arguments[0])?.trim().to_owned(),
+    ))
+}
+
+/// Splits a string at each occurrence of a separator into a list of strings. An empty
+/// separator splits the string into its characters.
+fn split(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = string(paren, &arguments[0])?;
+    let separator = string(paren, &arguments[1])?;
+
+    let parts = if separator.is_empty() {
+        value
+            .chars()
+            .map(|character| ExprResult::string(character.to_string()))
+            .collect()
+    } else {
+        value
+            .split(separator)
+            .map(|part| ExprResult::string(part.to_owned()))
+            .collect()
+    };
+    Ok(ExprResult::list(LoxList::new(parts)))
+}
+
+/// Replaces all occurrences of a substring.
+fn replace(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = string(paren, &arguments[0])?;
+    let from = string(paren, &arguments[1])?;
+    let to = string(paren, &arguments[2])?;
+
+    Ok(ExprResult::string(value.replace(from, to)))
+}
+
+/// Returns the character at an index of a string.
+fn char_at(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let value = string(paren, &arguments[0])?;
+    let index = interpreter.element_index(&arguments[1], value.chars().count(), paren)?;
+
+    Ok(ExprResult::string(
+        value.chars().nth(index).unwrap_or_default().to_string(),
+    ))
+}
+
+/// Wraps a function in a coroutine.
+fn coroutine(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    match &arguments[0] {
+        function if function.as_callable().is_some() => Ok(ExprResult::coroutine(
+            LoxCoroutine::new(function.to_owned()),
+        )),
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Runs a function without parameters on a separate thread. The function and everything it
+/// can reach, including the global scope, is deep-copied into a new interpreter, so the task
+/// can't observe or modify state of the spawning script.
+fn spawn(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let function = match &arguments[0] {
+        ExprResult::Function(function) if function.arity() == 0 => function,
+        _ => {
+            return Err(RuntimeError::InvalidArgument {
+                line: paren.line,
+                column: paren.column,
+            })
+        }
+    };
+
+    let transfer = Transfer::capture(
+        &ExprResult::Function(Rc::clone(function)),
+        Some(&interpreter.globals),
+    );
+    let locals = interpreter.locals.borrow().clone();
+    let captures = interpreter.captures.borrow().clone();
+    let task_paren = paren.to_owned();
+    let interrupt = interpreter.interrupt.to_owned();
+    let options = interpreter.options.as_ref().to_owned();
+
+    let handle = thread::Builder::new()
+        .stack_size(TASK_STACK_SIZE)
+        .spawn(move || {
+            let output = Rc::new(RefCell::new(Vec::new()));
+            let mut task_interpreter = Interpreter::new(Rc::clone(&output)).with_options(options);
+            task_interpreter.interrupt = interrupt;
+            task_interpreter.locals.replace(locals);
+            task_interpreter.captures.replace(captures);
+
+            let result = match transfer.restore(Some(&task_interpreter.globals)) {
+                ExprResult::Function(function) => function
+                    .call(&task_interpreter, &task_paren, &[])
+                    .map_err(|error| match error {
+                        RuntimeError::Exit { code } => TaskError::Exit {
+                            code,
+                            output: output.borrow().to_owned(),
+                        },
+                        error => TaskError::Failed(error.to_string()),
+                    })?,
+                _ => ExprResult::none(),
+            };
+
+            let output = output.borrow().to_owned();
+            Ok((Transfer::capture(&result, None), output))
+        })
+        .map_err(|error| RuntimeError::TaskFailed {
+            line: paren.line,
+            column: paren.column,
+            message: error.to_string(),
+        })?;
+
+    Ok(ExprResult::task(LoxTask::new(handle)))
+}
+
+/// Waits for a task to finish and returns its result. Output printed by the task is written
+/// to the output stream of the joining interpreter, and a task that called `exit` stops the
+/// joining script with the same exit code.
+fn join(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let task = match &arguments[0] {
+        ExprResult::Task(task) => task,
+        _ => {
+            return Err(RuntimeError::InvalidArgument {
+                line: paren.line,
+                column: paren.column,
+            })
+        }
+    };
+
+    let handle = task.take().ok_or(RuntimeError::TaskAlreadyJoined {
+        line: paren.line,
+        column: paren.column,
+    })?;
+
+    let (transfer, output) = match handle
+        .join()
+        .unwrap_or_else(|_| Err(TaskError::Failed(String::from("task panicked"))))
+    {
+        Ok(result) => result,
+        Err(TaskError::Exit { code, output }) => {
+            interpreter.write_output(&output)?;
+            return Err(RuntimeError::Exit { code });
+        }
+        Err(TaskError::Failed(message)) => {
+            return Err(RuntimeError::TaskFailed {
+                line: paren.line,
+                column: paren.column,
+                message,
+            })
+        }
+    };
+
+    interpreter.write_output(&output)?;
+
+    Ok(transfer.restore(None))
+}
+
+/// Creates a channel for passing values between tasks.
+fn channel(
+    _interpreter: &Interpreter,
+    _paren: &Token,
+    _arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    Ok(ExprResult::channel(LoxChannel::new()))
+}
+
+/// Sends a deep copy of a value through a channel without waiting for a receiver.
+fn send(
+    _interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    match &arguments[0] {
+        ExprResult::Channel(channel) => {
+            channel.send(Transfer::capture(&arguments[1], None));
+            Ok(ExprResult::none())
+        }
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Waits for the next value sent through a channel.
+fn recv(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    match &arguments[0] {
+        ExprResult::Channel(channel) => channel
+            .recv(&interpreter.interrupt)
+            .map(|value| value.restore(None))
+            .ok_or(RuntimeError::Interrupted),
+        _ => Err(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        }),
+    }
+}
+
+/// Returns a promise that is resolved after the given number of milliseconds.
+fn sleep_async(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let deadline = Instant::now()
+        .checked_add(duration(paren, &arguments[0])?)
+        .ok_or(RuntimeError::InvalidArgument {
+            line: paren.line,
+            column: paren.column,
+        })?;
+    Ok(ExprResult::promise(
+        interpreter.event_loop.borrow_mut().timer(deadline),
+    ))
+}
+
+/// Returns a promise that is resolved with the contents of the file at the given path, which is
+/// read on a background thread. The promise is rejected if the file can't be read.
+fn read_file_async(
+    interpreter: &Interpreter,
+    paren: &Token,
+    arguments: &[ExprResult],
+) -> Result<ExprResult, RuntimeError> {
+    let path = string(paren, &arguments[0])?.to_owned();
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .spawn(move || {
+            let result = std::fs::read_to_string(&path).map_err(|error| format!("{path}: {error}"));
+            let _ = sender.send(result);
+        })
+        .map_err(|error| RuntimeError::TaskFailed {
+            line: paren.line,
+            column: paren.column,
+            message: error.to_string(),
+        })?;
+
+    Ok(ExprResult::promise(
+        interpreter.event_loop.borrow_mut().read(receiver, paren),
+    ))
+}
@@ -0,0 +1,36 @@
+use crate::interpreter::lint::LintLevels;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Settings of an [`Interpreter`](crate::interpreter::interpreter::Interpreter), applied with
+/// [`Interpreter::with_options`](crate::interpreter::interpreter::Interpreter::with_options).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InterpreterOptions {
+    /// Rejects undeclared globals and `var` declarations without initializer while resolving.
+    /// At run time, rejects conditions that are not booleans, concatenating strings with other
+    /// values and comparing values of different types for equality, except with `nil`.
+    pub strict: bool,
+    /// Severity of the lints reported by the resolver.
+    pub lints: LintLevels,
+    /// Reports dividing by zero as an error instead of producing infinity or NaN.
+    pub checked_division: bool,
+    /// Maximum number of bytes a script may write to the output stream.
+    pub max_output: Option<usize>,
+    /// Maximum number of statements a script may execute. The virtual machine counts loop
+    /// iterations and calls instead.
+    pub max_steps: Option<usize>,
+    /// Maximum time a script may run.
+    pub max_duration: Option<Duration>,
+    /// Maximum number of bytes a script may hold in strings, lists, maps, instances and
+    /// environments at a time. The estimate is approximate.
+    pub max_memory: Option<usize>,
+    /// Collects garbage before every function call instead of only once enough objects were
+    /// created, which surfaces objects that are freed while they are still in use.
+    pub gc_stress: bool,
+    /// Directories searched for imported modules that are not found next to the importing
+    /// script.
+    pub import_paths: Vec<PathBuf>,
+    /// Replaces invalid UTF-8 in imported modules with U+FFFD instead of rejecting them.
+    pub lossy_utf8: bool,
+}
@@ -0,0 +1,52 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Pseudo-random number generator of an interpreter and its forks, based on SplitMix64. The
+/// same seed always produces the same sequence of numbers.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: Rc<Cell<u64>>,
+}
+
+impl Random {
+    /// Creates a generator seeded from the current time.
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Self::with_seed(nanos as u64)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            state: Rc::new(Cell::new(seed)),
+        }
+    }
+
+    pub fn seed(&self, seed: u64) {
+        self.state.set(seed);
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in the range `[0, 1)`.
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,12 +1,17 @@
-use crate::base::expr::{Expr, ExprUuid};
-use crate::base::scanner::Token;
+use crate::base::ast::{Ast, ExprRef, StmtRef};
+use crate::base::expr::{Expr, ExprId, NodeId};
+use crate::base::scanner::{Pragma, Token, TokenType};
 use crate::base::stmt::Stmt;
 use crate::base::visitor::Visitor;
+use crate::interpreter::environment::Slot;
 use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::lint::{Lint, LintError, LintLevels, Severity, Warning};
+use crate::interpreter::modules;
 use crate::interpreter::runtime_error::RuntimeError;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Clone, Debug, PartialEq)]
 enum FunctionType {
@@ -23,53 +28,269 @@ enum ClassType {
     Subclass,
 }
 
+/// A local variable of a scope.
+#[derive(Clone, Copy, Debug)]
+struct Local {
+    /// Position of the value in the environment of the scope at runtime.
+    slot: usize,
+    defined: bool,
+}
+
+/// A function whose body is being resolved.
+struct FunctionScope {
+    /// Index of the outermost scope of the function, the scope of `this` for methods.
+    base: usize,
+    /// Slots of the variables the function captures, relative to the scope it is declared in.
+    captures: Vec<Slot>,
+}
+
 pub struct Resolver<'a> {
     interpreter: Rc<Interpreter<'a>>,
-    scopes: RefCell<Vec<HashMap<String, bool>>>,
+    /// The tree of the statements being resolved.
+    ast: RefCell<Arc<Ast>>,
+    scopes: RefCell<Vec<HashMap<String, Local>>>,
+    /// The enclosing functions, innermost last. The script itself is the outermost one.
+    functions: RefCell<Vec<FunctionScope>>,
+    /// Lines and lints of the declarations per scope that haven't been read yet.
+    unread: RefCell<Vec<HashMap<String, (Token, Lint)>>>,
+    /// Names of the constants per scope.
+    constants: RefCell<Vec<HashSet<String>>>,
+    declared_globals: RefCell<HashSet<String>>,
+    global_constants: RefCell<HashSet<String>>,
     current_function_type: RefCell<FunctionType>,
+    current_function_async: RefCell<bool>,
     current_class_type: RefCell<ClassType>,
+    /// Labels of the enclosing loops of the current function, innermost last.
+    loops: RefCell<Vec<Option<String>>>,
+    /// Method names of the declared traits, for reporting conflicts between included traits.
+    traits: RefCell<HashMap<String, Vec<String>>>,
+    lints: LintLevels,
+    warnings: RefCell<Vec<Warning>>,
 }
 
 impl<'a> Resolver<'a> {
     pub fn new(interpreter: Rc<Interpreter<'a>>) -> Self {
+        let lints = interpreter.options.lints.to_owned();
         Self {
             interpreter,
+            ast: RefCell::new(Arc::new(Ast::default())),
             scopes: RefCell::new(Vec::new()),
+            functions: RefCell::new(vec![FunctionScope {
+                base: 0,
+                captures: Vec::new(),
+            }]),
+            unread: RefCell::new(Vec::new()),
+            constants: RefCell::new(Vec::new()),
+            declared_globals: RefCell::new(HashSet::new()),
+            global_constants: RefCell::new(HashSet::new()),
             current_function_type: RefCell::new(FunctionType::None),
+            current_function_async: RefCell::new(false),
             current_class_type: RefCell::new(ClassType::None),
+            loops: RefCell::new(Vec::new()),
+            traits: RefCell::new(HashMap::new()),
+            lints,
+            warnings: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn resolve_stmts(&self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+    /// Applies the lint levels of the pragmas of the resolved script.
+    pub fn with_pragmas(mut self, pragmas: &[Pragma]) -> Result<Self, LintError> {
+        self.lints.apply_pragmas(pragmas)?;
+        Ok(self)
+    }
+
+    /// Returns the warnings reported so far.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.warnings.borrow().to_owned()
+    }
+
+    pub fn resolve_stmts(&self, ast: &Arc<Ast>) -> Result<(), RuntimeError> {
+        self.resolve_statements(ast, ast.statements())
+    }
+
+    /// Resolves some of the top-level statements of a script.
+    pub(crate) fn resolve_statements(
+        &self,
+        ast: &Arc<Ast>,
+        statements: &[StmtRef],
+    ) -> Result<(), RuntimeError> {
+        self.ast.replace(Arc::clone(ast));
+        self.resolve_block(statements)
+    }
+
+    /// Resolves an expression parsed on its own, as by [`Parser::parse_expression`].
+    ///
+    /// [`Parser::parse_expression`]: crate::base::parser::Parser::parse_expression
+    pub fn resolve_expression(&self, ast: &Arc<Ast>, expr: ExprRef) -> Result<(), RuntimeError> {
+        self.ast.replace(Arc::clone(ast));
+        self.resolve_expr(expr)
+    }
+
+    fn resolve_block(&self, statements: &[StmtRef]) -> Result<(), RuntimeError> {
+        if self.scopes.borrow().is_empty() {
+            self.declare_globals(statements);
+        }
+
         for statement in statements {
-            self.resolve_stmt(statement)?
+            self.resolve_stmt(*statement)?
+        }
+
+        let ast = Arc::clone(&self.ast.borrow());
+        let returning = statements
+            .split_last()
+            .and_then(|(_, init)| init.iter().find_map(|s| Self::always_returns(&ast, *s)));
+        if let Some(keyword) = returning {
+            self.lint(
+                Lint::UnreachableCode,
+                keyword,
+                String::from("Code after 'return' is unreachable!"),
+            )?;
         }
 
         Ok(())
     }
 
-    fn resolve_stmt(&self, statement: &Stmt) -> Result<(), RuntimeError> {
-        statement.accept(self)
+    /// Returns the keyword of the `return` that ends the statement, if it always returns.
+    fn always_returns(ast: &Ast, statement: StmtRef) -> Option<&Token> {
+        match &ast[statement] {
+            Stmt::Return { keyword, .. } => Some(keyword),
+            Stmt::Block { statements } => statements
+                .iter()
+                .find_map(|statement| Self::always_returns(ast, *statement)),
+            Stmt::If {
+                then_branch,
+                else_branch: Some(else_branch),
+                ..
+            } => {
+                Self::always_returns(ast, *then_branch).and(Self::always_returns(ast, *else_branch))
+            }
+            _ => None,
+        }
+    }
+
+    fn resolve_stmt(&self, statement: StmtRef) -> Result<(), RuntimeError> {
+        self.ast.borrow()[statement].accept(self)
     }
 
-    fn resolve_expr(&self, expression: &Expr) -> Result<(), RuntimeError> {
-        expression.accept(self)
+    fn resolve_expr(&self, expression: ExprRef) -> Result<(), RuntimeError> {
+        self.ast.borrow()[expression].accept(self)
+    }
+
+    /// Remembers the global declarations of a script, so that functions may refer to globals
+    /// that are declared after them.
+    fn declare_globals(&self, statements: &[StmtRef]) {
+        let ast = self.ast.borrow();
+        for statement in statements {
+            let statement = &ast[*statement];
+            match statement {
+                Stmt::Class { name, .. }
+                | Stmt::Function { name, .. }
+                | Stmt::Trait { name, .. }
+                | Stmt::Var { name, .. } => {
+                    self.declared_globals
+                        .borrow_mut()
+                        .insert(name.lexeme.to_owned());
+                }
+                Stmt::Destructure { names, .. } => {
+                    self.declared_globals
+                        .borrow_mut()
+                        .extend(names.iter().map(|name| name.lexeme.to_owned()));
+                }
+                _ => {}
+            }
+
+            if let Stmt::Var {
+                name,
+                is_const: true,
+                ..
+            } = statement
+            {
+                self.global_constants
+                    .borrow_mut()
+                    .insert(name.lexeme.to_owned());
+            }
+        }
     }
 
     fn begin_scope(&self) {
         self.scopes.borrow_mut().push(HashMap::new());
+        self.unread.borrow_mut().push(HashMap::new());
+        self.constants.borrow_mut().push(HashSet::new());
     }
 
-    fn end_scope(&self) {
+    fn end_scope(&self) -> Result<(), RuntimeError> {
         self.scopes.borrow_mut().pop();
+        self.constants.borrow_mut().pop();
+
+        let mut unread: Vec<(String, (Token, Lint))> = self
+            .unread
+            .borrow_mut()
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        unread.sort_by_key(|(name, (token, _))| (token.line, name.to_owned()));
+
+        for (name, (token, lint)) in unread {
+            let message = match lint {
+                Lint::UnusedFunction => format!("Local function {:?} is never used!", name),
+                _ => format!("Local variable {:?} is never read!", name),
+            };
+            self.lint(lint, &token, message)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reports a lint according to its configured severity.
+    fn lint(&self, lint: Lint, token: &Token, message: String) -> Result<(), RuntimeError> {
+        match self.lints.severity(lint, self.interpreter.options.strict) {
+            Severity::Allow => Ok(()),
+            Severity::Warn => {
+                self.warnings.borrow_mut().push(Warning {
+                    lint,
+                    line: token.line,
+                    column: token.column,
+                    message,
+                });
+                Ok(())
+            }
+            Severity::Deny => Err(RuntimeError::DeniedLint {
+                line: token.line,
+                column: token.column,
+                lint: lint.name().to_owned(),
+                message,
+            }),
+        }
+    }
+
+    /// Marks the innermost local variable called `name` as read.
+    fn read(&self, name: &Token) {
+        let scopes = self.scopes.borrow();
+        if let Some(i) = (0..scopes.len())
+            .rev()
+            .find(|i| scopes[*i].contains_key(&name.lexeme))
+        {
+            self.unread.borrow_mut()[i].remove(&name.lexeme);
+        }
     }
 
     fn declare(&self, name: &Token) -> Result<(), RuntimeError> {
         if let Some(scope) = self.scopes.borrow_mut().last_mut() {
             if scope.contains_key(&name.lexeme) {
-                return Err(RuntimeError::VariableAlreadyDefinedInScope { line: name.line });
+                return Err(RuntimeError::VariableAlreadyDefinedInScope {
+                    line: name.line,
+                    column: name.column,
+                });
             } else {
-                scope.insert(name.lexeme.to_owned(), false);
+                let slot = scope.len();
+                scope.insert(
+                    name.lexeme.to_owned(),
+                    Local {
+                        slot,
+                        defined: false,
+                    },
+                );
             }
         }
 
@@ -77,23 +298,205 @@ impl<'a> Resolver<'a> {
     }
 
     fn define(&self, name: &Token) {
+        self.define_name(&name.lexeme);
+    }
+
+    /// Marks a local as defined, declaring it first if needed. Locals must be declared in the
+    /// order the interpreter defines them, so that their slots match.
+    fn define_name(&self, name: &str) {
         if let Some(scope) = self.scopes.borrow_mut().last_mut() {
-            scope.insert(name.lexeme.to_owned(), true);
+            let slot = scope.len();
+            scope
+                .entry(name.to_owned())
+                .or_insert(Local {
+                    slot,
+                    defined: false,
+                })
+                .defined = true;
         }
     }
 
-    fn resolve_local(&self, expression: &dyn ExprUuid, name: &Token) -> Result<(), RuntimeError> {
-        for i in (0..self.scopes.borrow().len()).rev() {
-            if self
-                .scopes
-                .borrow()
-                .get(i)
-                .unwrap()
-                .contains_key(&name.lexeme)
-            {
-                self.interpreter
-                    .resolve(&expression.uuid(), self.scopes.borrow().len() - 1 - i);
-                break;
+    fn resolve_loop_body(&self, label: &Option<Token>, body: StmtRef) -> Result<(), RuntimeError> {
+        self.loops
+            .borrow_mut()
+            .push(label.as_ref().map(|label| label.lexeme.to_owned()));
+        let result = self.resolve_stmt(body);
+        self.loops.borrow_mut().pop();
+
+        result
+    }
+
+    /// Checks that the label of a `break` or `continue` names an enclosing loop.
+    fn resolve_label(&self, label: &Option<Token>) -> Result<(), RuntimeError> {
+        if let Some(label) = label {
+            if !self.loops.borrow().contains(&Some(label.lexeme.to_owned())) {
+                return Err(RuntimeError::UndefinedLabel {
+                    line: label.line,
+                    column: label.column,
+                    name: label.lexeme.to_owned(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rejects assignments to the variable called `name` if it resolves to a constant.
+    fn check_assignable(&self, name: &Token) -> Result<(), RuntimeError> {
+        let scopes = self.scopes.borrow();
+        let is_const = match (0..scopes.len())
+            .rev()
+            .find(|i| scopes[*i].contains_key(&name.lexeme))
+        {
+            Some(i) => self.constants.borrow()[i].contains(&name.lexeme),
+            None => {
+                self.global_constants.borrow().contains(&name.lexeme)
+                    || self.interpreter.globals.borrow().is_constant(&name.lexeme)
+            }
+        };
+
+        if is_const {
+            return Err(RuntimeError::ConstantAssignment {
+                line: name.line,
+                column: name.column,
+                name: name.lexeme.to_owned(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the slot of the innermost local called `name`. Locals of enclosing functions are
+    /// captured, so that they are accessed through the closure of the current function.
+    fn lookup(&self, name: &str) -> Option<Slot> {
+        let scopes = self.scopes.borrow();
+        let (scope, index) = scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, scope)| scope.get(name).map(|local| (i, local.slot)))?;
+        let top = scopes.len() - 1;
+
+        let mut functions = self.functions.borrow_mut();
+        let current = functions.len() - 1;
+        let base = functions[current].base;
+        if scope >= base {
+            return Some(Slot {
+                depth: top - scope,
+                index,
+            });
+        }
+
+        /* the closure is the scope right outside the outermost scope of the function. */
+        Some(Slot {
+            depth: top + 1 - base,
+            index: capture(&mut functions, current, scope, index),
+        })
+    }
+
+    fn resolve_local(&self, expression: &dyn ExprId, name: &Token) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.lookup(&name.lexeme) {
+            self.interpreter.resolve(&expression.id(), slot);
+            return Ok(());
+        }
+
+        if self.interpreter.options.strict
+            && !self.declared_globals.borrow().contains(&name.lexeme)
+            && !self.interpreter.globals.borrow().contains(&name.lexeme)
+        {
+            return Err(RuntimeError::UndeclaredVariable {
+                line: name.line,
+                column: name.column,
+                name: name.lexeme.to_owned(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Reports a method that is included from more than one trait, unless the class overrides it.
+    fn check_trait_conflicts(
+        &self,
+        class_name: &Token,
+        traits: &[ExprRef],
+        methods: &[StmtRef],
+    ) -> Result<(), RuntimeError> {
+        let ast = self.ast.borrow();
+        let own_methods: HashSet<&str> = methods
+            .iter()
+            .filter_map(|method| match &ast[*method] {
+                Stmt::Function { name, .. } => Some(name.lexeme.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let declared_traits = self.traits.borrow();
+        let mut included = HashSet::new();
+        for lox_trait in traits {
+            let Expr::Variable { name, .. } = &ast[*lox_trait] else {
+                continue;
+            };
+
+            for method in declared_traits.get(&name.lexeme).into_iter().flatten() {
+                if !own_methods.contains(method.as_str()) && !included.insert(method) {
+                    return Err(RuntimeError::TraitMethodConflict {
+                        line: class_name.line,
+                        column: class_name.column,
+                        name: method.to_owned(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the enclosing function may be a generator.
+    fn check_yield(&self, keyword: &Token) -> Result<(), RuntimeError> {
+        match *self.current_function_type.borrow() {
+            FunctionType::None => Err(RuntimeError::TopLevelYield {
+                line: keyword.line,
+                column: keyword.column,
+            }),
+            FunctionType::Initializer => Err(RuntimeError::YieldFromInitializer {
+                line: keyword.line,
+                column: keyword.column,
+            }),
+            _ if *self.current_function_async.borrow() => {
+                Err(RuntimeError::YieldFromAsyncFunction {
+                    line: keyword.line,
+                    column: keyword.column,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolves a `yield` expression in one of the positions a generator can resume at.
+    fn resolve_yield(&self, expression: ExprRef) -> Result<(), RuntimeError> {
+        if let Expr::Yield { keyword, value, .. } = &self.ast.borrow()[expression] {
+            self.check_yield(keyword)?;
+            if let Some(value) = value {
+                self.resolve_expr(*value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reports the second declaration of a method of a class or trait.
+    fn check_duplicate_methods(&self, methods: &[StmtRef]) -> Result<(), RuntimeError> {
+        let ast = Arc::clone(&self.ast.borrow());
+        let mut names = HashSet::new();
+        for method in methods {
+            if let Stmt::Function { name, .. } = &ast[*method] {
+                if !names.insert(name.lexeme.as_str()) {
+                    return Err(RuntimeError::DuplicateMethod {
+                        line: name.line,
+                        column: name.column,
+                        name: name.lexeme.to_owned(),
+                    });
+                }
             }
         }
 
@@ -106,40 +509,133 @@ impl<'a> Resolver<'a> {
         function_type: FunctionType,
     ) -> Result<(), RuntimeError> {
         if let Stmt::Function {
+            id,
             name: _name,
             params,
             body,
+            is_async,
+            is_variadic: _is_variadic,
+            is_generator: _is_generator,
         } = statement
         {
-            let enclosing_function = self.current_function_type.replace(function_type);
-            self.begin_scope();
+            self.resolve_function_body(id, params, body, *is_async, function_type)?;
+        }
 
-            for param in params {
-                self.declare(param)?;
-                self.define(param);
+        Ok(())
+    }
+
+    fn resolve_function_body(
+        &self,
+        id: &NodeId,
+        params: &[Token],
+        body: &[StmtRef],
+        is_async: bool,
+        function_type: FunctionType,
+    ) -> Result<(), RuntimeError> {
+        let base = match function_type {
+            FunctionType::Initializer | FunctionType::Method => self.scopes.borrow().len() - 1,
+            _ => self.scopes.borrow().len(),
+        };
+        self.functions.borrow_mut().push(FunctionScope {
+            base,
+            captures: Vec::new(),
+        });
+
+        let enclosing_function = self.current_function_type.replace(function_type);
+        let enclosing_async = self.current_function_async.replace(is_async);
+        let enclosing_loops = self.loops.replace(Vec::new());
+        self.begin_scope();
+
+        let mut names = HashSet::new();
+        for param in params {
+            if !names.insert(param.lexeme.as_str()) {
+                return Err(RuntimeError::DuplicateParameter {
+                    line: param.line,
+                    column: param.column,
+                    name: param.lexeme.to_owned(),
+                });
             }
+            self.declare(param)?;
+            self.define(param);
+        }
 
-            self.resolve_stmts(body)?;
+        self.resolve_block(body)?;
 
-            self.end_scope();
-            self.current_function_type.replace(enclosing_function);
+        self.end_scope()?;
+        self.current_function_type.replace(enclosing_function);
+        self.current_function_async.replace(enclosing_async);
+        self.loops.replace(enclosing_loops);
+
+        if let Some(function) = self.functions.borrow_mut().pop() {
+            if !function.captures.is_empty() {
+                self.interpreter.resolve_captures(id, function.captures);
+            }
         }
 
         Ok(())
     }
 }
 
+/// Captures the local at `index` of `scope` in the closure of `function`, and in the closures of
+/// the functions in between. Returns the position of the variable in the closure.
+fn capture(functions: &mut [FunctionScope], function: usize, scope: usize, index: usize) -> usize {
+    let base = functions[function].base;
+    let enclosing_base = functions[function - 1].base;
+    let slot = if scope >= enclosing_base {
+        Slot {
+            depth: base - 1 - scope,
+            index,
+        }
+    } else {
+        Slot {
+            depth: base - enclosing_base,
+            index: capture(functions, function - 1, scope, index),
+        }
+    };
+
+    let captures = &mut functions[function].captures;
+    match captures.iter().position(|captured| *captured == slot) {
+        Some(position) => position,
+        None => {
+            captures.push(slot);
+            captures.len() - 1
+        }
+    }
+}
+
 impl Visitor<Stmt, (), RuntimeError> for Resolver<'_> {
     fn visit(&self, input: &Stmt) -> Result<(), RuntimeError> {
+        let ast = self.ast.borrow();
         match input {
             Stmt::Block { statements } => {
                 self.begin_scope();
-                self.resolve_stmts(statements)?;
-                self.end_scope()
+                self.resolve_block(statements)?;
+                self.end_scope()?
+            }
+            Stmt::Break { keyword, label } => {
+                if self.loops.borrow().is_empty() {
+                    return Err(RuntimeError::BreakOutsideLoop {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
+                }
+                self.resolve_label(label)?;
+            }
+            Stmt::Continue { keyword, label } => {
+                if self.loops.borrow().is_empty() {
+                    return Err(RuntimeError::ContinueOutsideLoop {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
+                }
+                self.resolve_label(label)?;
             }
             Stmt::Class {
+                id,
                 name,
                 superclass,
+                traits,
+                fields,
                 methods,
             } => {
                 let enclosing_class = self.current_class_type.replace(ClassType::Class);
@@ -147,40 +643,52 @@ impl Visitor<Stmt, (), RuntimeError> for Resolver<'_> {
                 self.declare(name)?;
                 self.define(name);
 
-                if let Some(sc) = superclass.as_ref() {
+                for lox_trait in traits {
+                    self.resolve_expr(*lox_trait)?;
+                }
+                self.check_duplicate_methods(methods)?;
+                self.check_trait_conflicts(name, traits, methods)?;
+
+                if let Some(sc) = superclass {
                     self.current_class_type.replace(ClassType::Subclass);
 
                     if let Expr::Variable {
-                        uuid: _uuid,
+                        id: _id,
                         name: sc_name,
-                    } = sc
+                    } = &ast[*sc]
                     {
                         if name.lexeme == sc_name.lexeme {
                             return Err(RuntimeError::SuperclassSelfInheritance {
                                 line: name.line,
+                                column: name.column,
                             });
                         }
                     }
 
-                    self.resolve_expr(sc)?;
+                    self.resolve_expr(*sc)?;
 
                     self.begin_scope();
-                    if let Some(scope) = self.scopes.borrow_mut().last_mut() {
-                        scope.insert(String::from("super"), true);
-                    }
+                    self.define_name("super");
                 }
 
                 self.begin_scope();
-                if let Some(scope) = self.scopes.borrow_mut().last_mut() {
-                    scope.insert(String::from("this"), true);
+                self.define_name("this");
+
+                /* field initializers are run like a method without parameters. */
+                if !fields.is_empty() {
+                    self.resolve_function_body(id, &[], fields, false, FunctionType::Method)?;
                 }
 
                 for method in methods {
                     if let Stmt::Function {
+                        id: _id,
                         name,
                         params: _params,
                         body: _body,
-                    } = method
+                        is_async: _is_async,
+                        is_variadic: _is_variadic,
+                        is_generator: _is_generator,
+                    } = &ast[*method]
                     {
                         let declaration = if name.lexeme.eq("init") {
                             FunctionType::Initializer
@@ -188,66 +696,223 @@ impl Visitor<Stmt, (), RuntimeError> for Resolver<'_> {
                             FunctionType::Method
                         };
 
-                        self.resolve_function(method, declaration)?;
+                        self.resolve_function(&ast[*method], declaration)?;
                     }
                 }
 
                 if superclass.is_some() {
-                    self.end_scope();
+                    self.end_scope()?;
                 }
 
-                self.end_scope();
+                self.end_scope()?;
                 self.current_class_type.replace(enclosing_class);
             }
-            Stmt::Expression { expression } => {
-                self.resolve_expr(expression)?;
+            Stmt::Destructure {
+                keyword: _keyword,
+                pattern: _pattern,
+                names,
+                initializer,
+            } => {
+                self.resolve_expr(*initializer)?;
+                for name in names {
+                    self.declare(name)?;
+                    self.define(name);
+
+                    if let Some(unread) = self.unread.borrow_mut().last_mut() {
+                        unread.insert(
+                            name.lexeme.to_owned(),
+                            (name.to_owned(), Lint::UnusedVariable),
+                        );
+                    }
+                }
+            }
+            Stmt::Expression { expression } => match &ast[*expression] {
+                assign @ Expr::Assign { name, value, .. }
+                    if matches!(ast[*value], Expr::Yield { .. }) =>
+                {
+                    self.resolve_yield(*value)?;
+                    self.check_assignable(name)?;
+                    self.resolve_local(assign, name)?;
+                }
+                _ => self.resolve_expr(*expression)?,
+            },
+            Stmt::ForIn {
+                keyword: _keyword,
+                label,
+                variable,
+                iterable,
+                body,
+            } => {
+                self.resolve_expr(*iterable)?;
+
+                self.begin_scope();
+                self.declare(variable)?;
+                self.define(variable);
+
+                self.resolve_loop_body(label, *body)?;
+
+                self.end_scope()?;
             }
             Stmt::Function {
+                id: _id,
                 name,
                 params: _params,
                 body: _body,
+                is_async: _is_async,
+                is_variadic: _is_variadic,
+                is_generator: _is_generator,
             } => {
                 self.declare(name)?;
                 self.define(name);
                 self.resolve_function(input, FunctionType::Function)?;
+
+                /* calls from its own body don't count as uses. */
+                if let Some(unread) = self.unread.borrow_mut().last_mut() {
+                    unread.insert(
+                        name.lexeme.to_owned(),
+                        (name.as_ref().to_owned(), Lint::UnusedFunction),
+                    );
+                }
             }
             Stmt::If {
+                keyword: _keyword,
                 condition,
                 then_branch,
                 else_branch,
             } => {
-                self.resolve_expr(condition)?;
-                self.resolve_stmt(then_branch)?;
-                if let Some(branch) = else_branch.as_ref() {
-                    self.resolve_stmt(branch)?;
+                self.resolve_expr(*condition)?;
+                self.resolve_stmt(*then_branch)?;
+                if let Some(branch) = else_branch {
+                    self.resolve_stmt(*branch)?;
+                }
+            }
+            Stmt::Import { keyword, path } => {
+                if !self.scopes.borrow().is_empty() {
+                    return Err(RuntimeError::ImportOutsideTopLevel {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
+                }
+
+                /* the names of a module are only known once it ran, strict mode needs them now. */
+                if self.interpreter.options.strict {
+                    if let TokenType::String { value } = &path.token_type {
+                        let options = &self.interpreter.options;
+                        let path = modules::module_path(keyword, value, &options.import_paths)?;
+                        let module = modules::parse_module(keyword, &path, options.lossy_utf8)?;
+                        self.declared_globals
+                            .borrow_mut()
+                            .extend(modules::declared_names(&module));
+                    }
                 }
             }
             Stmt::Print { expression } => {
-                self.resolve_expr(expression)?;
+                self.resolve_expr(*expression)?;
             }
             Stmt::Return { keyword, value } => {
                 if *self.current_function_type.borrow() == FunctionType::None {
-                    return Err(RuntimeError::TopLevelReturn { line: keyword.line });
+                    return Err(RuntimeError::TopLevelReturn {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
                 }
 
-                if let Some(expr) = value.as_ref() {
+                if let Some(expr) = value {
                     if *self.current_function_type.borrow() == FunctionType::Initializer {
                         return Err(RuntimeError::ReturnValueFromInitializer {
                             line: keyword.line,
+                            column: keyword.column,
                         });
                     }
 
-                    self.resolve_expr(expr)?;
+                    self.resolve_expr(*expr)?;
                 }
             }
-            Stmt::Var { name, initializer } => {
+            Stmt::Trait { name, methods } => {
                 self.declare(name)?;
-                self.resolve_expr(initializer)?;
                 self.define(name);
+                self.check_duplicate_methods(methods)?;
+
+                let enclosing_class = self.current_class_type.replace(ClassType::Class);
+                self.begin_scope();
+                self.define_name("this");
+
+                let mut method_names = vec![];
+                for method in methods {
+                    if let function @ Stmt::Function { name, .. } = &ast[*method] {
+                        method_names.push(name.lexeme.to_owned());
+                        self.resolve_function(function, FunctionType::Method)?;
+                    }
+                }
+
+                self.end_scope()?;
+                self.current_class_type.replace(enclosing_class);
+                self.traits
+                    .borrow_mut()
+                    .insert(name.lexeme.to_owned(), method_names);
             }
-            Stmt::While { condition, body } => {
-                self.resolve_expr(condition)?;
-                self.resolve_stmt(body)?;
+            Stmt::Var {
+                name,
+                initializer,
+                is_const,
+            } => {
+                self.declare(name)?;
+                match initializer {
+                    Some(initializer) if matches!(ast[*initializer], Expr::Yield { .. }) => {
+                        self.resolve_yield(*initializer)?
+                    }
+                    Some(initializer) => self.resolve_expr(*initializer)?,
+                    None if self.interpreter.options.strict => {
+                        return Err(RuntimeError::UninitializedVariable {
+                            line: name.line,
+                            column: name.column,
+                            name: name.lexeme.to_owned(),
+                        })
+                    }
+                    None => {}
+                }
+                self.define(name);
+
+                match self.constants.borrow_mut().last_mut() {
+                    Some(constants) if *is_const => {
+                        constants.insert(name.lexeme.to_owned());
+                    }
+                    Some(_) => {}
+                    None if *is_const => {
+                        self.global_constants
+                            .borrow_mut()
+                            .insert(name.lexeme.to_owned());
+                    }
+                    None => {
+                        self.global_constants.borrow_mut().remove(&name.lexeme);
+                    }
+                }
+
+                if let Some(unread) = self.unread.borrow_mut().last_mut() {
+                    unread.insert(
+                        name.lexeme.to_owned(),
+                        (name.as_ref().to_owned(), Lint::UnusedVariable),
+                    );
+                }
+            }
+            Stmt::While {
+                keyword: _keyword,
+                label,
+                condition,
+                body,
+                increment,
+            } => {
+                self.resolve_expr(*condition)?;
+                self.resolve_loop_body(label, *body)?;
+                if let Some(increment) = increment {
+                    self.resolve_expr(*increment)?;
+                }
+            }
+            Stmt::Yield { keyword, value } => {
+                self.check_yield(keyword)?;
+                if let Some(expr) = value {
+                    self.resolve_expr(*expr)?;
+                }
             }
         }
 
@@ -257,106 +922,214 @@ impl Visitor<Stmt, (), RuntimeError> for Resolver<'_> {
 
 impl Visitor<Expr, (), RuntimeError> for Resolver<'_> {
     fn visit(&self, input: &Expr) -> Result<(), RuntimeError> {
+        let ast = self.ast.borrow();
         match input {
             Expr::Assign {
-                uuid: _uuid,
+                id: _id,
                 name,
                 value,
             } => {
-                self.resolve_expr(value)?;
+                self.resolve_expr(*value)?;
+                self.check_assignable(name)?;
                 self.resolve_local(input, name)?;
             }
+            Expr::Await {
+                id: _id,
+                keyword,
+                value,
+            } => {
+                if *self.current_function_type.borrow() != FunctionType::None
+                    && !*self.current_function_async.borrow()
+                {
+                    return Err(RuntimeError::AwaitOutsideAsyncFunction {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
+                }
+
+                self.resolve_expr(*value)?;
+            }
             Expr::Binary {
-                uuid: _uuid,
+                id: _id,
                 left,
                 operator: _operator,
                 right,
             } => {
-                self.resolve_expr(left)?;
-                self.resolve_expr(right)?;
+                self.resolve_expr(*left)?;
+                self.resolve_expr(*right)?;
             }
             Expr::Call {
-                uuid: _uuid,
+                id: _id,
                 paren: _parent,
                 callee,
                 arguments,
             } => {
-                self.resolve_expr(callee)?;
+                self.resolve_expr(*callee)?;
                 for argument in arguments {
-                    self.resolve_expr(argument)?;
+                    self.resolve_expr(*argument)?;
                 }
             }
             Expr::Get {
-                uuid: _uuid,
+                id: _id,
                 object,
                 name: _name,
             } => {
-                self.resolve_expr(object)?;
+                self.resolve_expr(*object)?;
             }
             Expr::Grouping {
-                uuid: _uuid,
+                id: _id,
                 expression,
             } => {
-                self.resolve_expr(expression)?;
+                self.resolve_expr(*expression)?;
             }
             Expr::Literal { .. } => {}
+            Expr::Index {
+                id: _id,
+                object,
+                bracket: _bracket,
+                index,
+            } => {
+                self.resolve_expr(*object)?;
+                self.resolve_expr(*index)?;
+            }
+            Expr::IndexSet {
+                id: _id,
+                object,
+                bracket: _bracket,
+                index,
+                value,
+            } => {
+                self.resolve_expr(*value)?;
+                self.resolve_expr(*object)?;
+                self.resolve_expr(*index)?;
+            }
+            Expr::Lambda { id: _id, function } => {
+                self.resolve_function(&ast[*function], FunctionType::Function)?;
+            }
+            Expr::List {
+                id: _id,
+                bracket: _bracket,
+                elements,
+            } => {
+                for element in elements {
+                    self.resolve_expr(*element)?;
+                }
+            }
+            Expr::Map {
+                id: _id,
+                brace: _brace,
+                entries,
+            } => {
+                for (key, value) in entries {
+                    self.resolve_expr(*key)?;
+                    self.resolve_expr(*value)?;
+                }
+            }
             Expr::Logical {
-                uuid: _uuid,
+                id: _id,
                 left,
                 operator: _operator,
                 right,
             } => {
-                self.resolve_expr(left)?;
-                self.resolve_expr(right)?;
+                self.resolve_expr(*left)?;
+                self.resolve_expr(*right)?;
             }
             Expr::Set {
-                uuid: _uuid,
+                id: _id,
                 object,
                 name: _name,
                 value,
             } => {
-                self.resolve_expr(value)?;
-                self.resolve_expr(object)?;
+                self.resolve_expr(*value)?;
+                self.resolve_expr(*object)?;
+            }
+            Expr::Stringify {
+                id: _id,
+                expression,
+            } => {
+                self.resolve_expr(*expression)?;
             }
             Expr::Super {
-                uuid: _uuid,
+                id: _id,
+                this_id,
                 keyword,
                 method: _method,
             } => {
                 if *self.current_class_type.borrow() == ClassType::None {
-                    return Err(RuntimeError::SuperOutsideClass { line: keyword.line });
+                    return Err(RuntimeError::SuperOutsideClass {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
                 } else if *self.current_class_type.borrow() != ClassType::Subclass {
-                    return Err(RuntimeError::SuperWithoutSuperclass { line: keyword.line });
+                    return Err(RuntimeError::SuperWithoutSuperclass {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
                 }
 
                 self.resolve_local(input, keyword)?;
+                if let Some(slot) = self.lookup("this") {
+                    self.interpreter.resolve(this_id, slot);
+                }
             }
-            Expr::This {
-                uuid: _uuid,
-                keyword,
-            } => {
+            Expr::This { id: _id, keyword } => {
                 if *self.current_class_type.borrow() == ClassType::None {
-                    return Err(RuntimeError::ThisOutsideClass { line: keyword.line });
+                    return Err(RuntimeError::ThisOutsideClass {
+                        line: keyword.line,
+                        column: keyword.column,
+                    });
                 }
 
                 self.resolve_local(input, keyword)?;
             }
+            Expr::Ternary {
+                id: _id,
+                condition,
+                question: _question,
+                then_branch,
+                else_branch,
+            } => {
+                self.resolve_expr(*condition)?;
+                self.resolve_expr(*then_branch)?;
+                self.resolve_expr(*else_branch)?;
+            }
             Expr::Unary {
-                uuid: _uuid,
+                id: _id,
                 operator: _operator,
                 right,
             } => {
-                self.resolve_expr(right)?;
+                self.resolve_expr(*right)?;
             }
-            Expr::Variable { uuid: _uuid, name } => {
+            Expr::Update {
+                id: _id,
+                operator: _operator,
+                target,
+                prefix: _prefix,
+            } => {
+                if let Expr::Variable { name, .. } = &ast[*target] {
+                    self.check_assignable(name)?;
+                }
+                self.resolve_expr(*target)?;
+            }
+            Expr::Yield { keyword, .. } => {
+                return Err(RuntimeError::YieldInsideExpression {
+                    line: keyword.line,
+                    column: keyword.column,
+                })
+            }
+            Expr::Variable { id: _id, name } => {
                 if let Some(scope) = self.scopes.borrow().last() {
-                    if let Some(definition) = scope.get(&name.lexeme) {
-                        if !definition {
-                            return Err(RuntimeError::VariableNotDefined { line: name.line });
+                    if let Some(local) = scope.get(&name.lexeme) {
+                        if !local.defined {
+                            return Err(RuntimeError::VariableNotDefined {
+                                line: name.line,
+                                column: name.column,
+                            });
                         }
                     }
                 }
 
+                self.read(name);
                 self.resolve_local(input, name)?;
             }
         }
@@ -1,50 +1,425 @@
+use crate::base::diagnostic::DiagnosticError;
 use crate::base::expr_result::ExprResult;
+use crate::interpreter::suggestion::did_you_mean;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum RuntimeError {
     #[error("Output error.")]
     OutputError,
+    #[error("Output limit of {limit:?} bytes exceeded!")]
+    OutputLimitExceeded { limit: usize },
     #[error("{line:?}: Invalid value!")]
-    InvalidValue { line: usize },
-    #[error("{line:?}: Number expected!")]
-    NumberExpected { line: usize },
-    #[error("{line:?}: Number or String expected!")]
-    NumberOrStringExpected { line: usize },
-    #[error("{line:?}: Undefined variable {name:?}!")]
-    UndefinedVariable { line: usize, name: String },
+    InvalidValue { line: usize, column: usize },
+    #[error("{line:?}: Operand of '{operator}' must be a number, got {value}!")]
+    NumberExpected {
+        line: usize,
+        column: usize,
+        operator: String,
+        /// The offending operand, with strings in quotes.
+        value: String,
+    },
+    #[error("{line:?}: Boolean expected!")]
+    BooleanExpected { line: usize, column: usize },
+    #[error("{line:?}: Operands of '{operator}' must be numbers or strings, got {value}!")]
+    NumberOrStringExpected {
+        line: usize,
+        column: usize,
+        operator: String,
+        /// The offending operand, with strings in quotes.
+        value: String,
+    },
+    #[error("{line:?}: Input error!")]
+    InputError { line: usize, column: usize },
+    #[error("{line:?}: Division by zero!")]
+    DivisionByZero { line: usize, column: usize },
+    #[error("{line:?}: Operands must have the same type!")]
+    MismatchedTypes { line: usize, column: usize },
+    #[error("{line:?}: Undefined variable {name:?}!{}", did_you_mean(suggestion))]
+    UndefinedVariable {
+        line: usize,
+        column: usize,
+        name: String,
+        /// A similar name in scope, which might have been meant.
+        suggestion: Option<String>,
+    },
+    #[error("{line:?}: Stack overflow!")]
+    StackOverflow { line: usize, column: usize },
     #[error("{line:?}: Undefined callable!")]
-    UndefinedCallable { line: usize },
+    UndefinedCallable { line: usize, column: usize },
     #[error("{line:?}: Invalid argument!")]
-    InvalidArgument { line: usize },
+    InvalidArgument { line: usize, column: usize },
     #[error("{line:?}: Block expected!")]
-    BlockExpected { line: usize },
+    BlockExpected { line: usize, column: usize },
     #[error("{line:?}: Number of arguments does not match number of parameters!")]
-    NonMatchingNumberOfArguments { line: usize },
+    NonMatchingNumberOfArguments { line: usize, column: usize },
+    #[error("{line:?}: Undeclared variable {name:?}!")]
+    UndeclaredVariable {
+        line: usize,
+        column: usize,
+        name: String,
+    },
+    #[error("{line:?}: Variable {name:?} must be initialized!")]
+    UninitializedVariable {
+        line: usize,
+        column: usize,
+        name: String,
+    },
+    #[error("{line:?}: {message} [{lint}]")]
+    DeniedLint {
+        line: usize,
+        column: usize,
+        lint: String,
+        message: String,
+    },
+    #[error("{line:?}: Can't assign to constant {name:?}!")]
+    ConstantAssignment {
+        line: usize,
+        column: usize,
+        name: String,
+    },
     #[error("{line:?}: Can't read local variable in its own initializer!")]
-    VariableNotDefined { line: usize },
+    VariableNotDefined { line: usize, column: usize },
     #[error("{line:?}: Already a variable with this name in this scope!")]
-    VariableAlreadyDefinedInScope { line: usize },
+    VariableAlreadyDefinedInScope { line: usize, column: usize },
+    #[error("{line:?}: Duplicate parameter {name:?}!")]
+    DuplicateParameter {
+        line: usize,
+        column: usize,
+        name: String,
+    },
+    #[error("{line:?}: Method {name:?} is already defined!")]
+    DuplicateMethod {
+        line: usize,
+        column: usize,
+        name: String,
+    },
     #[error("{line:?}: Can't return from top-level code!")]
-    TopLevelReturn { line: usize },
-    #[error("{line:?}: Undefined property!")]
-    UndefinedProperty { line: usize },
-    #[error("{line:?}: Only instances have properties!")]
-    InvalidPropertyAccess { line: usize },
+    TopLevelReturn { line: usize, column: usize },
+    #[error("{line:?}: Can't use 'break' outside of a loop!")]
+    BreakOutsideLoop { line: usize, column: usize },
+    #[error("{line:?}: List expected!")]
+    ListExpected { line: usize, column: usize },
+    #[error("{line:?}: Expected {expected:?} values, got {actual:?}!")]
+    ValueCountMismatch {
+        line: usize,
+        column: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{line:?}: Can't use 'continue' outside of a loop!")]
+    ContinueOutsideLoop { line: usize, column: usize },
+    #[error("{line:?}: Undefined loop label {name:?}!")]
+    UndefinedLabel {
+        line: usize,
+        column: usize,
+        name: String,
+    },
+    #[error("{line:?}: Only lists, maps and strings can be iterated!")]
+    NotIterable { line: usize, column: usize },
+    #[error("{line:?}: Only lists, maps and strings can be indexed!")]
+    NotIndexable { line: usize, column: usize },
+    #[error("{line:?}: Only list elements and map entries can be assigned!")]
+    InvalidIndexAssignment { line: usize, column: usize },
+    #[error("{line:?}: Index must be a non-negative integer!")]
+    InvalidIndex { line: usize, column: usize },
+    #[error("{line:?}: Index {index:?} out of bounds for length {length:?}!")]
+    IndexOutOfBounds {
+        line: usize,
+        column: usize,
+        index: usize,
+        length: usize,
+    },
+    #[error("{line:?}: Undefined property!{}", did_you_mean(suggestion))]
+    UndefinedProperty {
+        line: usize,
+        column: usize,
+        /// A similar property of the object, which might have been meant.
+        suggestion: Option<String>,
+    },
+    #[error("{line:?}: Only instances, lists, generators and coroutines have properties!")]
+    InvalidPropertyAccess { line: usize, column: usize },
     #[error("{line:?}: Only instances have fields!")]
-    InvalidFieldAccess { line: usize },
+    InvalidFieldAccess { line: usize, column: usize },
     #[error("{line:?}: Can't use 'this' outside of a class!")]
-    ThisOutsideClass { line: usize },
+    ThisOutsideClass { line: usize, column: usize },
     #[error("{line:?}: Can't return a value from an initializer!")]
-    ReturnValueFromInitializer { line: usize },
+    ReturnValueFromInitializer { line: usize, column: usize },
+    #[error("{line:?}: Can't yield from top-level code!")]
+    TopLevelYield { line: usize, column: usize },
+    #[error("{line:?}: Can't yield from an initializer!")]
+    YieldFromInitializer { line: usize, column: usize },
+    #[error("{line:?}: Can't yield from an async function!")]
+    YieldFromAsyncFunction { line: usize, column: usize },
+    #[error("{line:?}: Can't use 'yield' inside an expression!")]
+    YieldInsideExpression { line: usize, column: usize },
+    #[error("{line:?}: Generator is already running!")]
+    GeneratorRunning { line: usize, column: usize },
+    #[error("{line:?}: Can't resume dead coroutine!")]
+    DeadCoroutine { line: usize, column: usize },
     #[error("{line:?}: A class can't inherit from itself!")]
-    SuperclassSelfInheritance { line: usize },
+    SuperclassSelfInheritance { line: usize, column: usize },
     #[error("{line:?}: Superclass must be a class!")]
-    SuperclassInvalidType { line: usize },
+    SuperclassInvalidType { line: usize, column: usize },
+    #[error("{line:?}: Right operand of 'is' must be a class!")]
+    ClassExpected { line: usize, column: usize },
+    #[error("{line:?}: Can only include traits!")]
+    TraitInvalidType { line: usize, column: usize },
+    #[error("{line:?}: Method {name:?} is defined by more than one trait!")]
+    TraitMethodConflict {
+        line: usize,
+        column: usize,
+        name: String,
+    },
     #[error("{line:?}: Can't use 'super' outside of a class!")]
-    SuperOutsideClass { line: usize },
+    SuperOutsideClass { line: usize, column: usize },
     #[error("{line:?}: Can't use 'super' in a class with no superclass!")]
-    SuperWithoutSuperclass { line: usize },
+    SuperWithoutSuperclass { line: usize, column: usize },
+    #[error("{line:?}: Can only import modules at the top level!")]
+    ImportOutsideTopLevel { line: usize, column: usize },
+    #[error("{line:?}: Module {path:?} not found!")]
+    ModuleNotFound {
+        line: usize,
+        column: usize,
+        path: String,
+    },
+    #[error("{line:?}: Module failed to load: {message}")]
+    ModuleError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("{line:?}: Module {path:?} imports itself!")]
+    CyclicImport {
+        line: usize,
+        column: usize,
+        path: String,
+    },
+    #[error("{line:?}: Task failed: {message}")]
+    TaskFailed {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("{line:?}: Task has already been joined!")]
+    TaskAlreadyJoined { line: usize, column: usize },
+    #[error("{line:?}: Can't use 'await' outside of an async function!")]
+    AwaitOutsideAsyncFunction { line: usize, column: usize },
+    #[error("{line:?}: Awaited promise was rejected: {message}")]
+    PromiseRejected {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("{line:?}: Awaited promise can never be settled!")]
+    PromiseNeverSettled { line: usize, column: usize },
+    #[error("Interrupted!")]
+    Interrupted,
+    #[error("Execution budget exceeded!")]
+    BudgetExceeded,
+    #[error("Memory limit of {limit:?} bytes exceeded!")]
+    MemoryLimitExceeded { limit: usize },
+    #[error("{file}:{error}")]
+    InFile {
+        file: Arc<str>,
+        error: Box<RuntimeError>,
+    },
     #[error(transparent)]
-    Return { ret_val: ExprResult },
+    Return { ret_val: Box<ExprResult> },
+    #[error("Break")]
+    Break { label: Option<String> },
+    #[error("Continue")]
+    Continue { label: Option<String> },
+    /// Stops the script with an exit code, raised by the `exit` native function.
+    #[error("Exit with code {code:?}")]
+    Exit { code: i32 },
+}
+
+impl RuntimeError {
+    /// Annotates the error with the script file it occurred in, unless it is already annotated
+    /// or doesn't refer to a line.
+    pub fn in_file(self, file: &Arc<str>) -> Self {
+        match self {
+            RuntimeError::OutputError
+            | RuntimeError::OutputLimitExceeded { .. }
+            | RuntimeError::Interrupted
+            | RuntimeError::BudgetExceeded
+            | RuntimeError::MemoryLimitExceeded { .. }
+            | RuntimeError::InFile { .. }
+            | RuntimeError::Return { .. }
+            | RuntimeError::Break { .. }
+            | RuntimeError::Continue { .. }
+            | RuntimeError::Exit { .. } => self,
+            error => RuntimeError::InFile {
+                file: Arc::clone(file),
+                error: Box::new(error),
+            },
+        }
+    }
+    /// Returns the line and column at which the error occurred, if it refers to a line.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            RuntimeError::InvalidValue { line, column, .. }
+            | RuntimeError::NumberExpected { line, column, .. }
+            | RuntimeError::BooleanExpected { line, column, .. }
+            | RuntimeError::NumberOrStringExpected { line, column, .. }
+            | RuntimeError::InputError { line, column, .. }
+            | RuntimeError::DivisionByZero { line, column, .. }
+            | RuntimeError::MismatchedTypes { line, column, .. }
+            | RuntimeError::UndefinedVariable { line, column, .. }
+            | RuntimeError::StackOverflow { line, column, .. }
+            | RuntimeError::UndefinedCallable { line, column, .. }
+            | RuntimeError::InvalidArgument { line, column, .. }
+            | RuntimeError::BlockExpected { line, column, .. }
+            | RuntimeError::NonMatchingNumberOfArguments { line, column, .. }
+            | RuntimeError::UndeclaredVariable { line, column, .. }
+            | RuntimeError::UninitializedVariable { line, column, .. }
+            | RuntimeError::DeniedLint { line, column, .. }
+            | RuntimeError::ConstantAssignment { line, column, .. }
+            | RuntimeError::VariableNotDefined { line, column, .. }
+            | RuntimeError::VariableAlreadyDefinedInScope { line, column, .. }
+            | RuntimeError::DuplicateParameter { line, column, .. }
+            | RuntimeError::DuplicateMethod { line, column, .. }
+            | RuntimeError::TopLevelReturn { line, column, .. }
+            | RuntimeError::BreakOutsideLoop { line, column, .. }
+            | RuntimeError::ListExpected { line, column, .. }
+            | RuntimeError::ValueCountMismatch { line, column, .. }
+            | RuntimeError::ContinueOutsideLoop { line, column, .. }
+            | RuntimeError::UndefinedLabel { line, column, .. }
+            | RuntimeError::NotIterable { line, column, .. }
+            | RuntimeError::NotIndexable { line, column, .. }
+            | RuntimeError::InvalidIndexAssignment { line, column, .. }
+            | RuntimeError::InvalidIndex { line, column, .. }
+            | RuntimeError::IndexOutOfBounds { line, column, .. }
+            | RuntimeError::UndefinedProperty { line, column, .. }
+            | RuntimeError::InvalidPropertyAccess { line, column, .. }
+            | RuntimeError::InvalidFieldAccess { line, column, .. }
+            | RuntimeError::ThisOutsideClass { line, column, .. }
+            | RuntimeError::ReturnValueFromInitializer { line, column, .. }
+            | RuntimeError::TopLevelYield { line, column, .. }
+            | RuntimeError::YieldFromInitializer { line, column, .. }
+            | RuntimeError::YieldFromAsyncFunction { line, column, .. }
+            | RuntimeError::YieldInsideExpression { line, column, .. }
+            | RuntimeError::GeneratorRunning { line, column, .. }
+            | RuntimeError::DeadCoroutine { line, column, .. }
+            | RuntimeError::SuperclassSelfInheritance { line, column, .. }
+            | RuntimeError::SuperclassInvalidType { line, column, .. }
+            | RuntimeError::ClassExpected { line, column, .. }
+            | RuntimeError::TraitInvalidType { line, column, .. }
+            | RuntimeError::TraitMethodConflict { line, column, .. }
+            | RuntimeError::SuperOutsideClass { line, column, .. }
+            | RuntimeError::SuperWithoutSuperclass { line, column, .. }
+            | RuntimeError::ImportOutsideTopLevel { line, column, .. }
+            | RuntimeError::ModuleNotFound { line, column, .. }
+            | RuntimeError::ModuleError { line, column, .. }
+            | RuntimeError::CyclicImport { line, column, .. }
+            | RuntimeError::TaskFailed { line, column, .. }
+            | RuntimeError::TaskAlreadyJoined { line, column, .. }
+            | RuntimeError::AwaitOutsideAsyncFunction { line, column, .. }
+            | RuntimeError::PromiseRejected { line, column, .. }
+            | RuntimeError::PromiseNeverSettled { line, column, .. } => Some((*line, *column)),
+            RuntimeError::InFile { error, .. } => error.location(),
+            RuntimeError::OutputError
+            | RuntimeError::OutputLimitExceeded { .. }
+            | RuntimeError::Interrupted
+            | RuntimeError::BudgetExceeded
+            | RuntimeError::MemoryLimitExceeded { .. }
+            | RuntimeError::Return { .. }
+            | RuntimeError::Break { .. }
+            | RuntimeError::Continue { .. }
+            | RuntimeError::Exit { .. } => None,
+        }
+    }
+}
+
+impl DiagnosticError for RuntimeError {
+    fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::OutputError => "OutputError",
+            RuntimeError::OutputLimitExceeded { .. } => "OutputLimitExceeded",
+            RuntimeError::InvalidValue { .. } => "InvalidValue",
+            RuntimeError::NumberExpected { .. } => "NumberExpected",
+            RuntimeError::BooleanExpected { .. } => "BooleanExpected",
+            RuntimeError::NumberOrStringExpected { .. } => "NumberOrStringExpected",
+            RuntimeError::InputError { .. } => "InputError",
+            RuntimeError::DivisionByZero { .. } => "DivisionByZero",
+            RuntimeError::MismatchedTypes { .. } => "MismatchedTypes",
+            RuntimeError::UndefinedVariable { .. } => "UndefinedVariable",
+            RuntimeError::StackOverflow { .. } => "StackOverflow",
+            RuntimeError::UndefinedCallable { .. } => "UndefinedCallable",
+            RuntimeError::InvalidArgument { .. } => "InvalidArgument",
+            RuntimeError::BlockExpected { .. } => "BlockExpected",
+            RuntimeError::NonMatchingNumberOfArguments { .. } => "NonMatchingNumberOfArguments",
+            RuntimeError::UndeclaredVariable { .. } => "UndeclaredVariable",
+            RuntimeError::UninitializedVariable { .. } => "UninitializedVariable",
+            RuntimeError::DeniedLint { .. } => "DeniedLint",
+            RuntimeError::ConstantAssignment { .. } => "ConstantAssignment",
+            RuntimeError::VariableNotDefined { .. } => "VariableNotDefined",
+            RuntimeError::VariableAlreadyDefinedInScope { .. } => "VariableAlreadyDefinedInScope",
+            RuntimeError::DuplicateParameter { .. } => "DuplicateParameter",
+            RuntimeError::DuplicateMethod { .. } => "DuplicateMethod",
+            RuntimeError::TopLevelReturn { .. } => "TopLevelReturn",
+            RuntimeError::BreakOutsideLoop { .. } => "BreakOutsideLoop",
+            RuntimeError::ListExpected { .. } => "ListExpected",
+            RuntimeError::ValueCountMismatch { .. } => "ValueCountMismatch",
+            RuntimeError::ContinueOutsideLoop { .. } => "ContinueOutsideLoop",
+            RuntimeError::UndefinedLabel { .. } => "UndefinedLabel",
+            RuntimeError::NotIterable { .. } => "NotIterable",
+            RuntimeError::NotIndexable { .. } => "NotIndexable",
+            RuntimeError::InvalidIndexAssignment { .. } => "InvalidIndexAssignment",
+            RuntimeError::InvalidIndex { .. } => "InvalidIndex",
+            RuntimeError::IndexOutOfBounds { .. } => "IndexOutOfBounds",
+            RuntimeError::UndefinedProperty { .. } => "UndefinedProperty",
+            RuntimeError::InvalidPropertyAccess { .. } => "InvalidPropertyAccess",
+            RuntimeError::InvalidFieldAccess { .. } => "InvalidFieldAccess",
+            RuntimeError::ThisOutsideClass { .. } => "ThisOutsideClass",
+            RuntimeError::ReturnValueFromInitializer { .. } => "ReturnValueFromInitializer",
+            RuntimeError::TopLevelYield { .. } => "TopLevelYield",
+            RuntimeError::YieldFromInitializer { .. } => "YieldFromInitializer",
+            RuntimeError::YieldFromAsyncFunction { .. } => "YieldFromAsyncFunction",
+            RuntimeError::YieldInsideExpression { .. } => "YieldInsideExpression",
+            RuntimeError::GeneratorRunning { .. } => "GeneratorRunning",
+            RuntimeError::DeadCoroutine { .. } => "DeadCoroutine",
+            RuntimeError::SuperclassSelfInheritance { .. } => "SuperclassSelfInheritance",
+            RuntimeError::SuperclassInvalidType { .. } => "SuperclassInvalidType",
+            RuntimeError::ClassExpected { .. } => "ClassExpected",
+            RuntimeError::TraitInvalidType { .. } => "TraitInvalidType",
+            RuntimeError::TraitMethodConflict { .. } => "TraitMethodConflict",
+            RuntimeError::SuperOutsideClass { .. } => "SuperOutsideClass",
+            RuntimeError::SuperWithoutSuperclass { .. } => "SuperWithoutSuperclass",
+            RuntimeError::ImportOutsideTopLevel { .. } => "ImportOutsideTopLevel",
+            RuntimeError::ModuleNotFound { .. } => "ModuleNotFound",
+            RuntimeError::ModuleError { .. } => "ModuleError",
+            RuntimeError::CyclicImport { .. } => "CyclicImport",
+            RuntimeError::TaskFailed { .. } => "TaskFailed",
+            RuntimeError::TaskAlreadyJoined { .. } => "TaskAlreadyJoined",
+            RuntimeError::AwaitOutsideAsyncFunction { .. } => "AwaitOutsideAsyncFunction",
+            RuntimeError::PromiseRejected { .. } => "PromiseRejected",
+            RuntimeError::PromiseNeverSettled { .. } => "PromiseNeverSettled",
+            RuntimeError::Interrupted => "Interrupted",
+            RuntimeError::BudgetExceeded => "BudgetExceeded",
+            RuntimeError::MemoryLimitExceeded { .. } => "MemoryLimitExceeded",
+            RuntimeError::InFile { error, .. } => error.code(),
+            RuntimeError::Return { .. } => "Return",
+            RuntimeError::Break { .. } => "Break",
+            RuntimeError::Continue { .. } => "Continue",
+            RuntimeError::Exit { .. } => "Exit",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        self.location().map(|(line, _)| line)
+    }
+
+    fn column(&self) -> Option<usize> {
+        self.location().map(|(_, column)| column)
+    }
+
+    fn file(&self) -> Option<&str> {
+        match self {
+            RuntimeError::InFile { file, .. } => Some(file),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,47 @@
+/// Returns the candidate most similar to a misspelled `name`, if any is similar enough to be
+/// what was meant. A name may differ in one edit per three characters, at least one.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate.to_owned())
+}
+
+/// Formats the suggestion appended to the message of an error.
+pub fn did_you_mean(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(" Did you mean {:?}?", suggestion),
+        None => String::new(),
+    }
+}
+
+/// Counts the characters to insert, delete, replace or swap with their neighbour to turn `a`
+/// into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    /* distances of the prefixes of `a` to the prefixes of `b`, for the last two rows. */
+    let mut previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut current: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let before = std::mem::replace(&mut previous, current);
+        current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+    }
+
+    current[b.len()]
+}
@@ -0,0 +1,468 @@
+use crate::base::ast::Body;
+use crate::base::expr_result::{
+    ExprResult, LoxChannel, LoxClass, LoxFunction, LoxInstance, LoxList, LoxMap, LoxTrait,
+};
+use crate::base::scanner::Token;
+use crate::interpreter::environment::{Binding, Environment, Upvalue};
+use crate::interpreter::heap;
+use crate::interpreter::natives;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Debug)]
+enum TransferValue {
+    Number(f64),
+    String(String),
+    Boolean(bool),
+    Function(TransferFunction),
+    NativeFunction(String),
+    Class(TransferClass),
+    Trait(TransferTrait),
+    Instance(usize),
+    List(usize),
+    Map(usize),
+    Channel(LoxChannel),
+    None,
+}
+
+#[derive(Debug)]
+struct TransferFunction {
+    name: Token,
+    params: Arc<[Token]>,
+    body: Body,
+    closure: usize,
+    is_initializer: bool,
+    is_async: bool,
+    is_variadic: bool,
+    is_generator: bool,
+}
+
+#[derive(Debug)]
+struct TransferClass {
+    name: Token,
+    superclass: Option<Box<TransferClass>>,
+    fields: Option<TransferFunction>,
+    methods: Vec<(String, TransferFunction)>,
+}
+
+#[derive(Debug)]
+struct TransferTrait {
+    name: Token,
+    methods: Vec<(String, TransferFunction)>,
+}
+
+#[derive(Debug)]
+struct TransferInstance {
+    class: TransferClass,
+    fields: Vec<(String, TransferValue)>,
+}
+
+#[derive(Debug)]
+enum TransferBinding {
+    Value(Box<TransferValue>),
+    Upvalue(usize),
+}
+
+#[derive(Debug, Default)]
+struct TransferEnvironment {
+    enclosing: Option<usize>,
+    values: Vec<TransferBinding>,
+    slots: Option<HashMap<String, usize>>,
+}
+
+/// A deep copy of a value, including every environment, instance, list and map reachable from
+/// it, that can be sent to another thread and restored into another interpreter.
+///
+/// Shared environments, instances, lists, maps and captured variables stay shared within the
+/// copy, so values containing themselves are copied as well, and channels are shared with the
+/// original. Values that are bound to the interpreter they were created in (e.g. task handles
+/// and promises) are restored as `nil`.
+#[derive(Debug)]
+pub struct Transfer {
+    environments: Vec<TransferEnvironment>,
+    upvalues: Vec<TransferValue>,
+    instances: Vec<TransferInstance>,
+    lists: Vec<Vec<TransferValue>>,
+    maps: Vec<Vec<(TransferValue, TransferValue)>>,
+    value: TransferValue,
+}
+
+#[derive(Default)]
+struct Capture {
+    environments: Vec<TransferEnvironment>,
+    upvalues: Vec<TransferValue>,
+    instances: Vec<TransferInstance>,
+    lists: Vec<Vec<TransferValue>>,
+    maps: Vec<Vec<(TransferValue, TransferValue)>>,
+    environment_ids: HashMap<*const RefCell<Environment>, usize>,
+    upvalue_ids: HashMap<*const RefCell<ExprResult>, usize>,
+    instance_ids: HashMap<*const RefCell<HashMap<String, ExprResult>>, usize>,
+    list_ids: HashMap<*const RefCell<Vec<ExprResult>>, usize>,
+    map_ids: HashMap<*const RefCell<Vec<(ExprResult, ExprResult)>>, usize>,
+}
+
+impl Capture {
+    fn value(&mut self, value: &ExprResult) -> TransferValue {
+        match value {
+            ExprResult::Number(value) => TransferValue::Number(*value),
+            ExprResult::String(value) => TransferValue::String(value.to_string()),
+            ExprResult::Boolean(value) => TransferValue::Boolean(*value),
+            ExprResult::Function(function) => TransferValue::Function(self.function(function)),
+            ExprResult::NativeFunction(function) => {
+                TransferValue::NativeFunction(function.name.to_owned())
+            }
+            ExprResult::Class(class) => TransferValue::Class(self.class(class)),
+            ExprResult::Trait(lox_trait) => TransferValue::Trait(TransferTrait {
+                name: lox_trait.name.to_owned(),
+                methods: lox_trait
+                    .methods
+                    .iter()
+                    .map(|(name, method)| (name.to_owned(), self.function(method)))
+                    .collect(),
+            }),
+            ExprResult::Instance(instance) => TransferValue::Instance(self.instance(instance)),
+            ExprResult::List(list) => TransferValue::List(self.list(list)),
+            ExprResult::Map(map) => TransferValue::Map(self.map(map)),
+            ExprResult::Channel(channel) => TransferValue::Channel(channel.to_owned()),
+            ExprResult::Task(_)
+            | ExprResult::Promise(_)
+            | ExprResult::Generator(_)
+            | ExprResult::Coroutine(_)
+            | ExprResult::None => TransferValue::None,
+        }
+    }
+
+    fn function(&mut self, function: &LoxFunction) -> TransferFunction {
+        TransferFunction {
+            name: function.name.to_owned(),
+            params: Arc::clone(&function.params),
+            body: function.body.clone(),
+            closure: self.environment(&function.closure),
+            is_initializer: function.is_initializer,
+            is_async: function.is_async,
+            is_variadic: function.is_variadic,
+            is_generator: function.is_generator,
+        }
+    }
+
+    fn class(&mut self, class: &LoxClass) -> TransferClass {
+        TransferClass {
+            name: class.name.to_owned(),
+            superclass: class
+                .superclass
+                .as_ref()
+                .as_ref()
+                .map(|superclass| Box::new(self.class(superclass))),
+            fields: class
+                .fields
+                .as_ref()
+                .as_ref()
+                .map(|fields| self.function(fields)),
+            methods: class
+                .methods
+                .iter()
+                .map(|(name, method)| (name.to_owned(), self.function(method)))
+                .collect(),
+        }
+    }
+
+    fn instance(&mut self, instance: &LoxInstance) -> usize {
+        let key = Rc::as_ptr(&instance.fields);
+        if let Some(id) = self.instance_ids.get(&key) {
+            return *id;
+        }
+
+        let id = self.instances.len();
+        self.instance_ids.insert(key, id);
+        let class = self.class(&instance.class);
+        self.instances.push(TransferInstance {
+            class,
+            fields: vec![],
+        });
+
+        let fields = instance
+            .fields
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.to_owned(), self.value(value)))
+            .collect();
+        self.instances[id].fields = fields;
+
+        id
+    }
+
+    fn list(&mut self, list: &LoxList) -> usize {
+        let key = Rc::as_ptr(&list.elements);
+        if let Some(id) = self.list_ids.get(&key) {
+            return *id;
+        }
+
+        let id = self.lists.len();
+        self.list_ids.insert(key, id);
+        self.lists.push(vec![]);
+
+        let elements = list
+            .to_vec()
+            .iter()
+            .map(|element| self.value(element))
+            .collect();
+        self.lists[id] = elements;
+
+        id
+    }
+
+    fn map(&mut self, map: &LoxMap) -> usize {
+        let key = Rc::as_ptr(&map.entries);
+        if let Some(id) = self.map_ids.get(&key) {
+            return *id;
+        }
+
+        let id = self.maps.len();
+        self.map_ids.insert(key, id);
+        self.maps.push(vec![]);
+
+        let entries = map.entries.borrow().to_owned();
+        let entries = entries
+            .iter()
+            .map(|(key, value)| (self.value(key), self.value(value)))
+            .collect();
+        self.maps[id] = entries;
+
+        id
+    }
+
+    fn environment(&mut self, environment: &Rc<RefCell<Environment>>) -> usize {
+        let key = Rc::as_ptr(environment);
+        if let Some(id) = self.environment_ids.get(&key) {
+            return *id;
+        }
+
+        let id = self.environments.len();
+        self.environment_ids.insert(key, id);
+        self.environments.push(TransferEnvironment::default());
+
+        let enclosing = environment
+            .borrow()
+            .enclosing
+            .as_ref()
+            .map(|enclosing| self.environment(enclosing));
+        let (values, slots) = {
+            let environment = environment.borrow();
+            (environment.values.to_owned(), environment.slots.to_owned())
+        };
+        let values = values
+            .iter()
+            .map(|binding| match binding {
+                Binding::Value(value) => TransferBinding::Value(Box::new(self.value(value))),
+                Binding::Captured(upvalue) => TransferBinding::Upvalue(self.upvalue(upvalue)),
+            })
+            .collect();
+        self.environments[id] = TransferEnvironment {
+            enclosing,
+            values,
+            slots,
+        };
+
+        id
+    }
+
+    fn upvalue(&mut self, upvalue: &Upvalue) -> usize {
+        let key = Rc::as_ptr(upvalue);
+        if let Some(id) = self.upvalue_ids.get(&key) {
+            return *id;
+        }
+
+        let id = self.upvalues.len();
+        self.upvalue_ids.insert(key, id);
+        self.upvalues.push(TransferValue::None);
+
+        let value = upvalue.borrow().to_owned();
+        self.upvalues[id] = self.value(&value);
+
+        id
+    }
+}
+
+struct Restore {
+    environments: Vec<Rc<RefCell<Environment>>>,
+    upvalues: Vec<Upvalue>,
+    instances: Vec<LoxInstance>,
+    lists: Vec<LoxList>,
+    maps: Vec<LoxMap>,
+}
+
+impl Restore {
+    fn value(&self, value: TransferValue) -> ExprResult {
+        match value {
+            TransferValue::Number(value) => ExprResult::number(value),
+            TransferValue::String(value) => ExprResult::string(value),
+            TransferValue::Boolean(value) => ExprResult::boolean(value),
+            TransferValue::Function(function) => ExprResult::function(self.function(function)),
+            TransferValue::NativeFunction(name) => natives::builtin(&name)
+                .map(ExprResult::native_function)
+                .unwrap_or_default(),
+            TransferValue::Class(class) => ExprResult::Class(self.class(class)),
+            TransferValue::Trait(lox_trait) => ExprResult::trait_value(LoxTrait::new(
+                lox_trait.name,
+                lox_trait
+                    .methods
+                    .into_iter()
+                    .map(|(name, method)| (name, self.function(method)))
+                    .collect(),
+            )),
+            TransferValue::Instance(id) => ExprResult::instance(self.instances[id].to_owned()),
+            TransferValue::List(id) => ExprResult::list(self.lists[id].to_owned()),
+            TransferValue::Map(id) => ExprResult::map(self.maps[id].to_owned()),
+            TransferValue::Channel(channel) => ExprResult::channel(channel),
+            TransferValue::None => ExprResult::none(),
+        }
+    }
+
+    fn function(&self, function: TransferFunction) -> LoxFunction {
+        LoxFunction::new(
+            function.name,
+            function.params,
+            function.body,
+            Rc::clone(&self.environments[function.closure]),
+            function.is_initializer,
+            function.is_async,
+            function.is_variadic,
+        )
+        .with_generator(function.is_generator)
+    }
+
+    fn class(&self, class: TransferClass) -> Rc<LoxClass> {
+        Rc::new(LoxClass::new(
+            class.name,
+            class.superclass.map(|superclass| self.class(*superclass)),
+            class.fields.map(|fields| self.function(fields)),
+            class
+                .methods
+                .into_iter()
+                .map(|(name, method)| (name, self.function(method)))
+                .collect(),
+        ))
+    }
+}
+
+impl Transfer {
+    /// Copies `value`. If `globals` is reachable from the value, it is always captured as the
+    /// first environment, so that [`Transfer::restore`] can map it onto another global scope.
+    pub fn capture(value: &ExprResult, globals: Option<&Rc<RefCell<Environment>>>) -> Self {
+        let mut capture = Capture::default();
+        if let Some(globals) = globals {
+            capture.environment(globals);
+        }
+
+        let value = capture.value(value);
+
+        Self {
+            environments: capture.environments,
+            upvalues: capture.upvalues,
+            instances: capture.instances,
+            lists: capture.lists,
+            maps: capture.maps,
+            value,
+        }
+    }
+
+    /// Rebuilds the copied value. If `globals` is given, the values of the first captured
+    /// environment are defined in it instead of a new environment.
+    pub fn restore(self, globals: Option<&Rc<RefCell<Environment>>>) -> ExprResult {
+        let mut environments = vec![];
+        for id in 0..self.environments.len() {
+            match globals {
+                Some(globals) if id == 0 => environments.push(Rc::clone(globals)),
+                _ => {
+                    let environment = Rc::new(RefCell::new(Environment::new()));
+                    heap::track_environment(&environment);
+                    environments.push(environment);
+                }
+            }
+        }
+
+        for (id, environment) in self.environments.iter().enumerate() {
+            if let Some(enclosing) = environment.enclosing {
+                environments[id].borrow_mut().enclosing = Some(Rc::clone(&environments[enclosing]));
+            }
+        }
+
+        let mut restore = Restore {
+            environments,
+            upvalues: self.upvalues.iter().map(|_| Upvalue::default()).collect(),
+            instances: vec![],
+            lists: self.lists.iter().map(|_| LoxList::default()).collect(),
+            maps: self.maps.iter().map(|_| LoxMap::default()).collect(),
+        };
+
+        let mut fields = vec![];
+        for instance in self.instances {
+            restore
+                .instances
+                .push(LoxInstance::new(restore.class(instance.class)));
+            fields.push(instance.fields);
+        }
+
+        for (id, instance_fields) in fields.into_iter().enumerate() {
+            for (name, value) in instance_fields {
+                let value = restore.value(value);
+                restore.instances[id]
+                    .fields
+                    .borrow_mut()
+                    .insert(name, value);
+            }
+        }
+
+        for (id, elements) in self.lists.into_iter().enumerate() {
+            let elements: Vec<ExprResult> = elements
+                .into_iter()
+                .map(|element| restore.value(element))
+                .collect();
+            *restore.lists[id].elements.borrow_mut() = elements;
+        }
+
+        /* the entries of a copied map are already unique, so they are not compared again. */
+        for (id, entries) in self.maps.into_iter().enumerate() {
+            let entries: Vec<(ExprResult, ExprResult)> = entries
+                .into_iter()
+                .map(|(key, value)| (restore.value(key), restore.value(value)))
+                .collect();
+            *restore.maps[id].entries.borrow_mut() = entries;
+        }
+
+        for (id, value) in self.upvalues.into_iter().enumerate() {
+            let value = restore.value(value);
+            *restore.upvalues[id].borrow_mut() = value;
+        }
+
+        for (id, environment) in self.environments.into_iter().enumerate() {
+            let values: Vec<Binding> = environment
+                .values
+                .into_iter()
+                .map(|binding| match binding {
+                    TransferBinding::Value(value) => Binding::Value(restore.value(*value)),
+                    TransferBinding::Upvalue(id) => {
+                        Binding::Captured(Rc::clone(&restore.upvalues[id]))
+                    }
+                })
+                .collect();
+
+            let mut restored = restore.environments[id].borrow_mut();
+            match (globals, environment.slots) {
+                (Some(_), Some(slots)) if id == 0 => {
+                    for (name, slot) in slots {
+                        restored.define(&name, values[slot].get());
+                    }
+                }
+                (_, slots) => {
+                    restored.values = values;
+                    restored.slots = slots;
+                }
+            }
+        }
+
+        restore.value(self.value)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::base::parser::Parser;
+use crate::base::scanner::Scanner;
+use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::interrupt::InterruptHandle;
+use crate::interpreter::natives::TASK_STACK_SIZE;
+use crate::interpreter::options::InterpreterOptions;
+use crate::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Sender};
+use std::thread::{self, JoinHandle};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum WorkerError {
+    #[error("{0}")]
+    Script(String),
+    #[error("Interpreter worker has stopped!")]
+    Stopped,
+}
+
+struct Request {
+    source: String,
+    reply: Sender<Result<String, String>>,
+}
+
+/// A thread-safe handle to an interpreter running on a thread of its own.
+///
+/// The interpreter is built on `Rc<RefCell<...>>` and can't leave the thread it was created
+/// on, but the handle is `Send + Sync`, so it can be shared between worker threads or moved
+/// into async tasks. Scripts are run one after another and share their globals, like the
+/// lines entered in the REPL.
+pub struct InterpreterWorker {
+    requests: Option<Sender<Request>>,
+    interrupt: InterruptHandle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl InterpreterWorker {
+    pub fn new(options: InterpreterOptions) -> Self {
+        let (requests, received) = channel::<Request>();
+        let (handle, interrupt) = channel();
+
+        let thread = thread::Builder::new()
+            .stack_size(TASK_STACK_SIZE)
+            .spawn(move || {
+                let output = Rc::new(RefCell::new(Vec::new()));
+                let interpreter =
+                    Rc::new(Interpreter::new(Rc::clone(&output)).with_options(options));
+                let _ = handle.send(interpreter.interrupt_handle());
+
+                for request in received {
+                    let result = run(&interpreter, &request.source);
+                    let written = output.take();
+                    let reply = result
+                        .map(|_| String::from_utf8_lossy(&written).into_owned())
+                        .map_err(|error| error.to_string());
+                    let _ = request.reply.send(reply);
+                }
+            })
+            .expect("failed to spawn interpreter worker");
+
+        Self {
+            requests: Some(requests),
+            interrupt: interrupt.recv().unwrap_or_default(),
+            thread: Some(thread),
+        }
+    }
+
+    /// Runs a script and returns what it printed. Blocks until the script and all scripts
+    /// submitted before it have finished.
+    pub fn run(&self, source: &str) -> Result<String, WorkerError> {
+        let (reply, result) = channel();
+        self.requests
+            .as_ref()
+            .ok_or(WorkerError::Stopped)?
+            .send(Request {
+                source: source.to_owned(),
+                reply,
+            })
+            .map_err(|_| WorkerError::Stopped)?;
+
+        result
+            .recv()
+            .map_err(|_| WorkerError::Stopped)?
+            .map_err(WorkerError::Script)
+    }
+
+    /// Aborts the script that is currently running.
+    pub fn interrupt(&self) {
+        self.interrupt.interrupt();
+    }
+}
+
+impl Drop for InterpreterWorker {
+    fn drop(&mut self) {
+        self.requests.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(interpreter: &Rc<Interpreter>, source: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let tokens = Scanner::new(source).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Resolver::new(Rc::clone(interpreter)).resolve_stmts(&statements)?;
+    interpreter.interpret(&statements)?;
+
+    Ok(())
+}
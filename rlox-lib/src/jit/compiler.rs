@@ -0,0 +1,866 @@
+use crate::base::ast::{Ast, ExprRef, StmtRef};
+use crate::base::diagnostic::DiagnosticError;
+use crate::base::expr::{Expr, LiteralValue, NodeId, NodeMap};
+use crate::base::expr_result::CompiledFunction;
+use crate::base::scanner::{Token, TokenType};
+use crate::base::stmt::Stmt;
+use crate::interpreter::interrupt::InterruptHandle;
+use crate::interpreter::options::InterpreterOptions;
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{types, AbiParam, Block, InstBuilder, MemFlags};
+use cranelift_codegen::ir::{StackSlotData, StackSlotKind, Type, UserFuncName, Value};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, FuncId, Linkage, Module};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+use thiserror::Error;
+
+/// Calls nested deeper than this give up and leave the recursion to the interpreter, so that
+/// compiled code does not overflow the native stack.
+const MAX_DEPTH: i64 = 1000;
+
+#[derive(Debug, Error)]
+pub enum CompilerError {
+    #[error("Failed to compile to machine code: {message}")]
+    Codegen { message: String },
+}
+
+impl DiagnosticError for CompilerError {
+    fn code(&self) -> &'static str {
+        match self {
+            CompilerError::Codegen { .. } => "Codegen",
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        None
+    }
+
+    fn column(&self) -> Option<usize> {
+        None
+    }
+}
+
+fn codegen_error(error: impl Display) -> CompilerError {
+    CompilerError::Codegen {
+        message: error.to_string(),
+    }
+}
+
+/// Entry point of a compiled function. Takes the arguments, the depth of the call, the flag to
+/// set when giving up and the interrupt flag of the interpreter.
+type Entry = unsafe extern "C" fn(*const f64, i64, *mut u8, *const bool) -> f64;
+
+/// Machine code of the functions compiled from one script, freed with the last of them.
+struct Code(Option<JITModule>);
+
+impl Drop for Code {
+    fn drop(&mut self) {
+        if let Some(module) = self.0.take() {
+            // SAFETY: the functions that could run the code are gone.
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+struct Compiled {
+    _code: Rc<Code>,
+    entry: Entry,
+    arity: usize,
+    interrupt: InterruptHandle,
+}
+
+impl Compiled {
+    fn call(&self, arguments: &[f64]) -> Option<f64> {
+        if arguments.len() != self.arity {
+            return None;
+        }
+
+        let mut bail = 0;
+        // SAFETY: the code lives as long as `self` and reads `arity` arguments.
+        let result =
+            unsafe { (self.entry)(arguments.as_ptr(), 0, &mut bail, self.interrupt.as_ptr()) };
+        (bail == 0).then_some(result)
+    }
+}
+
+/// Compiles the functions of a script that only compute with numbers to machine code. Calls
+/// of these functions with numbers run the machine code, everything else stays with the
+/// [`Interpreter`](crate::interpreter::interpreter::Interpreter).
+///
+/// Only functions declared once at the top level of a script and never assigned to qualify,
+/// whose bodies use nothing but their parameters, local variables, number literals,
+/// arithmetic, comparisons, conditions, loops and calls of each other. Their calls have no
+/// effects besides their result, so the machine code may give up at any point, e.g. on deep
+/// recursion or when a function ends without returning a number, and the interpreter runs
+/// the body instead. Compiled functions assume that later scripts do not redeclare them.
+///
+/// Machine code doesn't account for [`InterpreterOptions::max_steps`] and
+/// [`InterpreterOptions::max_duration`], so nothing is compiled when either is set.
+pub struct Compiler {
+    options: InterpreterOptions,
+    interrupt: InterruptHandle,
+}
+
+impl Compiler {
+    /// Creates a compiler whose code gives up when `interrupt` is interrupted.
+    pub fn new(interrupt: InterruptHandle) -> Self {
+        Self {
+            options: InterpreterOptions::default(),
+            interrupt,
+        }
+    }
+
+    pub fn with_options(mut self, options: InterpreterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Compiles the numeric functions of a script. Returns the compiled functions by the id of
+    /// their declarations, for [`Interpreter::add_compiled_functions`](
+    /// crate::interpreter::interpreter::Interpreter::add_compiled_functions).
+    pub fn compile(&self, ast: &Ast) -> Result<NodeMap<CompiledFunction>, CompilerError> {
+        let mut compiled = NodeMap::default();
+        if self.options.max_steps.is_some() || self.options.max_duration.is_some() {
+            return Ok(compiled);
+        }
+
+        let functions = numeric_functions(ast, self.options.checked_division);
+        if functions.is_empty() {
+            return Ok(compiled);
+        }
+
+        let mut module =
+            JITModule::new(JITBuilder::new(default_libcall_names()).map_err(codegen_error)?);
+        let pointer = module.target_config().pointer_type();
+        let mut signature = module.make_signature();
+        signature.params.extend([
+            AbiParam::new(pointer),
+            AbiParam::new(types::I64),
+            AbiParam::new(pointer),
+            AbiParam::new(pointer),
+        ]);
+        signature.returns.push(AbiParam::new(types::F64));
+
+        let mut ids = HashMap::new();
+        for function in &functions {
+            let id = module
+                .declare_function(&function.name.lexeme, Linkage::Local, &signature)
+                .map_err(codegen_error)?;
+            ids.insert(function.name.lexeme.to_owned(), (id, function.params.len()));
+        }
+
+        let mut context = module.make_context();
+        let mut builder_context = FunctionBuilderContext::new();
+        for function in &functions {
+            let (id, _) = ids[&function.name.lexeme];
+            context.func.signature = signature.clone();
+            context.func.name = UserFuncName::user(0, id.as_u32());
+
+            Translator {
+                ast,
+                builder: FunctionBuilder::new(&mut context.func, &mut builder_context),
+                module: &mut module,
+                functions: &ids,
+                pointer,
+                parameters: [None; 4],
+                scopes: vec![],
+                variables: 0,
+                loops: vec![],
+            }
+            .function(function);
+
+            module
+                .define_function(id, &mut context)
+                .map_err(codegen_error)?;
+            module.clear_context(&mut context);
+        }
+        module.finalize_definitions().map_err(codegen_error)?;
+
+        let entries: Vec<_> = functions
+            .iter()
+            .map(|function| {
+                let address = module.get_finalized_function(ids[&function.name.lexeme].0);
+                // SAFETY: the function was compiled with the signature of `Entry`.
+                let entry = unsafe { std::mem::transmute::<*const u8, Entry>(address) };
+                (function.id, entry, function.params.len())
+            })
+            .collect();
+
+        let code = Rc::new(Code(Some(module)));
+        for (id, entry, arity) in entries {
+            let function = Compiled {
+                _code: Rc::clone(&code),
+                entry,
+                arity,
+                interrupt: self.interrupt.to_owned(),
+            };
+            compiled.insert(
+                id,
+                CompiledFunction::new(move |arguments| function.call(arguments)),
+            );
+        }
+
+        Ok(compiled)
+    }
+}
+
+/// A function declaration considered for compilation.
+struct Declaration<'a> {
+    id: NodeId,
+    name: &'a Token,
+    params: &'a [Token],
+    body: &'a [StmtRef],
+}
+
+/// Finds the functions at the top level of the script that only compute with numbers.
+fn numeric_functions(ast: &Ast, checked_division: bool) -> Vec<Declaration<'_>> {
+    let mut declared: HashMap<&str, usize> = HashMap::new();
+    for statement in ast.statements() {
+        let names = match &ast[*statement] {
+            Stmt::Var { name, .. }
+            | Stmt::Function { name, .. }
+            | Stmt::Class { name, .. }
+            | Stmt::Trait { name, .. } => vec![name.lexeme.as_str()],
+            Stmt::Destructure { names, .. } => names.iter().map(|n| n.lexeme.as_str()).collect(),
+            _ => vec![],
+        };
+        for name in names {
+            *declared.entry(name).or_default() += 1;
+        }
+    }
+
+    let assigned: Vec<&str> = ast
+        .expressions()
+        .iter()
+        .filter_map(|expression| match expression {
+            Expr::Assign { name, .. } => Some(name.lexeme.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut candidates: Vec<Declaration> = ast
+        .statements()
+        .iter()
+        .filter_map(|statement| match &ast[*statement] {
+            Stmt::Function {
+                id,
+                name,
+                params,
+                body,
+                is_async: false,
+                is_variadic: false,
+                is_generator: false,
+            } if declared[name.lexeme.as_str()] == 1
+                && !assigned.contains(&name.lexeme.as_str()) =>
+            {
+                Some(Declaration {
+                    id: *id,
+                    name,
+                    params,
+                    body,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+
+    /* functions calling rejected functions are rejected as well, until none is left. */
+    loop {
+        let arities: HashMap<&str, usize> = candidates
+            .iter()
+            .map(|function| (function.name.lexeme.as_str(), function.params.len()))
+            .collect();
+
+        let count = candidates.len();
+        candidates.retain(|function| {
+            Checker {
+                ast,
+                functions: &arities,
+                checked_division,
+                scopes: vec![],
+                loops: 0,
+            }
+            .function(function)
+        });
+
+        if candidates.len() == count {
+            return candidates;
+        }
+    }
+}
+
+/// Checks whether a function only computes with numbers.
+struct Checker<'a> {
+    ast: &'a Ast,
+    /// Arities of the functions that may be called.
+    functions: &'a HashMap<&'a str, usize>,
+    checked_division: bool,
+    scopes: Vec<Vec<&'a str>>,
+    loops: usize,
+}
+
+impl<'a> Checker<'a> {
+    fn function(&mut self, function: &Declaration<'a>) -> bool {
+        self.scopes
+            .push(function.params.iter().map(|p| p.lexeme.as_str()).collect());
+        function.body.iter().all(|s| self.statement(*s))
+    }
+
+    fn is_local(&self, name: &str) -> bool {
+        self.scopes.iter().flatten().any(|local| *local == name)
+    }
+
+    fn statement(&mut self, statement: StmtRef) -> bool {
+        let ast = self.ast;
+        match &ast[statement] {
+            Stmt::Block { statements } => {
+                self.scopes.push(vec![]);
+                let result = statements.iter().all(|s| self.statement(*s));
+                self.scopes.pop();
+                result
+            }
+            Stmt::Break { label, .. } | Stmt::Continue { label, .. } => {
+                label.is_none() && self.loops > 0
+            }
+            Stmt::Expression { expression } => self.number(*expression),
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.condition(*condition)
+                    && self.statement(*then_branch)
+                    && else_branch.is_none_or(|branch| self.statement(branch))
+            }
+            Stmt::Return { value, .. } => value.is_some_and(|value| self.number(value)),
+            Stmt::Var {
+                name,
+                initializer: Some(initializer),
+                ..
+            } => {
+                let result = self.number(*initializer);
+                self.scopes.last_mut().unwrap().push(&name.lexeme);
+                result
+            }
+            Stmt::While {
+                label,
+                condition,
+                body,
+                increment,
+                ..
+            } => {
+                self.loops += 1;
+                let result = label.is_none()
+                    && self.condition(*condition)
+                    && self.statement(*body)
+                    && increment.is_none_or(|increment| self.number(increment));
+                self.loops -= 1;
+                result
+            }
+            _ => false,
+        }
+    }
+
+    fn number(&mut self, expression: ExprRef) -> bool {
+        let ast = self.ast;
+        match &ast[expression] {
+            Expr::Assign { name, value, .. } => self.is_local(&name.lexeme) && self.number(*value),
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let supported = match operator.token_type {
+                    TokenType::Plus | TokenType::Minus | TokenType::Star => true,
+                    TokenType::Slash => !self.checked_division,
+                    _ => false,
+                };
+                supported && self.number(*left) && self.number(*right)
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => match &ast[*callee] {
+                Expr::Variable { name, .. } if !self.is_local(&name.lexeme) => {
+                    self.functions.get(name.lexeme.as_str()) == Some(&arguments.len())
+                        && arguments.iter().all(|argument| self.number(*argument))
+                }
+                _ => false,
+            },
+            Expr::Grouping { expression, .. } => self.number(*expression),
+            Expr::Literal {
+                value: LiteralValue::Number(_),
+                ..
+            } => true,
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.condition(*condition) && self.number(*then_branch) && self.number(*else_branch)
+            }
+            Expr::Unary {
+                operator, right, ..
+            } => operator.token_type == TokenType::Minus && self.number(*right),
+            Expr::Update { target, .. } => {
+                matches!(&ast[*target], Expr::Variable { name, .. } if self.is_local(&name.lexeme))
+            }
+            Expr::Variable { name, .. } => self.is_local(&name.lexeme),
+            _ => false,
+        }
+    }
+
+    fn condition(&mut self, expression: ExprRef) -> bool {
+        let ast = self.ast;
+        match &ast[expression] {
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                comparison(&operator.token_type).is_some()
+                    && self.number(*left)
+                    && self.number(*right)
+            }
+            Expr::Grouping { expression, .. } => self.condition(*expression),
+            Expr::Literal {
+                value: LiteralValue::Boolean(_),
+                ..
+            } => true,
+            Expr::Logical { left, right, .. } => self.condition(*left) && self.condition(*right),
+            Expr::Unary {
+                operator, right, ..
+            } => operator.token_type == TokenType::Bang && self.condition(*right),
+            _ => false,
+        }
+    }
+}
+
+fn comparison(token_type: &TokenType) -> Option<FloatCC> {
+    match token_type {
+        TokenType::Greater => Some(FloatCC::GreaterThan),
+        TokenType::GreaterEqual => Some(FloatCC::GreaterThanOrEqual),
+        TokenType::Less => Some(FloatCC::LessThan),
+        TokenType::LessEqual => Some(FloatCC::LessThanOrEqual),
+        TokenType::EqualEqual => Some(FloatCC::Equal),
+        TokenType::BangEqual => Some(FloatCC::NotEqual),
+        _ => None,
+    }
+}
+
+/// Translates a function accepted by the [`Checker`] to Cranelift IR. Numbers are `f64`
+/// values, conditions are `i8` values.
+struct Translator<'a, 'b> {
+    ast: &'b Ast,
+    builder: FunctionBuilder<'a>,
+    module: &'b mut JITModule,
+    functions: &'b HashMap<String, (FuncId, usize)>,
+    pointer: Type,
+    /// The parameters of the entry point, see [`Entry`].
+    parameters: [Option<Value>; 4],
+    scopes: Vec<Vec<(String, Variable)>>,
+    variables: u32,
+    /// Blocks to continue and to break out of the enclosing loops, innermost last.
+    loops: Vec<(Block, Block)>,
+}
+
+impl Translator<'_, '_> {
+    fn function(mut self, function: &Declaration) {
+        let entry = self.builder.create_block();
+        self.builder.append_block_params_for_function_params(entry);
+        self.builder.switch_to_block(entry);
+        for (index, parameter) in self.builder.block_params(entry).iter().enumerate() {
+            self.parameters[index] = Some(*parameter);
+        }
+
+        let depth = self.depth();
+        let too_deep =
+            self.builder
+                .ins()
+                .icmp_imm(IntCC::SignedGreaterThanOrEqual, depth, MAX_DEPTH);
+        self.give_up_if(too_deep);
+        self.check_interrupt();
+
+        let arguments = self.parameter(0);
+        let mut scope = vec![];
+        for (index, param) in function.params.iter().enumerate() {
+            let value = self.builder.ins().load(
+                types::F64,
+                MemFlags::trusted(),
+                arguments,
+                (index * size_of::<f64>()) as i32,
+            );
+            scope.push((param.lexeme.to_owned(), self.variable(value)));
+        }
+        self.scopes.push(scope);
+
+        for statement in function.body {
+            self.statement(*statement);
+        }
+
+        /* a function ending without a return returns nil, which is not a number. */
+        self.give_up();
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+    }
+
+    fn parameter(&self, index: usize) -> Value {
+        self.parameters[index].unwrap()
+    }
+
+    fn depth(&self) -> Value {
+        self.parameter(1)
+    }
+
+    fn variable(&mut self, value: Value) -> Variable {
+        let variable = Variable::from_u32(self.variables);
+        self.variables += 1;
+        self.builder.declare_var(variable, types::F64);
+        self.builder.def_var(variable, value);
+        variable
+    }
+
+    fn lookup(&self, name: &str) -> Variable {
+        self.scopes
+            .iter()
+            .rev()
+            .flatten()
+            .find(|(local, _)| local == name)
+            .map(|(_, variable)| *variable)
+            .expect("checked functions only use local variables")
+    }
+
+    /// Continues in a new block, after an instruction ending the current one.
+    fn unreachable(&mut self) {
+        let block = self.builder.create_block();
+        self.builder.switch_to_block(block);
+    }
+
+    /// Sets the flag to give up and returns.
+    fn give_up(&mut self) {
+        let bail = self.parameter(2);
+        let one = self.builder.ins().iconst(types::I8, 1);
+        self.builder.ins().store(MemFlags::trusted(), one, bail, 0);
+        let zero = self.builder.ins().f64const(0.0);
+        self.builder.ins().return_(&[zero]);
+        self.unreachable();
+    }
+
+    fn give_up_if(&mut self, condition: Value) {
+        let give_up = self.builder.create_block();
+        let next = self.builder.create_block();
+        self.builder.ins().brif(condition, give_up, &[], next, &[]);
+
+        self.builder.switch_to_block(give_up);
+        self.give_up();
+        self.builder.switch_to_block(next);
+    }
+
+    fn check_interrupt(&mut self) {
+        let interrupt = self.parameter(3);
+        let interrupted = self
+            .builder
+            .ins()
+            .atomic_load(types::I8, MemFlags::trusted(), interrupt);
+        self.give_up_if(interrupted);
+    }
+
+    fn statement(&mut self, statement: StmtRef) {
+        let ast = self.ast;
+        match &ast[statement] {
+            Stmt::Block { statements } => {
+                self.scopes.push(vec![]);
+                for statement in statements {
+                    self.statement(*statement);
+                }
+                self.scopes.pop();
+            }
+            Stmt::Break { .. } => {
+                let (_, exit) = *self.loops.last().unwrap();
+                self.builder.ins().jump(exit, &[]);
+                self.unreachable();
+            }
+            Stmt::Continue { .. } => {
+                let (next, _) = *self.loops.last().unwrap();
+                self.builder.ins().jump(next, &[]);
+                self.unreachable();
+            }
+            Stmt::Expression { expression } => {
+                self.number(*expression);
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let condition = self.condition(*condition);
+                let then_block = self.builder.create_block();
+                let else_block = self.builder.create_block();
+                let merge = self.builder.create_block();
+                self.builder
+                    .ins()
+                    .brif(condition, then_block, &[], else_block, &[]);
+
+                self.builder.switch_to_block(then_block);
+                self.statement(*then_branch);
+                self.builder.ins().jump(merge, &[]);
+
+                self.builder.switch_to_block(else_block);
+                if let Some(else_branch) = else_branch {
+                    self.statement(*else_branch);
+                }
+                self.builder.ins().jump(merge, &[]);
+
+                self.builder.switch_to_block(merge);
+            }
+            Stmt::Return {
+                value: Some(value), ..
+            } => {
+                let value = self.number(*value);
+                self.builder.ins().return_(&[value]);
+                self.unreachable();
+            }
+            Stmt::Var {
+                name,
+                initializer: Some(initializer),
+                ..
+            } => {
+                let value = self.number(*initializer);
+                let variable = self.variable(value);
+                self.scopes
+                    .last_mut()
+                    .unwrap()
+                    .push((name.lexeme.to_owned(), variable));
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                ..
+            } => {
+                let header = self.builder.create_block();
+                let body_block = self.builder.create_block();
+                let next = self.builder.create_block();
+                let exit = self.builder.create_block();
+                self.builder.ins().jump(header, &[]);
+
+                self.builder.switch_to_block(header);
+                self.check_interrupt();
+                let condition = self.condition(*condition);
+                self.builder
+                    .ins()
+                    .brif(condition, body_block, &[], exit, &[]);
+
+                self.builder.switch_to_block(body_block);
+                self.loops.push((next, exit));
+                self.statement(*body);
+                self.loops.pop();
+                self.builder.ins().jump(next, &[]);
+
+                self.builder.switch_to_block(next);
+                if let Some(increment) = increment {
+                    self.number(*increment);
+                }
+                self.builder.ins().jump(header, &[]);
+
+                self.builder.switch_to_block(exit);
+            }
+            _ => unreachable!("checked functions only contain numeric statements"),
+        }
+    }
+
+    fn number(&mut self, expression: ExprRef) -> Value {
+        let ast = self.ast;
+        match &ast[expression] {
+            Expr::Assign { name, value, .. } => {
+                let value = self.number(*value);
+                self.builder.def_var(self.lookup(&name.lexeme), value);
+                value
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.number(*left);
+                let right = self.number(*right);
+                match operator.token_type {
+                    TokenType::Plus => self.builder.ins().fadd(left, right),
+                    TokenType::Minus => self.builder.ins().fsub(left, right),
+                    TokenType::Star => self.builder.ins().fmul(left, right),
+                    _ => self.builder.ins().fdiv(left, right),
+                }
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                let Expr::Variable { name, .. } = &ast[*callee] else {
+                    unreachable!("checked functions only call functions by name")
+                };
+
+                let slot = self.builder.create_sized_stack_slot(StackSlotData::new(
+                    StackSlotKind::ExplicitSlot,
+                    (arguments.len().max(1) * size_of::<f64>()) as u32,
+                    3,
+                ));
+                for (index, argument) in arguments.iter().enumerate() {
+                    let argument = self.number(*argument);
+                    self.builder.ins().stack_store(
+                        argument,
+                        slot,
+                        (index * size_of::<f64>()) as i32,
+                    );
+                }
+
+                let (id, _) = self.functions[&name.lexeme];
+                let function = self.module.declare_func_in_func(id, self.builder.func);
+                let arguments = self.builder.ins().stack_addr(self.pointer, slot, 0);
+                let (depth, bail, interrupt) = (self.depth(), self.parameter(2), self.parameter(3));
+                let depth = self.builder.ins().iadd_imm(depth, 1);
+                let call = self
+                    .builder
+                    .ins()
+                    .call(function, &[arguments, depth, bail, interrupt]);
+                let result = self.builder.inst_results(call)[0];
+
+                /* the callee gave up, so the caller gives up as well. */
+                let gave_up = self
+                    .builder
+                    .ins()
+                    .load(types::I8, MemFlags::trusted(), bail, 0);
+                self.give_up_if(gave_up);
+                result
+            }
+            Expr::Grouping { expression, .. } => self.number(*expression),
+            Expr::Literal {
+                value: LiteralValue::Number(value),
+                ..
+            } => self.builder.ins().f64const(*value),
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let condition = self.condition(*condition);
+                let then_block = self.builder.create_block();
+                let else_block = self.builder.create_block();
+                let merge = self.builder.create_block();
+                let result = self.builder.append_block_param(merge, types::F64);
+                self.builder
+                    .ins()
+                    .brif(condition, then_block, &[], else_block, &[]);
+
+                self.builder.switch_to_block(then_block);
+                let value = self.number(*then_branch);
+                self.builder.ins().jump(merge, &[value]);
+
+                self.builder.switch_to_block(else_block);
+                let value = self.number(*else_branch);
+                self.builder.ins().jump(merge, &[value]);
+
+                self.builder.switch_to_block(merge);
+                result
+            }
+            Expr::Unary { right, .. } => {
+                let right = self.number(*right);
+                self.builder.ins().fneg(right)
+            }
+            Expr::Update {
+                operator,
+                target,
+                prefix,
+                ..
+            } => {
+                let Expr::Variable { name, .. } = &ast[*target] else {
+                    unreachable!("checked functions only update local variables")
+                };
+
+                let variable = self.lookup(&name.lexeme);
+                let old = self.builder.use_var(variable);
+                let delta = if operator.token_type == TokenType::PlusPlus {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let delta = self.builder.ins().f64const(delta);
+                let new = self.builder.ins().fadd(old, delta);
+                self.builder.def_var(variable, new);
+
+                if *prefix {
+                    new
+                } else {
+                    old
+                }
+            }
+            Expr::Variable { name, .. } => {
+                let variable = self.lookup(&name.lexeme);
+                self.builder.use_var(variable)
+            }
+            _ => unreachable!("checked functions only contain numeric expressions"),
+        }
+    }
+
+    fn condition(&mut self, expression: ExprRef) -> Value {
+        let ast = self.ast;
+        match &ast[expression] {
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.number(*left);
+                let right = self.number(*right);
+                let comparison = comparison(&operator.token_type).unwrap();
+                self.builder.ins().fcmp(comparison, left, right)
+            }
+            Expr::Grouping { expression, .. } => self.condition(*expression),
+            Expr::Literal {
+                value: LiteralValue::Boolean(value),
+                ..
+            } => self.builder.ins().iconst(types::I8, i64::from(*value)),
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.condition(*left);
+                let evaluate_right = self.builder.create_block();
+                let merge = self.builder.create_block();
+                let result = self.builder.append_block_param(merge, types::I8);
+
+                if operator.token_type == TokenType::Or {
+                    self.builder
+                        .ins()
+                        .brif(left, merge, &[left], evaluate_right, &[]);
+                } else {
+                    self.builder
+                        .ins()
+                        .brif(left, evaluate_right, &[], merge, &[left]);
+                }
+
+                self.builder.switch_to_block(evaluate_right);
+                let right = self.condition(*right);
+                self.builder.ins().jump(merge, &[right]);
+
+                self.builder.switch_to_block(merge);
+                result
+            }
+            Expr::Unary { right, .. } => {
+                let right = self.condition(*right);
+                self.builder.ins().icmp_imm(IntCC::Equal, right, 0)
+            }
+            _ => unreachable!("checked functions only contain comparisons as conditions"),
+        }
+    }
+}
@@ -1,2 +1,7 @@
 pub mod base;
+pub mod bytecode;
+pub mod closures;
 pub mod interpreter;
+#[cfg(feature = "jit")]
+pub mod jit;
+pub mod project;
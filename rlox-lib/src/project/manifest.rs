@@ -0,0 +1,136 @@
+use crate::interpreter::lint::{LintError, LintLevels};
+use crate::interpreter::natives;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the optional manifest file in the root directory of a project.
+pub const MANIFEST_FILE: &str = "lox.toml";
+
+#[derive(Error, Debug)]
+pub enum ProjectError {
+    #[error("{}: {message}", path.display())]
+    UnreadableFile { path: PathBuf, message: String },
+    #[error("{}: {message}", path.display())]
+    InvalidManifest { path: PathBuf, message: String },
+    #[error("{}: Unknown standard library feature {feature:?}!", path.display())]
+    UnknownStdlibFeature { path: PathBuf, feature: String },
+    #[error("{}: Invalid lint setting: {message}", path.display())]
+    InvalidLint { path: PathBuf, message: String },
+    #[error("Entry point {path:?} does not exist!")]
+    MissingEntryPoint { path: PathBuf },
+}
+
+/// Settings of a project, read from its `lox.toml`.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// Script that is executed when running the project, relative to the project root.
+    pub entry: String,
+    /// Directories that are searched for imported modules, relative to the project root.
+    pub import_paths: Vec<String>,
+    /// Standard library features available to the project. All features are available if unset.
+    pub stdlib: Option<Vec<String>>,
+    /// Lint levels by lint name.
+    pub lints: BTreeMap<String, String>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            entry: String::from("main.lox"),
+            import_paths: vec![],
+            stdlib: None,
+            lints: BTreeMap::new(),
+        }
+    }
+}
+
+impl Manifest {
+    pub fn parse(input: &str, path: &Path) -> Result<Self, ProjectError> {
+        let manifest: Manifest =
+            toml::from_str(input).map_err(|error| ProjectError::InvalidManifest {
+                path: path.to_owned(),
+                message: error.message().to_owned(),
+            })?;
+
+        for feature in manifest.stdlib.iter().flatten() {
+            if !natives::FEATURES.contains(&feature.as_str()) {
+                return Err(ProjectError::UnknownStdlibFeature {
+                    path: path.to_owned(),
+                    feature: feature.to_owned(),
+                });
+            }
+        }
+
+        manifest
+            .lint_levels()
+            .map_err(|error| ProjectError::InvalidLint {
+                path: path.to_owned(),
+                message: error.to_string(),
+            })?;
+
+        Ok(manifest)
+    }
+
+    /// Returns the lint levels set in the manifest.
+    pub fn lint_levels(&self) -> Result<LintLevels, LintError> {
+        let mut levels = LintLevels::new();
+        for (lint, level) in &self.lints {
+            levels.set_by_name(lint, level)?;
+        }
+
+        Ok(levels)
+    }
+}
+
+/// A directory containing an entry point script and an optional manifest.
+#[derive(Debug)]
+pub struct Project {
+    root: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Project {
+    pub fn open(root: &Path) -> Result<Self, ProjectError> {
+        let path = root.join(MANIFEST_FILE);
+        let manifest = match fs::read_to_string(&path) {
+            Ok(input) => Manifest::parse(&input, &path)?,
+            Err(error) if error.kind() == ErrorKind::NotFound => Manifest::default(),
+            Err(error) => {
+                return Err(ProjectError::UnreadableFile {
+                    path,
+                    message: error.to_string(),
+                })
+            }
+        };
+
+        let project = Self {
+            root: root.to_owned(),
+            manifest,
+        };
+
+        if !project.entry_point().is_file() {
+            return Err(ProjectError::MissingEntryPoint {
+                path: project.entry_point(),
+            });
+        }
+
+        Ok(project)
+    }
+
+    pub fn entry_point(&self) -> PathBuf {
+        self.root.join(&self.manifest.entry)
+    }
+
+    pub fn import_paths(&self) -> Vec<PathBuf> {
+        self.manifest
+            .import_paths
+            .iter()
+            .map(|path| self.root.join(path))
+            .collect()
+    }
+}
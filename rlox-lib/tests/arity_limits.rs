@@ -0,0 +1,42 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+
+fn names(count: usize) -> String {
+    (0..count)
+        .map(|i| format!("a{i}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[test]
+fn test_maximum_arity() {
+    let input = format!(
+        "fun f({}) {{ return a254; }} print f({});",
+        names(255),
+        vec!["1"; 255].join(", ")
+    );
+
+    assert_eq!(common::interpret(&input).unwrap(), "1\n");
+}
+
+#[test]
+fn test_too_many_parameters() {
+    let error = common::interpret(&format!("fun f({}) {{}}", names(256))).unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::TooManyParameters { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_too_many_arguments() {
+    let error =
+        common::interpret(&format!("print clock({});", vec!["1"; 256].join(", "))).unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::TooManyArguments { line: 1, .. })
+    ));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+const INPUT: &str = r###"
+fun twice(f, x) {
+  return f(f(x));
+}
+
+print twice(fun (x) -> x * 2, 3);
+print twice(fun (x) { return x + 1; }, 3);
+
+fun square(x) -> x * x;
+print square(5);
+
+var add = fun (a, b) -> a + b;
+print add(1, 2);
+print add;
+
+fun counter() {
+  var count = 0;
+  return fun () -> count = count + 1;
+}
+
+var next = counter();
+next();
+print next();
+
+class Rectangle {
+  init(width, height) {
+    this.width = width;
+    this.height = height;
+  }
+
+  area() -> this.width * this.height;
+}
+
+print Rectangle(2, 3).area();
+"###;
+
+const RESULT: &str = r###"
+12
+5
+25
+3
+<fn lambda>
+2
+6
+"###;
+
+#[test]
+fn test_arrow_functions() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
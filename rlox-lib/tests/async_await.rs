@@ -0,0 +1,168 @@
+mod common;
+
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+const INPUT: &str = r###"
+async fun delayed(label, ms) {
+  await sleepAsync(ms);
+  print label;
+  return ms;
+}
+
+async fun main() {
+  var slow = delayed("slow", 30);
+  var fast = delayed("fast", 10);
+  print "started";
+  print await slow + await fast;
+}
+
+main();
+print "scheduled";
+
+class Greeter {
+  async greet(name) {
+    return "Hello, " + name + "!";
+  }
+}
+
+print await Greeter().greet("async");
+"###;
+
+const RESULT: &str = r###"
+scheduled
+started
+fast
+slow
+40
+Hello, async!
+"###;
+
+const INPUT_AWAIT_OUTSIDE_ASYNC: &str = r###"
+fun wait() {
+  await sleepAsync(10);
+}
+"###;
+
+#[test]
+fn test_async_await() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_await_outside_async_function() {
+    let error = common::interpret(INPUT_AWAIT_OUTSIDE_ASYNC).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::AwaitOutsideAsyncFunction { line: 3, .. })
+    ))
+}
+
+const INPUT_NESTED_REJECTION: &str = r###"
+async fun inner() {
+  return 1 + nil;
+}
+async fun outer() {
+  return await inner();
+}
+await outer();
+"###;
+
+#[test]
+fn test_nested_rejection() {
+    let error = common::interpret(INPUT_NESTED_REJECTION).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "8: Awaited promise was rejected: 3: Operand of '+' must be a number, got nil!"
+    );
+}
+
+#[test]
+fn test_exit_in_async_function() {
+    for input in [
+        "async fun f() { exit(3); }\nawait f();",
+        "async fun f() { exit(3); }\nf();",
+    ] {
+        let error = common::interpret(input).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::Exit { code: 3 })
+        ));
+    }
+}
+
+#[test]
+fn test_interrupt_in_async_function() {
+    let interpreter = Rc::new(Interpreter::new(Rc::new(RefCell::new(Vec::new()))));
+
+    let tokens = Scanner::new("async fun spin() { while (true) {} }\nawait spin();")
+        .scan_tokens()
+        .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    let interrupt = interpreter.interrupt_handle();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        interrupt.interrupt();
+    });
+
+    assert!(matches!(
+        interpreter.interpret(&statements),
+        Err(RuntimeError::Interrupted)
+    ));
+}
+
+#[test]
+fn test_sleep_async_invalid_duration() {
+    for input in ["sleepAsync(-1);", "sleepAsync(1/0);", "sleepAsync(1e300);"] {
+        let error = common::interpret(input).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::InvalidArgument { line: 1, .. })
+        ));
+    }
+}
+
+#[test]
+fn test_read_file_async() {
+    let path = std::env::temp_dir().join(format!("rlox-read-{}.txt", std::process::id()));
+    fs::write(&path, "from the file").unwrap();
+
+    let input = format!(
+        r###"
+async fun tick() {{
+  print "tick";
+}}
+
+var contents = readFileAsync("{}");
+tick();
+print "reading";
+print await contents;
+"###,
+        path.display()
+    );
+    let result = common::interpret(&input);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(result.unwrap(), "reading\ntick\nfrom the file\n");
+
+    let error = common::interpret("await readFileAsync(\"/nonexistent/rlox.txt\");").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::PromiseRejected { line: 1, .. })
+    ));
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use rlox_lib::base::scanner::ScannerError;
+
+const INPUT: &str = r###"
+/* a block comment */
+print 1; /* spanning
+several
+lines */ print 2;
+/* outer /* nested
+   comment */ still outer */
+print 3 /* inside a statement */ + 1;
+/**/
+print "/* not a comment */";
+fail;
+"###;
+
+const RESULT: &str = r###"
+1
+2
+4
+/* not a comment */
+"###;
+
+#[test]
+fn test_block_comments() {
+    let input = INPUT.replace("fail;\n", "");
+
+    assert_eq!(
+        common::interpret(&input).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_block_comments_line_counting() {
+    let error = common::interpret(INPUT).unwrap_err().to_string();
+
+    assert_eq!(error, "11: Undefined variable \"fail\"!");
+}
+
+#[test]
+fn test_block_comments_unterminated() {
+    let error = common::interpret("print 1;\n/* /* */").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ScannerError>(),
+        Some(ScannerError::UnterminatedComment { line: 2, column: 1 })
+    ));
+}
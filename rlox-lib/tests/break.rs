@@ -0,0 +1,65 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+var i = 0;
+while (true) {
+  if (i == 3) break;
+  print i;
+  i = i + 1;
+}
+
+for (var j = 0; j < 3; j = j + 1) {
+  for (var k = 0; k < 3; k = k + 1) {
+    if (k == 1) break;
+    print j * 10 + k;
+  }
+}
+
+fun find(limit) {
+  var n = 0;
+  while (n < limit) {
+    var next = n + 1;
+    if (next * next > limit) break;
+    n = next;
+  }
+  return n;
+}
+
+print find(30);
+"###;
+
+const RESULT: &str = r###"
+0
+1
+2
+0
+10
+20
+5
+"###;
+
+#[test]
+fn test_break() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_break_outside_loop() {
+    for input in [
+        "break;",
+        "if (true) { break; }",
+        "while (true) { fun f() { break; } }",
+    ] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::BreakOutsideLoop { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun add(a, b) {
+  return a + b;
+}
+
+fun log(message, ...values) {}
+
+class Point {
+  init(x, y) {}
+
+  length() {}
+}
+
+class Empty {}
+
+fun apply(callback, value) {
+  if (arity(callback) != 1) {
+    return name(callback) + " expects " + arity(callback) + " arguments";
+  }
+  return callback(value);
+}
+
+print arity(add);
+print name(add);
+print arity(log);
+print arity(Point);
+print name(Point);
+print arity(Empty);
+print arity(Point(1, 2).length);
+print name(Point(1, 2).length);
+print arity(clock);
+print name(sqrt);
+print name(fun (x) -> x);
+print apply(fun (x) -> x * 2, 21);
+print apply(add, 1);
+"###;
+
+const RESULT: &str = r###"
+2
+add
+1
+2
+Point
+0
+0
+length
+0
+sqrt
+lambda
+42
+add expects 2 arguments
+"###;
+
+#[test]
+fn test_callable_introspection() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_introspection_of_non_callable() {
+    for input in ["arity(1);", "name(\"add\");"] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::InvalidArgument { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+const INPUT: &str = r###"
+var requests = channel();
+var responses = channel();
+
+fun worker() {
+  var n = recv(requests);
+  while (n != nil) {
+    send(responses, n * n);
+    n = recv(requests);
+  }
+
+  return "done";
+}
+
+var task = spawn(worker);
+
+for (var i = 1; i <= 3; i = i + 1) {
+  send(requests, i);
+  print recv(responses);
+}
+
+send(requests, nil);
+print join(task);
+
+class Message {
+  init(text) {
+    this.text = text;
+  }
+}
+
+var message = Message("original");
+send(requests, message);
+message.text = "changed";
+print recv(requests).text;
+"###;
+
+const RESULT: &str = r###"
+1
+4
+9
+done
+original
+"###;
+
+#[test]
+fn test_channels() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
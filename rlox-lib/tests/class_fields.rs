@@ -0,0 +1,61 @@
+mod common;
+
+const INPUT: &str = r###"
+var created = 0;
+
+class Point {
+  x = 0;
+  y = this.x + 1;
+  id = created = created + 1;
+
+  init(x) {
+    print "init sees " + "${this.x}, ${this.y}";
+    this.x = x;
+  }
+}
+
+var p = Point(5);
+print p.x;
+print p.y;
+print Point(1).id;
+
+class Shape {
+  sides = 0;
+  name = "shape";
+}
+
+class Square < Shape {
+  sides = 4;
+
+  describe() -> this.name + " with ${this.sides} sides";
+}
+
+print Square().describe();
+
+class Bag {
+  items = {};
+}
+
+var a = Bag();
+var b = Bag();
+a.items["apple"] = 1;
+print b.items;
+"###;
+
+const RESULT: &str = r###"
+init sees 0, 1
+5
+1
+init sees 0, 1
+2
+shape with 4 sides
+{}
+"###;
+
+#[test]
+fn test_class_fields() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
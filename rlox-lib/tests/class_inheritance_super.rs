@@ -1,3 +1,5 @@
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
 mod common;
 
 const INPUT: &str = r###"
@@ -29,3 +31,19 @@ fn test_class_inheritance_super() {
         RESULT.strip_prefix('\n').unwrap()
     )
 }
+
+#[test]
+fn test_invalid_super() {
+    /* the functions are never called, so the errors must be reported before running. */
+    let error = common::interpret("fun f() {\n  super.cook();\n}").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::SuperOutsideClass { line: 2, .. })
+    ));
+
+    let error = common::interpret("class A {\n  cook() {\n    super.cook();\n  }\n}").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::SuperWithoutSuperclass { line: 3, .. })
+    ));
+}
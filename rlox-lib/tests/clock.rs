@@ -0,0 +1,39 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+var start = clock();
+var sum = 0;
+for (var i = 0; i < 1000; i = i + 1) {
+  sum = sum + i;
+}
+var elapsed = clock() - start;
+print start > 0;
+print elapsed >= 0;
+print clock;
+"###;
+
+const RESULT: &str = r###"
+true
+true
+<native fn>
+"###;
+
+#[test]
+fn test_clock() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_clock_with_arguments() {
+    let error = common::interpret("clock(1);").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NonMatchingNumberOfArguments { line: 1, .. })
+    ));
+}
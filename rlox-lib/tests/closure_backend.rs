@@ -0,0 +1,184 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::closures::compiler::Compiler;
+use rlox_lib::closures::runtime::Runtime;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+
+const INPUT: &str = r###"
+fun fib(n) {
+  if (n <= 1) return n;
+  return fib(n - 2) + fib(n - 1);
+}
+print fib(15);
+
+fun makeCounter() {
+  var count = 0;
+  fun increment() {
+    count = count + 1;
+    return count;
+  }
+  return increment;
+}
+
+var counter = makeCounter();
+counter();
+print counter();
+
+class Shape {
+  init(name) {
+    this.name = name;
+  }
+
+  describe() {
+    return this.name + " with area " + this.area();
+  }
+
+  toString() {
+    return "Shape(" + this.name + ")";
+  }
+}
+
+class Square < Shape {
+  init(side) {
+    super.init("square");
+    this.side = side;
+  }
+
+  area() {
+    return this.side * this.side;
+  }
+}
+
+var square = Square(3);
+print square.describe();
+print square;
+var describe = square.describe;
+print describe();
+
+var total = 0;
+for (var i = 0; i < 10; i = i + 1) {
+  if (i == 5) break;
+  if (i == 2) continue;
+  total = total + i;
+}
+print total;
+print "total: ${total}";
+print !nil and 1 >= 1;
+print 7 / 2 - -1;
+
+var closures = "";
+for (var i = 0; i < 3; i = i + 1) {
+  fun capture() { return i; }
+  closures = closures + capture();
+}
+print closures;
+
+outer: while (true) {
+  while (true) {
+    break outer;
+  }
+}
+"###;
+
+const RESULT: &str = r###"
+610
+2
+square with area 9
+Shape(square)
+square with area 9
+8
+total: 8
+true
+4.5
+012
+"###;
+
+fn run_closures(input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::new(RefCell::new(Vec::new()))));
+
+    let tokens = Scanner::new(input).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Resolver::new(Rc::clone(&interpreter)).resolve_stmts(&statements)?;
+
+    let function = Compiler::new().compile(&statements)?;
+    Runtime::new(Rc::clone(&buf)).interpret(function)?;
+
+    let output = std::str::from_utf8(buf.borrow().as_slice())?.to_string();
+    Ok(output)
+}
+
+#[test]
+fn test_closure_backend() {
+    assert_eq!(
+        run_closures(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_closure_backend_matches_tree_walker() {
+    assert_eq!(
+        run_closures(INPUT).unwrap(),
+        common::interpret(INPUT).unwrap()
+    );
+}
+
+#[test]
+fn test_closure_backend_globals_persist() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let compiler = Compiler::new();
+    let mut runtime = Runtime::new(Rc::clone(&buf));
+
+    for input in ["var a = 1;", "print a + 1;"] {
+        let tokens = Scanner::new(input).scan_tokens().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        runtime
+            .interpret(compiler.compile(&statements).unwrap())
+            .unwrap();
+    }
+
+    assert_eq!(std::str::from_utf8(buf.borrow().as_slice()).unwrap(), "2\n");
+}
+
+#[test]
+fn test_closure_backend_errors() {
+    let error = run_closures("print 1;\nprint -\"a\";").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NumberExpected { line: 2, .. })
+    ));
+
+    let error = run_closures("fun f() { return f(); }\nf();").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::StackOverflow { line: 1, .. })
+    ));
+
+    let error = run_closures("fun f(a) {}\nf();").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NonMatchingNumberOfArguments { line: 2, .. })
+    ));
+
+    let error = run_closures("print missing;").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::UndefinedVariable { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_closure_backend_operand_in_error_message() {
+    let error = run_closures("var a = nil;\na++;").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "2: Operand of '++' must be a number, got nil!"
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+const INPUT: &str = r###"
+fun makeCounter() {
+  var unused = "not captured";
+  var count = 0;
+  fun increment() {
+    count = count + 1;
+    return count;
+  }
+  fun get() {
+    return count;
+  }
+  return [increment, get];
+}
+
+var counter = makeCounter();
+counter[0]();
+counter[0]();
+print counter[1]();
+
+fun outer() {
+  var x = "outer";
+  fun middle() {
+    fun inner() {
+      return x;
+    }
+    return inner;
+  }
+  x = "assigned";
+  return middle();
+}
+print outer()();
+
+var closures = [];
+for (var i = 0; i < 3; i = i + 1) {
+  var j = i;
+  closures.push(fun () { return j; });
+}
+print closures.map(fun (closure) { return closure(); });
+
+{
+  fun countdown(n) {
+    if (n == 0) return "done";
+    return countdown(n - 1);
+  }
+  print countdown(3);
+}
+
+class Base {
+  greet() {
+    return "base";
+  }
+}
+
+class Derived < Base {
+  greet() {
+    var greeting = fun () { return super.greet() + " " + this.name; };
+    return greeting();
+  }
+}
+
+var derived = Derived();
+derived.name = "derived";
+print derived.greet();
+"###;
+
+const RESULT: &str = r###"
+2
+assigned
+[0, 1, 2]
+done
+base derived
+"###;
+
+#[test]
+fn test_closures() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
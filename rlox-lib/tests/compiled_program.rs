@@ -0,0 +1,153 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::program::{Program, ProgramError, PROGRAM_VERSION};
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::lint::{Lint, LintLevels, Severity};
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+// rlox: allow(unused_variable)
+class Counter {
+  init(start) {
+    this.count = start;
+  }
+
+  next() {
+    this.count = this.count + 1;
+    return this.count;
+  }
+}
+
+fun makeAdder(n) {
+  return fun (x) { return x + n; };
+}
+
+var counter = Counter(41);
+var add = makeAdder(counter.next());
+var [a, b] = [add(1), "${add(2)}!"];
+print a;
+print b;
+"###;
+
+const RESULT: &str = r###"
+43
+44!
+"###;
+
+fn compile(input: &str, options: InterpreterOptions) -> Vec<u8> {
+    let mut scanner = Scanner::new(input);
+    let tokens = scanner.scan_tokens().unwrap();
+    let ast = Parser::new(tokens).parse().unwrap();
+
+    Program {
+        ast,
+        pragmas: scanner.pragmas().to_vec(),
+        options,
+    }
+    .to_bytes()
+    .unwrap()
+}
+
+fn run(interpreter: &Rc<Interpreter>, program: &Program) -> Result<(), RuntimeError> {
+    let resolver = Resolver::new(Rc::clone(interpreter))
+        .with_pragmas(&program.pragmas)
+        .unwrap();
+    resolver.resolve_stmts(&program.ast).unwrap();
+
+    interpreter.interpret(&program.ast)
+}
+
+#[test]
+fn test_compiled_program() {
+    let program = Program::from_bytes(&compile(INPUT, InterpreterOptions::default())).unwrap();
+    assert_eq!(program.pragmas.len(), 1);
+
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter =
+        Rc::new(Interpreter::new(Rc::clone(&buf)).with_options(program.options.clone()));
+    run(&interpreter, &program).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_compiled_program_loaded_twice() {
+    let bytes = compile(
+        "var x = 1;\nfun f() { var y = x; return y + 1; }\nprint f();",
+        InterpreterOptions::default(),
+    );
+
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+    for _ in 0..2 {
+        run(&interpreter, &Program::from_bytes(&bytes).unwrap()).unwrap();
+    }
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        "2\n2\n"
+    );
+}
+
+#[test]
+fn test_compiled_program_options() {
+    let mut lints = LintLevels::new();
+    lints.set(Lint::UnusedVariable, Severity::Deny);
+    let bytes = compile(
+        "print 1 / 0;",
+        InterpreterOptions {
+            strict: true,
+            lints,
+            checked_division: true,
+            max_steps: Some(100),
+            ..InterpreterOptions::default()
+        },
+    );
+
+    let program = Program::from_bytes(&bytes).unwrap();
+    assert!(program.options.strict);
+    assert_eq!(
+        program.options.lints.severity(Lint::UnusedVariable, false),
+        Severity::Deny
+    );
+    assert_eq!(program.options.max_steps, Some(100));
+
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::new(RefCell::new(Vec::new()))).with_options(program.options.clone()),
+    );
+    assert!(matches!(
+        run(&interpreter, &program),
+        Err(RuntimeError::DivisionByZero { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_invalid_compiled_program() {
+    let bytes = compile("print 1;", InterpreterOptions::default());
+
+    assert_eq!(
+        Program::from_bytes(b"print 1;").unwrap_err(),
+        ProgramError::InvalidFormat
+    );
+
+    let mut newer = bytes.clone();
+    newer[4..8].copy_from_slice(&(PROGRAM_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        Program::from_bytes(&newer).unwrap_err(),
+        ProgramError::UnsupportedVersion {
+            version: PROGRAM_VERSION + 1
+        }
+    );
+
+    assert!(matches!(
+        Program::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ProgramError::InvalidProgram { .. })
+    ));
+}
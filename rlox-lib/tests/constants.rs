@@ -0,0 +1,67 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+const greeting = "Hello";
+
+fun greet(name) {
+  const separator = ", ";
+  return greeting + separator + name;
+}
+
+{
+  const greeting = "Hi";
+  var shadow = greeting;
+  shadow = shadow + "!";
+  print shadow;
+}
+
+var counter = 0;
+counter = counter + 1;
+print greet("constants");
+print counter;
+"###;
+
+const RESULT: &str = r###"
+Hi!
+Hello, constants
+1
+"###;
+
+#[test]
+fn test_constants() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_constant_assignment() {
+    for input in [
+        "const a = 1; a = 2;",
+        "{ const a = 1; a = 2; }",
+        "{ const a = 1; { a = 2; } }",
+        "const a = 1; a++;",
+        "fun f() { a = 2; } const a = 1;",
+    ] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::ConstantAssignment { line: 1, .. })
+        ));
+    }
+}
+
+#[test]
+fn test_constant_without_initializer() {
+    let error = common::interpret("const a;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::MissingConstantInitializer { line: 1, .. })
+    ));
+}
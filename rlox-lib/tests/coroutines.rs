@@ -0,0 +1,105 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun accumulate(total) {
+  while (true) {
+    var amount = yield total;
+    if (amount == nil) return "total " + total;
+    total = total + amount;
+  }
+}
+
+var co = coroutine(accumulate);
+print co;
+print co.status();
+print co.resume(10);
+print co.resume(5);
+print co.resume(7);
+print co.status();
+print co.resume();
+print co.status();
+
+fun traffic() {
+  var state = "red";
+  var waited;
+  while (true) {
+    waited = yield state;
+    if (state == "red") {
+      state = "green";
+    } else if (state == "green") {
+      state = "yellow";
+    } else {
+      state = "red";
+    }
+    print "waited " + waited;
+  }
+}
+
+var light = coroutine(traffic);
+print light.resume();
+print light.resume(3);
+print light.resume(1);
+print light.resume(2);
+
+fun status() {
+  yield self.status();
+}
+
+var self = coroutine(status);
+print self.resume();
+
+print coroutine(fun (a, b) { return a + b; }).resume(1, 2);
+"###;
+
+const RESULT: &str = r###"
+<coroutine>
+suspended
+10
+15
+22
+suspended
+total 22
+dead
+red
+waited 3
+green
+waited 1
+yellow
+waited 2
+red
+running
+3
+"###;
+
+#[test]
+fn test_coroutines() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_resume_dead_coroutine() {
+    let error = common::interpret(
+        "var co = coroutine(fun () { yield 1; });\nco.resume();\nco.resume();\nco.resume();",
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::DeadCoroutine { line: 4, .. })
+    ));
+}
+
+#[test]
+fn test_yield_inside_expression() {
+    let error = common::interpret("fun f() {\n  var x;\n  print (x = yield 1);\n}").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::YieldInsideExpression { line: 3, .. })
+    ));
+}
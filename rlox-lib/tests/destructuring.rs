@@ -0,0 +1,83 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+class Point {
+  init(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  length() {
+    return this.x + this.y;
+  }
+}
+
+var pair = ["left", "right", "ignored"];
+var [a, b] = pair;
+print a;
+print b;
+
+fun swap(list) {
+  var [first, second] = list;
+  return [second, first];
+}
+
+print swap([1, 2]);
+
+var {x, y, length} = Point(3, 4);
+print x;
+print y;
+print length();
+
+{
+  var [inner] = [x * 10];
+  print inner;
+}
+"###;
+
+const RESULT: &str = r###"
+left
+right
+[2, 1]
+3
+4
+7
+30
+"###;
+
+#[test]
+fn test_destructuring() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_destructuring_errors() {
+    let error = common::interpret("var [a, b] = [1];").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::IndexOutOfBounds { line: 1, .. })
+    ));
+
+    let error = common::interpret("var [a] = 1;").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::ListExpected { line: 1, .. })
+    ));
+
+    let error = common::interpret("var {a} = [1];").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidPropertyAccess { line: 1, .. })
+    ));
+
+    let error = common::interpret("{ var [a, a] = [1, 2]; }").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::VariableAlreadyDefinedInScope { line: 1, .. })
+    ));
+}
@@ -0,0 +1,139 @@
+use rlox_lib::base::diagnostic::{
+    display_column, render_caret, Diagnostic, DiagnosticSeverity, Position, Span,
+};
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::lint::{Lint, Warning};
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::sync::Arc;
+
+mod common;
+
+const INPUT: &str = "var a = 1;\n\tvar b = \"日本\" + @;\n";
+
+const RESULT: &str = r###"2 |     var b = "日本" + @;
+  |                      ^"###;
+
+#[test]
+fn test_display_column() {
+    assert_eq!(display_column("abc", 3, 4), 3);
+    assert_eq!(display_column("\tabc", 2, 4), 5);
+    assert_eq!(display_column("ab\tc", 4, 4), 5);
+    assert_eq!(display_column("\tabc", 2, 8), 9);
+    assert_eq!(display_column("日本x", 3, 4), 5);
+    assert_eq!(display_column("e\u{301}x", 3, 4), 2);
+}
+
+#[test]
+fn test_render_caret() {
+    let error = Scanner::new(INPUT).scan_tokens().unwrap_err();
+    let (line, column) = error.location();
+
+    assert_eq!((line, column), (2, 17));
+    assert_eq!(render_caret(INPUT, line, column, 4).unwrap(), RESULT);
+    assert_eq!(render_caret(INPUT, 5, 1, 4), None);
+}
+
+#[test]
+fn test_scanner_error_diagnostic() {
+    let error = Scanner::new(INPUT).scan_tokens().unwrap_err();
+    let diagnostic = Diagnostic::error(&error).with_file(Some("main.lox"));
+
+    assert_eq!(diagnostic.severity, DiagnosticSeverity::Error);
+    assert_eq!(diagnostic.code, "UnknownSymbol");
+    assert_eq!(diagnostic.message, "Unknown symbol '@' detected!");
+    assert_eq!(diagnostic.file.as_deref(), Some("main.lox"));
+    assert_eq!(diagnostic.line, Some(2));
+    assert_eq!(diagnostic.column, Some(17));
+    assert_eq!(
+        diagnostic.span,
+        Some(Span {
+            start: Position {
+                line: 2,
+                column: 17
+            },
+            end: Position {
+                line: 2,
+                column: 18
+            },
+        })
+    );
+}
+
+#[test]
+fn test_parser_error_diagnostic() {
+    let tokens = Scanner::new("print 1;\nprint 2").scan_tokens().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+    let diagnostic = Diagnostic::error(&error);
+
+    assert_eq!(diagnostic.code, "MissingSemicolonAfterValue");
+    assert_eq!(diagnostic.message, "Expect ';' after value.");
+    assert_eq!(diagnostic.file, None);
+    assert_eq!(diagnostic.line, Some(2));
+    assert_eq!(diagnostic.column, Some(8));
+    assert_eq!(
+        diagnostic.span,
+        Some(Span {
+            start: Position { line: 2, column: 8 },
+            end: Position { line: 2, column: 9 },
+        })
+    );
+}
+
+#[test]
+fn test_runtime_error_diagnostic() {
+    let error = common::interpret("var a = 1;\nprint a - nil;").unwrap_err();
+    let error = error.downcast_ref::<RuntimeError>().unwrap();
+    let diagnostic = Diagnostic::error(error);
+
+    assert_eq!(diagnostic.code, "NumberExpected");
+    assert_eq!(
+        diagnostic.message,
+        "Operand of '-' must be a number, got nil!"
+    );
+    assert_eq!(diagnostic.line, Some(2));
+    assert_eq!(diagnostic.column, Some(9));
+}
+
+#[test]
+fn test_resolver_error_diagnostic() {
+    let error = common::interpret("{\n  var a = 1;\n  var a = 2;\n}").unwrap_err();
+    let error = error.downcast_ref::<RuntimeError>().unwrap();
+    let diagnostic = Diagnostic::error(error);
+
+    assert_eq!(diagnostic.code, "VariableAlreadyDefinedInScope");
+    assert_eq!(diagnostic.line, Some(3));
+    assert_eq!(diagnostic.column, Some(7));
+}
+
+#[test]
+fn test_error_in_file_diagnostic() {
+    let error = RuntimeError::DivisionByZero {
+        line: 4,
+        column: 11,
+    }
+    .in_file(&Arc::from("m.lox"));
+    let diagnostic = Diagnostic::error(&error).with_file(Some("main.lox"));
+
+    assert_eq!(diagnostic.code, "DivisionByZero");
+    assert_eq!(diagnostic.message, "Division by zero!");
+    assert_eq!(diagnostic.file.as_deref(), Some("m.lox"));
+    assert_eq!(diagnostic.line, Some(4));
+    assert_eq!(diagnostic.column, Some(11));
+}
+
+#[test]
+fn test_warning_diagnostic() {
+    let diagnostic = Diagnostic::warning(&Warning {
+        lint: Lint::UnusedVariable,
+        line: 3,
+        column: 7,
+        message: String::from("Local variable \"a\" is never read!"),
+    });
+
+    assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+    assert_eq!(diagnostic.code, "unused_variable");
+    assert_eq!(diagnostic.message, "Local variable \"a\" is never read!");
+    assert_eq!(diagnostic.line, Some(3));
+    assert_eq!(diagnostic.column, Some(7));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use rlox_lib::base::scanner::{Scanner, ScannerError};
+
+const INPUT: &str = r###"
+print 1_000_000;
+print 3.141_592;
+print 1_0 + 2_0;
+print 0xFF_FF;
+print 0b1_0;
+print 1_0e1_0;
+"###;
+
+const RESULT: &str = r###"
+1000000
+3.141592
+30
+65535
+2
+100000000000
+"###;
+
+#[test]
+fn test_digit_separators() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_digit_separators() {
+    for input in ["1_;", "1__0;", "1_.5;"] {
+        assert!(matches!(
+            Scanner::new(input).scan_tokens(),
+            Err(ScannerError::InvalidDigitSeparator { line: 1, column: 2 })
+        ));
+    }
+
+    for input in ["0x_1;", "0b_1;", "1e_5;", "0xF_;"] {
+        assert!(matches!(
+            Scanner::new(input).scan_tokens(),
+            Err(ScannerError::InvalidDigitSeparator { line: 1, .. })
+        ));
+    }
+}
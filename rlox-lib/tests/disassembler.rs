@@ -0,0 +1,93 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::bytecode::compiler::Compiler;
+use rlox_lib::bytecode::disassembler::disassemble;
+use rlox_lib::bytecode::vm::Vm;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+var a = 1;
+{
+  var b = a + 2;
+  print -b;
+}
+fun f(x) {
+  return x and !x;
+}
+"###;
+
+const RESULT: &str = r###"== <script> ==
+0000    1 OP_CONSTANT         1 '1'
+0002    2 OP_DEFINE_GLOBAL    0 'a'
+0004    4 OP_GET_GLOBAL       0 'a'
+0006    | OP_CONSTANT         2 '2'
+0008    | OP_ADD
+0009    5 OP_GET_LOCAL        1
+0011    | OP_NEGATE
+0012    | OP_PRINT
+0013    | OP_POP
+0014    7 OP_CLOSURE          4 <fn f>
+0016    | OP_DEFINE_GLOBAL    3 'f'
+0018    | OP_NIL
+0019    | OP_RETURN
+
+== <fn f> ==
+0000    8 OP_GET_LOCAL        1
+0002    | OP_JUMP_IF_FALSE    2 -> 9
+0005    | OP_POP
+0006    | OP_GET_LOCAL        1
+0008    | OP_NOT
+0009    | OP_RETURN
+0010    | OP_NIL
+0011    | OP_RETURN
+"###;
+
+#[test]
+fn test_disassembler() {
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let function = Compiler::new().compile(&statements).unwrap();
+
+    assert_eq!(disassemble(&function), RESULT);
+}
+
+const TRACE_INPUT: &str = r###"
+var a = 1;
+print -a;
+"###;
+
+const TRACE_RESULT: &str = r###"
+          [ <script> ]
+0000    1 OP_CONSTANT         1 '1'
+          [ <script> ][ 1 ]
+0002    2 OP_DEFINE_GLOBAL    0 'a'
+          [ <script> ]
+0004    3 OP_GET_GLOBAL       0 'a'
+          [ <script> ][ 1 ]
+0006    | OP_NEGATE
+          [ <script> ][ -1 ]
+0007    | OP_PRINT
+-1
+          [ <script> ]
+0008    | OP_NIL
+          [ <script> ][ nil ]
+0009    | OP_RETURN
+"###;
+
+#[test]
+fn test_trace_execution() {
+    let tokens = Scanner::new(TRACE_INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let function = Compiler::new().compile(&statements).unwrap();
+
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let mut vm = Vm::new(Rc::clone(&buf));
+    vm.set_trace(true);
+    vm.interpret(function).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        TRACE_RESULT.strip_prefix('\n').unwrap()
+    );
+}
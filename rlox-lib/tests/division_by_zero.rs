@@ -0,0 +1,55 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+
+const INPUT: &str = r###"
+print 1 / 0;
+print -1 / 0;
+print 0 / 0 == 0 / 0;
+"###;
+
+const RESULT: &str = r###"
+Infinity
+-Infinity
+false
+"###;
+
+#[test]
+fn test_division_by_zero() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_checked_division_by_zero() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf)).with_options(InterpreterOptions {
+            checked_division: true,
+            ..InterpreterOptions::default()
+        }),
+    );
+
+    let tokens = Scanner::new("print 6 / 3;\nprint 1 / 0;")
+        .scan_tokens()
+        .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    assert!(matches!(
+        interpreter.interpret(&statements),
+        Err(RuntimeError::DivisionByZero { line: 2, .. })
+    ));
+    assert_eq!(std::str::from_utf8(buf.borrow().as_slice()).unwrap(), "2\n");
+}
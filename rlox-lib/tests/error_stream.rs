@@ -0,0 +1,48 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+print "before";
+print -"text";
+print "after";
+"###;
+
+const OUTPUT: &str = r###"
+before
+"###;
+
+const ERRORS: &str = r###"
+3: Operand of '-' must be a number, got "text"!
+"###;
+
+#[test]
+fn test_error_stream() {
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new_with_error_stream(
+        Rc::clone(&output),
+        Rc::clone(&errors),
+    ));
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    let error = interpreter.interpret(&statements).unwrap_err();
+    interpreter.report_error(&error).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(output.borrow().as_slice()).unwrap(),
+        OUTPUT.strip_prefix('\n').unwrap()
+    );
+    assert_eq!(
+        std::str::from_utf8(errors.borrow().as_slice()).unwrap(),
+        ERRORS.strip_prefix('\n').unwrap()
+    );
+}
@@ -0,0 +1,179 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::bytecode::compiler::Compiler;
+use rlox_lib::bytecode::vm::Vm;
+use rlox_lib::closures::compiler::Compiler as ClosureCompiler;
+use rlox_lib::closures::runtime::Runtime;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+const INPUT: &str = r###"
+var i = 0;
+while (true) {
+  i = i + 1;
+  if (i <= 3) print i;
+}
+"###;
+
+fn run(options: InterpreterOptions, input: &str) -> (Result<(), RuntimeError>, String) {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)).with_options(options));
+
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    let result = interpreter.interpret(&statements);
+    let output = std::str::from_utf8(buf.borrow().as_slice())
+        .unwrap()
+        .to_string();
+
+    (result, output)
+}
+
+fn run_vm(options: InterpreterOptions, input: &str) -> (Result<(), RuntimeError>, String) {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let function = Compiler::new().compile(&statements).unwrap();
+    let result = Vm::new(Rc::clone(&buf))
+        .with_options(options)
+        .interpret(function);
+    let output = std::str::from_utf8(buf.borrow().as_slice())
+        .unwrap()
+        .to_string();
+
+    (result, output)
+}
+
+fn run_closures(options: InterpreterOptions, input: &str) -> (Result<(), RuntimeError>, String) {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::new(RefCell::new(Vec::new()))));
+
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    let function = ClosureCompiler::new().compile(&statements).unwrap();
+    let result = Runtime::new(Rc::clone(&buf))
+        .with_options(options)
+        .interpret(function);
+    let output = std::str::from_utf8(buf.borrow().as_slice())
+        .unwrap()
+        .to_string();
+
+    (result, output)
+}
+
+#[test]
+fn test_max_steps() {
+    let (result, output) = run(
+        InterpreterOptions {
+            max_steps: Some(1000),
+            ..InterpreterOptions::default()
+        },
+        INPUT,
+    );
+
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+    assert_eq!(output, "1\n2\n3\n");
+}
+
+#[test]
+fn test_max_steps_not_exceeded() {
+    let (result, output) = run(
+        InterpreterOptions {
+            max_steps: Some(3),
+            ..InterpreterOptions::default()
+        },
+        "var a = 1;\nprint a;\nprint a + 1;",
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output, "1\n2\n");
+}
+
+#[test]
+fn test_max_duration() {
+    let (result, _) = run(
+        InterpreterOptions {
+            max_duration: Some(Duration::from_millis(50)),
+            ..InterpreterOptions::default()
+        },
+        INPUT,
+    );
+
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+}
+
+#[test]
+fn test_vm_budget() {
+    let (result, output) = run_vm(
+        InterpreterOptions {
+            max_steps: Some(1000),
+            ..InterpreterOptions::default()
+        },
+        INPUT,
+    );
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+    assert_eq!(output, "1\n2\n3\n");
+
+    let (result, _) = run_vm(
+        InterpreterOptions {
+            max_steps: Some(100),
+            ..InterpreterOptions::default()
+        },
+        "fun count(n) { if (n > 0) count(n - 1); }\ncount(900);",
+    );
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+
+    let (result, _) = run_vm(
+        InterpreterOptions {
+            max_duration: Some(Duration::from_millis(50)),
+            ..InterpreterOptions::default()
+        },
+        INPUT,
+    );
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+}
+
+#[test]
+fn test_closure_backend_budget() {
+    let (result, output) = run_closures(
+        InterpreterOptions {
+            max_steps: Some(1000),
+            ..InterpreterOptions::default()
+        },
+        INPUT,
+    );
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+    assert_eq!(output, "1\n2\n3\n");
+
+    let (result, output) = run_closures(
+        InterpreterOptions {
+            max_steps: Some(3),
+            ..InterpreterOptions::default()
+        },
+        "var a = 1;\nprint a;\nprint a + 1;",
+    );
+    assert!(result.is_ok());
+    assert_eq!(output, "1\n2\n");
+
+    let (result, _) = run_closures(
+        InterpreterOptions {
+            max_duration: Some(Duration::from_millis(50)),
+            ..InterpreterOptions::default()
+        },
+        INPUT,
+    );
+    assert!(matches!(result, Err(RuntimeError::BudgetExceeded)));
+}
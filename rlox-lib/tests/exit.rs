@@ -0,0 +1,54 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+
+const INPUT: &str = r###"
+fun check(value) {
+  for (var i = 0; i < 10; i = i + 1) {
+    if (i == value) {
+      print "found";
+      exit(3);
+    }
+  }
+}
+
+check(2);
+print "unreachable";
+"###;
+
+#[test]
+fn test_exit() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    assert!(matches!(
+        interpreter.interpret(&statements),
+        Err(RuntimeError::Exit { code: 3 })
+    ));
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        "found\n"
+    );
+}
+
+#[test]
+fn test_exit_invalid_code() {
+    let error = common::interpret("exit(1.5);").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+}
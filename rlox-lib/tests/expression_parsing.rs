@@ -0,0 +1,70 @@
+use rlox_lib::base::expr::Expr;
+use rlox_lib::base::parser::{Parser, ParserError};
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+fn evaluate(interpreter: &Rc<Interpreter>, input: &str) -> Result<String, Box<dyn Error>> {
+    let tokens = Scanner::new(input).scan_tokens()?;
+    let parsed = Parser::new(tokens).parse_expression()?;
+    Resolver::new(Rc::clone(interpreter)).resolve_expression(&parsed.ast, parsed.expr)?;
+
+    let value = interpreter.evaluate_expression(&parsed.ast, parsed.expr)?;
+    Ok(value.to_string())
+}
+
+#[test]
+fn test_parse_expression() {
+    let tokens = Scanner::new("1 + 2 * x").scan_tokens().unwrap();
+    let parsed = Parser::new(tokens).parse_expression().unwrap();
+
+    assert!(matches!(parsed.ast[parsed.expr], Expr::Binary { .. }));
+    assert!(parsed.ast.statements().is_empty());
+}
+
+#[test]
+fn test_parse_expression_trailing_tokens() {
+    for input in ["1 + 2;", "1 2", "x = 1\nprint x;"] {
+        let tokens = Scanner::new(input).scan_tokens().unwrap();
+        assert!(matches!(
+            Parser::new(tokens).parse_expression(),
+            Err(ParserError::UnexpectedTokenAfterExpression { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_parse_expression_broken_lambda_body() {
+    let tokens = Scanner::new("fun (x) { print x +; }")
+        .scan_tokens()
+        .unwrap();
+    assert!(matches!(
+        Parser::new(tokens).parse_expression(),
+        Err(ParserError::MissingExpression { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_evaluate_expression() {
+    let interpreter = Rc::new(Interpreter::new(Rc::new(RefCell::new(Vec::new()))));
+
+    let tokens = Scanner::new("var x = 4;\nfun square(n) { return n * n; }")
+        .scan_tokens()
+        .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    interpreter.interpret(&statements).unwrap();
+
+    assert_eq!(evaluate(&interpreter, "1 + 2 * x").unwrap(), "9");
+    assert_eq!(evaluate(&interpreter, "square(x) - 1").unwrap(), "15");
+    assert_eq!(evaluate(&interpreter, "\"x is ${x}\"").unwrap(), "x is 4");
+    assert_eq!(
+        evaluate(&interpreter, "-\"x\"").unwrap_err().to_string(),
+        "1: Operand of '-' must be a number, got \"x\"!"
+    );
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+var numbers = [1, 2, 3];
+var sum = 0;
+for (n in numbers) {
+  sum = sum + n;
+}
+print sum;
+
+for (c in "héllo") {
+  if (c == "l") break;
+  print c;
+}
+
+var ages = {"alice": 30, "bob": 25};
+ages["carol"] = 35;
+for (name in ages) {
+  print name + ": " + "${ages[name]}";
+}
+
+for (a in [1, 2]) {
+  for (b in [3, 4]) {
+    numbers[0] = numbers[0] + a * b;
+  }
+}
+print numbers;
+print ages;
+print [];
+print [[1, "two"], nil];
+print numbers[1] + numbers[2];
+print ages["dave"];
+print "lox"[1];
+"###;
+
+const RESULT: &str = r###"
+6
+h
+é
+alice: 30
+bob: 25
+carol: 35
+[22, 2, 3]
+{"alice": 30, "bob": 25, "carol": 35}
+[]
+[[1, "two"], nil]
+5
+nil
+o
+"###;
+
+#[test]
+fn test_for_in() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_for_in_closures() {
+    let input = r###"
+var functions = {};
+for (i in [1, 2, 3]) {
+  functions[i] = fun () -> i;
+}
+for (i in functions) {
+  print functions[i]();
+}
+"###;
+
+    assert_eq!(common::interpret(input).unwrap(), "1\n2\n3\n");
+}
+
+#[test]
+fn test_for_in_errors() {
+    let error = common::interpret("for (x in 42) print x;").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NotIterable { line: 1, .. })
+    ));
+
+    let error = common::interpret("print [1, 2][2];").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::IndexOutOfBounds {
+            line: 1,
+            index: 2,
+            length: 2,
+            ..
+        })
+    ));
+
+    let error = common::interpret("print [1, 2][0.5];").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidIndex { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_cyclic_collections() {
+    let input = r###"
+var list = [1];
+list.push(list);
+var map = {"self": nil};
+map["self"] = map;
+map["list"] = list;
+print list;
+print map;
+print [list, list];
+print list == list;
+print [1] == [1];
+var key = [];
+var keys = {key: "list"};
+key.push(1);
+print keys[key];
+"###;
+
+    assert_eq!(
+        common::interpret(input).unwrap(),
+        "[1, [...]]\n{\"self\": {...}, \"list\": [1, [...]]}\n[[1, [...]], [1, [...]]]\ntrue\nfalse\nlist\n"
+    );
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+class Point {
+  init(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  toString() {
+    return format("({}, {})", this.x, this.y);
+  }
+}
+
+print format("x={}, y={}", 1, 2.5);
+print format("no placeholders");
+print format("{} and {}", nil, [true, "a"]);
+print format("{{}} {}", Point(1, 2));
+print format("ünï{}cödé", "-");
+"###;
+
+const RESULT: &str = r###"
+x=1, y=2.5
+no placeholders
+nil and [true, "a"]
+{} (1, 2)
+ünï-cödé
+"###;
+
+#[test]
+fn test_format() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_format_errors() {
+    for input in [
+        "format(\"{} {}\", 1);",
+        "format(\"{}\", 1, 2);",
+        "format(\"{x}\", 1);",
+        "format(1);",
+    ] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::InvalidArgument { line: 1, .. })
+        ));
+    }
+
+    let error = common::interpret("format();").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NonMatchingNumberOfArguments { line: 1, .. })
+    ));
+}
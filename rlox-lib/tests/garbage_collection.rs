@@ -0,0 +1,89 @@
+mod common;
+
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::heap;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+class Node {
+  init(id) {
+    this.id = id;
+    this.describe = this.show;
+    this.next = nil;
+  }
+
+  show() {
+    return "node " + this.id;
+  }
+}
+
+fun counter() {
+  var count = 0;
+  fun increment() {
+    count = count + 1;
+    return count;
+  }
+  return increment;
+}
+
+var kept = [];
+for (var i = 0; i < 50; i = i + 1) {
+  var node = Node(i);
+  var other = Node(i);
+  node.next = other;
+  other.next = node;
+  if (i == len(kept) * 10) kept.push(node);
+  var next = counter();
+  next();
+}
+
+var tick = counter();
+tick();
+print tick();
+print kept.map(fun (node) { return node.next.describe(); });
+"###;
+
+const RESULT: &str = r###"
+2
+["node 0", "node 10", "node 20", "node 30", "node 40"]
+"###;
+
+#[test]
+fn test_collect_cycles() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+
+    assert!(heap::live_objects() > 0);
+    assert!(heap::collect() > 0);
+    assert_eq!(heap::live_objects(), 0);
+}
+
+#[test]
+fn test_gc_stress() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf)).with_options(InterpreterOptions {
+            gc_stress: true,
+            ..InterpreterOptions::default()
+        }),
+    );
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    interpreter.interpret(&statements).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun range(start, end) {
+  for (var i = start; i < end; i = i + 1) {
+    yield i;
+  }
+}
+
+for (n in range(1, 4)) print n;
+
+fun naturals() {
+  var n = 0;
+  while (true) {
+    n = n + 1;
+    if (n == 3) continue;
+    yield n;
+  }
+}
+
+var numbers = naturals();
+print numbers.next();
+print numbers.next();
+print numbers.next();
+
+fun letters(words) {
+  print "start";
+  for (word in words) {
+    for (letter in word) {
+      if (letter == "c") return;
+      yield letter;
+    }
+    yield "-";
+  }
+  print "unreachable";
+}
+
+var gen = letters(["ab", "cd"]);
+print gen;
+print gen.done();
+print gen.next();
+print gen.next();
+print gen.next();
+print gen.next();
+print gen.done();
+print gen.next();
+
+fun evens(source) {
+  for (n in source) {
+    if (n > 8) {
+      break;
+    } else if (n / 2 == floor(n / 2)) {
+      yield n;
+    }
+  }
+}
+
+var total = 0;
+for (n in evens(naturals())) total = total + n;
+print total;
+
+class Tree {
+  init(value, children) {
+    this.value = value;
+    this.children = children;
+  }
+
+  walk() {
+    yield this.value;
+    for (child in this.children) {
+      for (value in child.walk()) yield value;
+    }
+  }
+}
+
+var tree = Tree(1, [Tree(2, [Tree(3, [])]), Tree(4, [])]);
+var values = [];
+for (value in tree.walk()) values.push(value);
+print values;
+"###;
+
+const RESULT: &str = r###"
+1
+2
+3
+1
+2
+4
+<generator letters>
+false
+start
+a
+b
+-
+nil
+true
+nil
+20
+[1, 2, 3, 4]
+"###;
+
+#[test]
+fn test_generators() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_yield_outside_function() {
+    let error = common::interpret("yield 1;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::TopLevelYield { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_resume_running_generator() {
+    let error = common::interpret("fun g() { yield gen.next(); }\nvar gen = g();\ngen.next();")
+        .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::GeneratorRunning { line: 1, .. })
+    ));
+}
@@ -0,0 +1,84 @@
+use rlox_lib::base::expr_result::ExprResult;
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+print add(1, 2);
+print add("a", "b");
+count();
+count();
+print count();
+"###;
+
+const RESULT: &str = r###"
+3
+ab
+3
+"###;
+
+fn interpret(interpreter: &Rc<Interpreter>, input: &str) -> Result<(), RuntimeError> {
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    interpreter.interpret(&statements)
+}
+
+#[test]
+fn test_host_functions() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf)).with_options(InterpreterOptions {
+            strict: true,
+            ..InterpreterOptions::default()
+        }),
+    );
+
+    interpreter.define_native("add", 2, |arguments| match arguments {
+        [ExprResult::Number(a), ExprResult::Number(b)] => Ok(ExprResult::number(a + b)),
+        [ExprResult::String(a), ExprResult::String(b)] => Ok(ExprResult::string(format!("{a}{b}"))),
+        _ => Err(RuntimeError::InvalidArgument { line: 0, column: 0 }),
+    });
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    interpreter.define_native("count", 0, move |_| {
+        counter.set(counter.get() + 1);
+        Ok(ExprResult::number(counter.get() as f64))
+    });
+
+    interpret(&interpreter, INPUT).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn test_host_function_errors() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+
+    interpreter.define_native("fail", 1, |_| {
+        Err(RuntimeError::InvalidArgument { line: 0, column: 0 })
+    });
+
+    assert!(matches!(
+        interpret(&interpreter, "fail(1);"),
+        Err(RuntimeError::InvalidArgument { line: 0, .. })
+    ));
+    assert!(matches!(
+        interpret(&interpreter, "fail();"),
+        Err(RuntimeError::NonMatchingNumberOfArguments { line: 1, .. })
+    ));
+}
@@ -0,0 +1,131 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+var count = 1;
+
+fun greet(name) {
+  return "Hello " + name;
+}
+
+class Counter {
+  next() {
+    count = count + 1;
+    return count;
+  }
+}
+"###;
+
+const CHANGED: &str = r###"
+var count = 100;
+
+fun greet(name) {
+  var greeting = "Hi ";
+  return greeting + name;
+}
+
+class Counter {
+  next() {
+    count = count + 1;
+    return count;
+  }
+}
+"###;
+
+const CALLS: &str = r###"
+print greet("Lox");
+print Counter().next();
+"###;
+
+const RESULT: &str = r###"Hi Lox
+2
+"###;
+
+fn run(interpreter: &Rc<Interpreter>, input: &str) {
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    interpreter.interpret(&statements).unwrap();
+}
+
+#[test]
+fn test_hot_reload() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+    interpreter.track_declarations(&Scanner::new(INPUT).scan_tokens().unwrap());
+    run(&interpreter, INPUT);
+
+    let reloaded = interpreter.reload(CHANGED, "script.lox").unwrap();
+    assert_eq!(reloaded, vec!["greet"]);
+    run(&interpreter, CALLS);
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT
+    );
+}
+
+#[test]
+fn test_hot_reload_pending() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let error = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new_with_error_stream(
+        Rc::clone(&buf),
+        Rc::clone(&error),
+    ));
+    interpreter.track_declarations(&Scanner::new(INPUT).scan_tokens().unwrap());
+    run(&interpreter, INPUT);
+
+    interpreter
+        .reload_handle()
+        .request(CHANGED.to_string(), "script.lox");
+    run(&interpreter, CALLS);
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT
+    );
+    assert_eq!(
+        std::str::from_utf8(error.borrow().as_slice()).unwrap(),
+        "script.lox: Reloaded greet.\n"
+    );
+}
+
+#[test]
+fn test_hot_reload_error() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+    interpreter.track_declarations(&Scanner::new(INPUT).scan_tokens().unwrap());
+    run(&interpreter, INPUT);
+
+    assert!(interpreter.reload("fun greet(", "script.lox").is_err());
+    run(&interpreter, "print greet(\"Lox\");");
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        "Hello Lox\n"
+    );
+}
+
+#[test]
+fn test_hot_reload_arrow_function() {
+    let input = "fun double(x) -> x * 2;\nfun wrap() -> fun () { return 1; };\n";
+    let changed = "fun double(x) -> x * 3;\nfun wrap() -> fun () { return 1; };\n";
+
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+    interpreter.track_declarations(&Scanner::new(input).scan_tokens().unwrap());
+    run(&interpreter, input);
+
+    let reloaded = interpreter.reload(changed, "script.lox").unwrap();
+    assert_eq!(reloaded, vec!["double"]);
+    run(&interpreter, "print double(2);");
+
+    assert_eq!(std::str::from_utf8(buf.borrow().as_slice()).unwrap(), "6\n");
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+var a = 1;
+print a++;
+print a;
+print ++a;
+print a--;
+print --a;
+print a;
+
+class Counter {
+  init() {
+    this.count = 0;
+  }
+}
+
+var counter = Counter();
+counter.count++;
+++counter.count;
+print counter.count--;
+print counter.count;
+
+fun make() {
+  var i = 10;
+  fun next() {
+    return i++;
+  }
+  return next;
+}
+
+var next = make();
+print next();
+print next();
+
+for (var j = 0; j < 3; j++) {
+  print j;
+}
+"###;
+
+const RESULT: &str = r###"
+1
+2
+3
+3
+1
+1
+2
+1
+10
+11
+0
+1
+2
+"###;
+
+#[test]
+fn test_increment() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_increment_invalid_target() {
+    let error = common::interpret("1++;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::InvalidUpdateTarget { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_increment_non_number() {
+    let error = common::interpret("var s = \"a\"; s++;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NumberExpected { line: 1, .. })
+    ));
+}
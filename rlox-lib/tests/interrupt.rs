@@ -0,0 +1,39 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+const INPUT: &str = r###"
+var i = 0;
+while (true) {
+  i = i + 1;
+}
+"###;
+
+#[test]
+fn test_interrupt() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    let interrupt = interpreter.interrupt_handle();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        interrupt.interrupt();
+    });
+
+    assert!(matches!(
+        interpreter.interpret(&statements),
+        Err(RuntimeError::Interrupted)
+    ));
+}
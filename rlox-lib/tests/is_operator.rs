@@ -0,0 +1,52 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+class Shape {}
+class Circle < Shape {}
+class Square < Shape {}
+class Unrelated {}
+
+var circle = Circle();
+print circle is Circle;
+print circle is Shape;
+print circle is Square;
+print circle is Unrelated;
+print Shape() is Circle;
+print 1 is Shape;
+print nil is Shape;
+
+if (circle is Shape and !(circle is Square)) {
+  print "a round shape";
+}
+"###;
+
+const RESULT: &str = r###"
+true
+true
+false
+false
+false
+false
+false
+a round shape
+"###;
+
+#[test]
+fn test_is_operator() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_is_operator_without_class() {
+    let error = common::interpret("class A {} print A() is 1;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::ClassExpected { line: 1, .. })
+    ));
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "jit")]
+
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use rlox_lib::jit::compiler::Compiler;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+mod common;
+
+const INPUT: &str = r###"
+fun fib(n) {
+  if (n < 2) return n;
+  return fib(n - 2) + fib(n - 1);
+}
+print fib(20);
+
+fun sum(n) {
+  var total = 0;
+  for (var i = 1; i <= n; i++) {
+    if (i == 3) continue;
+    if (i > 100 or !(i != 50)) break;
+    total = total + i;
+  }
+  return total;
+}
+print sum(10);
+print sum(1000);
+
+fun add(a, b) {
+  return a + b;
+}
+print add(1, 2);
+print add("a", "b");
+
+fun positive(n) {
+  if (n > 0) return n;
+}
+print positive(1);
+print positive(-1);
+
+fun greet() {
+  print "hello";
+  return 1;
+}
+print greet();
+"###;
+
+const RESULT: &str = r###"
+6765
+52
+1222
+3
+ab
+1
+nil
+hello
+1
+"###;
+
+fn run_jit(
+    input: &str,
+    options: InterpreterOptions,
+) -> Result<(String, usize), Box<dyn std::error::Error>> {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)).with_options(options.clone()));
+
+    let tokens = Scanner::new(input).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Resolver::new(Rc::clone(&interpreter)).resolve_stmts(&statements)?;
+
+    let functions = Compiler::new(interpreter.interrupt_handle())
+        .with_options(options)
+        .compile(&statements)?;
+    let compiled = functions.len();
+    interpreter.add_compiled_functions(functions);
+    interpreter.interpret(&statements)?;
+
+    let output = std::str::from_utf8(buf.borrow().as_slice())?.to_string();
+    Ok((output, compiled))
+}
+
+#[test]
+fn test_jit() {
+    let (output, compiled) = run_jit(INPUT, InterpreterOptions::default()).unwrap();
+    assert_eq!(output, RESULT.strip_prefix('\n').unwrap());
+    assert_eq!(compiled, 4);
+}
+
+#[test]
+fn test_jit_matches_tree_walker() {
+    let (output, _) = run_jit(INPUT, InterpreterOptions::default()).unwrap();
+    assert_eq!(output, common::interpret(INPUT).unwrap());
+}
+
+#[test]
+fn test_jit_skips_reassigned_functions() {
+    let input = "fun f(n) { return n; }\nfun g(n) { return f(n); }\nf = nil;";
+    let (_, compiled) = run_jit(input, InterpreterOptions::default()).unwrap();
+    assert_eq!(compiled, 0);
+}
+
+#[test]
+fn test_jit_checked_division() {
+    let input = "fun half(n) { return n / 2; }\nprint half(3);";
+    let (output, compiled) = run_jit(input, InterpreterOptions::default()).unwrap();
+    assert_eq!((output.as_str(), compiled), ("1.5\n", 1));
+
+    let options = InterpreterOptions {
+        checked_division: true,
+        ..InterpreterOptions::default()
+    };
+    let (output, compiled) = run_jit(input, options).unwrap();
+    assert_eq!((output.as_str(), compiled), ("1.5\n", 0));
+}
+
+#[test]
+fn test_jit_gives_up_on_deep_recursion() {
+    let input =
+        "fun countdown(n) { return n <= 0 ? n : countdown(n - 1); }\nprint countdown(1010);";
+    let (output, compiled) = run_jit(input, InterpreterOptions::default()).unwrap();
+    assert_eq!((output.as_str(), compiled), ("0\n", 1));
+}
+
+#[test]
+fn test_jit_skipped_with_budget() {
+    let input = "fun spin(n) { while (true) n = n + 1; }\nspin(0);";
+    for options in [
+        InterpreterOptions {
+            max_steps: Some(1000),
+            ..InterpreterOptions::default()
+        },
+        InterpreterOptions {
+            max_duration: Some(Duration::from_millis(50)),
+            ..InterpreterOptions::default()
+        },
+    ] {
+        let error = run_jit(input, options).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::BudgetExceeded)
+        ));
+    }
+
+    let options = InterpreterOptions {
+        max_steps: Some(1000),
+        ..InterpreterOptions::default()
+    };
+    let (output, compiled) = run_jit("fun one() { return 1; }\nprint one();", options).unwrap();
+    assert_eq!((output.as_str(), compiled), ("1\n", 0));
+}
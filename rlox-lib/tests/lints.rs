@@ -0,0 +1,167 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::lint::{Lint, LintLevels, Severity, Warning};
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+var unused_global = 1;
+fun f(unused_parameter) {
+  var unused = 1;
+  var used = 2;
+  {
+    var shadowed = 3;
+    print shadowed;
+  }
+  return used;
+}
+"###;
+
+fn resolve(input: &str, lints: LintLevels) -> Result<Vec<Warning>, Box<dyn std::error::Error>> {
+    resolve_with_options(
+        input,
+        InterpreterOptions {
+            lints,
+            ..InterpreterOptions::default()
+        },
+    )
+}
+
+fn resolve_with_options(
+    input: &str,
+    options: InterpreterOptions,
+) -> Result<Vec<Warning>, Box<dyn std::error::Error>> {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)).with_options(options));
+
+    let mut scanner = Scanner::new(input);
+    let tokens = scanner.scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+
+    let resolver = Resolver::new(Rc::clone(&interpreter)).with_pragmas(scanner.pragmas())?;
+    resolver.resolve_stmts(&statements)?;
+
+    Ok(resolver.warnings())
+}
+
+#[test]
+fn test_unused_variable() {
+    let warnings = resolve(INPUT, LintLevels::new()).unwrap();
+
+    assert_eq!(
+        warnings,
+        vec![Warning {
+            lint: Lint::UnusedVariable,
+            line: 4,
+            column: 7,
+            message: String::from("Local variable \"unused\" is never read!"),
+        }]
+    );
+}
+
+#[test]
+fn test_unused_function() {
+    let input = r###"
+fun unused_global() {}
+fun outer() {
+  fun unused(n) {
+    if (n > 0) unused(n - 1);
+  }
+  fun called() {}
+  fun returned() {}
+  called();
+  return returned;
+}
+"###;
+    let warnings = resolve(input, LintLevels::new()).unwrap();
+
+    assert_eq!(
+        warnings,
+        vec![Warning {
+            lint: Lint::UnusedFunction,
+            line: 4,
+            column: 7,
+            message: String::from("Local function \"unused\" is never used!"),
+        }]
+    );
+}
+
+const UNREACHABLE: &str = r###"
+fun f(n) {
+  if (n > 0) {
+    return 1;
+  } else {
+    { return 2; }
+  }
+  print n;
+}
+fun g() {
+  return 3;
+}
+"###;
+
+#[test]
+fn test_unreachable_code() {
+    let warnings = resolve(UNREACHABLE, LintLevels::new()).unwrap();
+
+    assert_eq!(
+        warnings,
+        vec![Warning {
+            lint: Lint::UnreachableCode,
+            line: 6,
+            column: 7,
+            message: String::from("Code after 'return' is unreachable!"),
+        }]
+    );
+}
+
+#[test]
+fn test_unreachable_code_in_strict_mode() {
+    let error = resolve_with_options(
+        UNREACHABLE,
+        InterpreterOptions {
+            strict: true,
+            ..InterpreterOptions::default()
+        },
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::DeniedLint { line: 6, .. })
+    ));
+}
+
+#[test]
+fn test_allowed_lint() {
+    let mut lints = LintLevels::new();
+    lints.set(Lint::UnusedVariable, Severity::Allow);
+
+    assert!(resolve(INPUT, lints).unwrap().is_empty());
+}
+
+#[test]
+fn test_deny_warnings() {
+    let mut lints = LintLevels::new();
+    lints.deny_warnings();
+
+    let error = resolve(INPUT, lints).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::DeniedLint { line: 4, .. })
+    ));
+}
+
+#[test]
+fn test_lint_pragma() {
+    let mut lints = LintLevels::new();
+    lints.deny_warnings();
+    let input = format!("// rlox: allow(unused_variable)\n{}", INPUT);
+
+    assert!(resolve(&input, lints).unwrap().is_empty());
+    assert!(resolve("// rlox: allow(unknown)", LintLevels::new()).is_err());
+}
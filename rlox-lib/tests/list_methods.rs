@@ -0,0 +1,88 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+var list = [3, 1];
+list.push(2);
+print list;
+print list.pop();
+list.insert(0, 5);
+list.insert(3, 4);
+print list;
+print list.remove(1);
+print list;
+print list.contains(4);
+print list.contains("4");
+print list.indexOf(4);
+print list.indexOf(9);
+list.sort();
+print list;
+
+var names = ["carol", "alice", "bob"];
+names.sort();
+print names;
+
+var push = names.push;
+push("dave");
+print names;
+
+print [1, 2, 3, 4].map(fun (x) -> x * x);
+print [1, 2, 3, 4].filter(fun (x) -> x > 2);
+print [1, 2, 3, 4].reduce(fun (sum, x) -> sum + x, 0);
+print [].pop();
+"###;
+
+const RESULT: &str = r###"
+[3, 1, 2]
+2
+[5, 3, 1, 4]
+3
+[5, 1, 4]
+true
+false
+2
+-1
+[1, 4, 5]
+["alice", "bob", "carol"]
+["alice", "bob", "carol", "dave"]
+[1, 4, 9, 16]
+[3, 4]
+10
+nil
+"###;
+
+#[test]
+fn test_list_methods() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_list_method_errors() {
+    let error = common::interpret("[1].shuffle();").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::UndefinedProperty { line: 1, .. })
+    ));
+
+    let error = common::interpret("[1, \"a\"].sort();").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+
+    let error = common::interpret("[1].map(1);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+
+    let error = common::interpret("[1].remove(1);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::IndexOutOfBounds { line: 1, .. })
+    ));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+for (var i = 0; i < 5; i = i + 1) {
+  if (i == 1 or i == 3) continue;
+  print i;
+}
+
+outer: for (var i = 0; i < 3; i = i + 1) {
+  for (var j = 0; j < 3; j = j + 1) {
+    if (j == 2) continue outer;
+    if (i == 2) break outer;
+    print i * 10 + j;
+  }
+}
+
+var n = 0;
+rows: while (n < 3) {
+  n = n + 1;
+  for (c in ["a", "b", "c"]) {
+    if (c == "b") continue rows;
+    print c + "${n}";
+  }
+}
+
+items: for (item in [1, 2, 3]) {
+  while (true) {
+    if (item == 2) continue items;
+    break;
+  }
+  print item;
+}
+"###;
+
+const RESULT: &str = r###"
+0
+2
+4
+0
+1
+10
+11
+a1
+a2
+a3
+1
+3
+"###;
+
+#[test]
+fn test_loop_labels() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_loop_label_errors() {
+    let error = common::interpret("continue;").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::ContinueOutsideLoop { line: 1, .. })
+    ));
+
+    for input in [
+        "while (true) { break missing; }",
+        "outer: while (true) { fun f() { while (true) { continue outer; } } }",
+    ] {
+        let error = common::interpret(input).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::UndefinedLabel { line: 1, .. })
+        ));
+    }
+
+    let error = common::interpret("label: print 1;").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::MissingLoopAfterLabel { line: 1, .. })
+    ));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+print sqrt(16);
+print abs(-2.5);
+print floor(2.7);
+print ceil(2.2);
+print round(2.5);
+print min(3, -1);
+print max(3, -1);
+print pow(2, 10);
+print sin(0);
+print cos(0);
+print log(1);
+print floor(PI * 100);
+print sqrt(-1);
+"###;
+
+const RESULT: &str = r###"
+4
+2.5
+2
+3
+3
+-1
+3
+1024
+0
+1
+0
+314
+NaN
+"###;
+
+#[test]
+fn test_math() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_math_errors() {
+    let error = common::interpret("sqrt(\"four\");").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+
+    let error = common::interpret("PI = 3;").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::ConstantAssignment { line: 1, .. })
+    ));
+}
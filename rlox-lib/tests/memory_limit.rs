@@ -0,0 +1,155 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::bytecode::compiler::Compiler;
+use rlox_lib::bytecode::vm::Vm;
+use rlox_lib::closures::compiler::Compiler as ClosureCompiler;
+use rlox_lib::closures::runtime::Runtime;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn run(input: &str) -> (Result<(), RuntimeError>, String) {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf)).with_options(InterpreterOptions {
+            max_memory: Some(10_000),
+            ..InterpreterOptions::default()
+        }),
+    );
+
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    let result = interpreter.interpret(&statements);
+    let output = std::str::from_utf8(buf.borrow().as_slice())
+        .unwrap()
+        .to_string();
+
+    (result, output)
+}
+
+#[test]
+fn test_memory_limit_strings() {
+    let (result, output) = run(r#"
+var text = "a";
+while (true) {
+  text = text + text;
+  print len(text);
+}
+"#);
+
+    assert!(matches!(
+        result,
+        Err(RuntimeError::MemoryLimitExceeded { limit: 10_000 })
+    ));
+    assert_eq!(
+        output,
+        "2\n4\n8\n16\n32\n64\n128\n256\n512\n1024\n2048\n4096\n"
+    );
+}
+
+#[test]
+fn test_memory_limit_instances() {
+    let (result, _) = run(r#"
+class Node {}
+var nodes = [];
+while (true) nodes.push(Node());
+"#);
+
+    assert!(matches!(
+        result,
+        Err(RuntimeError::MemoryLimitExceeded { limit: 10_000 })
+    ));
+}
+
+#[test]
+fn test_memory_limit_not_exceeded() {
+    let (result, output) = run("var greeting = \"Hello, \" + \"world!\";\nprint greeting;");
+
+    assert!(result.is_ok());
+    assert_eq!(output, "Hello, world!\n");
+}
+
+#[test]
+fn test_memory_limit_freed_memory() {
+    let (result, output) = run(r#"
+class Node {}
+var i = 0;
+while (i < 10000) {
+  var list = [Node(), "${i}", {"key": i}];
+  var cycle = Node();
+  cycle.self = cycle;
+  i = i + 1;
+}
+print i;
+"#);
+
+    assert!(result.is_ok());
+    assert_eq!(output, "10000\n");
+}
+
+#[test]
+fn test_memory_limit_vm() {
+    let run_vm = |input: &str| {
+        let tokens = Scanner::new(input).scan_tokens().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        let function = Compiler::new().compile(&statements).unwrap();
+        Vm::new(Rc::new(RefCell::new(Vec::new())))
+            .with_options(InterpreterOptions {
+                max_memory: Some(10_000),
+                ..InterpreterOptions::default()
+            })
+            .interpret(function)
+    };
+
+    assert!(matches!(
+        run_vm("var text = \"a\";\nwhile (true) text = text + text;"),
+        Err(RuntimeError::MemoryLimitExceeded { limit: 10_000 })
+    ));
+    assert!(matches!(
+        run_vm("class Node {}\nvar node = nil;\nwhile (true) {\n  var next = Node();\n  next.next = node;\n  node = next;\n}"),
+        Err(RuntimeError::MemoryLimitExceeded { limit: 10_000 })
+    ));
+    assert!(run_vm(
+        "var i = 0;\nwhile (i < 10000) {\n  var s = \"${i}\" + \"!\";\n  i = i + 1;\n}"
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_memory_limit_closure_backend() {
+    let run_closures = |input: &str| {
+        let interpreter = Rc::new(Interpreter::new(Rc::new(RefCell::new(Vec::new()))));
+        let tokens = Scanner::new(input).scan_tokens().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        Resolver::new(Rc::clone(&interpreter))
+            .resolve_stmts(&statements)
+            .unwrap();
+        let function = ClosureCompiler::new().compile(&statements).unwrap();
+        Runtime::new(Rc::new(RefCell::new(Vec::new())))
+            .with_options(InterpreterOptions {
+                max_memory: Some(10_000),
+                ..InterpreterOptions::default()
+            })
+            .interpret(function)
+    };
+
+    assert!(matches!(
+        run_closures("var text = \"a\";\nwhile (true) text = text + text;"),
+        Err(RuntimeError::MemoryLimitExceeded { limit: 10_000 })
+    ));
+    assert!(matches!(
+        run_closures("class Node {}\nfun grow() {\n  var node = nil;\n  while (true) {\n    var next = Node();\n    next.next = node;\n    node = next;\n  }\n}\ngrow();"),
+        Err(RuntimeError::MemoryLimitExceeded { limit: 10_000 })
+    ));
+    assert!(run_closures(
+        "var i = 0;\nwhile (i < 10000) {\n  var s = \"${i}\" + \"!\";\n  i = i + 1;\n}"
+    )
+    .is_ok());
+}
@@ -0,0 +1,167 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+import "lib/greeting.lox";
+import "lib/greeting.lox";
+
+print greet("modules");
+print counter;
+"###;
+
+const GREETING: &str = r###"
+import "strings.lox";
+
+print "loading greeting";
+var counter = 1;
+
+fun greet(name) {
+  return prefix + name;
+}
+"###;
+
+const STRINGS: &str = r###"
+var prefix = "Hello, ";
+"###;
+
+const RESULT: &str = r###"
+loading greeting
+Hello, modules
+1
+"###;
+
+fn module_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rlox-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    dir
+}
+
+fn interpret_file(path: &Path, input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    interpret_file_with_options(path, input, InterpreterOptions::default())
+}
+
+fn interpret_file_with_options(
+    path: &Path,
+    input: &str,
+    options: InterpreterOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)).with_options(options));
+
+    let tokens = Scanner::new_with_file(input, &path.display().to_string()).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Resolver::new(Rc::clone(&interpreter)).resolve_stmts(&statements)?;
+    interpreter.interpret(&statements)?;
+
+    let output = std::str::from_utf8(buf.borrow().as_slice())?.to_string();
+    Ok(output)
+}
+
+fn root_cause(error: &RuntimeError) -> &RuntimeError {
+    match error {
+        RuntimeError::InFile { error, .. } => root_cause(error),
+        error => error,
+    }
+}
+
+#[test]
+fn test_import() {
+    let dir = module_dir("import");
+    std::fs::write(dir.join("lib/greeting.lox"), GREETING).unwrap();
+    std::fs::write(dir.join("lib/strings.lox"), STRINGS).unwrap();
+
+    assert_eq!(
+        interpret_file(&dir.join("main.lox"), INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_import_cycle() {
+    let dir = module_dir("cycle");
+    std::fs::write(dir.join("lib/a.lox"), "import \"b.lox\";").unwrap();
+    std::fs::write(dir.join("lib/b.lox"), "import \"a.lox\";").unwrap();
+
+    let error = interpret_file(&dir.join("main.lox"), "import \"lib/a.lox\";").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>().map(root_cause),
+        Some(RuntimeError::CyclicImport { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_import_errors() {
+    let dir = module_dir("errors");
+
+    let error = interpret_file(&dir.join("main.lox"), "import \"missing.lox\";").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>().map(root_cause),
+        Some(RuntimeError::ModuleNotFound { line: 1, .. })
+    ));
+
+    let error = interpret_file(&dir.join("main.lox"), "{ import \"missing.lox\"; }").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::ImportOutsideTopLevel { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_import_paths() {
+    let dir = module_dir("import-paths");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("lib/strings.lox"), STRINGS).unwrap();
+    std::fs::write(dir.join("src/strings.lox"), "var prefix = \"Hi, \";").unwrap();
+    let input = "import \"strings.lox\";\nprint prefix;";
+
+    let error = interpret_file(&dir.join("main.lox"), input).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>().map(root_cause),
+        Some(RuntimeError::ModuleNotFound { line: 1, .. })
+    ));
+
+    let options = || InterpreterOptions {
+        import_paths: vec![dir.join("lib")],
+        ..InterpreterOptions::default()
+    };
+    assert_eq!(
+        interpret_file_with_options(&dir.join("main.lox"), input, options()).unwrap(),
+        "Hello, \n"
+    );
+
+    /* modules next to the importing script take precedence. */
+    assert_eq!(
+        interpret_file_with_options(&dir.join("src/main.lox"), input, options()).unwrap(),
+        "Hi, \n"
+    );
+}
+
+#[test]
+fn test_import_invalid_utf8() {
+    let dir = module_dir("invalid-utf8");
+    std::fs::write(dir.join("lib/text.lox"), b"var text = \"a\xFFb\";").unwrap();
+    let input = "import \"lib/text.lox\";\nprint text;";
+
+    let error = interpret_file(&dir.join("main.lox"), input).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>().map(root_cause),
+        Some(RuntimeError::ModuleError { line: 1, .. })
+    ));
+
+    let options = InterpreterOptions {
+        lossy_utf8: true,
+        ..InterpreterOptions::default()
+    };
+    assert_eq!(
+        interpret_file_with_options(&dir.join("main.lox"), input, options).unwrap(),
+        "a\u{FFFD}b\n"
+    );
+}
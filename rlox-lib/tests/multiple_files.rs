@@ -0,0 +1,44 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT_LIBRARY: &str = r###"
+fun negate(n) {
+  return -n;
+}
+"###;
+
+const INPUT_MAIN: &str = r###"
+print negate(1);
+print negate("one");
+"###;
+
+#[test]
+fn test_multiple_files() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+
+    let mut results = vec![];
+    for (file, input) in [("library.lox", INPUT_LIBRARY), ("main.lox", INPUT_MAIN)] {
+        let tokens = Scanner::new_with_file(input, file).scan_tokens().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        Resolver::new(Rc::clone(&interpreter))
+            .resolve_stmts(&statements)
+            .unwrap();
+
+        results.push(interpreter.interpret(&statements));
+    }
+
+    assert!(results[0].is_ok());
+    assert_eq!(
+        results[1].as_ref().unwrap_err().to_string(),
+        "library.lox:3: Operand of '-' must be a number, got \"one\"!"
+    );
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        "-1\n"
+    );
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun divide(a, b) {
+  var quotient = 0;
+  while (a >= b) {
+    a = a - b;
+    quotient = quotient + 1;
+  }
+  return quotient, a;
+}
+
+var quotient, remainder = divide(17, 5);
+print quotient;
+print remainder;
+
+fun swap(a, b) -> [b, a];
+
+{
+  var first, second = swap("a", "b");
+  print first + second;
+}
+
+print divide(9, 3);
+"###;
+
+const RESULT: &str = r###"
+3
+2
+ba
+[3, 0]
+"###;
+
+#[test]
+fn test_multiple_returns() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_multiple_variables_without_initializer() {
+    let error = common::interpret("var a, b;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::MissingDestructuringInitializer { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_multiple_variables_with_other_number_of_values() {
+    for (input, expected, actual) in [
+        ("fun f() { return 1, 2, 3; }\nvar a, b = f();", 2, 3),
+        ("fun f() { return 1; }\nvar a, b = f();", 2, 1),
+    ] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::ValueCountMismatch {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!("2: Expected {expected} values, got {actual}!")
+        );
+    }
+}
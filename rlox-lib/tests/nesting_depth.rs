@@ -0,0 +1,118 @@
+use rlox_lib::base::parser::{Parser, ParserError};
+use rlox_lib::base::scanner::Scanner;
+use std::thread;
+
+mod common;
+
+fn parse(input: &str, max_depth: usize) -> Result<(), ParserError> {
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    Parser::new(tokens).with_max_depth(max_depth).parse()?;
+    Ok(())
+}
+
+fn parentheses(depth: usize) -> String {
+    format!("print {}1{};", "(".repeat(depth), ")".repeat(depth))
+}
+
+#[test]
+fn test_nesting_depth_limit() {
+    assert!(parse(&parentheses(15), 16).is_ok());
+    assert!(matches!(
+        parse(&parentheses(16), 16),
+        Err(ParserError::TooDeeplyNested { line: 1, .. })
+    ));
+
+    let blocks = format!("{}print 1;{}", "{".repeat(20), "}".repeat(20));
+    assert!(matches!(
+        parse(&blocks, 16),
+        Err(ParserError::TooDeeplyNested { line: 1, .. })
+    ));
+
+    let negations = format!("print {}1;", "- ".repeat(20));
+    assert!(matches!(
+        parse(&negations, 16),
+        Err(ParserError::TooDeeplyNested { line: 1, .. })
+    ));
+
+    let conditions = format!("{}print 1;", "if (true) ".repeat(20));
+    assert!(matches!(
+        parse(&conditions, 16),
+        Err(ParserError::TooDeeplyNested { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_nesting_depth_limit_chains() {
+    for operator in [" + 1", "()", ".b"] {
+        let chain = format!("print a{};", operator.repeat(15));
+        assert!(parse(&chain, 16).is_ok(), "{chain}");
+
+        let chain = format!("print a{};", operator.repeat(16));
+        assert!(
+            matches!(
+                parse(&chain, 16),
+                Err(ParserError::TooDeeplyNested { line: 1, .. })
+            ),
+            "{chain}"
+        );
+    }
+}
+
+#[test]
+fn test_default_nesting_depth_limit() {
+    /* parse on a thread with the stack of a main thread, like the command line interpreter. */
+    let result = thread::Builder::new()
+        .stack_size(8 * 1024 * 1024)
+        .spawn(|| {
+            let tokens = Scanner::new(&parentheses(100_000)).scan_tokens().unwrap();
+            Parser::new(tokens).parse().map(|_| ())
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert!(matches!(
+        result,
+        Err(ParserError::TooDeeplyNested { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_default_nesting_depth_limit_chains() {
+    /* chains just below the limit still run, longer ones are rejected before they overflow the
+    stack of the resolver or interpreter. */
+    let result = thread::Builder::new()
+        .stack_size(8 * 1024 * 1024)
+        .spawn(|| {
+            let input = r###"
+fun f() { return f; }
+class A { init() { this.b = this; } }
+var a = A();
+"###;
+            let sum = format!("print 1{};", " + 1".repeat(250));
+            let calls = format!("print f{};", "()".repeat(250));
+            let gets = format!("print a{};", ".b".repeat(250));
+            let output = common::interpret(&format!("{input}{sum}\n{calls}\n{gets}")).unwrap();
+
+            let rejected = [" + 1", "()", ".b"].map(|operator| {
+                let chain = format!("{input}print a{};", operator.repeat(10_000));
+                common::interpret(&chain)
+                    .unwrap_err()
+                    .downcast_ref::<ParserError>()
+                    .map(ToString::to_string)
+            });
+
+            (output, rejected)
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(result.0, "251\n<fn f>\nA instance\n");
+    for rejected in result.1 {
+        assert_eq!(
+            rejected.as_deref(),
+            Some("5: Expressions and statements are nested too deeply.")
+        );
+    }
+}
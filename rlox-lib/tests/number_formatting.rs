@@ -0,0 +1,41 @@
+mod common;
+
+const INPUT: &str = r###"
+print 2 + 3;
+print 7 / 2;
+print -0.5 * 4;
+print 0.1 + 0.2;
+print 1 / 3;
+print 123456789 * 1000;
+print 1e21;
+print 2.5e-8;
+print 0.0000001;
+print -0;
+print 0 / 0;
+print [1.0, 2.5];
+print "value: " + 10 / 4;
+"###;
+
+const RESULT: &str = r###"
+5
+3.5
+-2
+0.30000000000000004
+0.3333333333333333
+123456789000
+1e21
+2.5e-8
+0.0000001
+-0
+NaN
+[1, 2.5]
+value: 2.5
+"###;
+
+#[test]
+fn test_number_formatting() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use rlox_lib::base::scanner::{NumberFormat, Scanner, ScannerError};
+
+const INPUT: &str = r###"
+print 0xFF;
+print 0Xff + 1;
+print 0b1010;
+print 0b1111_0000;
+print 0xFF_FF;
+print 1.5e-3;
+print 2E3;
+print 1e+2;
+print 0;
+"###;
+
+const RESULT: &str = r###"
+255
+256
+10
+240
+65535
+0.0015
+2000
+100
+0
+"###;
+
+#[test]
+fn test_number_literals() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_number_literals() {
+    for (input, expected_format) in [
+        ("0x;", NumberFormat::Hexadecimal),
+        ("0xFG;", NumberFormat::Hexadecimal),
+        ("0b102;", NumberFormat::Binary),
+        ("1e;", NumberFormat::Decimal),
+        ("1.5e+;", NumberFormat::Decimal),
+    ] {
+        assert!(matches!(
+            Scanner::new(input).scan_tokens(),
+            Err(ScannerError::NumberParsingError { line: 1, column: 1, format, .. }) if format == expected_format
+        ));
+    }
+}
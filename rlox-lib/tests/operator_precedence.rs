@@ -0,0 +1,41 @@
+mod common;
+
+const INPUT: &str = r###"
+print 2 + 3 * 4;
+print 10 - 4 - 3;
+print 48 / 4 / 2;
+print -(1 + 2) * 3 - -4;
+print 1 < 2 == 2 > 1;
+print !true == false;
+print nil or 1 and 2;
+print false and true or true;
+print 1 + 1 == 2 ? "yes" : "no";
+print false ? 1 : true ? 2 : 3;
+print true or false ? "a" : "b";
+var a = 1;
+a = true ? a + 1 : a;
+print a;
+"###;
+
+const RESULT: &str = r###"
+14
+3
+6
+-5
+true
+true
+2
+true
+yes
+2
+a
+2
+"###;
+
+#[test]
+fn test_operator_precedence() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
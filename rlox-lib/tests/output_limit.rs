@@ -0,0 +1,40 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+while (true) {
+  print "flood";
+}
+"###;
+
+#[test]
+fn test_output_limit() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf)).with_options(InterpreterOptions {
+            max_output: Some(20),
+            ..InterpreterOptions::default()
+        }),
+    );
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    assert!(matches!(
+        interpreter.interpret(&statements),
+        Err(RuntimeError::OutputLimitExceeded { limit: 20 })
+    ));
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        "flood\nflood\nflood\n"
+    );
+}
@@ -0,0 +1,35 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+
+mod common;
+
+fn parse_error(input: &str) -> String {
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    Parser::new(tokens).parse().unwrap_err().to_string()
+}
+
+#[test]
+fn test_parse_hints() {
+    let condition = "1: Use '==' to compare, or parenthesize the assignment used as condition.";
+    let print = "1: 'print' is a statement printing a single value, as in 'print a;'.";
+
+    assert_eq!(
+        parse_error("var a == 1;"),
+        "1: Expect '=' to initialize variable, '==' compares values."
+    );
+    assert_eq!(parse_error("if (a = 1) print a;"), condition);
+    assert_eq!(parse_error("while (a.b = 1) {}"), condition);
+    assert_eq!(parse_error("for (;a[0] = 1;) {}"), condition);
+    assert_eq!(
+        parse_error("greet(name) {\n  print name;\n}"),
+        "1: Expect 'fun' before function declaration."
+    );
+    assert_eq!(parse_error("print(a, 2);"), print);
+    assert_eq!(parse_error("print();"), print);
+}
+
+#[test]
+fn test_parenthesized_assignment_condition() {
+    let input = "var a;\nif ((a = 1)) print a;\nprint (a);";
+    assert_eq!(common::interpret(input).unwrap(), "1\n1\n");
+}
@@ -0,0 +1,69 @@
+use rlox_lib::base::parser::{Parser, ParserError};
+use rlox_lib::base::scanner::Scanner;
+
+const INPUT: &str = r###"
+var a = ;
+print a
+print 1;
+{
+  var b = (1;
+  print b;
+}
+fun f( { }
+print "ok";
+"###;
+
+#[test]
+fn test_parser_recovery() {
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let outcome = Parser::new(tokens).parse_all();
+
+    let lines: Vec<String> = outcome.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        lines,
+        [
+            "2: Unknown token detected.",
+            "4: Expect ';' after value.",
+            "6: Expect ')' after expression.",
+            "9: Expect function name.",
+        ]
+    );
+    /* the block and the last print statement parsed successfully. */
+    assert_eq!(outcome.ast.statements().len(), 2);
+}
+
+#[test]
+fn test_parser_returns_first_error() {
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+
+    assert!(matches!(
+        error,
+        ParserError::MissingExpression { line: 2, .. }
+    ));
+}
+
+#[test]
+fn test_parser_recovery_without_errors() {
+    let tokens = Scanner::new("print 1;\nprint 2;").scan_tokens().unwrap();
+    let outcome = Parser::new(tokens).parse_all();
+
+    assert!(outcome.errors.is_empty());
+    assert_eq!(outcome.ast.statements().len(), 2);
+}
+
+#[test]
+fn test_parser_incomplete_input() {
+    let incomplete = |input: &str| {
+        let tokens = Scanner::new(input).scan_tokens().unwrap();
+        Parser::new(tokens).parse_all().incomplete
+    };
+
+    assert!(incomplete("fun f(a) {\n  return a;\n"));
+    assert!(incomplete("class A { m() {} "));
+    assert!(incomplete("var a = 1 +"));
+    assert!(incomplete("print (1"));
+    assert!(!incomplete("print 1;"));
+    assert!(!incomplete("var = 1; {"));
+    assert!(!incomplete("print 1; }"));
+}
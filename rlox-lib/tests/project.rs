@@ -0,0 +1,81 @@
+use rlox_lib::project::manifest::{Manifest, Project, ProjectError};
+use std::fs;
+use std::path::Path;
+
+const MANIFEST: &str = r###"
+entry = "src/app.lox"
+import_paths = ["lib", "vendor"]
+stdlib = ["async"]
+
+[lints]
+unused_variable = "deny"
+"###;
+
+#[test]
+fn test_manifest() {
+    let manifest = Manifest::parse(MANIFEST, Path::new("lox.toml")).unwrap();
+
+    assert_eq!(manifest.entry, "src/app.lox");
+    assert_eq!(manifest.import_paths, vec!["lib", "vendor"]);
+    assert_eq!(manifest.stdlib, Some(vec![String::from("async")]));
+    assert_eq!(manifest.lints["unused_variable"], "deny");
+}
+
+#[test]
+fn test_manifest_defaults() {
+    assert_eq!(
+        Manifest::parse("", Path::new("lox.toml")).unwrap(),
+        Manifest::default()
+    );
+}
+
+#[test]
+fn test_manifest_unknown_stdlib_feature() {
+    assert!(matches!(
+        Manifest::parse("stdlib = [\"network\"]", Path::new("lox.toml")),
+        Err(ProjectError::UnknownStdlibFeature { feature, .. }) if feature == "network"
+    ));
+}
+
+#[test]
+fn test_manifest_invalid_lint() {
+    assert!(matches!(
+        Manifest::parse(
+            "[lints]\nunused_variable = \"forbid\"",
+            Path::new("lox.toml")
+        ),
+        Err(ProjectError::InvalidLint { .. })
+    ));
+}
+
+#[test]
+fn test_project_without_manifest() {
+    let root = std::env::temp_dir().join(format!("rlox-project-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+
+    assert!(matches!(
+        Project::open(&root),
+        Err(ProjectError::MissingEntryPoint { .. })
+    ));
+
+    fs::write(root.join("main.lox"), "print 1;").unwrap();
+    let project = Project::open(&root).unwrap();
+    assert_eq!(project.entry_point(), root.join("main.lox"));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_manifest_error_messages() {
+    let error = Manifest::parse("import_paths = \"lib\"", Path::new("app/lox.toml")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "app/lox.toml: invalid type: string \"lib\", expected a sequence"
+    );
+
+    let error = Manifest::parse("stdlib = [\"network\"]", Path::new("app/lox.toml")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "app/lox.toml: Unknown standard library feature \"network\"!"
+    );
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun sample() {
+  var values = "";
+  for (var i = 0; i < 5; i = i + 1) {
+    values = values + randomInt(1, 6);
+  }
+  return values;
+}
+
+setRandomSeed(42);
+var first = sample();
+var number = random();
+setRandomSeed(42);
+print sample() == first;
+print random() == number;
+
+var inRange = true;
+for (var i = 0; i < 100; i = i + 1) {
+  var value = random();
+  var die = randomInt(-2, 2);
+  if (value < 0 or value >= 1 or die < -2 or die > 2 or floor(die) != die) {
+    inRange = false;
+  }
+}
+print inRange;
+print randomInt(3, 3);
+"###;
+
+const RESULT: &str = r###"
+true
+true
+true
+3
+"###;
+
+#[test]
+fn test_random() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_random_int_invalid_range() {
+    for input in ["randomInt(2, 1);", "randomInt(0.5, 1);"] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::InvalidArgument { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use rlox_lib::base::scanner::{Scanner, ScannerError};
+
+const INPUT: &str = r###"
+print r"C:\temp\new";
+print r"\d+\.\d*";
+print r"${not interpolated}";
+print r#"say "hi""#;
+print r##"a "# inside"##;
+print r"first
+second";
+var r = "variable";
+print r;
+print r + r"!";
+"###;
+
+const RESULT: &str = r###"
+C:\temp\new
+\d+\.\d*
+${not interpolated}
+say "hi"
+a "# inside
+first
+second
+variable
+variable!
+"###;
+
+#[test]
+fn test_raw_strings() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_unterminated_raw_string() {
+    for input in ["r\"abc", "r#\"abc\"", "\n\nr\"a\nb"] {
+        assert!(matches!(
+            Scanner::new(input).scan_tokens(),
+            Err(ScannerError::UnterminatedString { .. })
+        ));
+    }
+}
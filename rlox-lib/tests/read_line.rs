@@ -0,0 +1,46 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::io::Cursor;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+print "What is your name?";
+var name = readLine();
+print "Hello, " + name + "!";
+
+var line = readLine();
+while (line != nil) {
+  print "> " + line;
+  line = readLine();
+}
+"###;
+
+const RESULT: &str = r###"
+What is your name?
+Hello, Lox!
+> first
+> 
+> last
+"###;
+
+#[test]
+fn test_read_line() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let input = Rc::new(RefCell::new(Cursor::new("Lox\nfirst\r\n\nlast")));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)).with_input_stream(input));
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    interpreter.interpret(&statements).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+class User {
+  init(name, age) {
+    this.name = name;
+    this.age = age;
+  }
+
+  greet() {
+    return "Hi, " + this.name;
+  }
+}
+
+fun serialize(object) {
+  var parts = [];
+  for (field in fields(object)) {
+    parts.push(field + "=" + getField(object, field));
+  }
+  return parts;
+}
+
+var user = User("Ada", 36);
+print fields(user);
+print serialize(user);
+print hasField(user, "name");
+print hasField(user, "greet");
+print setField(user, "email", "ada@example.com");
+print user.email;
+setField(user, "name", "Grace");
+print user.greet();
+"###;
+
+const RESULT: &str = r###"
+["age", "name"]
+["age=36", "name=Ada"]
+true
+false
+ada@example.com
+ada@example.com
+Hi, Grace
+"###;
+
+#[test]
+fn test_reflection() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_reflection_errors() {
+    let error = common::interpret("class A {} getField(A(), \"missing\");").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::UndefinedProperty { line: 1, .. })
+    ));
+
+    let error = common::interpret("fields([1]);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+}
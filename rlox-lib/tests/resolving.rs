@@ -1,3 +1,5 @@
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
 mod common;
 
 const INPUT: &str = r###"
@@ -28,3 +30,24 @@ fn test_resolving() {
         RESULT.strip_prefix('\n').unwrap()
     )
 }
+
+#[test]
+fn test_duplicate_declarations() {
+    let error = common::interpret("fun f(a, b,\n a) {}").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::DuplicateParameter { line: 2, name, .. }) if name == "a"
+    ));
+
+    let error = common::interpret("class A {\n  m() {}\n  m(a) {}\n}").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::DuplicateMethod { line: 3, name, .. }) if name == "m"
+    ));
+
+    let error = common::interpret("trait T { m() {} m() {} }").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::DuplicateMethod { line: 1, name, .. }) if name == "m"
+    ));
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun sum(...numbers) {
+  var total = 0;
+  for (number in numbers) {
+    total = total + number;
+  }
+  return total;
+}
+
+print sum();
+print sum(1, 2, 3);
+
+fun tag(name, ...values) {
+  print name;
+  print values;
+}
+
+tag("empty");
+tag("values", 1, "two");
+
+class Bag {
+  init(...items) {
+    this.items = items;
+  }
+}
+
+print Bag(1, 2).items;
+
+var first = fun (head, ...tail) -> head;
+print first(4, 5, 6);
+"###;
+
+const RESULT: &str = r###"
+0
+6
+empty
+[]
+values
+[1, "two"]
+[1, 2]
+4
+"###;
+
+#[test]
+fn test_rest_parameters() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_rest_parameters_missing_arguments() {
+    let error = common::interpret("fun f(a, ...rest) {} f();").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NonMatchingNumberOfArguments { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_rest_parameter_not_last() {
+    let error = common::interpret("fun f(...rest, a) {}").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::RestParameterNotLast { line: 1, .. })
+    ));
+}
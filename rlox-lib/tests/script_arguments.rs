@@ -0,0 +1,53 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+
+const INPUT: &str = r###"
+print ARGS;
+for (argument in ARGS) {
+  print argument;
+}
+print env("RLOX_TEST_VARIABLE");
+print env("RLOX_TEST_UNDEFINED_VARIABLE");
+"###;
+
+const RESULT: &str = r###"
+["-v", "input file.txt"]
+-v
+input file.txt
+value
+nil
+"###;
+
+#[test]
+fn test_script_arguments() {
+    std::env::set_var("RLOX_TEST_VARIABLE", "value");
+
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf))
+            .with_arguments(&[String::from("-v"), String::from("input file.txt")]),
+    );
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    interpreter.interpret(&statements).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(buf.borrow().as_slice()).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_no_script_arguments() {
+    assert_eq!(common::interpret("print ARGS;").unwrap(), "[]\n");
+}
@@ -0,0 +1,26 @@
+use rlox_lib::base::scanner::{Scanner, ScannerError};
+
+mod common;
+
+const INPUT: &str = r###"#!/usr/bin/env rlox
+print "executable";
+print 2;
+"###;
+
+#[test]
+fn test_shebang() {
+    assert_eq!(common::interpret(INPUT).unwrap(), "executable\n2\n");
+    assert_eq!(
+        common::interpret("\u{FEFF}#!/usr/bin/env rlox\nprint 1;").unwrap(),
+        "1\n"
+    );
+}
+
+#[test]
+fn test_shebang_only_on_first_line() {
+    let tokens = Scanner::new("print 1;\n#!/usr/bin/env rlox\n").scan_tokens();
+    assert!(matches!(
+        tokens,
+        Err(ScannerError::UnknownSymbol { line: 2, .. })
+    ));
+}
@@ -0,0 +1,81 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod common;
+
+const INPUT: &str = r###"
+var start = clock();
+sleep(20);
+print clock() - start >= 0.02;
+"###;
+
+const RESULT: &str = r###"
+true
+"###;
+
+#[test]
+fn test_sleep() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_sleep_interrupted() {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new(Rc::clone(&buf)));
+
+    let tokens = Scanner::new("sleep(60000);").scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+
+    let interrupt = interpreter.interrupt_handle();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        interrupt.interrupt();
+    });
+
+    let start = Instant::now();
+    assert!(matches!(
+        interpreter.interpret(&statements),
+        Err(RuntimeError::Interrupted)
+    ));
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_sleep_invalid_duration() {
+    let error = common::interpret("sleep(-1);").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_sleep_unrepresentable_duration() {
+    for input in [
+        "sleep(1e300);",
+        "sleep(1e22);",
+        "sleep(1/0);",
+        "sleep(0/0);",
+    ] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::InvalidArgument { line: 1, .. })
+        ));
+    }
+}
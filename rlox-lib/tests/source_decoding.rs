@@ -0,0 +1,51 @@
+mod common;
+
+use rlox_lib::base::source::{decode_source, SourceError};
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = "\u{FEFF}var a = \"first\r\nsecond\";\r\nprint a;\r\n";
+
+const RESULT: &str = r###"
+first
+second
+"###;
+
+const INPUT_LINE_NUMBERS: &str = "print 1;\r\nprint 2;\r\nprint -\"three\";\r\n";
+
+#[test]
+fn test_bom_and_crlf() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_crlf_line_numbers() {
+    let error = common::interpret(INPUT_LINE_NUMBERS).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NumberExpected { line: 3, .. })
+    ))
+}
+
+#[test]
+fn test_invalid_utf8() {
+    let bytes = b"print 1;\nprint \"\xff\";\n";
+
+    assert!(matches!(
+        decode_source(bytes, false),
+        Err(SourceError::InvalidUtf8 {
+            line: 2,
+            offset: 16
+        })
+    ));
+    assert_eq!(
+        decode_source(bytes, false).unwrap_err().to_string(),
+        "2: Invalid UTF-8 at byte offset 16!"
+    );
+    assert_eq!(
+        decode_source(bytes, true).unwrap(),
+        "print 1;\nprint \"\u{FFFD}\";\n"
+    );
+}
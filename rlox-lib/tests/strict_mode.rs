@@ -0,0 +1,112 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::resolver::Resolver;
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+fun first() {
+  return second();
+}
+
+fun second() {
+  return count;
+}
+
+var count = 1;
+if (first() == 1 and true) {
+  print "strict";
+}
+"###;
+
+const RESULT: &str = r###"
+strict
+"###;
+
+fn interpret_strict(input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(
+        Interpreter::new(Rc::clone(&buf)).with_options(InterpreterOptions {
+            strict: true,
+            ..InterpreterOptions::default()
+        }),
+    );
+
+    let tokens = Scanner::new(input).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Resolver::new(Rc::clone(&interpreter)).resolve_stmts(&statements)?;
+    interpreter.interpret(&statements)?;
+
+    let output = std::str::from_utf8(buf.borrow().as_slice())?.to_string();
+    Ok(output)
+}
+
+#[test]
+fn test_strict_mode() {
+    assert_eq!(
+        interpret_strict(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_strict_mode_undeclared_global() {
+    let error = interpret_strict("fun f() { return missing; }").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::UndeclaredVariable { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_strict_mode_uninitialized_variable() {
+    let error = interpret_strict("var a;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::UninitializedVariable { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_strict_mode_mismatched_types() {
+    for input in [
+        "print \"count: \" + 1;",
+        "print 1 + \"1\";",
+        "print 1 == \"1\";",
+        "print true != 0;",
+    ] {
+        let error = interpret_strict(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::MismatchedTypes { line: 1, .. })
+        ));
+    }
+
+    assert_eq!(
+        interpret_strict("var a = nil; print a == nil; print 1 != nil;").unwrap(),
+        "true\ntrue\n"
+    );
+}
+
+#[test]
+fn test_strict_mode_truthiness() {
+    for input in [
+        "if (1) print 1;",
+        "while (nil) {}",
+        "print !0;",
+        "print nil or true;",
+    ] {
+        let error = interpret_strict(input).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<RuntimeError>(),
+            Some(RuntimeError::BooleanExpected { line: 1, .. })
+        ));
+    }
+}
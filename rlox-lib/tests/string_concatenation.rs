@@ -0,0 +1,60 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+print "count: " + 3;
+print 2.5 + " apples";
+print "flag: " + true;
+print "nothing: " + nil;
+print "list: " + [1, "two"];
+print 1 + 2 + " is three";
+print "three is " + 1 + 2;
+
+fun greet() {}
+print "fn: " + greet;
+"###;
+
+const RESULT: &str = r###"
+count: 3
+2.5 apples
+flag: true
+nothing: nil
+list: [1, "two"]
+3 is three
+three is 12
+fn: <fn greet>
+"###;
+
+#[test]
+fn test_string_concatenation() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_invalid_addition() {
+    let error = common::interpret("print 1 + nil;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::NumberExpected { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_operand_in_error_message() {
+    let error = common::interpret("print -\"abc\";").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "1: Operand of '-' must be a number, got \"abc\"!"
+    );
+
+    let error = common::interpret("print 2 * nil;").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "1: Operand of '*' must be a number, got nil!"
+    );
+}
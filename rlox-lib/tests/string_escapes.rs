@@ -0,0 +1,40 @@
+mod common;
+
+use rlox_lib::base::scanner::ScannerError;
+
+const INPUT: &str = r###"
+print "a\tb";
+print "line\nbreak";
+print "quote \"inside\"";
+print "back\\slash";
+print "\u{48}\u{e9}\u{1F600}";
+"###;
+
+const RESULT: &str = "a\tb\nline\nbreak\nquote \"inside\"\nback\\slash\nHé😀\n";
+
+#[test]
+fn test_string_escapes() {
+    assert_eq!(common::interpret(INPUT).unwrap(), RESULT)
+}
+
+#[test]
+fn test_string_invalid_escapes() {
+    for input in [
+        r#"print "\q";"#,
+        r#"print "\u{}";"#,
+        r#"print "\u{D800}";"#,
+        r#"print "\u{110000}";"#,
+        r#"print "\u{41";"#,
+        r#"print "\u41";"#,
+    ] {
+        let error = common::interpret(input).unwrap_err();
+
+        assert!(
+            matches!(
+                error.downcast_ref::<ScannerError>(),
+                Some(ScannerError::InvalidEscapeSequence { line: 1, column: 8 })
+            ),
+            "{input}"
+        );
+    }
+}
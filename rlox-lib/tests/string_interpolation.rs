@@ -0,0 +1,45 @@
+mod common;
+
+use rlox_lib::base::scanner::ScannerError;
+
+const INPUT: &str = r###"
+var name = "Lox";
+var version = 2;
+print "Hello ${name}!";
+print "${name} ${version + 1}";
+print "nested: ${"inner ${version * 2}"}";
+print "values: ${nil}, ${true}, ${1.5}";
+
+class Point {}
+fun f() {}
+print "${Point} ${f}";
+
+print "literal: \${name}, dollar: $";
+"###;
+
+const RESULT: &str = r###"
+Hello Lox!
+Lox 3
+nested: inner 4
+values: nil, true, 1.5
+Point <fn f>
+literal: ${name}, dollar: $
+"###;
+
+#[test]
+fn test_string_interpolation() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_string_interpolation_unterminated() {
+    let error = common::interpret("print \"a ${1 + 2\";").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ScannerError>(),
+        Some(ScannerError::UnterminatedString { line: 1, .. })
+    ));
+}
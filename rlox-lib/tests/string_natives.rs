@@ -0,0 +1,77 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+var text = "  Hello, Wörld!  ";
+var trimmed = trim(text);
+print trimmed;
+print len(trimmed);
+print len([1, 2, 3]);
+print substring(trimmed, 7, 12);
+print substring(trimmed, 0, 0) == "";
+print indexOf(trimmed, "ö");
+print indexOf(trimmed, "xyz");
+print toUpper(trimmed);
+print toLower(trimmed);
+print split("a,b,,c", ",");
+print split("abc", "");
+print replace("one two one", "one", "1");
+print charAt(trimmed, 8);
+"###;
+
+const RESULT: &str = r###"
+Hello, Wörld!
+13
+3
+Wörld
+true
+8
+-1
+HELLO, WÖRLD!
+hello, wörld!
+["a", "b", "", "c"]
+["a", "b", "c"]
+1 two 1
+ö
+"###;
+
+#[test]
+fn test_string_natives() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_string_natives_errors() {
+    let error = common::interpret("substring(\"abc\", 1, 4);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::IndexOutOfBounds {
+            line: 1,
+            index: 4,
+            length: 3,
+            ..
+        })
+    ));
+
+    let error = common::interpret("substring(\"abc\", 2, 1);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+
+    let error = common::interpret("toUpper(1);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::InvalidArgument { line: 1, .. })
+    ));
+
+    let error = common::interpret("charAt(\"abc\", 3);").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::IndexOutOfBounds { line: 1, .. })
+    ));
+}
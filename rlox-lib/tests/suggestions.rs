@@ -0,0 +1,49 @@
+use rlox_lib::interpreter::suggestion::closest;
+
+mod common;
+
+#[test]
+fn test_closest() {
+    let names = ["length", "print", "clock", "len"];
+
+    assert_eq!(closest("lenght", names), Some(String::from("length")));
+    assert_eq!(closest("pritn", names), Some(String::from("print")));
+    assert_eq!(closest("lem", names), Some(String::from("len")));
+    assert_eq!(closest("time", names), None);
+    assert_eq!(closest("len", names), None);
+}
+
+#[test]
+fn test_undefined_variable_suggestion() {
+    let error = common::interpret("var length = 1;\nprint lenght;").unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "2: Undefined variable \"lenght\"! Did you mean \"length\"?"
+    );
+}
+
+#[test]
+fn test_undefined_property_suggestion() {
+    let input = r###"
+class Point {
+  init() { this.x = 1; }
+  norm() { return this.x; }
+}
+print Point().nrom();
+"###;
+    let error = common::interpret(input).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "6: Undefined property! Did you mean \"norm\"?"
+    );
+
+    let error = common::interpret("class Point {}\nprint Point().y;").unwrap_err();
+    assert_eq!(error.to_string(), "2: Undefined property!");
+
+    let error = common::interpret("[1].psuh(2);").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "1: Undefined property! Did you mean \"push\"?"
+    );
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+fun fib(n) {
+  if (n < 2) return n;
+  return fib(n - 1) + fib(n - 2);
+}
+
+var counter = 0;
+
+fun work() {
+  counter = counter + 1;
+  print "working";
+  return fib(15);
+}
+
+var t1 = spawn(work);
+var t2 = spawn(work);
+print join(t1) + join(t2);
+print counter;
+
+fun makeTask(n) {
+  fun task() {
+    return n * 2;
+  }
+
+  return spawn(task);
+}
+
+print join(makeTask(21));
+
+class Point {
+  init(x) {
+    this.x = x;
+  }
+}
+
+fun makePoint() {
+  return Point(42);
+}
+
+print join(spawn(makePoint)).x;
+"###;
+
+const RESULT: &str = r###"
+working
+working
+1220
+0
+42
+42
+"###;
+
+const INPUT_JOINED_TWICE: &str = r###"
+fun work() {}
+
+var task = spawn(work);
+join(task);
+join(task);
+"###;
+
+#[test]
+fn test_tasks() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_tasks_joined_twice() {
+    let error = common::interpret(INPUT_JOINED_TWICE).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::TaskAlreadyJoined { line: 6, .. })
+    ))
+}
+
+#[test]
+fn test_exit_in_task() {
+    let error = common::interpret("fun work() { exit(3); }\njoin(spawn(work));").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::Exit { code: 3 })
+    ))
+}
+
+const INPUT_CYCLES: &str = r###"
+var list = [1];
+list.push(list);
+var map = {};
+map["self"] = map;
+
+fun work() {
+  return [list, map];
+}
+
+var copy = join(spawn(work));
+print copy;
+print copy[0][1][1][0];
+print copy[1]["self"]["self"] == copy[1];
+"###;
+
+const RESULT_CYCLES: &str = r###"
+[[1, [...]], {"self": {...}}]
+1
+true
+"###;
+
+#[test]
+fn test_tasks_with_cycles() {
+    assert_eq!(
+        common::interpret(INPUT_CYCLES).unwrap(),
+        RESULT_CYCLES.strip_prefix('\n').unwrap()
+    )
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use rlox_lib::base::parser::ParserError;
+
+const INPUT: &str = r###"
+fun sign(n) {
+  return n < 0 ? "negative" : n == 0 ? "zero" : "positive";
+}
+
+print sign(-3);
+print sign(0);
+print sign(7);
+
+var calls = 0;
+fun count() {
+  calls = calls + 1;
+  return calls;
+}
+
+print true ? count() : count();
+print calls;
+
+var a = false ? 1 : 2;
+print a;
+print (nil ? "yes" : "no") + "!";
+"###;
+
+const RESULT: &str = r###"
+negative
+zero
+positive
+1
+1
+2
+no!
+"###;
+
+#[test]
+fn test_ternary() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_ternary_missing_colon() {
+    let error = common::interpret("print true ? 1;").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<ParserError>(),
+        Some(ParserError::MissingColonInConditional { line: 1, .. })
+    ));
+}
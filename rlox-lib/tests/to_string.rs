@@ -0,0 +1,56 @@
+mod common;
+
+const INPUT: &str = r###"
+class Point {
+  init(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  toString() {
+    return "(" + "${this.x}" + ", " + "${this.y}" + ")";
+  }
+}
+
+class Point3 < Point {
+  init(x, y, z) {
+    super.init(x, y);
+    this.z = z;
+  }
+}
+
+class Plain {}
+
+var p = Point(1, 2);
+print p;
+print "p = " + p;
+print p + "!";
+print "interpolated ${p}";
+print Point3(3, 4, 5);
+print Plain();
+print "plain: " + Plain();
+print [p, "p", Plain(), [Point3(6, 7, 8)]];
+print {"p": p, p: 1};
+print "in a list: ${[p]}";
+"###;
+
+const RESULT: &str = r###"
+(1, 2)
+p = (1, 2)
+(1, 2)!
+interpolated (1, 2)
+(3, 4)
+Plain instance
+plain: Plain instance
+[(1, 2), "p", Plain instance, [(6, 7)]]
+{"p": (1, 2), (1, 2): 1}
+in a list: [(1, 2)]
+"###;
+
+#[test]
+fn test_to_string() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
@@ -0,0 +1,109 @@
+use rlox_lib::base::scanner::{Scanner, ScannerError, TokenType};
+
+#[test]
+fn test_token_stream() {
+    let mut scanner = Scanner::new("var x = 1;\nprint x;");
+
+    let first = scanner.next().unwrap().unwrap();
+    assert_eq!(first.token_type(), &TokenType::Var);
+    assert_eq!(first.lexeme(), "var");
+
+    let lexemes: Vec<String> = scanner
+        .map(|token| token.unwrap().lexeme().to_owned())
+        .collect();
+    assert_eq!(lexemes, ["x", "=", "1", ";", "print", "x", ";", ""]);
+}
+
+#[test]
+fn test_token_stream_ends_after_error() {
+    let mut scanner = Scanner::new("var @ x;");
+
+    assert_eq!(
+        scanner.next().unwrap().unwrap().token_type(),
+        &TokenType::Var
+    );
+    assert!(matches!(
+        scanner.next(),
+        Some(Err(ScannerError::UnknownSymbol { line: 1, .. }))
+    ));
+    assert!(scanner.next().is_none());
+}
+
+#[test]
+fn test_scan_all_collects_errors() {
+    let outcome = Scanner::new("var a = 1 @ 2;\nprint \"\\q\" + 1__0;\nprint \"${a\n").scan_all();
+
+    assert!(matches!(
+        outcome.errors.as_slice(),
+        [
+            ScannerError::UnknownSymbol { line: 1, .. },
+            ScannerError::InvalidEscapeSequence { line: 2, .. },
+            ScannerError::InvalidDigitSeparator { line: 2, .. },
+            ScannerError::UnterminatedString { line: 4, .. },
+        ]
+    ));
+
+    let lexemes: Vec<&str> = outcome.tokens.iter().map(|token| token.lexeme()).collect();
+    assert_eq!(
+        lexemes,
+        ["var", "a", "=", "1", "2", ";", "print", "+", ";", "print", "\"${", "a", ""]
+    );
+}
+
+#[test]
+fn test_scan_all_unterminated_string_in_interpolation() {
+    for input in ["print \"${\";\n", "print \"${r\";\n"] {
+        let outcome = Scanner::new(input).scan_all();
+
+        assert!(matches!(
+            outcome.errors.as_slice(),
+            [ScannerError::UnterminatedString { line: 1, .. }]
+        ));
+    }
+}
+
+#[test]
+fn test_trivia() {
+    let input = "#!/usr/bin/env rlox\n// greeting\r\nvar name = \"${1 + 2}\"; /* a /* nested */ comment */\n\n  print name;\t";
+
+    let tokens = Scanner::new(input).with_trivia().scan_tokens().unwrap();
+    let source: String = tokens.iter().map(|token| token.lexeme()).collect();
+    assert_eq!(source, input);
+
+    let trivia: Vec<(&TokenType, &str, usize)> = tokens
+        .iter()
+        .filter(|token| {
+            matches!(
+                token.token_type(),
+                TokenType::Whitespace | TokenType::Comment
+            )
+        })
+        .map(|token| (token.token_type(), token.lexeme(), token.line()))
+        .collect();
+    assert_eq!(
+        trivia[..4],
+        [
+            (&TokenType::Comment, "#!/usr/bin/env rlox", 1),
+            (&TokenType::Whitespace, "\n", 1),
+            (&TokenType::Comment, "// greeting", 2),
+            (&TokenType::Whitespace, "\r\n", 2),
+        ]
+    );
+    assert!(trivia.contains(&(&TokenType::Whitespace, "\n\n  ", 3)));
+    assert_eq!(trivia.last(), Some(&(&TokenType::Whitespace, "\t", 5)));
+
+    let tokens = Scanner::new(input).scan_tokens().unwrap();
+    assert!(tokens.iter().all(|token| !matches!(
+        token.token_type(),
+        TokenType::Whitespace | TokenType::Comment
+    )));
+}
+
+#[test]
+fn test_comments_end_at_carriage_return() {
+    for input in ["// comment\rprint 1;", "#!/usr/bin/env rlox\rprint 1;"] {
+        let tokens = Scanner::new(input).scan_tokens().unwrap();
+        let lexemes: Vec<&str> = tokens.iter().map(|token| token.lexeme()).collect();
+        assert_eq!(lexemes, ["print", "1", ";", ""]);
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use rlox_lib::interpreter::runtime_error::RuntimeError;
+
+const INPUT: &str = r###"
+trait Greets {
+  greet() {
+    return "Hello, " + this.name() + "!";
+  }
+}
+
+trait Describes {
+  describe() -> "I am " + this.name();
+  greet() -> "overridden by the class";
+}
+
+class Animal {
+  name() -> "an animal";
+}
+
+class Dog < Animal with Greets {
+  name() -> "a dog";
+}
+
+class Robot with Describes {
+  name() -> "a robot";
+  greet() -> "Beep.";
+}
+
+print Dog().greet();
+print Robot().describe();
+print Robot().greet();
+print Greets;
+"###;
+
+const RESULT: &str = r###"
+Hello, a dog!
+I am a robot
+Beep.
+Greets
+"###;
+
+#[test]
+fn test_traits() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    )
+}
+
+#[test]
+fn test_traits_conflict() {
+    let input = r###"
+trait A { run() -> 1; }
+trait B { run() -> 2; }
+class C with A, B {}
+"###;
+    let error = common::interpret(input).unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::TraitMethodConflict { line: 4, name, .. }) if name == "run"
+    ));
+}
+
+#[test]
+fn test_traits_invalid_type() {
+    let error = common::interpret("class A {}\nclass B with A {}").unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<RuntimeError>(),
+        Some(RuntimeError::TraitInvalidType { line: 2, .. })
+    ));
+}
@@ -0,0 +1,89 @@
+use rlox_lib::base::scanner::{Scanner, ScannerError};
+
+mod common;
+
+const INPUT: &str = r###"
+var größe = 1.5;
+var 変数 = "値";
+var π = 3;
+fun grüße(名前) {
+  return "Grüß dich, ${名前} 👋";
+}
+class Ärger {
+  init() {
+    this.ñ = "señor";
+  }
+}
+var _x1 = größe * π;
+print _x1;
+print 変数;
+print grüße("Zoë");
+print Ärger().ñ;
+print "naïve café — ελληνικά";
+"###;
+
+const RESULT: &str = r###"
+4.5
+値
+Grüß dich, Zoë 👋
+señor
+naïve café — ελληνικά
+"###;
+
+#[test]
+fn test_unicode_identifiers_and_strings() {
+    assert_eq!(
+        common::interpret(INPUT).unwrap(),
+        RESULT.strip_prefix('\n').unwrap()
+    );
+}
+
+#[test]
+fn test_unicode_positions() {
+    let tokens = Scanner::new("var ä = \"日本語\";\nvar 名 = ä;")
+        .scan_tokens()
+        .unwrap();
+    let positions: Vec<(&str, usize)> = tokens
+        .iter()
+        .filter(|token| token.line() == 2)
+        .map(|token| (token.lexeme(), token.column()))
+        .collect();
+    assert_eq!(
+        positions,
+        [
+            ("var", 1),
+            ("名", 5),
+            ("=", 7),
+            ("ä", 9),
+            (";", 10),
+            ("", 11)
+        ]
+    );
+
+    let error = Scanner::new("print \"ü\";\nvar é = \"ö\"; €").scan_tokens();
+    assert!(matches!(
+        error,
+        Err(ScannerError::UnknownSymbol {
+            line: 2,
+            column: 14,
+            symbol: '€'
+        })
+    ));
+}
+
+#[test]
+fn test_non_identifier_symbols() {
+    /* letters must start an identifier, digits of other scripts and symbols can't. */
+    assert!(matches!(
+        Scanner::new("var ١ = 1;").scan_tokens(),
+        Err(ScannerError::UnknownSymbol { symbol: '١', .. })
+    ));
+    assert!(matches!(
+        Scanner::new("var 😀 = 1;").scan_tokens(),
+        Err(ScannerError::UnknownSymbol { symbol: '😀', .. })
+    ));
+    assert!(matches!(
+        Scanner::new("print 1é;").scan_tokens(),
+        Err(ScannerError::NumberParsingError { .. })
+    ));
+}
@@ -0,0 +1,57 @@
+use rlox_lib::interpreter::options::InterpreterOptions;
+use rlox_lib::interpreter::worker::{InterpreterWorker, WorkerError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn test_share_worker_between_threads() {
+    let worker = Arc::new(InterpreterWorker::new(InterpreterOptions::default()));
+    assert_send_sync(&worker);
+
+    assert_eq!(worker.run("var count = 0;"), Ok(String::new()));
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let worker = Arc::clone(&worker);
+            thread::spawn(move || worker.run("count = count + 1; print \"counted\";"))
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), Ok(String::from("counted\n")));
+    }
+
+    assert_eq!(worker.run("print count;"), Ok(String::from("4\n")));
+}
+
+#[test]
+fn test_worker_reports_errors() {
+    let worker = InterpreterWorker::new(InterpreterOptions::default());
+
+    assert_eq!(
+        worker.run("print undefined;"),
+        Err(WorkerError::Script(String::from(
+            "1: Undefined variable \"undefined\"!"
+        )))
+    );
+    assert_eq!(worker.run("print 1;"), Ok(String::from("1\n")));
+}
+
+#[test]
+fn test_interrupt_worker() {
+    let worker = Arc::new(InterpreterWorker::new(InterpreterOptions::default()));
+
+    let running = Arc::clone(&worker);
+    let thread = thread::spawn(move || running.run("while (true) {}"));
+    while !thread.is_finished() {
+        worker.interrupt();
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(
+        thread.join().unwrap(),
+        Err(WorkerError::Script(String::from("Interrupted!")))
+    );
+}
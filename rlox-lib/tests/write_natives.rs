@@ -0,0 +1,53 @@
+use rlox_lib::base::parser::Parser;
+use rlox_lib::base::scanner::Scanner;
+use rlox_lib::interpreter::interpreter::Interpreter;
+use rlox_lib::interpreter::resolver::Resolver;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INPUT: &str = r###"
+write("Progress: ");
+for (var i = 0; i < 3; i = i + 1) {
+  write("#");
+}
+write(nil);
+print "";
+printErr("warning: " + 3);
+printErr([1, 2]);
+write(1.5);
+"###;
+
+const OUTPUT: &str = r###"
+Progress: ###nil
+1.5"###;
+
+const ERRORS: &str = r###"
+warning: 3
+[1, 2]
+"###;
+
+#[test]
+fn test_write_natives() {
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let interpreter = Rc::new(Interpreter::new_with_error_stream(
+        Rc::clone(&output),
+        Rc::clone(&errors),
+    ));
+
+    let tokens = Scanner::new(INPUT).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(Rc::clone(&interpreter))
+        .resolve_stmts(&statements)
+        .unwrap();
+    interpreter.interpret(&statements).unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(output.borrow().as_slice()).unwrap(),
+        OUTPUT.strip_prefix('\n').unwrap()
+    );
+    assert_eq!(
+        std::str::from_utf8(errors.borrow().as_slice()).unwrap(),
+        ERRORS.strip_prefix('\n').unwrap()
+    );
+}